@@ -2,19 +2,57 @@ use std::sync::Arc;
 use std::path::PathBuf;
 use std::fs;
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use std::time::Duration;
+use tauri::ipc::Response;
+use tauri::{AppHandle, Emitter, State};
 use knowhere::{Table, Schema, DataFusionContext, FileLoader};
+use knowhere::datafusion::{table_to_ipc, LoadReport, QueryHandle, QueryOptions};
+use knowhere::render::MaskRules;
+use knowhere::storage::{load_versioned, save_versioned, Migration};
+use crate::results::{self, ResultCache};
+use crate::snippets::{self, Snippet};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TableInfo {
     pub name: String,
     pub columns: Vec<ColumnInfo>,
+    /// Source format such as "csv" or "delta", if loaded from disk
+    pub format: Option<String>,
+    /// Bytes on disk, summed over all files for directory tables
+    pub size_bytes: Option<u64>,
+    pub row_count: Option<u64>,
+    /// True when row_count comes from a line count rather than file metadata
+    pub row_count_estimated: bool,
+    /// Version, file count and partitioning of Delta and Iceberg tables
+    pub layout: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ColumnInfo {
     pub name: String,
     pub data_type: String,
+    pub nullable: bool,
+    /// Type as the source declares it, e.g. "DECIMAL(10,2)"
+    pub source_type: Option<String>,
+}
+
+/// Per-query limits set in the toolbar; unset ones fall back to the
+/// session's settings
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct QueryLimits {
+    pub timeout_secs: Option<u64>,
+    pub max_memory_mb: Option<usize>,
+    pub max_rows: Option<usize>,
+}
+
+impl From<QueryLimits> for QueryOptions {
+    fn from(limits: QueryLimits) -> Self {
+        QueryOptions {
+            timeout: limits.timeout_secs.map(Duration::from_secs),
+            memory_limit: limits.max_memory_mb.map(|mb| mb.saturating_mul(1024 * 1024)),
+            max_rows: limits.max_rows,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +62,96 @@ pub struct QueryResult {
     pub row_count: usize,
 }
 
+/// Rows `offset..offset + rows.len()` of a result cached by
+/// `execute_sql_paged`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultPage {
+    /// Id to fetch more pages of the result with
+    pub query_id: u64,
+    pub columns: Vec<ColumnInfo>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+    pub offset: usize,
+    /// Rows in the whole result
+    pub total_rows: usize,
+    /// The query stopped at its `max_rows` limit, so there were more rows
+    pub truncated: bool,
+}
+
+/// Payload of the `query-progress` event emitted while a query runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryProgressEvent {
+    pub rows_scanned: u64,
+    pub rows_produced: u64,
+    pub operator: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+/// How often `execute_sql` reports progress to the frontend
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Outcome of loading one path in a `load_paths` call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadOutcome {
+    pub path: String,
+    pub tables: Vec<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadPathsResult {
+    pub outcomes: Vec<LoadOutcome>,
+    /// Every table registered in the session after loading
+    pub tables: Vec<String>,
+}
+
+/// A table created by the last load, with its inferred schema
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadedTableInfo {
+    pub name: String,
+    /// File name the table was named after, when it had to be sanitized
+    pub original_name: Option<String>,
+    pub path: String,
+    pub format: String,
+    pub row_count: Option<u64>,
+    pub row_count_estimated: bool,
+    pub columns: Vec<ColumnInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedFileInfo {
+    pub path: String,
+    pub reason: String,
+}
+
+/// Files loaded, skipped and warned about by the last load command
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct LoadReportInfo {
+    pub loaded: Vec<LoadedTableInfo>,
+    pub skipped: Vec<SkippedFileInfo>,
+    pub warnings: Vec<String>,
+}
+
+impl From<&LoadReport> for LoadReportInfo {
+    fn from(report: &LoadReport) -> Self {
+        Self {
+            loaded: report.loaded.iter().map(|table| LoadedTableInfo {
+                name: table.name.clone(),
+                original_name: table.original_name.clone(),
+                path: table.path.to_string_lossy().to_string(),
+                format: table.format.clone(),
+                row_count: table.row_count,
+                row_count_estimated: table.row_count_estimated,
+                columns: schema_to_columns(&table.schema),
+            }).collect(),
+            skipped: report.skipped.iter().map(|file| SkippedFileInfo {
+                path: file.path.to_string_lossy().to_string(),
+                reason: file.reason.clone(),
+            }).collect(),
+            warnings: report.warnings.clone(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RecentQuery {
     pub name: String,
@@ -35,12 +163,26 @@ pub struct RecentQuery {
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct AppConfig {
     pub recent_queries: Vec<RecentQuery>,
+    /// Data paths loaded in the current session, restored on the next launch
+    pub session_paths: Vec<String>,
 }
 
+/// Upgrades of config.json, one per format version. Add one here whenever
+/// `AppConfig` changes shape, so older configs load rather than failing.
+const CONFIG_MIGRATIONS: &[Migration] = &[
+    // Version 1 adds the format version; configs from before session
+    // restore have no session_paths
+    |config| {
+        config.entry("session_paths").or_insert_with(|| serde_json::json!([]));
+    },
+];
+
 fn schema_to_columns(schema: &Schema) -> Vec<ColumnInfo> {
     schema.columns.iter().map(|col| ColumnInfo {
         name: col.name.clone(),
         data_type: format!("{:?}", col.data_type),
+        nullable: col.nullable,
+        source_type: col.source_type.clone(),
     }).collect()
 }
 
@@ -51,6 +193,9 @@ fn value_to_json(value: &knowhere::Value) -> serde_json::Value {
         knowhere::Value::Integer(n) => serde_json::json!(n),
         knowhere::Value::Float(n) => serde_json::json!(n),
         knowhere::Value::String(s) => serde_json::Value::String(s.clone()),
+        knowhere::Value::Date(_) | knowhere::Value::Timestamp(_) => {
+            serde_json::Value::String(value.to_string())
+        }
     }
 }
 
@@ -77,48 +222,89 @@ fn get_queries_dir() -> Result<PathBuf, String> {
     Ok(knowhere_home.join("queries"))
 }
 
+/// Get the snippets directory ($HOME/knowhere/snippets)
+fn get_snippets_dir() -> Result<PathBuf, String> {
+    let knowhere_home = get_knowhere_home()?;
+    Ok(knowhere_home.join("snippets"))
+}
+
+/// Get the masking rules path ($HOME/knowhere/mask_rules.txt)
+fn get_mask_rules_path() -> Result<PathBuf, String> {
+    let knowhere_home = get_knowhere_home()?;
+    Ok(knowhere_home.join("mask_rules.txt"))
+}
+
+/// Masking rules applied to every result shown, rendered or exported.
+/// No file means no masking; an invalid file is an error so that nothing
+/// is shown unmasked by accident.
+fn load_mask_rules() -> Result<MaskRules, String> {
+    let path = get_mask_rules_path()?;
+    if !path.exists() {
+        return Ok(MaskRules::default());
+    }
+    MaskRules::from_file(&path).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
 /// Get the config file path ($HOME/knowhere/config.json)
 fn get_config_path() -> Result<PathBuf, String> {
     let knowhere_home = get_knowhere_home()?;
     Ok(knowhere_home.join("config.json"))
 }
 
-/// Load app config from JSON file
-fn load_config() -> AppConfig {
-    let config_path = match get_config_path() {
-        Ok(p) => p,
-        Err(_) => return AppConfig::default(),
-    };
-    
-    if !config_path.exists() {
-        return AppConfig::default();
-    }
-    
-    match fs::read_to_string(&config_path) {
-        Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
-        Err(_) => AppConfig::default(),
+/// Load app config from JSON file, upgrading configs saved by older
+/// versions. A config that cannot be read is backed up and replaced by the
+/// defaults; one saved by a newer version is an error, so it is never
+/// overwritten.
+fn load_config() -> Result<AppConfig, String> {
+    let config_path = get_config_path()?;
+    match load_versioned(&config_path, CONFIG_MIGRATIONS) {
+        Ok(config) => Ok(config.unwrap_or_default()),
+        Err(e) if e.kind() == std::io::ErrorKind::InvalidData => {
+            log::warn!("{}", e);
+            Ok(AppConfig::default())
+        }
+        Err(e) => Err(e.to_string()),
     }
 }
 
 /// Save app config to JSON file
 fn save_config(config: &AppConfig) -> Result<(), String> {
-    let knowhere_home = get_knowhere_home()?;
-    fs::create_dir_all(&knowhere_home).map_err(|e| e.to_string())?;
-    
     let config_path = get_config_path()?;
-    let json = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
-    fs::write(&config_path, json).map_err(|e| e.to_string())?;
-    
-    Ok(())
+    save_versioned(&config_path, config, CONFIG_MIGRATIONS.len() as u32)
+        .map_err(|e| e.to_string())
+}
+
+/// Record paths that loaded successfully so the session can be restored
+fn remember_session_paths(paths: &[String]) -> Result<(), String> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+    let mut config = load_config()?;
+    for path in paths {
+        if !config.session_paths.contains(path) {
+            config.session_paths.push(path.clone());
+        }
+    }
+    save_config(&config)
+}
+
+fn forget_session_paths() -> Result<(), String> {
+    let mut config = load_config()?;
+    config.session_paths.clear();
+    save_config(&config)
 }
 
 pub struct AppState {
     pub context: Option<DataFusionContext>,
+    /// Report from the most recent load command
+    pub load_report: Option<LoadReportInfo>,
+    /// Results of `execute_sql_paged`, fetched a page at a time
+    pub results: ResultCache,
 }
 
 impl AppState {
     pub fn new() -> Self {
-        Self { context: None }
+        Self { context: None, load_report: None, results: ResultCache::default() }
     }
 }
 
@@ -132,10 +318,20 @@ pub type SharedState = Arc<std::sync::Mutex<AppState>>;
 
 // ============== Data Loading Commands ==============
 
+fn load_into(loader: &mut FileLoader, path: &str) -> Result<Vec<String>, String> {
+    let path_ref = std::path::Path::new(path);
+
+    if path_ref.is_file() {
+        loader.load_file(path_ref).map_err(|e| e.to_string())
+    } else if path_ref.is_dir() {
+        loader.load_directory(path_ref).map_err(|e| e.to_string())
+    } else {
+        Err(format!("Path does not exist: {}", path_ref.display()))
+    }
+}
+
 #[tauri::command]
 pub fn load_path(path: String, state: State<'_, SharedState>) -> Result<Vec<String>, String> {
-    let path_ref = std::path::Path::new(&path);
-
     let mut app_state = state.lock().map_err(|e| e.to_string())?;
 
     // Reuse the existing context so previously loaded tables are preserved.
@@ -144,52 +340,252 @@ pub fn load_path(path: String, state: State<'_, SharedState>) -> Result<Vec<Stri
         None => FileLoader::new().map_err(|e| e.to_string())?,
     };
 
-    if path_ref.is_file() {
-        loader.load_file(path_ref).map_err(|e| e.to_string())?;
-    } else if path_ref.is_dir() {
-        loader.load_directory(path_ref).map_err(|e| e.to_string())?;
-    } else {
-        return Err(format!("Path does not exist: {}", path_ref.display()));
-    }
-
+    let loaded = load_into(&mut loader, &path);
+    app_state.load_report = Some(loader.load_report().into());
     let ctx = loader.into_context();
     let tables = ctx.list_tables();
+    app_state.context = Some(ctx);
+    loaded?;
 
     if tables.is_empty() {
         return Err("No valid data files found".to_string());
     }
 
+    remember_session_paths(&[path])?;
+    Ok(tables)
+}
+
+/// Load several files or folders in one call, e.g. from a drag-and-drop.
+/// A path that fails to load does not stop the others; each gets its own outcome.
+#[tauri::command]
+pub fn load_paths(paths: Vec<String>, state: State<'_, SharedState>) -> Result<LoadPathsResult, String> {
+    let result = load_paths_into_state(paths, &state)?;
+    let loaded: Vec<String> = result.outcomes.iter()
+        .filter(|o| o.error.is_none())
+        .map(|o| o.path.clone())
+        .collect();
+    remember_session_paths(&loaded)?;
+    Ok(result)
+}
+
+/// Re-load the data paths from the previous session, if any. Paths that no
+/// longer load are reported in the outcomes and dropped from the saved session.
+#[tauri::command]
+pub fn restore_last_session(state: State<'_, SharedState>) -> Result<LoadPathsResult, String> {
+    // Only restore into an empty session; a second call (e.g. a frontend
+    // reload) must not try to register the same tables again
+    if let Some(ctx) = state.lock().map_err(|e| e.to_string())?.context.as_ref() {
+        return Ok(LoadPathsResult { outcomes: Vec::new(), tables: ctx.list_tables() });
+    }
+
+    let mut config = load_config()?;
+    if config.session_paths.is_empty() {
+        return Ok(LoadPathsResult { outcomes: Vec::new(), tables: Vec::new() });
+    }
+
+    let result = load_paths_into_state(config.session_paths.clone(), &state)?;
+    config.session_paths = result.outcomes.iter()
+        .filter(|o| o.error.is_none())
+        .map(|o| o.path.clone())
+        .collect();
+    save_config(&config)?;
+    Ok(result)
+}
+
+fn load_paths_into_state(paths: Vec<String>, state: &SharedState) -> Result<LoadPathsResult, String> {
+    let mut app_state = state.lock().map_err(|e| e.to_string())?;
+
+    let mut loader = match app_state.context.take() {
+        Some(ctx) => FileLoader::from_context(ctx),
+        None => FileLoader::new().map_err(|e| e.to_string())?,
+    };
+
+    let outcomes = paths.into_iter().map(|path| {
+        match load_into(&mut loader, &path) {
+            Ok(tables) => LoadOutcome { path, tables, error: None },
+            Err(e) => LoadOutcome { path, tables: Vec::new(), error: Some(e) },
+        }
+    }).collect();
+
+    app_state.load_report = Some(loader.load_report().into());
+    let ctx = loader.into_context();
+    let tables = ctx.list_tables();
     app_state.context = Some(ctx);
 
-    Ok(tables)
+    Ok(LoadPathsResult { outcomes, tables })
+}
+
+/// The load report of the most recent `load_path`, `load_paths` or
+/// `restore_last_session` call: loaded tables with their schemas, skipped
+/// files with the reason, and warnings.
+#[tauri::command]
+pub fn get_load_report(state: State<'_, SharedState>) -> Result<Option<LoadReportInfo>, String> {
+    let app_state = state.lock().map_err(|e| e.to_string())?;
+    Ok(app_state.load_report.clone())
+}
+
+/// Re-read `table_name` (or every table loaded from a file) from disk so
+/// changes to the underlying files become visible. Returns the names of the
+/// refreshed tables.
+#[tauri::command]
+pub fn refresh_tables(table_name: Option<String>, state: State<'_, SharedState>) -> Result<Vec<String>, String> {
+    let mut app_state = state.lock().map_err(|e| e.to_string())?;
+    let ctx = app_state.context.as_mut()
+        .ok_or_else(|| "No data loaded.".to_string())?;
+
+    match table_name {
+        Some(name) => ctx.refresh_table(&name).map(|_| vec![name]),
+        None => ctx.refresh_all(),
+    }
+    .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub fn clear_session(state: State<'_, SharedState>) -> Result<(), String> {
     let mut app_state = state.lock().map_err(|e| e.to_string())?;
     app_state.context = None;
+    app_state.load_report = None;
+    app_state.results = ResultCache::default();
+    forget_session_paths()
+}
+
+/// Run a query, emitting `query-progress` events until it finishes. Runs off
+/// the main thread so the window stays responsive.
+#[tauri::command(async)]
+pub fn execute_sql(sql: String, limits: Option<QueryLimits>, app: AppHandle, state: State<'_, SharedState>) -> Result<QueryResult, String> {
+    let masks = load_mask_rules()?;
+    let handle = start_query(&state, &sql, limits.unwrap_or_default().into())?;
+    
+    let mut table = wait_with_progress(handle, &app)?;
+    // Only the first rows of a spilled result are in memory; sending those
+    // alone would look like the whole result
+    if let Some(spilled) = &table.spilled {
+        return Err(format!(
+            "The result has {} rows, more than result_memory_mb holds in memory; \
+             page through it with execute_sql_paged",
+            spilled.row_count()
+        ));
+    }
+    masks.apply(&mut table);
+    Ok(table_to_result(&table))
+}
+
+/// Run a query like `execute_sql`, but return the result in the Arrow IPC
+/// stream format as a raw binary response, skipping the per-cell JSON
+/// conversion. Every row of a spilled result is sent, read back from disk a
+/// page at a time.
+#[tauri::command(async)]
+pub fn execute_sql_arrow(sql: String, limits: Option<QueryLimits>, app: AppHandle, state: State<'_, SharedState>) -> Result<Response, String> {
+    let masks = load_mask_rules()?;
+    let handle = start_query(&state, &sql, limits.unwrap_or_default().into())?;
+    
+    let mut table = wait_with_progress(handle, &app)?;
+    masks.apply(&mut table);
+    table_to_ipc(&table, &masks)
+        .map(Response::new)
+        .map_err(|e| e.to_string())
+}
+
+/// Run a query like `execute_sql`, but keep the result in the backend and
+/// return only `limit` rows from `offset`. Fetch the rest with
+/// `fetch_result_page` using the returned `query_id`, so large results are
+/// not sent to the webview all at once.
+#[tauri::command(async)]
+pub fn execute_sql_paged(sql: String, offset: usize, limit: usize, limits: Option<QueryLimits>, app: AppHandle, state: State<'_, SharedState>) -> Result<ResultPage, String> {
+    let handle = start_query(&state, &sql, limits.unwrap_or_default().into())?;
+    let table = wait_with_progress(handle, &app)?;
+    
+    let mut app_state = state.lock().map_err(|e| e.to_string())?;
+    let query_id = app_state.results.insert(table);
+    result_page(&app_state.results, query_id, offset, limit)
+}
+
+/// Rows `offset..offset + limit` of a result kept by `execute_sql_paged`
+#[tauri::command]
+pub fn fetch_result_page(query_id: u64, offset: usize, limit: usize, state: State<'_, SharedState>) -> Result<ResultPage, String> {
+    let app_state = state.lock().map_err(|e| e.to_string())?;
+    result_page(&app_state.results, query_id, offset, limit)
+}
+
+/// Drop a result kept by `execute_sql_paged` once it is no longer shown
+#[tauri::command]
+pub fn release_result(query_id: u64, state: State<'_, SharedState>) -> Result<(), String> {
+    let mut app_state = state.lock().map_err(|e| e.to_string())?;
+    app_state.results.remove(query_id);
     Ok(())
 }
 
+/// One page of the cached result `query_id`. Masks are applied to each page
+/// as it is fetched, so a spilled result read back from disk is masked too
+fn result_page(cache: &ResultCache, query_id: u64, offset: usize, limit: usize) -> Result<ResultPage, String> {
+    let table = cache.get(query_id)
+        .ok_or_else(|| format!("Result {} is no longer available; run the query again.", query_id))?;
+    let mut page = results::page(table, offset, limit)?;
+    load_mask_rules()?.apply(&mut page);
+    let QueryResult { columns, rows, .. } = table_to_result(&page);
+    
+    Ok(ResultPage {
+        query_id,
+        columns,
+        rows,
+        offset,
+        total_rows: table.total_row_count(),
+        truncated: table.truncated,
+    })
+}
+
+/// Wait for `handle`, emitting `query-progress` events until it finishes
+fn wait_with_progress(mut handle: QueryHandle, app: &AppHandle) -> Result<Table, String> {
+    loop {
+        if let Some(result) = handle.try_result() {
+            return result.map_err(|e| e.to_string());
+        }
+        
+        let progress = handle.progress();
+        let _ = app.emit("query-progress", QueryProgressEvent {
+            rows_scanned: progress.rows_scanned,
+            rows_produced: progress.rows_produced,
+            operator: progress.operator,
+            elapsed_ms: handle.elapsed().as_millis() as u64,
+        });
+        std::thread::sleep(PROGRESS_INTERVAL);
+    }
+}
+
 #[tauri::command]
-pub fn execute_sql(sql: String, state: State<'_, SharedState>) -> Result<QueryResult, String> {
+pub fn list_tables(state: State<'_, SharedState>) -> Result<Vec<String>, String> {
     let app_state = state.lock().map_err(|e| e.to_string())?;
     
     let ctx = app_state.context.as_ref()
-        .ok_or_else(|| "No data loaded. Please open a file or folder first.".to_string())?;
+        .ok_or_else(|| "No data loaded.".to_string())?;
     
-    let table = ctx.execute_sql(&sql).map_err(|e| e.to_string())?;
-    Ok(table_to_result(&table))
+    Ok(ctx.list_tables())
 }
 
+/// Like `list_tables`, with columns, size and an approximate row count per
+/// table. Counts come from file metadata, so no query is run.
 #[tauri::command]
-pub fn list_tables(state: State<'_, SharedState>) -> Result<Vec<String>, String> {
+pub fn list_tables_detailed(state: State<'_, SharedState>) -> Result<Vec<TableInfo>, String> {
     let app_state = state.lock().map_err(|e| e.to_string())?;
     
     let ctx = app_state.context.as_ref()
         .ok_or_else(|| "No data loaded.".to_string())?;
     
-    Ok(ctx.list_tables())
+    Ok(ctx.list_table_details().into_iter().map(|details| {
+        let columns = ctx.get_table_schema(&details.name)
+            .map(|schema| schema_to_columns(&schema))
+            .unwrap_or_default();
+        let layout = details.layout_summary();
+        TableInfo {
+            name: details.name,
+            columns,
+            format: details.format.map(str::to_string),
+            size_bytes: details.size_bytes,
+            row_count: details.row_count,
+            row_count_estimated: details.row_count_estimated,
+            layout,
+        }
+    }).collect())
 }
 
 #[tauri::command]
@@ -205,18 +601,97 @@ pub fn get_schema(table_name: String, state: State<'_, SharedState>) -> Result<V
     Ok(schema_to_columns(&schema))
 }
 
+/// Columns the query would return, without running it.
 #[tauri::command]
-pub fn get_table_preview(table_name: String, limit: i32, state: State<'_, SharedState>) -> Result<QueryResult, String> {
-    let sql = format!("SELECT * FROM \"{}\" LIMIT {}", table_name, limit);
+pub fn get_result_schema(sql: String, state: State<'_, SharedState>) -> Result<Vec<ColumnInfo>, String> {
+    let app_state = state.lock().map_err(|e| e.to_string())?;
+    
+    let ctx = app_state.context.as_ref()
+        .ok_or_else(|| "No data loaded.".to_string())?;
+    
+    let schema = ctx.infer_result_schema(&sql).map_err(|e| e.to_string())?;
+    
+    Ok(schema_to_columns(&schema))
+}
+
+/// Start `sql` on the loaded context and release the state lock, so other
+/// commands (a preview, the main query) can run while it does. `COPY` and
+/// `EXPORT` are refused while mask rules are active, since they write
+/// unmasked rows
+fn start_query(state: &State<'_, SharedState>, sql: &str, options: QueryOptions) -> Result<QueryHandle, String> {
+    load_mask_rules()?.check_statement(sql).map_err(|e| e.to_string())?;
     let app_state = state.lock().map_err(|e| e.to_string())?;
     
     let ctx = app_state.context.as_ref()
         .ok_or_else(|| "No data loaded. Please open a file or folder first.".to_string())?;
     
-    let table = ctx.execute_sql(&sql).map_err(|e| e.to_string())?;
+    Ok(ctx.execute_sql_async_with_options(sql, options))
+}
+
+#[tauri::command]
+pub fn get_table_preview(table_name: String, limit: i32, state: State<'_, SharedState>) -> Result<QueryResult, String> {
+    let sql = format!("SELECT * FROM {} LIMIT {}", knowhere::datafusion::quote_identifier(&table_name), limit);
+    let handle = start_query(&state, &sql, QueryOptions::default())?;
+    
+    let mut table = handle.wait().map_err(|e| e.to_string())?;
+    load_mask_rules()?.apply(&mut table);
     Ok(table_to_result(&table))
 }
 
+/// Execute a query and write its result into a SQLite database file,
+/// replacing any existing table with the same name. With `manifest`, also
+/// write a reproducibility manifest next to it (`results.db` gets
+/// `results.manifest.json`)
+#[tauri::command]
+pub fn export_sqlite(sql: String, path: String, table_name: String, manifest: bool, state: State<'_, SharedState>) -> Result<usize, String> {
+    let app_state = state.lock().map_err(|e| e.to_string())?;
+
+    let ctx = app_state.context.as_ref()
+        .ok_or_else(|| "No data loaded. Please open a file or folder first.".to_string())?;
+
+    let masks = load_mask_rules()?;
+    masks.check_statement(&sql).map_err(|e| e.to_string())?;
+    let mut table = ctx.execute_sql(&sql).map_err(|e| e.to_string())?;
+    masks.apply(&mut table);
+    let path = std::path::Path::new(&path);
+    let rows = knowhere::datafusion::write_sqlite(&table, path, &table_name, &masks)
+        .map_err(|e| e.to_string())?;
+
+    if manifest {
+        let query = knowhere::datafusion::ManifestQuery {
+            name: table_name,
+            sql,
+            row_count: rows as u64,
+        };
+        ctx.query_manifest(vec![query])
+            .and_then(|manifest| manifest.write(&path.with_extension("manifest.json")))
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(rows)
+}
+
+/// Execute a query and render its result in a text format (csv, json,
+/// ndjson, markdown, html or table), e.g. for copying to the clipboard
+#[tauri::command]
+pub fn render_result(sql: String, format: String, state: State<'_, SharedState>) -> Result<String, String> {
+    let max_col_width = {
+        let app_state = state.lock().map_err(|e| e.to_string())?;
+        app_state.context.as_ref().map(|ctx| ctx.settings().max_col_width).unwrap_or_default()
+    };
+    let mut renderer = knowhere::render::renderer_for(&format, max_col_width)
+        .ok_or_else(|| format!("Unknown output format: {}", format))?;
+
+    let handle = start_query(&state, &sql, QueryOptions::default())?;
+    let mut table = handle.wait().map_err(|e| e.to_string())?;
+    let masks = load_mask_rules()?;
+    masks.apply(&mut table);
+    // Spilled rows are read back from disk, so the copy has every row
+    let mut buf = Vec::new();
+    knowhere::render::render_all(renderer.as_mut(), &mut buf, &table, &masks).map_err(|e| e.to_string())?;
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
+
 // ============== Query Persistence Commands ==============
 
 /// Get the default queries directory path
@@ -244,7 +719,7 @@ pub fn save_query(path: String, sql: String, name: String) -> Result<(), String>
     fs::write(&path, &sql).map_err(|e| e.to_string())?;
     
     // Add to recent queries
-    let mut config = load_config();
+    let mut config = load_config()?;
     
     // Remove existing entry for this path if present
     config.recent_queries.retain(|q| q.path != path.to_string_lossy());
@@ -274,7 +749,7 @@ pub fn load_query(path: String) -> Result<String, String> {
     let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
     
     // Update recent queries
-    let mut config = load_config();
+    let mut config = load_config()?;
     
     // Move this query to the top if it exists, otherwise add it
     if let Some(idx) = config.recent_queries.iter().position(|q| q.path == path) {
@@ -306,15 +781,35 @@ pub fn load_query(path: String) -> Result<String, String> {
 /// Get recent queries
 #[tauri::command]
 pub fn get_recent_queries() -> Result<Vec<RecentQuery>, String> {
-    let config = load_config();
+    let config = load_config()?;
     Ok(config.recent_queries)
 }
 
 /// Clear recent queries
 #[tauri::command]
 pub fn clear_recent_queries() -> Result<(), String> {
-    let mut config = load_config();
+    let mut config = load_config()?;
     config.recent_queries.clear();
     save_config(&config)?;
     Ok(())
 }
+
+// ============== Snippet Commands ==============
+
+/// List built-in and saved snippets
+#[tauri::command]
+pub fn list_snippets() -> Result<Vec<Snippet>, String> {
+    Ok(snippets::list(&get_snippets_dir()?))
+}
+
+/// Save a snippet, replacing any saved snippet with the same name
+#[tauri::command]
+pub fn save_snippet(name: String, description: String, sql: String) -> Result<Snippet, String> {
+    snippets::save(&get_snippets_dir()?, &name, &description, &sql)
+}
+
+/// Delete a saved snippet. Built-in snippets cannot be deleted.
+#[tauri::command]
+pub fn delete_snippet(name: String) -> Result<(), String> {
+    snippets::delete(&get_snippets_dir()?, &name)
+}
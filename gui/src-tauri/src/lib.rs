@@ -1,4 +1,6 @@
 mod commands;
+mod results;
+mod snippets;
 
 use std::sync::{Arc, Mutex};
 use commands::{AppState, SharedState};
@@ -11,16 +13,31 @@ pub fn run() {
         .manage(Arc::new(Mutex::new(AppState::new())) as SharedState)
         .invoke_handler(tauri::generate_handler![
             commands::load_path,
+            commands::load_paths,
+            commands::get_load_report,
             commands::execute_sql,
+            commands::execute_sql_paged,
+            commands::fetch_result_page,
+            commands::release_result,
+            commands::execute_sql_arrow,
             commands::list_tables,
+            commands::list_tables_detailed,
             commands::get_schema,
+            commands::get_result_schema,
             commands::get_table_preview,
+            commands::export_sqlite,
+            commands::render_result,
             commands::get_queries_directory,
             commands::save_query,
             commands::load_query,
             commands::get_recent_queries,
             commands::clear_recent_queries,
             commands::clear_session,
+            commands::refresh_tables,
+            commands::restore_last_session,
+            commands::list_snippets,
+            commands::save_snippet,
+            commands::delete_snippet,
         ])
         .setup(|app| {
             if cfg!(debug_assertions) {
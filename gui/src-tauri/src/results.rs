@@ -0,0 +1,49 @@
+use std::collections::VecDeque;
+use knowhere::Table;
+
+/// Results kept for paging. Running another query drops the oldest, so an
+/// abandoned result does not stay in memory for the rest of the session.
+pub const MAX_CACHED_RESULTS: usize = 4;
+
+/// Query results held in the backend under an id, so that the frontend can
+/// fetch them a page at a time instead of receiving every row over IPC.
+#[derive(Default)]
+pub struct ResultCache {
+    results: VecDeque<(u64, Table)>,
+    next_id: u64,
+}
+
+impl ResultCache {
+    /// Keep `table` and return the id its pages are fetched with
+    pub fn insert(&mut self, table: Table) -> u64 {
+        self.next_id += 1;
+        if self.results.len() >= MAX_CACHED_RESULTS {
+            self.results.pop_front();
+        }
+        self.results.push_back((self.next_id, table));
+        self.next_id
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Table> {
+        self.results.iter().find(|(i, _)| *i == id).map(|(_, table)| table)
+    }
+
+    /// Drop the result with `id`. Returns whether it was still cached.
+    pub fn remove(&mut self, id: u64) -> bool {
+        let before = self.results.len();
+        self.results.retain(|(i, _)| *i != id);
+        self.results.len() < before
+    }
+}
+
+/// Up to `limit` rows of `table` starting at row `offset`, read back from
+/// disk when the result was spilled
+pub fn page(table: &Table, offset: usize, limit: usize) -> Result<Table, String> {
+    match &table.spilled {
+        Some(spilled) => {
+            let rows = spilled.rows(offset, limit).map_err(|e| e.to_string())?;
+            Ok(Table::with_rows(table.name.clone(), table.schema.clone(), rows))
+        }
+        None => Ok(table.slice(offset, limit)),
+    }
+}
@@ -0,0 +1,173 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+
+/// A reusable SQL template. Placeholders are written `{{name}}` and listed in
+/// `params` so the frontend can ask for table and column names before
+/// inserting the snippet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snippet {
+    pub name: String,
+    pub description: String,
+    pub sql: String,
+    pub params: Vec<String>,
+    /// Built-in snippets ship with the app and cannot be deleted
+    pub builtin: bool,
+}
+
+const BUILTIN_SNIPPETS: &[(&str, &str, &str)] = &[
+    (
+        "top_n_per_group",
+        "Top N rows within each group",
+        "SELECT *
+FROM (
+    SELECT *,
+        ROW_NUMBER() OVER (PARTITION BY {{group_column}} ORDER BY {{order_column}} DESC) AS rn
+    FROM {{table}}
+) ranked
+WHERE rn <= {{n}}
+ORDER BY {{group_column}}, rn",
+    ),
+    (
+        "pivot",
+        "Pivot two values of a column into separate columns",
+        "SELECT {{row_column}},
+    SUM(CASE WHEN {{pivot_column}} = '{{value_a}}' THEN {{value_column}} END) AS \"{{value_a}}\",
+    SUM(CASE WHEN {{pivot_column}} = '{{value_b}}' THEN {{value_column}} END) AS \"{{value_b}}\"
+FROM {{table}}
+GROUP BY {{row_column}}
+ORDER BY {{row_column}}",
+    ),
+    (
+        "dedup",
+        "Keep the latest row for each key",
+        "SELECT *
+FROM (
+    SELECT *,
+        ROW_NUMBER() OVER (PARTITION BY {{key_column}} ORDER BY {{order_column}} DESC) AS rn
+    FROM {{table}}
+) deduped
+WHERE rn = 1",
+    ),
+    (
+        "date_bucket",
+        "Count rows per day, week or month",
+        "SELECT date_trunc('{{unit}}', {{date_column}}) AS bucket, COUNT(*) AS rows
+FROM {{table}}
+GROUP BY bucket
+ORDER BY bucket",
+    ),
+];
+
+/// Placeholder names in order of first appearance
+fn placeholders(sql: &str) -> Vec<String> {
+    let mut params: Vec<String> = Vec::new();
+    let mut rest = sql;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else { break };
+        let name = after[..end].trim();
+        if !name.is_empty() && !params.iter().any(|p| p == name) {
+            params.push(name.to_string());
+        }
+        rest = &after[end + 2..];
+    }
+    params
+}
+
+fn builtin_snippets() -> Vec<Snippet> {
+    BUILTIN_SNIPPETS.iter().map(|(name, description, sql)| Snippet {
+        name: name.to_string(),
+        description: description.to_string(),
+        sql: sql.to_string(),
+        params: placeholders(sql),
+        builtin: true,
+    }).collect()
+}
+
+/// Snippet names become file names, so keep them to a safe character set
+fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return Err(format!(
+            "Invalid snippet name '{}': use letters, digits, '_' and '-' only.",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// User snippets are stored one per file as `<name>.sql`, with the
+/// description on a leading `-- ` comment line so they stay hand-editable.
+fn read_snippet(path: &Path) -> Option<Snippet> {
+    let name = path.file_stem()?.to_string_lossy().to_string();
+    let content = fs::read_to_string(path).ok()?;
+    let (description, sql) = match content.split_once('\n') {
+        Some((first, rest)) if first.starts_with("-- ") => {
+            (first[3..].trim().to_string(), rest.to_string())
+        }
+        _ => (String::new(), content),
+    };
+    Some(Snippet {
+        name,
+        description,
+        params: placeholders(&sql),
+        sql,
+        builtin: false,
+    })
+}
+
+/// Built-in snippets followed by the user's own, which are sorted by name. A
+/// user snippet with the same name as a built-in replaces it.
+pub fn list(dir: &Path) -> Vec<Snippet> {
+    let mut user: Vec<Snippet> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "sql"))
+                .filter_map(|path| read_snippet(&path))
+                .collect()
+        })
+        .unwrap_or_default();
+    user.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut snippets: Vec<Snippet> = builtin_snippets()
+        .into_iter()
+        .filter(|builtin| !user.iter().any(|s| s.name == builtin.name))
+        .collect();
+    snippets.extend(user);
+    snippets
+}
+
+pub fn save(dir: &Path, name: &str, description: &str, sql: &str) -> Result<Snippet, String> {
+    validate_name(name)?;
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+
+    let description = description.lines().next().unwrap_or("").trim();
+    let content = format!("-- {}\n{}", description, sql);
+    fs::write(snippet_path(dir, name), content).map_err(|e| e.to_string())?;
+
+    Ok(Snippet {
+        name: name.to_string(),
+        description: description.to_string(),
+        sql: sql.to_string(),
+        params: placeholders(sql),
+        builtin: false,
+    })
+}
+
+pub fn delete(dir: &Path, name: &str) -> Result<(), String> {
+    validate_name(name)?;
+    let path = snippet_path(dir, name);
+    if !path.exists() {
+        if builtin_snippets().iter().any(|s| s.name == name) {
+            return Err(format!("'{}' is a built-in snippet and cannot be deleted.", name));
+        }
+        return Err(format!("Snippet '{}' not found.", name));
+    }
+    fs::remove_file(&path).map_err(|e| e.to_string())
+}
+
+fn snippet_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{}.sql", name))
+}
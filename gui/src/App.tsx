@@ -1,26 +1,38 @@
 import { useState, useCallback, useRef, useEffect } from 'react';
-import { FolderOpen, Play, RefreshCw, GripHorizontal, Save, FileText, Clock, ChevronDown, Database, Trash2 } from 'lucide-react';
+import { FolderOpen, Play, RefreshCw, GripHorizontal, Save, FileText, Clock, ChevronDown, Database, Trash2, Download } from 'lucide-react';
 import { open, save } from '@tauri-apps/plugin-dialog';
+import { getCurrentWebview } from '@tauri-apps/api/webview';
 
 import { Sidebar } from './components/Sidebar';
 import { SqlEditor } from './components/Editor';
 import { ResultsTable } from './components/Results';
 import { StatusBar } from './components/StatusBar';
 import { useTheme } from './hooks/useTheme';
-import { loadPath, executeSql, getQueriesDirectory, saveQuery, loadQuery, getRecentQueries, clearSession } from './lib/api';
-import type { QueryResult, RecentQuery } from './lib/types';
+import { useQueryLimits } from './hooks/useQueryLimits';
+import { loadPath, loadPaths, executeSqlPaged, fetchResultPage, releaseResult, getQueriesDirectory, saveQuery, loadQuery, getRecentQueries, clearSession, exportSqlite, restoreLastSession, onQueryProgress } from './lib/api';
+import { quoteIdent } from './lib/format';
+import type { QueryProgress, QueryResult, RecentQuery } from './lib/types';
+
+/** Rows fetched from the backend at a time as the results are scrolled */
+const RESULT_PAGE_SIZE = 500;
 
 function App() {
   const { theme, toggleTheme } = useTheme();
+  const { limits, setLimit } = useQueryLimits();
   const [tables, setTables] = useState<string[]>([]);
   const [selectedTable, setSelectedTable] = useState<string | null>(null);
   const [query, setQuery] = useState('SELECT * FROM ');
   const [result, setResult] = useState<QueryResult | null>(null);
+  // Backend id of the shown result, and whether more of its rows are on the way
+  const resultIdRef = useRef<number | null>(null);
+  const fetchingRowsRef = useRef(false);
   const [error, setError] = useState<string | null>(null);
   const [isExecuting, setIsExecuting] = useState(false);
+  const [progress, setProgress] = useState<QueryProgress | null>(null);
   const [executionTime, setExecutionTime] = useState<number | null>(null);
   const [status, setStatus] = useState<'idle' | 'running' | 'success' | 'error'>('idle');
   const [loadingPath, setLoadingPath] = useState(false);
+  const [writeManifest, setWriteManifest] = useState(false);
 
   // Resizable split pane
   const [editorHeight, setEditorHeight] = useState(40);
@@ -32,11 +44,28 @@ function App() {
   const [showRecentDropdown, setShowRecentDropdown] = useState(false);
   const [currentQueryPath, setCurrentQueryPath] = useState<string | null>(null);
 
-  // Load recent queries on mount
+  // Load recent queries and restore the previous session's data on mount
   useEffect(() => {
     loadRecentQueries();
+    restoreSession();
   }, []);
 
+  const restoreSession = async () => {
+    try {
+      setLoadingPath(true);
+      const { outcomes, tables: tableList } = await restoreLastSession();
+      setTables(tableList);
+      const failures = outcomes.filter((o) => o.error);
+      if (failures.length > 0) {
+        setError(`Could not restore:\n${failures.map((o) => `${o.path}: ${o.error}`).join('\n')}`);
+      }
+    } catch (err) {
+      console.error('Failed to restore last session:', err);
+    } finally {
+      setLoadingPath(false);
+    }
+  };
+
   const loadRecentQueries = async () => {
     try {
       const queries = await getRecentQueries();
@@ -115,6 +144,42 @@ function App() {
     }
   }, []);
 
+  const handleDropPaths = useCallback(async (paths: string[]) => {
+    if (paths.length === 0) return;
+    try {
+      setLoadingPath(true);
+      setError(null);
+      const { outcomes, tables: tableList } = await loadPaths(paths);
+      setTables(tableList);
+      setResult(null);
+      const failures = outcomes.filter((o) => o.error);
+      if (failures.length > 0) {
+        setError(failures.map((o) => `${o.path}: ${o.error}`).join('\n'));
+        setStatus('error');
+      } else {
+        setStatus('idle');
+      }
+    } catch (err) {
+      console.error('Failed to load dropped paths:', err);
+      setError(String(err));
+      setStatus('error');
+    } finally {
+      setLoadingPath(false);
+    }
+  }, []);
+
+  // Load files and folders dropped onto the window
+  useEffect(() => {
+    const unlisten = getCurrentWebview().onDragDropEvent((event) => {
+      if (event.payload.type === 'drop') {
+        handleDropPaths(event.payload.paths);
+      }
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [handleDropPaths]);
+
   const handleOpenQuery = useCallback(async () => {
     try {
       const selected = await open({
@@ -176,18 +241,36 @@ function App() {
     }
   }, []);
 
+  // Progress events only arrive while a query is running
+  useEffect(() => {
+    const unlisten = onQueryProgress(setProgress);
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
   const handleExecute = useCallback(async () => {
     if (!query.trim() || isExecuting) return;
 
     setIsExecuting(true);
+    setProgress(null);
     setStatus('running');
     setError(null);
     const startTime = performance.now();
 
     try {
-      const queryResult = await executeSql(query);
+      const page = await executeSqlPaged(query, 0, RESULT_PAGE_SIZE, limits);
       const endTime = performance.now();
-      setResult(queryResult);
+      if (resultIdRef.current !== null) {
+        releaseResult(resultIdRef.current).catch(() => {});
+      }
+      resultIdRef.current = page.query_id;
+      setResult({
+        columns: page.columns,
+        rows: page.rows,
+        row_count: page.total_rows,
+        truncated: page.truncated,
+      });
       setExecutionTime(endTime - startTime);
       setStatus('success');
     } catch (err) {
@@ -198,12 +281,56 @@ function App() {
       setStatus('error');
     } finally {
       setIsExecuting(false);
+      setProgress(null);
     }
-  }, [query, isExecuting]);
+  }, [query, isExecuting, limits]);
+
+  // Fetch the rows of the shown result up to `index` as they scroll into view
+  const handleNeedRows = useCallback(async (index: number) => {
+    const queryId = resultIdRef.current;
+    if (queryId === null || !result || fetchingRowsRef.current) return;
+    const offset = result.rows.length;
+    if (index < offset || offset >= result.row_count) return;
+
+    fetchingRowsRef.current = true;
+    try {
+      const page = await fetchResultPage(queryId, offset, Math.max(RESULT_PAGE_SIZE, index - offset + 1));
+      setResult((prev) =>
+        prev && resultIdRef.current === page.query_id && prev.rows.length === page.offset
+          ? { ...prev, rows: [...prev.rows, ...page.rows] }
+          : prev
+      );
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      fetchingRowsRef.current = false;
+    }
+  }, [result]);
+
+  const handleExportSqlite = useCallback(async () => {
+    if (!query.trim()) return;
+
+    try {
+      const selected = await save({
+        title: 'Export Results to SQLite',
+        defaultPath: 'results.db',
+        filters: [
+          { name: 'SQLite Database', extensions: ['db', 'sqlite', 'sqlite3'] },
+        ],
+      });
+
+      if (selected) {
+        await exportSqlite(query, selected, 'results', writeManifest);
+      }
+    } catch (err) {
+      console.error('Failed to export results:', err);
+      setError(String(err));
+    }
+  }, [query, writeManifest]);
 
   const handleTableSelect = useCallback((tableName: string) => {
     setSelectedTable(tableName);
-    setQuery(`SELECT * FROM "${tableName}" LIMIT 100`);
+    setQuery(`SELECT * FROM ${quoteIdent(tableName)} LIMIT 100`);
   }, []);
 
   // Handle split pane dragging
@@ -354,6 +481,50 @@ function App() {
 
         <div className="flex-1" />
 
+        {([
+          ['timeout_secs', 'Timeout (s)', 'Cancel queries that run longer than this'],
+          ['max_memory_mb', 'Memory (MB)', 'Memory a query may use for sorts, joins and aggregations'],
+          ['max_rows', 'Max rows', 'Stop reading results after this many rows'],
+        ] as const).map(([name, label, title]) => (
+          <label
+            key={name}
+            className="flex items-center gap-1.5 text-xs text-[color:var(--text-muted)]"
+            title={title}
+          >
+            {label}
+            <input
+              type="number"
+              min={1}
+              placeholder="none"
+              value={limits[name] ?? ''}
+              onChange={(e) => setLimit(name, e.target.value)}
+              className="w-16 px-1 py-0.5 rounded bg-transparent border border-[color:var(--border)]"
+            />
+          </label>
+        ))}
+
+        <label
+          className="flex items-center gap-1.5 text-xs text-[color:var(--text-muted)]"
+          title="Also write a .manifest.json with the query, row count, source file checksums and knowhere version"
+        >
+          <input
+            type="checkbox"
+            checked={writeManifest}
+            onChange={(e) => setWriteManifest(e.target.checked)}
+          />
+          Manifest
+        </label>
+
+        <button
+          onClick={handleExportSqlite}
+          disabled={!isLoaded || isExecuting}
+          className="btn btn-secondary text-xs gap-1.5 disabled:opacity-50"
+          title="Run the query and write its results into a SQLite database"
+        >
+          <Download size={14} />
+          Export
+        </button>
+
         <button
           onClick={handleExecute}
           disabled={!isLoaded || isExecuting}
@@ -423,6 +594,8 @@ function App() {
               result={result}
               error={error}
               isLoading={isExecuting}
+              progress={progress}
+              onNeedRows={handleNeedRows}
             />
           </div>
         </div>
@@ -433,6 +606,7 @@ function App() {
         theme={theme}
         onToggleTheme={toggleTheme}
         rowCount={result?.row_count ?? null}
+        truncated={result?.truncated ?? false}
         executionTime={executionTime}
         status={status}
       />
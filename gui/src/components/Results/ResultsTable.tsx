@@ -1,15 +1,26 @@
-import { useRef, useMemo, useState, useCallback } from 'react';
+import { useRef, useMemo, useState, useCallback, useEffect } from 'react';
 import { useVirtualizer } from '@tanstack/react-virtual';
 import clsx from 'clsx';
-import type { QueryResult } from '../../lib/types';
+import type { QueryProgress, QueryResult } from '../../lib/types';
+import { formatCount } from '../../lib/format';
 
 interface ResultsTableProps {
     result: QueryResult | null;
     error: string | null;
     isLoading: boolean;
+    progress?: QueryProgress | null;
+    /** Called when row `index` is scrolled into view before it is fetched */
+    onNeedRows?: (index: number) => void;
 }
 
-export function ResultsTable({ result, error, isLoading }: ResultsTableProps) {
+function progressLabel(progress: QueryProgress | null | undefined): string {
+    if (!progress || progress.rows_scanned === 0) return 'Executing query...';
+
+    const label = `${formatCount(progress.rows_scanned)} rows scanned…`;
+    return progress.operator ? `${label} (${progress.operator})` : label;
+}
+
+export function ResultsTable({ result, error, isLoading, progress, onNeedRows }: ResultsTableProps) {
     const parentRef = useRef<HTMLDivElement>(null);
     const headerScrollRef = useRef<HTMLDivElement>(null);
 
@@ -18,12 +29,21 @@ export function ResultsTable({ result, error, isLoading }: ResultsTableProps) {
     const [resizing, setResizing] = useState<{ index: number; startX: number; startWidth: number } | null>(null);
 
     const rowVirtualizer = useVirtualizer({
-        count: result?.rows.length || 0,
+        count: result?.row_count || 0,
         getScrollElement: () => parentRef.current,
         estimateSize: () => 32,
         overscan: 10,
     });
 
+    // Ask for the rows of a paged result that have scrolled into view
+    const virtualRows = rowVirtualizer.getVirtualItems();
+    const lastVisibleRow = virtualRows.length > 0 ? virtualRows[virtualRows.length - 1].index : -1;
+    useEffect(() => {
+        if (result && onNeedRows && lastVisibleRow >= result.rows.length) {
+            onNeedRows(lastVisibleRow);
+        }
+    }, [result, onNeedRows, lastVisibleRow]);
+
     // Calculate initial column widths based on content
     const defaultColumnWidths = useMemo(() => {
         if (!result) return [];
@@ -85,7 +105,7 @@ export function ResultsTable({ result, error, isLoading }: ResultsTableProps) {
             <div className="h-full flex items-center justify-center text-[color:var(--text-muted)]">
                 <div className="flex items-center gap-2">
                     <div className="w-4 h-4 animate-spin rounded-full border-2 border-primary-500 border-t-transparent" />
-                    <span>Executing query...</span>
+                    <span>{progressLabel(progress)}</span>
                 </div>
             </div>
         );
@@ -113,7 +133,7 @@ export function ResultsTable({ result, error, isLoading }: ResultsTableProps) {
         );
     }
 
-    if (result.rows.length === 0) {
+    if (result.row_count === 0) {
         return (
             <div className="h-full flex items-center justify-center text-[color:var(--text-muted)]">
                 <div className="text-center">
@@ -181,9 +201,24 @@ export function ResultsTable({ result, error, isLoading }: ResultsTableProps) {
                         position: 'relative',
                     }}
                 >
-                    {rowVirtualizer.getVirtualItems().map((virtualRow) => {
+                    {virtualRows.map((virtualRow) => {
                         const row = result.rows[virtualRow.index];
                         const isEven = virtualRow.index % 2 === 0;
+                        if (!row) {
+                            return (
+                                <div
+                                    key={virtualRow.index}
+                                    className="absolute w-full px-3 py-1.5 text-sm italic"
+                                    style={{
+                                        height: `${virtualRow.size}px`,
+                                        transform: `translateY(${virtualRow.start}px)`,
+                                        color: 'var(--text-muted)',
+                                    }}
+                                >
+                                    Loading…
+                                </div>
+                            );
+                        }
                         return (
                             <div
                                 key={virtualRow.index}
@@ -1,9 +1,27 @@
-import { useState } from 'react';
-import { Table2, ChevronRight, ChevronDown, Database, Columns } from 'lucide-react';
-import { getSchema } from '../../lib/api';
-import type { ColumnInfo } from '../../lib/types';
+import { useEffect, useState } from 'react';
+import { Table2, ChevronRight, ChevronDown, Database, Columns, RefreshCw } from 'lucide-react';
+import { getSchema, listTablesDetailed, refreshTables } from '../../lib/api';
+import type { ColumnInfo, TableInfo } from '../../lib/types';
+import { formatBytes, formatCount } from '../../lib/format';
 import clsx from 'clsx';
 
+function tableStats(info: TableInfo | undefined): { label: string; title: string } | null {
+    if (!info) return null;
+
+    const parts: string[] = [];
+    if (info.row_count !== null) {
+        parts.push(`${info.row_count_estimated ? '~' : ''}${formatCount(info.row_count)} rows`);
+    }
+    if (info.size_bytes !== null) {
+        parts.push(formatBytes(info.size_bytes));
+    }
+
+    const details = [`${info.columns.length} columns`];
+    if (info.format) details.unshift(info.format.toUpperCase());
+    const title = [...details, ...parts].join(' · ');
+    return { label: parts.join(' · '), title: info.layout ? `${title}\n${info.layout}` : title };
+}
+
 interface SidebarProps {
     tables: string[];
     onTableSelect: (tableName: string) => void;
@@ -12,6 +30,30 @@ interface SidebarProps {
 
 export function Sidebar({ tables, onTableSelect, selectedTable }: SidebarProps) {
     const [expandedState, setExpandedState] = useState<Record<string, { expanded: boolean; columns: ColumnInfo[]; loading: boolean }>>({});
+    const [details, setDetails] = useState<Record<string, TableInfo>>({});
+    const [refreshing, setRefreshing] = useState(false);
+    // Bumped after a refresh so the details below are fetched again
+    const [version, setVersion] = useState(0);
+
+    // Row counts and sizes come from file metadata, so refetching is cheap
+    useEffect(() => {
+        if (tables.length === 0) {
+            setDetails({});
+            return;
+        }
+
+        let cancelled = false;
+        listTablesDetailed()
+            .then(infos => {
+                if (!cancelled) {
+                    setDetails(Object.fromEntries(infos.map(info => [info.name, info])));
+                }
+            })
+            .catch(error => console.error('Failed to load table details:', error));
+        return () => {
+            cancelled = true;
+        };
+    }, [tables, version]);
 
     // Build table items from props + local expanded state
     const items = tables.map(name => ({
@@ -19,6 +61,7 @@ export function Sidebar({ tables, onTableSelect, selectedTable }: SidebarProps)
         expanded: expandedState[name]?.expanded ?? false,
         columns: expandedState[name]?.columns ?? [],
         loading: expandedState[name]?.loading ?? false,
+        stats: tableStats(details[name]),
     }));
 
     async function toggleTable(tableName: string) {
@@ -52,6 +95,20 @@ export function Sidebar({ tables, onTableSelect, selectedTable }: SidebarProps)
         }
     }
 
+    async function handleRefresh() {
+        setRefreshing(true);
+        try {
+            await refreshTables();
+            // Schemas may have changed on disk
+            setExpandedState({});
+            setVersion(v => v + 1);
+        } catch (error) {
+            console.error('Failed to refresh tables:', error);
+        } finally {
+            setRefreshing(false);
+        }
+    }
+
     function handleTableClick(tableName: string) {
         onTableSelect(tableName);
     }
@@ -63,6 +120,14 @@ export function Sidebar({ tables, onTableSelect, selectedTable }: SidebarProps)
                 <div className="flex items-center gap-2 text-sm font-semibold text-[color:var(--text-primary)]">
                     <Database size={16} className="text-primary-500" />
                     <span>Explorer</span>
+                    <button
+                        onClick={handleRefresh}
+                        disabled={tables.length === 0 || refreshing}
+                        className="ml-auto p-1 rounded text-[color:var(--text-muted)] hover:bg-surface-100 dark:hover:bg-surface-800 disabled:opacity-40"
+                        title="Re-read every table from disk"
+                    >
+                        <RefreshCw size={14} className={clsx(refreshing && 'animate-spin')} />
+                    </button>
                 </div>
             </div>
 
@@ -99,6 +164,14 @@ export function Sidebar({ tables, onTableSelect, selectedTable }: SidebarProps)
                                         )}
                                         <Table2 size={14} className="text-accent-orange" />
                                         <span className="truncate">{table.name}</span>
+                                        {table.stats && (
+                                            <span
+                                                className="ml-auto shrink-0 text-2xs text-[color:var(--text-muted)] font-mono"
+                                                title={table.stats.title}
+                                            >
+                                                {table.stats.label}
+                                            </span>
+                                        )}
                                     </button>
 
                                     {table.expanded && table.columns.length > 0 && (
@@ -110,8 +183,12 @@ export function Sidebar({ tables, onTableSelect, selectedTable }: SidebarProps)
                                                 >
                                                     <Columns size={12} className="text-accent-blue" />
                                                     <span className="truncate">{col.name}</span>
-                                                    <span className="ml-auto text-2xs text-[color:var(--text-muted)] font-mono">
-                                                        {col.data_type}
+                                                    <span
+                                                        className="ml-auto text-2xs text-[color:var(--text-muted)] font-mono"
+                                                        title={col.data_type}
+                                                    >
+                                                        {col.source_type ?? col.data_type}
+                                                        {!col.nullable && ' NOT NULL'}
                                                     </span>
                                                 </div>
                                             ))}
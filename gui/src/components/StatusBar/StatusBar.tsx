@@ -5,11 +5,13 @@ interface StatusBarProps {
     theme: Theme;
     onToggleTheme: () => void;
     rowCount: number | null;
+    /** The result stopped at the `max_rows` limit */
+    truncated: boolean;
     executionTime: number | null;
     status: 'idle' | 'running' | 'success' | 'error';
 }
 
-export function StatusBar({ theme, onToggleTheme, rowCount, executionTime, status }: StatusBarProps) {
+export function StatusBar({ theme, onToggleTheme, rowCount, truncated, executionTime, status }: StatusBarProps) {
     return (
         <div className="h-7 flex items-center justify-between px-3 text-xs border-t bg-[color:var(--bg-secondary)]">
             {/* Left side */}
@@ -28,6 +30,15 @@ export function StatusBar({ theme, onToggleTheme, rowCount, executionTime, statu
                     </div>
                 )}
 
+                {status === 'success' && truncated && (
+                    <div
+                        className="text-amber-500"
+                        title="The query stopped at the max_rows limit; raise or clear it to see the rest"
+                    >
+                        (max_rows reached)
+                    </div>
+                )}
+
                 {status === 'error' && (
                     <div className="flex items-center gap-1.5 text-red-500">
                         <XCircle size={12} />
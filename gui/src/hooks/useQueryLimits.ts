@@ -0,0 +1,31 @@
+import { useState, useEffect } from 'react';
+import type { QueryLimits } from '../lib/types';
+
+const NO_LIMITS: QueryLimits = { timeout_secs: null, max_memory_mb: null, max_rows: null };
+
+export function useQueryLimits() {
+    const [limits, setLimits] = useState<QueryLimits>(() => {
+        if (typeof window !== 'undefined') {
+            const stored = localStorage.getItem('queryLimits');
+            if (stored) {
+                try {
+                    return { ...NO_LIMITS, ...JSON.parse(stored) };
+                } catch {
+                    // Fall through to no limits
+                }
+            }
+        }
+        return NO_LIMITS;
+    });
+
+    useEffect(() => {
+        localStorage.setItem('queryLimits', JSON.stringify(limits));
+    }, [limits]);
+
+    const setLimit = (name: keyof QueryLimits, value: string) => {
+        const parsed = parseInt(value, 10);
+        setLimits(prev => ({ ...prev, [name]: parsed > 0 ? parsed : null }));
+    };
+
+    return { limits, setLimit };
+}
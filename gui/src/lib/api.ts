@@ -1,26 +1,88 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { ColumnInfo, QueryResult, RecentQuery } from './types';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import { tableFromIPC } from 'apache-arrow';
+import type { ArrowResult, ColumnInfo, LoadPathsResult, LoadReport, QueryLimits, QueryProgress, QueryResult, RecentQuery, ResultPage, Snippet, TableInfo } from './types';
 
 export async function loadPath(path: string): Promise<string[]> {
     return invoke<string[]>('load_path', { path });
 }
 
-export async function executeSql(sql: string): Promise<QueryResult> {
-    return invoke<QueryResult>('execute_sql', { sql });
+export async function loadPaths(paths: string[]): Promise<LoadPathsResult> {
+    return invoke<LoadPathsResult>('load_paths', { paths });
+}
+
+/** Report of the most recent load: loaded tables, skipped files and warnings. */
+export async function getLoadReport(): Promise<LoadReport | null> {
+    return invoke<LoadReport | null>('get_load_report');
+}
+
+export async function executeSql(sql: string, limits?: QueryLimits): Promise<QueryResult> {
+    return invoke<QueryResult>('execute_sql', { sql, limits });
+}
+
+/**
+ * Run a query, keeping its result in the backend, and get `limit` rows from
+ * `offset`. Fetch more with `fetchResultPage` instead of sending every row
+ * over IPC at once.
+ */
+export async function executeSqlPaged(sql: string, offset: number, limit: number, limits?: QueryLimits): Promise<ResultPage> {
+    return invoke<ResultPage>('execute_sql_paged', { sql, offset, limit, limits });
+}
+
+export async function fetchResultPage(queryId: number, offset: number, limit: number): Promise<ResultPage> {
+    return invoke<ResultPage>('fetch_result_page', { queryId, offset, limit });
+}
+
+/** Free a result kept by `executeSqlPaged` once it is no longer shown. */
+export async function releaseResult(queryId: number): Promise<void> {
+    return invoke<void>('release_result', { queryId });
+}
+
+/**
+ * Run a query like `executeSql`, but receive the result as Arrow IPC bytes
+ * and decode them with arrow-js, skipping the per-cell JSON conversion.
+ */
+export async function executeSqlArrow(sql: string, limits?: QueryLimits): Promise<ArrowResult> {
+    const bytes = await invoke<ArrayBuffer>('execute_sql_arrow', { sql, limits });
+    const table = tableFromIPC(new Uint8Array(bytes));
+    return { table, truncated: table.schema.metadata.get('knowhere:truncated') === 'true' };
+}
+
+/** Subscribe to progress updates emitted while `executeSql` runs. */
+export async function onQueryProgress(handler: (progress: QueryProgress) => void): Promise<UnlistenFn> {
+    return listen<QueryProgress>('query-progress', (event) => handler(event.payload));
 }
 
 export async function listTables(): Promise<string[]> {
     return invoke<string[]>('list_tables');
 }
 
+export async function listTablesDetailed(): Promise<TableInfo[]> {
+    return invoke<TableInfo[]>('list_tables_detailed');
+}
+
 export async function getSchema(tableName: string): Promise<ColumnInfo[]> {
     return invoke<ColumnInfo[]>('get_schema', { tableName });
 }
 
+export async function getResultSchema(sql: string): Promise<ColumnInfo[]> {
+    return invoke<ColumnInfo[]>('get_result_schema', { sql });
+}
+
 export async function getTablePreview(tableName: string, limit: number = 100): Promise<QueryResult> {
     return invoke<QueryResult>('get_table_preview', { tableName, limit });
 }
 
+export async function exportSqlite(sql: string, path: string, tableName: string = 'results', manifest: boolean = false): Promise<number> {
+    return invoke<number>('export_sqlite', { sql, path, tableName, manifest });
+}
+
+export type RenderFormat = 'table' | 'csv' | 'json' | 'ndjson' | 'markdown' | 'html';
+
+export async function renderResult(sql: string, format: RenderFormat): Promise<string> {
+    return invoke<string>('render_result', { sql, format });
+}
+
 // Query persistence APIs
 
 export async function getQueriesDirectory(): Promise<string> {
@@ -43,6 +105,32 @@ export async function clearRecentQueries(): Promise<void> {
     return invoke<void>('clear_recent_queries');
 }
 
+/** Re-read one table, or every table, from disk. Resolves to the refreshed names. */
+export async function refreshTables(tableName?: string): Promise<string[]> {
+    return invoke<string[]>('refresh_tables', { tableName: tableName ?? null });
+}
+
 export async function clearSession(): Promise<void> {
     return invoke<void>('clear_session');
 }
+
+export async function restoreLastSession(): Promise<LoadPathsResult> {
+    return invoke<LoadPathsResult>('restore_last_session');
+}
+
+export async function listSnippets(): Promise<Snippet[]> {
+    return invoke<Snippet[]>('list_snippets');
+}
+
+export async function saveSnippet(name: string, description: string, sql: string): Promise<Snippet> {
+    return invoke<Snippet>('save_snippet', { name, description, sql });
+}
+
+export async function deleteSnippet(name: string): Promise<void> {
+    return invoke<void>('delete_snippet', { name });
+}
+
+/** Replace `{{param}}` placeholders in a snippet with the given values. */
+export function fillSnippet(snippet: Snippet, values: Record<string, string>): string {
+    return snippet.sql.replace(/\{\{\s*([^}]+?)\s*\}\}/g, (match, name: string) => values[name] ?? match);
+}
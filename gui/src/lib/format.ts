@@ -0,0 +1,23 @@
+/** Compact row count, e.g. `1.2k` or `3.4M`. */
+export function formatCount(count: number): string {
+    if (count < 1000) return String(count);
+    if (count < 1_000_000) return `${(count / 1000).toFixed(1)}k`;
+    return `${(count / 1_000_000).toFixed(1)}M`;
+}
+
+/** Human-readable byte size, e.g. `3.4 MB`. */
+export function formatBytes(bytes: number): string {
+    const units = ['B', 'KB', 'MB', 'GB', 'TB'];
+    let value = bytes;
+    let unit = 0;
+    while (value >= 1024 && unit < units.length - 1) {
+        value /= 1024;
+        unit++;
+    }
+    return unit === 0 ? `${value} B` : `${value.toFixed(1)} ${units[unit]}`;
+}
+
+/** `name` as a double-quoted SQL identifier, with embedded quotes doubled. */
+export function quoteIdent(name: string): string {
+    return `"${name.replace(/"/g, '""')}"`;
+}
@@ -1,17 +1,93 @@
+import type { Table as ArrowTable } from 'apache-arrow';
+
 export interface ColumnInfo {
     name: string;
     data_type: string;
+    nullable: boolean;
+    source_type: string | null;
+}
+
+/** A query result decoded from Arrow IPC by `executeSqlArrow`. */
+export interface ArrowResult {
+    table: ArrowTable;
+    /** The query stopped at its `max_rows` limit, so there were more rows. */
+    truncated: boolean;
 }
 
 export interface QueryResult {
     columns: ColumnInfo[];
     rows: (string | number | boolean | null)[][];
+    /** Rows in the whole result; a paged result may hold fewer in `rows`. */
     row_count: number;
+    /** The query stopped at its `max_rows` limit, so there were more rows. */
+    truncated?: boolean;
+}
+
+/** Rows `offset` onwards of a result kept in the backend by `executeSqlPaged`. */
+export interface ResultPage {
+    query_id: number;
+    columns: ColumnInfo[];
+    rows: (string | number | boolean | null)[][];
+    offset: number;
+    total_rows: number;
+    truncated: boolean;
+}
+
+/** Limits for one query; `null` falls back to the session's setting. */
+export interface QueryLimits {
+    timeout_secs: number | null;
+    max_memory_mb: number | null;
+    max_rows: number | null;
+}
+
+export interface QueryProgress {
+    rows_scanned: number;
+    rows_produced: number;
+    operator: string | null;
+    elapsed_ms: number;
 }
 
 export interface TableInfo {
     name: string;
     columns: ColumnInfo[];
+    format: string | null;
+    size_bytes: number | null;
+    row_count: number | null;
+    row_count_estimated: boolean;
+    layout: string | null;
+}
+
+export interface LoadOutcome {
+    path: string;
+    tables: string[];
+    error: string | null;
+}
+
+export interface LoadPathsResult {
+    outcomes: LoadOutcome[];
+    tables: string[];
+}
+
+export interface LoadedTableInfo {
+    name: string;
+    /** File name the table was named after, when it had to be sanitized */
+    original_name: string | null;
+    path: string;
+    format: string;
+    row_count: number | null;
+    row_count_estimated: boolean;
+    columns: ColumnInfo[];
+}
+
+export interface SkippedFile {
+    path: string;
+    reason: string;
+}
+
+export interface LoadReport {
+    loaded: LoadedTableInfo[];
+    skipped: SkippedFile[];
+    warnings: string[];
 }
 
 export interface RecentQuery {
@@ -21,4 +97,12 @@ export interface RecentQuery {
     timestamp: number;
 }
 
+export interface Snippet {
+    name: string;
+    description: string;
+    sql: string;
+    params: string[];
+    builtin: boolean;
+}
+
 export type Theme = 'light' | 'dark';
@@ -1,4 +1,10 @@
-use clap::Parser;
+use clap::{ArgGroup, Args, Parser, Subcommand};
+
+use crate::datafusion::DEFAULT_INFER_ROWS;
+use crate::render::{
+    BarRenderer, CsvRenderer, HtmlRenderer, JsonRenderer, MarkdownRenderer, NdjsonRenderer,
+    ResultRenderer, TableRenderer,
+};
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
@@ -8,19 +14,140 @@ use std::path::PathBuf;
     version,
     about = "A lightweight SQL engine for querying CSV and Parquet files"
 )]
+#[command(group(ArgGroup::new("statements").args(["query", "script", "replay"])))]
+#[command(subcommand_negates_reqs = true, args_conflicts_with_subcommands = true)]
 pub struct Cli {
-    /// Path to a CSV/Parquet file or folder containing data files
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Path to a CSV/Parquet file or folder containing data files, a quoted
+    /// glob pattern such as 'events/2024-*.parquet', or an s3://, gs:// or
+    /// http(s):// URI
     #[arg(required = true)]
-    pub path: PathBuf,
+    pub path: Option<PathBuf>,
 
-    /// Execute a SQL query directly (non-interactive mode)
+    /// Execute a SQL query directly (non-interactive mode); repeat to run
+    /// several queries in order
     #[arg(short, long)]
-    pub query: Option<String>,
+    pub query: Vec<String>,
+
+    /// Execute the semicolon-separated statements in a SQL script file
+    #[arg(short, long, value_name = "FILE")]
+    pub script: Option<PathBuf>,
+
+    /// Run the statements of a session recorded with `--record` in order,
+    /// e.g. against a newer copy of the data
+    #[arg(long, value_name = "FILE")]
+    pub replay: Option<PathBuf>,
+
+    /// Write every statement that succeeds, from queries, scripts, the REPL
+    /// or the TUI, to FILE in the order they ran, for `--replay`
+    #[arg(long, value_name = "FILE")]
+    pub record: Option<PathBuf>,
+
+    /// Expand `${VAR}` and `${VAR:-default}` environment variable references
+    /// in paths, queries and scripts
+    #[arg(long)]
+    pub expand_env: bool,
+
+    /// List the loaded tables with their format, size and row count, then exit
+    #[arg(long, visible_alias = "tables", conflicts_with_all = ["statements", "describe"])]
+    pub list_tables: bool,
 
-    /// Output format for non-interactive mode
+    /// Print the columns and types of a table, then exit
+    #[arg(long, value_name = "TABLE", conflicts_with = "statements")]
+    pub describe: Option<String>,
+
+    /// Read statements ending in `;` from the terminal or stdin and print
+    /// each result in `--format`, keeping tables and settings between them
+    #[arg(long, conflicts_with_all = ["statements", "list_tables", "describe"])]
+    pub repl: bool,
+
+    /// Mask result columns with the rules in FILE before printing, exporting
+    /// or showing them in the TUI
+    #[arg(long, value_name = "FILE")]
+    pub mask: Option<PathBuf>,
+
+    /// Output format for non-interactive mode; `json-pretty` indents JSON,
+    /// `jsonl` is another name for `ndjson` and `bars` draws the last column
+    /// as a bar chart, e.g. of `histogram(...)`
     #[arg(short, long, default_value = "table")]
     pub format: OutputFormat,
 
+    /// Add `_row_id` (and `_source_file` for folders) pseudo-columns to every
+    /// loaded table
+    #[arg(long)]
+    pub provenance: bool,
+
+    /// Rename columns on load to trimmed, lowercase names with `_` in place of
+    /// spaces and symbols, e.g. "Total Sales ($)" to total_sales
+    #[arg(long)]
+    pub normalize_headers: bool,
+
+    /// Convert text columns that hold dates, Y/N-style booleans or numbers
+    /// with thousands separators to those types
+    #[arg(long)]
+    pub refine_types: bool,
+
+    /// Read CSV amounts such as "$1,234.56", "(500)" and "12%" as numbers
+    #[arg(long)]
+    pub lenient_numbers: bool,
+
+    /// Worksheet to load from Excel workbooks, by name or by position
+    /// counting from 1; the first sheet by default
+    #[arg(long, value_name = "SHEET")]
+    pub sheet: Option<String>,
+
+    /// Treat CSV files as growing logs: each query parses only newly appended
+    /// lines. Cannot be combined with the CSV read options
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "lenient_numbers",
+            "delimiter",
+            "no_header",
+            "infer_rows",
+            "date_format",
+            "decimal_separator",
+            "column_type",
+        ]
+    )]
+    pub tail: bool,
+
+    /// Only load the rows of Delta and Iceberg tables that match a SQL
+    /// predicate, e.g. "date >= '2024-01-01'"; partitions and files it rules
+    /// out are never read
+    #[arg(long, value_name = "PREDICATE")]
+    pub filter: Option<String>,
+
+    /// Load the files of a folder or glob pattern as one table named after
+    /// the folder; they must have the same columns and types
+    #[arg(long)]
+    pub union: bool,
+
+    /// Reject syntax from other SQL dialects (backtick identifiers, TOP n,
+    /// LIMIT offset, count) instead of translating it with a warning
+    #[arg(long)]
+    pub strict_sql: bool,
+
+    /// Cancel a query that runs for longer than this many seconds
+    #[arg(long, value_name = "SECS")]
+    pub timeout: Option<u64>,
+
+    /// Memory that a query's sorts, joins and aggregations may use, in
+    /// megabytes; sorts spill to disk at the limit and other queries fail
+    #[arg(long, value_name = "MB")]
+    pub max_memory_mb: Option<usize>,
+
+    /// Stop reading a query result after this many rows
+    #[arg(long, value_name = "N")]
+    pub max_rows: Option<usize>,
+
+    /// Print a report of loaded and skipped files, with inferred schemas, and
+    /// how many files and row groups each query scanned or pruned
+    #[arg(short, long)]
+    pub verbose: bool,
+
     /// CSV delimiter (only for CSV files)
     #[arg(short, long, default_value = ",")]
     pub delimiter: char,
@@ -28,6 +155,114 @@ pub struct Cli {
     /// Disable CSV header detection
     #[arg(long)]
     pub no_header: bool,
+
+    /// Rows of each CSV file to infer column types from, or 0 for every row
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_INFER_ROWS)]
+    pub infer_rows: usize,
+
+    /// Read CSV text columns as dates or timestamps when every value matches
+    /// this chrono format, e.g. "%d/%m/%Y"; may be repeated
+    #[arg(long, value_name = "FORMAT")]
+    pub date_format: Vec<String>,
+
+    /// Decimal separator of CSV numbers; with "," numbers are read as in
+    /// "1.234,5"
+    #[arg(long, value_name = "SEP", default_value = ".", value_parser = [".", ","])]
+    pub decimal_separator: String,
+
+    /// Read a CSV column as a type instead of inferring it, e.g. "zip:string";
+    /// types are string, int, float, bool, date and timestamp. May be repeated
+    #[arg(long, value_name = "COLUMN:TYPE")]
+    pub column_type: Vec<String>,
+
+    /// Write query results to a file instead of stdout (.csv, .json/.jsonl/.ndjson,
+    /// .parquet, or SQLite: .db, .sqlite, .sqlite3)
+    #[arg(short, long, requires = "statements")]
+    pub output: Option<PathBuf>,
+
+    /// Also write a JSON manifest of the queries, row counts, source file
+    /// checksums and knowhere version, to reproduce or verify the results
+    #[arg(long, value_name = "FILE", requires = "statements")]
+    pub manifest: Option<PathBuf>,
+
+    /// Table name to create when writing results into a SQLite database
+    #[arg(long = "table", default_value = "results")]
+    pub output_table: String,
+
+    /// Maximum width of a rendered column before values are truncated
+    #[arg(long, default_value_t = crate::render::DEFAULT_MAX_COL_WIDTH)]
+    pub max_col_width: usize,
+
+    /// Warn in the TUI when a result uses more than this many megabytes
+    #[arg(long, default_value_t = 512)]
+    pub memory_warning_mb: usize,
+
+    /// Spill TUI results that use more than this many megabytes to a
+    /// temporary file and page through them from disk (0 keeps them all in
+    /// memory)
+    #[arg(long, default_value_t = 512)]
+    pub result_memory_mb: usize,
+
+    /// File the TUI keeps its query history in between sessions
+    /// [default: $HOME/knowhere/history]
+    #[arg(long, value_name = "FILE")]
+    pub history_file: Option<PathBuf>,
+
+    /// Row limit applied by the TUI `:limit` command
+    #[arg(long, default_value_t = 10_000)]
+    pub row_limit: usize,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Write a synthetic dataset, e.g. for benchmarks and demos
+    Generate(GenerateArgs),
+    /// Write a small random sample of a table, optionally anonymized, e.g.
+    /// to attach to a bug report
+    Sample(SampleArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct GenerateArgs {
+    /// Number of rows, e.g. 1000, 1_000_000 or 1e6
+    #[arg(long, default_value = "1000")]
+    pub rows: String,
+
+    /// Comma-separated `name:type[:distribution]` columns. Types: int, float,
+    /// string, bool, date, timestamp. Distributions: uniform (default), seq,
+    /// skewed, normal, unique
+    #[arg(long)]
+    pub schema: String,
+
+    /// Seed for the random values; the same seed gives the same data
+    #[arg(long, default_value_t = 42)]
+    pub seed: u64,
+
+    /// File to write (.csv, .json/.jsonl/.ndjson or .parquet)
+    pub output: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct SampleArgs {
+    /// Data file or folder to sample from
+    pub input: PathBuf,
+
+    /// File to write (.csv, .json/.jsonl/.ndjson, or SQLite: .db, .sqlite,
+    /// .sqlite3)
+    pub output: PathBuf,
+
+    /// Number of rows to pick at random
+    #[arg(long, default_value_t = 100)]
+    pub rows: usize,
+
+    /// Comma-separated columns to anonymize, each optionally followed by
+    /// `:hash`, `:redact` or `:fake` (the default), e.g. `email,name:hash`
+    #[arg(long, value_name = "COLUMNS")]
+    pub anonymize: Option<String>,
+
+    /// Table to sample when the input holds several
+    #[arg(long)]
+    pub table: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, Default, clap::ValueEnum)]
@@ -36,6 +271,28 @@ pub enum OutputFormat {
     Table,
     Csv,
     Json,
+    JsonPretty,
+    #[value(alias = "jsonl")]
+    Ndjson,
+    Markdown,
+    Html,
+    #[value(alias = "histogram")]
+    Bars,
+}
+
+impl OutputFormat {
+    pub fn renderer(self, max_col_width: usize) -> Box<dyn ResultRenderer> {
+        match self {
+            OutputFormat::Table => Box::new(TableRenderer::new(max_col_width)),
+            OutputFormat::Csv => Box::new(CsvRenderer::new()),
+            OutputFormat::Json => Box::new(JsonRenderer::new()),
+            OutputFormat::JsonPretty => Box::new(JsonRenderer::pretty()),
+            OutputFormat::Ndjson => Box::new(NdjsonRenderer::new()),
+            OutputFormat::Markdown => Box::new(MarkdownRenderer::new()),
+            OutputFormat::Html => Box::new(HtmlRenderer::new()),
+            OutputFormat::Bars => Box::new(BarRenderer::default()),
+        }
+    }
 }
 
 impl Cli {
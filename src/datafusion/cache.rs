@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use datafusion::common::TableReference;
+use datafusion::datasource::MemTable;
+use datafusion::execution::context::SessionContext;
+
+use crate::storage::table::{Column, DataType, Row, Schema, Table, Value};
+
+use super::error::{DataFusionError, Result};
+use super::rewrite::sql_to_dataframe;
+
+/// A parsed `CACHE TABLE <name> AS <query>` statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct CacheTable<'a> {
+    pub(super) name: TableReference,
+    pub(super) query: &'a str,
+}
+
+/// Recognise `CACHE TABLE name AS query`, which DataFusion's parser does not
+/// know. Keywords are case-insensitive. Like other identifiers, `name` is
+/// lowercased unless it is double-quoted.
+pub(super) fn parse_cache_table(sql: &str) -> Option<CacheTable<'_>> {
+    let rest = strip_keyword(sql.trim_start(), "CACHE")?;
+    let rest = strip_keyword(rest, "TABLE")?;
+    parse_name_as_query(rest).map(|(name, query)| CacheTable { name, query })
+}
+
+/// Split `name AS query` into the table name and the query, lowercasing an
+/// unquoted name.
+pub(super) fn parse_name_as_query(text: &str) -> Option<(TableReference, &str)> {
+    let (name, rest) = if let Some(quoted) = text.strip_prefix('"') {
+        let end = quoted.find('"')?;
+        (TableReference::bare(&quoted[..end]), &quoted[end + 1..])
+    } else {
+        let end = text.find(char::is_whitespace).unwrap_or(text.len());
+        (
+            TableReference::bare(text[..end].to_lowercase()),
+            &text[end..],
+        )
+    };
+    let query = strip_keyword(rest.trim_start(), "AS")?;
+    let query = query.trim().trim_end_matches(';').trim_end();
+
+    (!name.table().is_empty() && !query.is_empty()).then_some((name, query))
+}
+
+pub(super) fn strip_keyword<'a>(text: &'a str, keyword: &str) -> Option<&'a str> {
+    let head = text.get(..keyword.len())?;
+    let rest = &text[keyword.len()..];
+    (head.eq_ignore_ascii_case(keyword) && rest.starts_with(char::is_whitespace))
+        .then(|| rest.trim_start())
+}
+
+/// Run `cache.query` and register its result in memory as `cache.name`.
+/// Returns a one-row table with the name and row count.
+pub(super) async fn cache_table(
+    session: &SessionContext,
+    cache: CacheTable<'_>,
+    strict: bool,
+) -> Result<Table> {
+    if session.table_exist(cache.name.clone())? {
+        return Err(DataFusionError::TableAlreadyExists(cache.name.to_string()));
+    }
+
+    let (table, rows) = query_to_memtable(session, cache.query, strict).await?;
+    session.register_table(cache.name.clone(), Arc::new(table))?;
+    Ok(stored_table_summary(cache.name.table(), rows))
+}
+
+/// Run `query` and collect its whole result into a [`MemTable`], returning
+/// it with its row count.
+pub(super) async fn query_to_memtable(
+    session: &SessionContext,
+    query: &str,
+    strict: bool,
+) -> Result<(MemTable, usize)> {
+    let df = sql_to_dataframe(session, query, strict).await?;
+    let logical_schema = Arc::new(df.schema().as_arrow().clone());
+    let batches = df.collect().await?;
+    let rows: usize = batches.iter().map(|batch| batch.num_rows()).sum();
+    let schema = batches
+        .first()
+        .map(|batch| batch.schema())
+        .unwrap_or(logical_schema);
+
+    Ok((MemTable::try_new(schema, vec![batches])?, rows))
+}
+
+/// The one-row `table_name`/`rows` result of storing a query's result.
+pub(super) fn stored_table_summary(name: &str, rows: usize) -> Table {
+    let schema = Schema::new(vec![
+        Column::new("table_name", DataType::String),
+        Column::new("rows", DataType::Integer),
+    ]);
+    Table::with_rows(
+        "result",
+        schema,
+        vec![Row::new(vec![
+            Value::String(name.to_string()),
+            Value::Integer(rows as i64),
+        ])],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cache_table() {
+        let cache = parse_cache_table("cache table Big_Orders AS\nSELECT * FROM orders;").unwrap();
+        assert_eq!(cache.name.table(), "big_orders");
+        assert_eq!(cache.query, "SELECT * FROM orders");
+
+        let cache = parse_cache_table("CACHE TABLE \"my orders\" as SELECT 1").unwrap();
+        assert_eq!(cache.name.table(), "my orders");
+
+        assert!(parse_cache_table("SELECT * FROM cache_table").is_none());
+        assert!(parse_cache_table("CACHE TABLE t").is_none());
+        assert!(parse_cache_table("CACHE TABLE t AS").is_none());
+    }
+}
@@ -0,0 +1,292 @@
+use std::sync::Arc;
+
+use arrow::datatypes::DataType as ArrowType;
+use datafusion::common::config::ConfigOptions;
+use datafusion::common::tree_node::{Transformed, TreeNode};
+use datafusion::common::{plan_err, DFSchema, Result as DFResult, ScalarValue};
+use datafusion::logical_expr::expr::{Between, InList};
+use datafusion::logical_expr::expr_rewriter::NamePreserver;
+use datafusion::logical_expr::utils::merge_schema;
+use datafusion::logical_expr::{BinaryExpr, Expr, ExprSchemable, LogicalPlan, Operator};
+use datafusion::optimizer::analyzer::Analyzer;
+use datafusion::optimizer::AnalyzerRule;
+
+use super::refine::parse_bool;
+
+/// Names of the analyzer rules whose errors are reported as they are,
+/// without the "<rule> caused by" context DataFusion wraps them in.
+pub(super) const RULE_NAMES: [&str; 1] = ["coerce_literals"];
+
+/// DataFusion's analyzer rules with [`CoerceLiterals`] run first.
+pub(super) fn analyzer_rules() -> Vec<Arc<dyn AnalyzerRule + Send + Sync>> {
+    let mut rules: Vec<Arc<dyn AnalyzerRule + Send + Sync>> = vec![Arc::new(CoerceLiterals)];
+    rules.extend(Analyzer::new().rules);
+    rules
+}
+
+/// Converts a literal compared with a numeric or boolean expression to
+/// that expression's type, in comparisons, `IN` lists and `BETWEEN`:
+///
+/// - a string compared with a number is parsed as a number, so
+///   `salary = '95000'` matches `95000.0` and `age > '100'` compares
+///   numerically; a string that is not a number is an error rather than
+///   a comparison of text;
+/// - a string compared with a boolean is read as `true`/`false`, `t`/`f`,
+///   `yes`/`no` or `y`/`n`;
+/// - an integer compared with a boolean must be `1` or `0`.
+///
+/// DataFusion would otherwise compare a number with a string as text, and
+/// fail to compare a boolean with a string or an integer. Integers and
+/// floats, and dates and strings, are already coerced by DataFusion.
+#[derive(Debug, Default)]
+pub(super) struct CoerceLiterals;
+
+impl AnalyzerRule for CoerceLiterals {
+    fn name(&self) -> &str {
+        RULE_NAMES[0]
+    }
+
+    fn analyze(&self, plan: LogicalPlan, _config: &ConfigOptions) -> DFResult<LogicalPlan> {
+        plan.transform_up_with_subqueries(|plan| {
+            let schema = merge_schema(&plan.inputs());
+            let names = NamePreserver::new(&plan);
+            plan.map_expressions(|expr| {
+                let name = names.save(&expr);
+                expr.transform_up(|expr| coerce(expr, &schema))
+                    .map(|expr| expr.update_data(|expr| name.restore(expr)))
+            })
+        })
+        .map(|plan| plan.data)
+    }
+}
+
+fn coerce(expr: Expr, schema: &DFSchema) -> DFResult<Transformed<Expr>> {
+    match expr {
+        Expr::BinaryExpr(BinaryExpr { left, op, right }) if is_comparison(op) => {
+            let (left, right, transformed) =
+                match (target_type(&left, schema), target_type(&right, schema)) {
+                    (Some(target), _) => {
+                        let right = convert(*right, &target, &left)?;
+                        (left, Box::new(right.data), right.transformed)
+                    }
+                    (None, Some(target)) => {
+                        let left = convert(*left, &target, &right)?;
+                        (Box::new(left.data), right, left.transformed)
+                    }
+                    (None, None) => (left, right, false),
+                };
+            let expr = Expr::BinaryExpr(BinaryExpr { left, op, right });
+            Ok(Transformed::new_transformed(expr, transformed))
+        }
+        Expr::InList(InList {
+            expr,
+            list,
+            negated,
+        }) => {
+            let (list, transformed) = match target_type(&expr, schema) {
+                Some(target) => {
+                    let items = list
+                        .into_iter()
+                        .map(|item| convert(item, &target, &expr))
+                        .collect::<DFResult<Vec<_>>>()?;
+                    let transformed = items.iter().any(|item| item.transformed);
+                    (
+                        items.into_iter().map(|item| item.data).collect(),
+                        transformed,
+                    )
+                }
+                None => (list, false),
+            };
+            let expr = Expr::InList(InList {
+                expr,
+                list,
+                negated,
+            });
+            Ok(Transformed::new_transformed(expr, transformed))
+        }
+        Expr::Between(Between {
+            expr,
+            negated,
+            low,
+            high,
+        }) => {
+            let (low, high, transformed) = match target_type(&expr, schema) {
+                Some(target) => {
+                    let low = convert(*low, &target, &expr)?;
+                    let high = convert(*high, &target, &expr)?;
+                    let transformed = low.transformed || high.transformed;
+                    (Box::new(low.data), Box::new(high.data), transformed)
+                }
+                None => (low, high, false),
+            };
+            let expr = Expr::Between(Between {
+                expr,
+                negated,
+                low,
+                high,
+            });
+            Ok(Transformed::new_transformed(expr, transformed))
+        }
+        expr => Ok(Transformed::no(expr)),
+    }
+}
+
+fn is_comparison(op: Operator) -> bool {
+    matches!(
+        op,
+        Operator::Eq
+            | Operator::NotEq
+            | Operator::Lt
+            | Operator::LtEq
+            | Operator::Gt
+            | Operator::GtEq
+            | Operator::IsDistinctFrom
+            | Operator::IsNotDistinctFrom
+    )
+}
+
+/// The type of `expr` if literals compared with it may need converting: a
+/// numeric or boolean expression that is not itself a literal.
+fn target_type(expr: &Expr, schema: &DFSchema) -> Option<ArrowType> {
+    if matches!(expr, Expr::Literal(..)) {
+        return None;
+    }
+    let data_type = expr.get_type(schema).ok()?;
+    (data_type.is_numeric() || data_type == ArrowType::Boolean).then_some(data_type)
+}
+
+/// `expr` converted to `target`, the type of `other`, if it is a literal
+/// that DataFusion would compare as a different type. Anything else comes
+/// back untransformed.
+fn convert(expr: Expr, target: &ArrowType, other: &Expr) -> DFResult<Transformed<Expr>> {
+    let Expr::Literal(value, metadata) = expr else {
+        return Ok(Transformed::no(expr));
+    };
+    let converted = match (&value, target) {
+        (
+            ScalarValue::Utf8(Some(text))
+            | ScalarValue::LargeUtf8(Some(text))
+            | ScalarValue::Utf8View(Some(text)),
+            _,
+        ) => Some(parse_literal(text, target, other)?),
+        (value, ArrowType::Boolean) if value.data_type().is_integer() => {
+            match value.to_string().as_str() {
+                "1" => Some(ScalarValue::Boolean(Some(true))),
+                "0" => Some(ScalarValue::Boolean(Some(false))),
+                "NULL" => None,
+                number => {
+                    return plan_err!(
+                    "Cannot compare boolean {} with {}: only 1 and 0 can stand for true and false",
+                    other,
+                    number
+                )
+                }
+            }
+        }
+        _ => None,
+    };
+    Ok(match converted {
+        Some(converted) => Transformed::yes(Expr::Literal(converted, metadata)),
+        None => Transformed::no(Expr::Literal(value, metadata)),
+    })
+}
+
+fn parse_literal(text: &str, target: &ArrowType, other: &Expr) -> DFResult<ScalarValue> {
+    let trimmed = text.trim();
+    if *target == ArrowType::Boolean {
+        return match parse_bool(trimmed) {
+            Some(value) => Ok(ScalarValue::Boolean(Some(value))),
+            None => plan_err!(
+                "Cannot compare boolean {} with '{}': expected true/false, t/f, yes/no or y/n",
+                other,
+                text
+            ),
+        };
+    }
+    if target.is_integer() {
+        if let Ok(value) = trimmed.parse::<i64>() {
+            return Ok(ScalarValue::Int64(Some(value)));
+        }
+    }
+    match trimmed.parse::<f64>() {
+        Ok(value) if value.is_finite() => Ok(ScalarValue::Float64(Some(value))),
+        _ => plan_err!(
+            "Cannot compare {} ({}) with '{}': the value is not a number",
+            other,
+            target,
+            text
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_literal() {
+        let column = Expr::Column("age".into());
+        assert_eq!(
+            parse_literal(" 30 ", &ArrowType::Int64, &column).unwrap(),
+            ScalarValue::Int64(Some(30))
+        );
+        assert_eq!(
+            parse_literal("30.5", &ArrowType::Int32, &column).unwrap(),
+            ScalarValue::Float64(Some(30.5))
+        );
+        assert_eq!(
+            parse_literal("Yes", &ArrowType::Boolean, &column).unwrap(),
+            ScalarValue::Boolean(Some(true))
+        );
+        assert!(parse_literal("abc", &ArrowType::Float64, &column).is_err());
+        assert!(parse_literal("maybe", &ArrowType::Boolean, &column).is_err());
+    }
+
+    #[test]
+    fn test_coerce_reports_only_converted_literals() {
+        let schema = DFSchema::try_from(arrow::datatypes::Schema::new(vec![
+            arrow::datatypes::Field::new("age", ArrowType::Int64, true),
+        ]))
+        .unwrap();
+        let age = || Expr::Column("age".into());
+        let compare = |right: Expr| {
+            Expr::BinaryExpr(BinaryExpr {
+                left: Box::new(age()),
+                op: Operator::Gt,
+                right: Box::new(right),
+            })
+        };
+
+        let typed = coerce(
+            compare(Expr::Literal(ScalarValue::Int64(Some(30)), None)),
+            &schema,
+        )
+        .unwrap();
+        assert!(!typed.transformed);
+        assert_eq!(
+            typed.data,
+            compare(Expr::Literal(ScalarValue::Int64(Some(30)), None))
+        );
+
+        let text = coerce(
+            compare(Expr::Literal(
+                ScalarValue::Utf8(Some("30".to_string())),
+                None,
+            )),
+            &schema,
+        )
+        .unwrap();
+        assert!(text.transformed);
+        assert_eq!(
+            text.data,
+            compare(Expr::Literal(ScalarValue::Int64(Some(30)), None))
+        );
+
+        let list = Expr::InList(InList {
+            expr: Box::new(age()),
+            list: vec![Expr::Literal(ScalarValue::Int64(Some(1)), None)],
+            negated: false,
+        });
+        assert!(!coerce(list, &schema).unwrap().transformed);
+    }
+}
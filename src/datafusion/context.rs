@@ -1,24 +1,71 @@
-use datafusion::catalog::TableProviderFactory;
+use arrow::record_batch::RecordBatch;
+use datafusion::catalog::{SchemaProvider, TableProviderFactory};
 use datafusion::datasource::file_format::options::NdJsonReadOptions;
+use datafusion::datasource::MemTable;
+use datafusion::datasource::{TableType, ViewTable};
 use datafusion::execution::context::SessionContext;
 use datafusion::execution::session_state::SessionStateBuilder;
+use datafusion::physical_plan::analyze::AnalyzeExec;
+use datafusion::physical_plan::execute_stream;
 use datafusion::prelude::*;
+use datafusion::sql::parser::Statement as DFStatement;
+use datafusion::sql::sqlparser::ast::visit_relations;
+use futures::StreamExt;
+#[cfg(feature = "iceberg")]
 use iceberg_datafusion::IcebergTableProviderFactory;
 use std::collections::HashMap;
+use std::ops::ControlFlow;
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use tokio::runtime::Runtime;
 
-use crate::storage::table::Table;
+use crate::render::MaskRules;
+use crate::storage::table::{Column, DataType, Row, Schema, Table, Value, SPILL_PAGE_ROWS};
 
-use super::conversion::record_batch_to_table;
+use super::cache::{cache_table, parse_cache_table};
+use super::coercion::analyzer_rules;
+use super::conversion::{convert_schema, record_batch_to_table};
+use super::csv_options::CsvOptions;
+use super::dialect::translate;
 use super::error::{DataFusionError, Result};
+use super::functions::register_functions;
+use super::headers::{HeaderNormalization, RenamedColumn};
+use super::loader::is_remote_path;
+use super::manifest::{fingerprint, ManifestQuery, ManifestSource, QueryManifest};
+use super::metadata::{SourceKind, TableDetails, TableSource};
+use super::names::quote_identifier;
+use super::provenance::{add_provenance_columns, Provenance};
+use super::pruning::PruningStats;
+use super::query::{ProgressTracker, QueryHandle, QueryOptions};
+use super::refine::{
+    apply_refinements, detect_lenient_numbers, detect_refinements, TypeRefinement,
+};
+#[cfg(feature = "remote")]
+use super::remote::{object_store_for, parse_remote_uri, store_url};
+use super::rewrite::{sql_to_dataframe, sql_to_plan, strip_digit_separators};
+use super::settings::{
+    apply_setting, parse_session_statement, run_session_statement, SessionSettings,
+};
+use super::spill::SpillWriter;
+#[cfg(feature = "sqlite")]
 use super::sqlite::SqliteTableProvider;
+use super::stream::{export_stream, ExportFormat, ExportSummary};
+use super::summarize::{parse_summarize, summarize};
+use super::tail::TailingCsvTable;
+use super::temp::{create_temp_table, parse_create_temp_table, temp_schema, SessionSchema};
+#[cfg(feature = "xlsx")]
+use super::xlsx::{read_sheet, Sheet};
 
 pub struct DataFusionContext {
     session: SessionContext,
     runtime: Arc<Runtime>,
     table_names: Vec<String>,
+    sources: HashMap<String, TableSource>,
+    /// Tables made by [`union_tables`](Self::union_tables), with the names
+    /// and sources of the tables they combine.
+    unions: HashMap<String, Vec<(String, TableSource)>>,
+    strict_sql: bool,
+    settings: Arc<Mutex<SessionSettings>>,
 }
 
 impl DataFusionContext {
@@ -30,11 +77,17 @@ impl DataFusionContext {
                 .map_err(DataFusionError::Io)?,
         );
 
+        // Statistics gathered when a table is registered let an unfiltered
+        // `COUNT(*)` be answered from Parquet footers and Delta logs without
+        // scanning any data.
         let session_config = SessionConfig::new()
             .with_information_schema(true)
+            .with_collect_statistics(true)
             .with_batch_size(8192);
 
+        #[allow(unused_mut)] // only mutated when a table format feature is enabled
         let mut table_factories: HashMap<String, Arc<dyn TableProviderFactory>> = HashMap::new();
+        #[cfg(feature = "iceberg")]
         table_factories.insert(
             "ICEBERG".to_string(),
             Arc::new(IcebergTableProviderFactory::new()),
@@ -43,19 +96,65 @@ impl DataFusionContext {
         let state = SessionStateBuilder::new()
             .with_config(session_config)
             .with_default_features()
+            .with_analyzer_rules(analyzer_rules())
             .with_table_factories(table_factories)
             .build();
 
         let session = SessionContext::from(state);
+        let defaults = session.state().config().options().catalog.clone();
+        if let Some(catalog) = session.catalog(&defaults.default_catalog) {
+            catalog.register_schema(&defaults.default_schema, Arc::new(SessionSchema::new()))?;
+        }
+        register_functions(&session);
 
         Ok(Self {
             session,
             runtime,
             table_names: Vec::new(),
+            sources: HashMap::new(),
+            unions: HashMap::new(),
+            strict_sql: false,
+            settings: Arc::default(),
         })
     }
 
+    /// Make the bucket or host of `uri`, an `s3://`, `gs://` or `http(s)://`
+    /// URI, readable by the `register_*` methods and queries, with
+    /// credentials from the environment. Register the store before
+    /// registering tables at such URIs.
+    #[cfg(feature = "remote")]
+    pub fn register_object_store(&self, uri: &str) -> Result<()> {
+        let url = parse_remote_uri(uri)?;
+        let store = object_store_for(&url)?;
+        self.session.register_object_store(&store_url(&url), store);
+        #[cfg(feature = "delta")]
+        super::remote::register_delta_schemes();
+        Ok(())
+    }
+
+    /// Without the `remote` feature no URI can be read.
+    #[cfg(not(feature = "remote"))]
+    pub fn register_object_store(&self, uri: &str) -> Result<()> {
+        Err(DataFusionError::UnsupportedFormat(format!(
+            "{}: reading s3://, gs:// and http(s):// URIs requires the `remote` feature",
+            uri
+        )))
+    }
+
     pub fn register_csv(&mut self, name: impl Into<String>, path: &Path) -> Result<()> {
+        self.register_csv_with_options(name, path, &CsvOptions::default())
+    }
+
+    /// Register a CSV file read with `options`: its delimiter and header,
+    /// how many rows its column types are inferred from, and the dates,
+    /// decimal commas and column types to look for. A refresh reads the
+    /// file with the same options.
+    pub fn register_csv_with_options(
+        &mut self,
+        name: impl Into<String>,
+        path: &Path,
+        options: &CsvOptions,
+    ) -> Result<()> {
         let name = name.into();
         let path_str = path
             .to_str()
@@ -63,12 +162,50 @@ impl DataFusionContext {
 
         self.runtime.block_on(async {
             let ctx = &self.session;
-            ctx.register_csv(&name, path_str, CsvReadOptions::default())
+            let read_options = options.read_options();
+            if !options.changes_schema() {
+                ctx.register_csv(&name, path_str, read_options).await?;
+                return Ok(());
+            }
+            let inferred = ctx.read_csv(path_str, read_options.clone()).await?;
+            let schema = options.read_schema(inferred.schema().as_arrow())?;
+            ctx.register_csv(&name, path_str, read_options.schema(&schema))
                 .await?;
             Ok::<_, DataFusionError>(())
         })?;
 
-        self.table_names.push(name);
+        if options.converts_columns() {
+            let converted = self
+                .collect_in_order(&name)
+                .and_then(|batches| options.convert(&batches));
+            let replaced = match converted {
+                Ok(Some(batches)) => self.replace_with_batches(&name, batches),
+                Ok(None) => Ok(()),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = replaced {
+                self.session.deregister_table(&name)?;
+                return Err(e);
+            }
+        }
+
+        self.track(name.clone(), SourceKind::Csv, path);
+        if *options != CsvOptions::default() {
+            if let Some(source) = self.sources.get_mut(&name) {
+                source.csv_options = Some(options.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Register a CSV file that is still growing. Each query parses only the
+    /// lines appended since the previous one; see [`TailingCsvTable`].
+    pub fn register_csv_tail(&mut self, name: impl Into<String>, path: &Path) -> Result<()> {
+        let name = name.into();
+        let table = TailingCsvTable::try_new(path)?;
+        self.session.register_table(&name, Arc::new(table))?;
+
+        self.track(name, SourceKind::CsvTail, path);
         Ok(())
     }
 
@@ -85,7 +222,7 @@ impl DataFusionContext {
             Ok::<_, DataFusionError>(())
         })?;
 
-        self.table_names.push(name);
+        self.track(name, SourceKind::Parquet, path);
         Ok(())
     }
 
@@ -102,10 +239,11 @@ impl DataFusionContext {
             Ok::<_, DataFusionError>(())
         })?;
 
-        self.table_names.push(name);
+        self.track(name, SourceKind::Json, path);
         Ok(())
     }
 
+    #[cfg(feature = "delta")]
     pub fn register_delta(&mut self, name: impl Into<String>, path: &Path) -> Result<()> {
         let name = name.into();
         let path_str = path
@@ -113,16 +251,19 @@ impl DataFusionContext {
             .ok_or_else(|| DataFusionError::Conversion("Invalid UTF-8 in path".to_string()))?;
 
         self.runtime.block_on(async {
-            let delta_table = deltalake::open_table(path_str).await?;
+            let delta_table = deltalake::open_table(path_str)
+                .await
+                .map_err(|err| unreadable_delta_table(&name, err))?;
             let ctx = &self.session;
             ctx.register_table(&name, Arc::new(delta_table))?;
             Ok::<_, DataFusionError>(())
         })?;
 
-        self.table_names.push(name);
+        self.track(name, SourceKind::Delta, path);
         Ok(())
     }
 
+    #[cfg(feature = "iceberg")]
     pub fn register_iceberg(&mut self, name: impl Into<String>, path: &Path) -> Result<()> {
         let name = name.into();
         let path_str = path
@@ -135,71 +276,1043 @@ impl DataFusionContext {
 
             let sql = format!(
                 "CREATE EXTERNAL TABLE {} STORED AS ICEBERG LOCATION '{}'",
-                name, metadata_path
+                quote_identifier(&name),
+                metadata_path.replace('\'', "''")
             );
             self.session.sql(&sql).await?;
             Ok::<_, DataFusionError>(())
         })?;
 
-        self.table_names.push(name);
+        self.track(name, SourceKind::Iceberg, path);
         Ok(())
     }
 
+    #[cfg(feature = "sqlite")]
     pub fn register_sqlite(&mut self, path: &Path) -> Result<Vec<String>> {
         let provider = SqliteTableProvider::new(path)?;
         let table_names = provider.list_tables()?;
         let registered_tables = table_names.clone();
 
         for table_name in table_names {
-            let table_provider = SqliteTableProvider::new_for_table(path, &table_name)?;
-            self.session
-                .register_table(&table_name, Arc::new(table_provider))?;
-            self.table_names.push(table_name);
+            self.register_sqlite_table(&table_name, path)?;
         }
 
         Ok(registered_tables)
     }
 
-    pub fn execute_sql(&self, sql: &str) -> Result<Table> {
-        let (schema, result) = self.runtime.block_on(async {
-            let df = self.session.sql(sql).await?;
-            let schema = df.schema().clone();
-            let batches = df.collect().await?;
-            Ok::<_, DataFusionError>((schema, batches))
+    #[cfg(feature = "sqlite")]
+    fn register_sqlite_table(&mut self, table_name: &str, path: &Path) -> Result<()> {
+        let table_provider = SqliteTableProvider::new_for_table(path, table_name)?;
+        self.session
+            .register_table(table_name, Arc::new(table_provider))?;
+        self.track(table_name.to_string(), SourceKind::Sqlite, path);
+        Ok(())
+    }
+
+    /// Register one worksheet of an Excel workbook, read whole into memory.
+    /// A refresh re-reads the sheet by the name it had when registered.
+    #[cfg(feature = "xlsx")]
+    pub fn register_xlsx(
+        &mut self,
+        name: impl Into<String>,
+        path: &Path,
+        sheet: &Sheet,
+    ) -> Result<()> {
+        let name = name.into();
+        let (sheet_name, batch) = read_sheet(path, sheet)?;
+        let table = MemTable::try_new(batch.schema(), vec![vec![batch]])?;
+        self.session.register_table(&name, Arc::new(table))?;
+
+        self.track(name.clone(), SourceKind::Xlsx, path);
+        if let Some(source) = self.sources.get_mut(&name) {
+            source.sheet = Some(sheet_name);
+        }
+        Ok(())
+    }
+
+    fn track(&mut self, name: String, kind: SourceKind, path: &Path) {
+        self.sources.insert(
+            name.clone(),
+            TableSource {
+                kind,
+                path: path.to_path_buf(),
+                provenance: None,
+                refine_types: false,
+                lenient_numbers: None,
+                headers: None,
+                filter: None,
+                csv_options: None,
+                #[cfg(feature = "xlsx")]
+                sheet: None,
+            },
+        );
+        if !self.table_names.contains(&name) {
+            self.table_names.push(name);
+        }
+    }
+
+    /// Replace the tables `parts`, which must have the same column names and
+    /// types, with one table `name` holding all their rows in order. The
+    /// parts are no longer listed, but a refresh re-reads each of them.
+    pub fn union_tables(&mut self, name: impl Into<String>, parts: &[String]) -> Result<()> {
+        let name = name.into();
+        let Some((first, rest)) = parts.split_first() else {
+            return Err(DataFusionError::TableNotFound(name));
+        };
+        let combined = self.runtime.block_on(async {
+            let mut combined = self.session.table(first).await?;
+            for part in rest {
+                let frame = self.session.table(part).await?;
+                let expected = column_types(combined.schema().fields());
+                let actual = column_types(frame.schema().fields());
+                if actual != expected {
+                    return Err(DataFusionError::SchemaMismatch(format!(
+                        "{} has columns ({}), but {} has ({})",
+                        self.source_label(part),
+                        actual.join(", "),
+                        self.source_label(first),
+                        expected.join(", ")
+                    )));
+                }
+                combined = combined.union(frame)?;
+            }
+            Ok::<_, DataFusionError>(combined)
+        })?;
+
+        let mut sources = Vec::with_capacity(parts.len());
+        for part in parts {
+            self.session.deregister_table(part)?;
+            self.table_names.retain(|table| table != part);
+            if let Some(source) = self.sources.remove(part) {
+                sources.push((part.clone(), source));
+            }
+        }
+        self.session.deregister_table(&name)?;
+        self.session.register_table(&name, combined.into_view())?;
+        if !self.table_names.contains(&name) {
+            self.table_names.push(name.clone());
+        }
+        self.unions.insert(name, sources);
+        Ok(())
+    }
+
+    /// The path `name` was loaded from, or its name if it has none.
+    fn source_label(&self, name: &str) -> String {
+        self.sources.get(name).map_or_else(
+            || name.to_string(),
+            |source| source.path.display().to_string(),
+        )
+    }
+
+    /// Replace `name` with a copy that has a `_row_id` column numbering its
+    /// rows from 1 in source order and, if `source_file` is set, a
+    /// `_source_file` column with the path it was loaded from. The table is
+    /// read once on a single partition and kept in memory, so the numbering
+    /// matches the order of rows in the file.
+    pub fn add_provenance_columns(&mut self, name: &str, source_file: bool) -> Result<()> {
+        let path = self
+            .sources
+            .get(name)
+            .map(|source| source.path.to_string_lossy().to_string())
+            .ok_or_else(|| DataFusionError::TableNotFound(name.to_string()))?;
+
+        let batches = self.collect_in_order(name)?;
+        let batches = add_provenance_columns(batches, source_file.then_some(path.as_str()))?;
+
+        self.replace_with_batches(name, batches)?;
+        if let Some(source) = self.sources.get_mut(name) {
+            source.provenance = Some(Provenance { source_file });
+        }
+        Ok(())
+    }
+
+    /// Text columns of `name` whose values all read as dates, booleans
+    /// (`Y`/`N`, `yes`/`no`, ...) or numbers with thousands separators.
+    /// Scans the whole table; nothing is changed.
+    pub fn detect_type_refinements(&self, name: &str) -> Result<Vec<TypeRefinement>> {
+        let provider = self.runtime.block_on(self.session.table_provider(name))?;
+        let batches = self
+            .runtime
+            .block_on(self.session.read_table(provider.clone())?.collect())?;
+        detect_refinements(&provider.schema(), &batches)
+    }
+
+    /// Replace `name` with an in-memory copy in which the columns found by
+    /// [`detect_type_refinements`](Self::detect_type_refinements) are
+    /// converted to their refined types. Returns the conversions made. A
+    /// refresh re-reads the source and refines it again.
+    pub fn refine_types(&mut self, name: &str) -> Result<Vec<TypeRefinement>> {
+        if !self.sources.contains_key(name) {
+            return Err(DataFusionError::TableNotFound(name.to_string()));
+        }
+        let batches = self.collect_in_order(name)?;
+        let refinements = detect_refinements(&batches[0].schema(), &batches)?;
+        if !refinements.is_empty() {
+            let batches = apply_refinements(batches, &refinements)?;
+            self.replace_with_batches(name, batches)?;
+        }
+        if let Some(source) = self.sources.get_mut(name) {
+            source.refine_types = true;
+        }
+        Ok(refinements)
+    }
+
+    /// Replace `name` with an in-memory copy in which text columns holding
+    /// amounts such as `$1,234.56`, `(500)` or `12%` are converted to
+    /// floats: currency symbols and thousands separators are dropped,
+    /// parentheses mean a negative and percentages are divided by 100. A
+    /// column is only converted if every value parses. Returns the columns
+    /// converted; a refresh converts them again.
+    pub fn coerce_lenient_numbers(&mut self, name: &str) -> Result<Vec<TypeRefinement>> {
+        if !self.sources.contains_key(name) {
+            return Err(DataFusionError::TableNotFound(name.to_string()));
+        }
+        let batches = self.collect_in_order(name)?;
+        let coerced = detect_lenient_numbers(&batches[0].schema(), &batches)?;
+        if !coerced.is_empty() {
+            let batches = apply_refinements(batches, &coerced)?;
+            self.replace_with_batches(name, batches)?;
+        }
+        if let Some(source) = self.sources.get_mut(name) {
+            source.lenient_numbers = Some(coerced.clone());
+        }
+        Ok(coerced)
+    }
+
+    /// The columns of `name` converted by
+    /// [`coerce_lenient_numbers`](Self::coerce_lenient_numbers).
+    pub fn lenient_number_columns(&self, name: &str) -> &[TypeRefinement] {
+        self.sources
+            .get(name)
+            .and_then(|source| source.lenient_numbers.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// Keep only the rows of `name` that match `predicate`, a SQL expression
+    /// such as `date >= '2024-01-01'`. The table is replaced by a filtered
+    /// view, and the predicate is pushed into every scan of it, so Delta and
+    /// Iceberg tables never open the partitions and files it rules out. A
+    /// refresh applies it again.
+    pub fn filter_table(&mut self, name: &str, predicate: &str) -> Result<()> {
+        if !self.sources.contains_key(name) {
+            return Err(DataFusionError::TableNotFound(name.to_string()));
+        }
+        let df = self.runtime.block_on(self.session.table(name))?;
+        let expr = df.parse_sql_expr(predicate)?;
+        let view = df.filter(expr)?.into_view();
+        self.session.deregister_table(name)?;
+        self.session.register_table(name, view)?;
+        if let Some(source) = self.sources.get_mut(name) {
+            source.filter = Some(predicate.to_string());
+        }
+        Ok(())
+    }
+
+    /// Rename the columns of `name` as `options` describe, e.g. from
+    /// `Total Sales ($)` to `total_sales`. The table is replaced by a view
+    /// over the original, so nothing is read into memory. Returns the
+    /// columns whose name changed; a refresh renames them again.
+    pub fn normalize_headers(
+        &mut self,
+        name: &str,
+        options: HeaderNormalization,
+    ) -> Result<Vec<RenamedColumn>> {
+        if !self.sources.contains_key(name) {
+            return Err(DataFusionError::TableNotFound(name.to_string()));
+        }
+        let df = self.runtime.block_on(self.session.table(name))?;
+        let originals: Vec<String> = df
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect();
+        let normalized = options.apply(&originals);
+
+        let renamed: Vec<RenamedColumn> = originals
+            .iter()
+            .zip(&normalized)
+            .filter(|(original, name)| original != name)
+            .map(|(original, name)| RenamedColumn {
+                original: original.clone(),
+                name: name.clone(),
+            })
+            .collect();
+        if !renamed.is_empty() {
+            let columns: Vec<Expr> = originals
+                .iter()
+                .zip(&normalized)
+                .map(|(original, name)| ident(original).alias(name))
+                .collect();
+            let view = df.select(columns)?.into_view();
+            self.session.deregister_table(name)?;
+            self.session.register_table(name, view)?;
+        }
+        if let Some(source) = self.sources.get_mut(name) {
+            source.headers = Some((options, renamed.clone()));
+        }
+        Ok(renamed)
+    }
+
+    /// The columns of `name` renamed by
+    /// [`normalize_headers`](Self::normalize_headers), with their original
+    /// names.
+    pub fn renamed_columns(&self, name: &str) -> &[RenamedColumn] {
+        self.sources
+            .get(name)
+            .and_then(|source| source.headers.as_ref())
+            .map(|(_, renamed)| renamed.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Read all of `name` on a single partition, so batches come back in
+    /// source order. Always returns at least one, possibly empty, batch.
+    fn collect_in_order(&self, name: &str) -> Result<Vec<RecordBatch>> {
+        let provider = self.runtime.block_on(self.session.table_provider(name))?;
+        let schema = provider.schema();
+        let mut batches = self.runtime.block_on(async {
+            let ordered =
+                SessionContext::new_with_config(SessionConfig::new().with_target_partitions(1));
+            ordered.read_table(provider)?.collect().await
         })?;
+        if batches.is_empty() {
+            batches.push(RecordBatch::new_empty(schema));
+        }
+        Ok(batches)
+    }
+
+    fn replace_with_batches(&mut self, name: &str, batches: Vec<RecordBatch>) -> Result<()> {
+        let table = MemTable::try_new(batches[0].schema(), vec![batches])?;
+        self.session.deregister_table(name)?;
+        self.session.register_table(name, Arc::new(table))?;
+        Ok(())
+    }
+
+    /// The file or directory a table was loaded from.
+    pub fn table_source(&self, name: &str) -> Option<&Path> {
+        self.sources.get(name).map(|source| source.path.as_path())
+    }
+
+    /// A manifest recording `queries` together with the size and checksum
+    /// of every loaded table's source, for reproducing their results.
+    pub fn query_manifest(&self, queries: Vec<ManifestQuery>) -> Result<QueryManifest> {
+        let mut sources = Vec::new();
+        for table in self.list_tables() {
+            for (_, part) in self.unions.get(&table).into_iter().flatten() {
+                let (size_bytes, sha256) = fingerprint(&part.path)?;
+                sources.push(ManifestSource {
+                    table: table.clone(),
+                    path: part.path.clone(),
+                    size_bytes,
+                    sha256,
+                });
+            }
+            // Remote sources would have to be downloaded to be checksummed
+            if let Some(path) = self
+                .table_source(&table)
+                .filter(|path| !is_remote_path(path))
+            {
+                let (size_bytes, sha256) = fingerprint(path)?;
+                sources.push(ManifestSource {
+                    table,
+                    path: path.to_path_buf(),
+                    size_bytes,
+                    sha256,
+                });
+            }
+        }
+        Ok(QueryManifest::new(queries, sources))
+    }
+
+    /// Re-read `name` from its source so changes on disk, including schema
+    /// changes and new Delta/Iceberg snapshots, become visible. If the source
+    /// can no longer be read the previous registration is kept and the error
+    /// is returned.
+    pub fn refresh_table(&mut self, name: &str) -> Result<()> {
+        if let Some(parts) = self.unions.get(name).cloned() {
+            return self.refresh_union(name, parts);
+        }
+        let source = self
+            .sources
+            .get(name)
+            .cloned()
+            .ok_or_else(|| DataFusionError::TableNotFound(name.to_string()))?;
+        if !is_remote_path(&source.path) && !source.path.exists() {
+            return Err(DataFusionError::FileNotFound(
+                source.path.to_string_lossy().to_string(),
+            ));
+        }
+
+        let previous = self.session.deregister_table(name)?;
+        let reloaded = match source.kind {
+            SourceKind::Csv => self.register_csv_with_options(
+                name,
+                &source.path,
+                &source.csv_options.clone().unwrap_or_default(),
+            ),
+            SourceKind::CsvTail => self.register_csv_tail(name, &source.path),
+            SourceKind::Parquet => self.register_parquet(name, &source.path),
+            SourceKind::Json => self.register_json(name, &source.path),
+            #[cfg(feature = "delta")]
+            SourceKind::Delta => self.register_delta(name, &source.path),
+            #[cfg(feature = "iceberg")]
+            SourceKind::Iceberg => self.register_iceberg(name, &source.path),
+            #[cfg(feature = "sqlite")]
+            SourceKind::Sqlite => self.register_sqlite_table(name, &source.path),
+            #[cfg(feature = "xlsx")]
+            SourceKind::Xlsx => {
+                let sheet = source
+                    .sheet
+                    .clone()
+                    .map_or_else(Sheet::default, Sheet::Name);
+                self.register_xlsx(name, &source.path, &sheet)
+            }
+        };
+
+        let reloaded = reloaded
+            .and_then(|_| match &source.filter {
+                Some(predicate) => self.filter_table(name, predicate),
+                None => Ok(()),
+            })
+            .and_then(|_| match source.lenient_numbers {
+                Some(_) => self.coerce_lenient_numbers(name).map(|_| ()),
+                None => Ok(()),
+            })
+            .and_then(|_| match source.headers {
+                Some((options, _)) => self.normalize_headers(name, options).map(|_| ()),
+                None => Ok(()),
+            })
+            .and_then(|_| match source.provenance {
+                Some(provenance) => self.add_provenance_columns(name, provenance.source_file),
+                None => Ok(()),
+            })
+            .and_then(|_| match source.refine_types {
+                true => self.refine_types(name).map(|_| ()),
+                false => Ok(()),
+            });
+        if reloaded.is_err() {
+            if let Some(provider) = previous {
+                self.session.deregister_table(name)?;
+                self.session.register_table(name, provider)?;
+            }
+            self.sources.insert(name.to_string(), source);
+            return reloaded;
+        }
+        self.rebind_views()
+    }
+
+    /// Plan every view created with `CREATE VIEW` again from its
+    /// definition. A view is planned against the tables as they were when
+    /// it was created, so after a table is reloaded its views would still
+    /// read the old files. Views are re-created after the views they read
+    /// from. One that no longer plans, e.g. because a column it selects is
+    /// gone, is kept as it was and reports the error when queried.
+    fn rebind_views(&self) -> Result<()> {
+        let mut views: Vec<(String, String, Vec<String>)> = Vec::new();
+        for name in self.session_table_names() {
+            let Some(provider) = self
+                .runtime
+                .block_on(self.session.table_provider(name.as_str()))
+                .ok()
+            else {
+                continue;
+            };
+            let Some(view) = provider.as_any().downcast_ref::<ViewTable>() else {
+                continue;
+            };
+            let Some(definition) = view.definition() else {
+                continue;
+            };
+            // The view's plan has the views it reads inlined, so find them
+            // in its definition
+            let mut reads = Vec::new();
+            let statement = self.session.state().sql_to_statement(definition, "generic");
+            if let Ok(DFStatement::Statement(statement)) = statement {
+                let _ = visit_relations(statement.as_ref(), |relation| {
+                    if let Some(part) = relation.0.last() {
+                        reads.push(part.to_string().trim_matches('"').to_lowercase());
+                    }
+                    ControlFlow::<()>::Continue(())
+                });
+            }
+            views.push((name, definition.clone(), reads));
+        }
+
+        while !views.is_empty() {
+            // Views that read no view still waiting to be re-created, or all
+            // the rest if they read each other
+            let ready: Vec<usize> = (0..views.len())
+                .filter(|&i| {
+                    !views[i]
+                        .2
+                        .iter()
+                        .any(|read| views.iter().any(|(name, _, _)| name == read))
+                })
+                .collect();
+            let ready = if ready.is_empty() {
+                (0..views.len()).collect()
+            } else {
+                ready
+            };
+            for i in ready.into_iter().rev() {
+                let (name, definition, _) = views.remove(i);
+                let previous = self.session.deregister_table(name.as_str())?;
+                let created = self
+                    .runtime
+                    .block_on(async { self.session.sql(&definition).await?.collect().await });
+                if created.is_err() {
+                    if let Some(provider) = previous {
+                        self.session.register_table(name.as_str(), provider)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
 
-        // Handle empty results - create table with schema but no rows
-        if result.is_empty() {
-            use super::conversion::convert_schema;
-            use crate::storage::table::Table;
-            let arrow_schema = schema.to_owned().into();
-            let table_schema = convert_schema(&arrow_schema)?;
-            return Ok(Table::new("result", table_schema));
+    /// Re-read every part of a table made by
+    /// [`union_tables`](Self::union_tables) and combine them again. If a
+    /// part can no longer be read the previous table is kept.
+    fn refresh_union(&mut self, name: &str, parts: Vec<(String, TableSource)>) -> Result<()> {
+        let part_names: Vec<String> = parts.iter().map(|(part, _)| part.clone()).collect();
+        let table_names = self.table_names.clone();
+        let mut reloaded = Ok(());
+        for (part, source) in &parts {
+            self.sources.insert(part.clone(), source.clone());
+            reloaded = self.refresh_table(part);
+            if reloaded.is_err() {
+                break;
+            }
         }
+        let reloaded = reloaded.and_then(|_| self.union_tables(name, &part_names));
+        if reloaded.is_err() {
+            for part in &part_names {
+                if part != name {
+                    self.session.deregister_table(part)?;
+                }
+                self.sources.remove(part);
+            }
+            self.table_names = table_names;
+            self.unions.insert(name.to_string(), parts);
+            return reloaded;
+        }
+        self.rebind_views()
+    }
 
-        let table = record_batch_to_table("result", result)?;
-        Ok(table)
+    /// Refresh every table loaded from a source, returning their names.
+    pub fn refresh_all(&mut self) -> Result<Vec<String>> {
+        let names: Vec<String> = self
+            .table_names
+            .iter()
+            .filter(|name| self.sources.contains_key(*name) || self.unions.contains_key(*name))
+            .cloned()
+            .collect();
+        for name in &names {
+            self.refresh_table(name)?;
+        }
+        Ok(names)
+    }
+
+    /// Reject syntax from other SQL dialects, such as `SELECT TOP n` or
+    /// backtick-quoted identifiers, instead of translating it. Off by default.
+    pub fn set_strict_sql(&mut self, strict: bool) {
+        self.strict_sql = strict;
+    }
+
+    /// Describe the syntax from other SQL dialects that running `sql` would
+    /// translate, one note per kind, so frontends can warn about it. Empty
+    /// in strict mode, where such syntax is an error, or when `sql` does not
+    /// parse.
+    pub fn dialect_notes(&self, sql: &str) -> Vec<String> {
+        if self.strict_sql {
+            return Vec::new();
+        }
+        let state = self.session.state();
+        let dialect = state.config().options().sql_parser.dialect.clone();
+        let sql = strip_digit_separators(sql);
+        state
+            .sql_to_statement(&sql, &dialect)
+            .and_then(|mut statement| translate(&mut statement, &sql))
+            .unwrap_or_default()
+    }
+
+    /// The session's current settings, as changed by `SET` statements.
+    pub fn settings(&self) -> SessionSettings {
+        self.settings
+            .lock()
+            .map(|settings| settings.clone())
+            .unwrap_or_default()
+    }
+
+    /// Change a setting as `SET name = value` would, e.g. to apply a
+    /// command-line option.
+    pub fn set_setting(&self, name: &str, value: &str) -> Result<()> {
+        self.runtime
+            .block_on(apply_setting(&self.session, &self.settings, name, value))
+    }
+
+    pub fn execute_sql(&self, sql: &str) -> Result<Table> {
+        self.execute_sql_with_options(sql, QueryOptions::default())
     }
 
+    /// Run `sql` like [`execute_sql`](Self::execute_sql) under the limits
+    /// of `options`, which override the session's settings for this query
+    /// only.
+    pub fn execute_sql_with_options(&self, sql: &str, options: QueryOptions) -> Result<Table> {
+        self.runtime.block_on(run_sql(
+            self.session.clone(),
+            self.settings.clone(),
+            sql.to_string(),
+            self.strict_sql,
+            Arc::default(),
+            options,
+        ))
+    }
+
+    /// The columns [`execute_sql`](Self::execute_sql) would return for
+    /// `sql`, found by planning the query without running it, so that no
+    /// data is read. Errors in the query, such as a missing column, are
+    /// reported as `execute_sql` would report them. `SET`, `CACHE`,
+    /// `CREATE TEMP TABLE` and `SUMMARIZE` are run outside the planner and
+    /// fail with [`ResultSchemaUnknown`](DataFusionError::ResultSchemaUnknown).
+    pub fn infer_result_schema(&self, sql: &str) -> Result<Schema> {
+        if parse_session_statement(sql).is_some()
+            || parse_cache_table(sql).is_some()
+            || parse_create_temp_table(sql).is_some()
+            || parse_summarize(sql).is_some()
+        {
+            return Err(DataFusionError::ResultSchemaUnknown(sql.trim().to_string()));
+        }
+        let plan = self
+            .runtime
+            .block_on(sql_to_plan(&self.session, sql, self.strict_sql))?;
+        convert_schema(plan.schema().as_arrow())
+    }
+
+    /// Run `sql` like [`execute_sql`](Self::execute_sql) and also return how
+    /// many files it read and how many Parquet row groups it could skip.
+    pub fn execute_sql_with_pruning(&self, sql: &str) -> Result<(Table, PruningStats)> {
+        let tracker = Arc::new(ProgressTracker::default());
+        let table = self.runtime.block_on(run_sql(
+            self.session.clone(),
+            self.settings.clone(),
+            sql.to_string(),
+            self.strict_sql,
+            tracker.clone(),
+            QueryOptions::default(),
+        ))?;
+        Ok((table, tracker.pruning()))
+    }
+
+    /// Run `sql` and stream its result into the file at `path`, without
+    /// holding the whole result in memory. The session's limits apply as
+    /// they do to [`execute_sql`](Self::execute_sql), and the columns
+    /// matched by `masks` are masked as they are written.
+    pub fn export_sql(
+        &self,
+        sql: &str,
+        path: &Path,
+        format: ExportFormat,
+        masks: &MaskRules,
+    ) -> Result<ExportSummary> {
+        self.export_sql_with_options(sql, path, format, masks, QueryOptions::default())
+    }
+
+    /// Export like [`export_sql`](Self::export_sql) under the limits of
+    /// `options`.
+    pub fn export_sql_with_options(
+        &self,
+        sql: &str,
+        path: &Path,
+        format: ExportFormat,
+        masks: &MaskRules,
+        options: QueryOptions,
+    ) -> Result<ExportSummary> {
+        let options = self
+            .settings
+            .lock()
+            .map(|settings| options.or_settings(&settings))
+            .unwrap_or(options);
+        self.runtime
+            .block_on(options.run_with_timeout(export_stream(
+                &self.session,
+                sql,
+                self.strict_sql,
+                path,
+                format,
+                options,
+                masks,
+            )))
+    }
+
+    /// Start executing `sql` on the context's runtime without blocking the caller.
+    ///
+    /// The returned handle can be polled for completion, cancelled or
+    /// awaited, which lets interactive frontends stay responsive during long
+    /// scans. Queries started this way run concurrently, so a frontend can
+    /// run a table preview next to the user's query without waiting for it.
+    pub fn execute_sql_async(&self, sql: &str) -> QueryHandle {
+        self.execute_sql_async_with_options(sql, QueryOptions::default())
+    }
+
+    /// Start executing `sql` like [`execute_sql_async`](Self::execute_sql_async)
+    /// under the limits of `options`.
+    pub fn execute_sql_async_with_options(&self, sql: &str, options: QueryOptions) -> QueryHandle {
+        let tracker = Arc::new(ProgressTracker::default());
+        let task = self.runtime.spawn(run_sql(
+            self.session.clone(),
+            self.settings.clone(),
+            sql.to_string(),
+            self.strict_sql,
+            tracker.clone(),
+            options,
+        ));
+        QueryHandle::new(task, self.runtime.clone(), tracker)
+    }
+
+    /// Tables loaded from sources, in load order, followed by tables and
+    /// views created in the session with SQL such as `CACHE TABLE` or
+    /// `CREATE VIEW`, sorted by name.
     pub fn list_tables(&self) -> Vec<String> {
-        self.table_names.clone()
+        let mut names = self.table_names.clone();
+        let mut created: Vec<String> = self
+            .session_table_names()
+            .into_iter()
+            .filter(|name| !self.table_names.contains(name))
+            .collect();
+        created.sort();
+        names.append(&mut created);
+        names
+    }
+
+    /// Tables created with `CREATE TEMP TABLE`, sorted by name. They are
+    /// not part of [`list_tables`](Self::list_tables).
+    pub fn temp_tables(&self) -> Vec<String> {
+        let mut names = temp_schema(&self.session)
+            .map(|temp| temp.table_names())
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+
+    /// Drop every table created with `CREATE TEMP TABLE`.
+    pub fn clear_temp_tables(&self) -> Result<()> {
+        if let Some(temp) = temp_schema(&self.session) {
+            for name in temp.table_names() {
+                temp.deregister_table(&name)?;
+            }
+        }
+        Ok(())
     }
 
     pub fn table_count(&self) -> usize {
-        self.table_names.len()
+        self.list_tables().len()
     }
 
-    pub fn get_table_schema(&self, table_name: &str) -> Option<crate::storage::table::Schema> {
-        use super::conversion::convert_schema;
+    fn session_table_names(&self) -> Vec<String> {
+        let state = self.session.state();
+        let defaults = &state.config().options().catalog;
+        self.session
+            .catalog(&defaults.default_catalog)
+            .and_then(|catalog| catalog.schema(&defaults.default_schema))
+            .map(|schema| schema.table_names())
+            .unwrap_or_default()
+    }
 
+    pub fn get_table_schema(&self, table_name: &str) -> Option<crate::storage::table::Schema> {
         self.runtime.block_on(async {
             let provider = self.session.table_provider(table_name).await.ok()?;
             let arrow_schema = provider.schema();
             convert_schema(&arrow_schema).ok()
         })
     }
+
+    /// Row count, size and format of a registered table, without running a
+    /// query against it. Returns `None` if no such table is registered.
+    pub fn table_details(&self, name: &str) -> Option<TableDetails> {
+        let source = self.sources.get(name);
+        self.runtime.block_on(async {
+            let provider = self.session.table_provider(name).await.ok()?;
+            if let Some(parts) = self.unions.get(name) {
+                let columns = provider.schema().fields().len();
+                return Some(TableDetails::combined(name, columns, parts));
+            }
+            let mut details = TableDetails::new(name, provider.schema().fields().len(), source);
+            if source.is_none() && provider.table_type() == TableType::View {
+                details.format = Some("view");
+            }
+            match source.map(|source| source.kind) {
+                #[cfg(feature = "delta")]
+                Some(SourceKind::Delta) => {
+                    if let Some(table) = provider.as_any().downcast_ref::<deltalake::DeltaTable>() {
+                        details.read_delta_layout(table);
+                    }
+                }
+                #[cfg(feature = "iceberg")]
+                Some(SourceKind::Iceberg) => {
+                    let metadata = source
+                        .and_then(|source| source.path.to_str())
+                        .and_then(|path| find_iceberg_metadata(path).ok());
+                    if let Some(metadata) = metadata {
+                        details.read_iceberg_layout(&metadata).await;
+                    }
+                }
+                _ => {}
+            }
+            Some(details)
+        })
+    }
+
+    /// [`table_details`](Self::table_details) for every table, in
+    /// registration order.
+    pub fn list_table_details(&self) -> Vec<TableDetails> {
+        self.list_tables()
+            .iter()
+            .filter_map(|name| self.table_details(name))
+            .collect()
+    }
+
+    /// One row per registered table with its format, column count, row
+    /// count and size, ready to hand to a renderer.
+    pub fn tables_overview(&self) -> Table {
+        let schema = Schema::new(vec![
+            Column::new("table_name", DataType::String),
+            Column::new("format", DataType::String),
+            Column::new("columns", DataType::Integer),
+            Column::new("rows", DataType::Integer),
+            Column::new("size_bytes", DataType::Integer),
+        ]);
+        let optional_int = |n: Option<u64>| {
+            n.and_then(|n| i64::try_from(n).ok())
+                .map_or(Value::Null, Value::Integer)
+        };
+
+        let rows = self
+            .list_table_details()
+            .into_iter()
+            .map(|details| {
+                Row::new(vec![
+                    Value::String(details.name),
+                    details
+                        .format
+                        .map_or(Value::Null, |f| Value::String(f.to_string())),
+                    Value::Integer(details.column_count as i64),
+                    optional_int(details.row_count),
+                    optional_int(details.size_bytes),
+                ])
+            })
+            .collect();
+        Table::with_rows("tables", schema, rows)
+    }
+
+    /// Column names, types and nullability of `name`, as returned by
+    /// `DESCRIBE`, followed by a `source_type` column with each column's
+    /// type as its source declares it (see [`Column::source_type`]). If its
+    /// headers were normalized, an `original_name` column gives each
+    /// column's name in the source.
+    pub fn describe_table(&self, name: &str) -> Result<Table> {
+        let schema = self
+            .get_table_schema(name)
+            .ok_or_else(|| DataFusionError::TableNotFound(name.to_string()))?;
+        let described = self.execute_sql(&format!("DESCRIBE {}", quote_identifier(name)))?;
+        let renamed = self.renamed_columns(name);
+
+        let mut columns = described.schema.columns.clone();
+        columns.push(Column::new("source_type", DataType::String));
+        if !renamed.is_empty() {
+            columns.push(Column::new("original_name", DataType::String));
+        }
+        let rows = described
+            .rows
+            .into_iter()
+            .map(|mut row| {
+                let column = row.values[0].as_string().unwrap_or_default().to_string();
+                let source_type = schema
+                    .column_index(&column)
+                    .and_then(|i| schema.columns[i].source_type.clone())
+                    .map_or(Value::Null, Value::String);
+                row.values.push(source_type);
+                if !renamed.is_empty() {
+                    let original = renamed
+                        .iter()
+                        .find(|r| r.name == column)
+                        .map_or(column.as_str(), |r| r.original.as_str())
+                        .to_string();
+                    row.values.push(Value::String(original));
+                }
+                row
+            })
+            .collect();
+        Ok(Table::with_rows(described.name, Schema::new(columns), rows))
+    }
 }
 
+/// Run `sql` under the limits of `options`, falling back to the session's
+/// settings, and give up once its timeout passes.
+async fn run_sql(
+    session: SessionContext,
+    settings: Arc<Mutex<SessionSettings>>,
+    sql: String,
+    strict: bool,
+    tracker: Arc<ProgressTracker>,
+    options: QueryOptions,
+) -> Result<Table> {
+    let options = settings
+        .lock()
+        .map(|settings| options.or_settings(&settings))
+        .unwrap_or(options);
+    options
+        .run_with_timeout(run_query(session, settings, sql, strict, tracker, options))
+        .await
+}
+
+/// Run `sql`, reading at most `options.max_rows` rows of the result and
+/// marking the table [`truncated`](Table::truncated) if there were more. Once
+/// the result's Arrow data passes `result_memory_mb`, it is written to a
+/// spill file and only its first [`SPILL_PAGE_ROWS`] rows are kept.
+async fn run_query(
+    session: SessionContext,
+    settings: Arc<Mutex<SessionSettings>>,
+    sql: String,
+    strict: bool,
+    tracker: Arc<ProgressTracker>,
+    options: QueryOptions,
+) -> Result<Table> {
+    if let Some(statement) = parse_session_statement(&sql) {
+        return run_session_statement(&session, &settings, statement).await;
+    }
+    if let Some(cache) = parse_cache_table(&sql) {
+        return cache_table(&session, cache, strict).await;
+    }
+    if let Some(create) = parse_create_temp_table(&sql) {
+        return create_temp_table(&session, create, strict).await;
+    }
+    if let Some(query) = parse_summarize(&sql) {
+        return summarize(&session, &query, strict).await;
+    }
+
+    let df = sql_to_dataframe(&session, &sql, strict).await?;
+    let schema = df.schema().clone();
+    let task_ctx = options.limit_memory(df.task_ctx())?;
+    let plan = df.create_physical_plan().await?;
+    tracker.set_plan(plan.clone());
+    let analyze = plan.as_any().is::<AnalyzeExec>();
+
+    let memory_limit = settings
+        .lock()
+        .map(|settings| settings.result_memory_mb)
+        .unwrap_or_default();
+    let max_rows = options.max_rows.unwrap_or(usize::MAX);
+    let memory_limit = memory_limit.map_or(usize::MAX, |mb| mb.saturating_mul(1024 * 1024));
+    let arrow_schema = plan.schema();
+    let mut stream = execute_stream(plan, Arc::new(task_ctx))?;
+    let mut batches = Vec::new();
+    let mut rows = 0;
+    let mut memory = 0;
+    let mut spill: Option<SpillWriter> = None;
+    let mut truncated = false;
+    while !truncated {
+        // Batches are often ready without waiting, so yield now and then to
+        // let a timeout or cancellation take effect
+        tokio::task::consume_budget().await;
+        let Some(batch) = stream.next().await else {
+            break;
+        };
+        let batch = batch?;
+        // Reading one batch past the limit tells a result of exactly
+        // `max_rows` rows apart from a longer one
+        truncated = batch.num_rows() > max_rows - rows;
+        let batch = batch.slice(0, batch.num_rows().min(max_rows - rows));
+        if batch.num_rows() == 0 {
+            continue;
+        }
+        rows += batch.num_rows();
+        tracker.add_rows(batch.num_rows());
+        if let Some(writer) = &mut spill {
+            writer.write(&batch)?;
+        } else {
+            memory += batch.get_array_memory_size();
+            batches.push(batch);
+            if memory > memory_limit {
+                let mut writer = SpillWriter::new(arrow_schema.clone())?;
+                for batch in &batches {
+                    writer.write(batch)?;
+                }
+                batches = first_rows(&batches, SPILL_PAGE_ROWS);
+                spill = Some(writer);
+            }
+        }
+    }
+
+    // Handle empty results - create table with schema but no rows
+    if batches.is_empty() {
+        let arrow_schema = schema.to_owned().into();
+        let table_schema = convert_schema(&arrow_schema)?;
+        let mut table = Table::new("result", table_schema);
+        table.truncated = truncated;
+        return Ok(table);
+    }
+
+    let mut table = record_batch_to_table("result", batches)?;
+    table.truncated = truncated;
+    if let Some(writer) = spill {
+        table.spilled = Some(Arc::new(writer.finish()?));
+    }
+    if analyze {
+        // EXPLAIN ANALYZE lists pruning per scan; add the totals
+        let pruning = tracker.pruning();
+        if !pruning.is_empty() {
+            table.rows.push(Row::new(vec![
+                Value::String("Pruning".to_string()),
+                Value::String(pruning.to_string()),
+            ]));
+        }
+    }
+    Ok(table)
+}
+
+/// Each field as `name type`, for comparing schemas by column names and
+/// types only.
+fn column_types(fields: &[arrow::datatypes::FieldRef]) -> Vec<String> {
+    fields
+        .iter()
+        .map(|field| format!("{} {}", field.name(), field.data_type()))
+        .collect()
+}
+
+/// The first `limit` rows of `batches`.
+fn first_rows(batches: &[RecordBatch], limit: usize) -> Vec<RecordBatch> {
+    let mut kept = Vec::new();
+    let mut rows = 0;
+    for batch in batches {
+        if rows >= limit {
+            break;
+        }
+        let batch = batch.slice(0, batch.num_rows().min(limit - rows));
+        rows += batch.num_rows();
+        kept.push(batch);
+    }
+    kept
+}
+
+/// Name the reader features a Delta table needs but the reader lacks,
+/// rather than deltalake's debug list of them.
+#[cfg(feature = "delta")]
+fn unreadable_delta_table(name: &str, err: deltalake::DeltaTableError) -> DataFusionError {
+    use deltalake::kernel::transaction::TransactionError;
+
+    match err {
+        deltalake::DeltaTableError::Transaction {
+            source: TransactionError::UnsupportedReaderFeatures(features),
+        } => DataFusionError::UnsupportedDeltaFeatures {
+            table: name.to_string(),
+            features: features.iter().map(|f| f.to_string()).collect(),
+        },
+        err => err.into(),
+    }
+}
+
+#[cfg(feature = "iceberg")]
 fn find_iceberg_metadata(table_path: &str) -> std::result::Result<String, String> {
     let metadata_dir = Path::new(table_path).join("metadata");
     if !metadata_dir.is_dir() {
@@ -273,9 +1386,11 @@ impl Default for DataFusionContext {
 
 #[cfg(test)]
 mod tests {
+    use super::super::stream::{table_to_ipc, IPC_TRUNCATED_KEY};
     use super::*;
     use std::env;
     use std::path::PathBuf;
+    use std::time::Duration;
 
     fn get_samples_path() -> PathBuf {
         PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("samples")
@@ -315,4 +1430,850 @@ mod tests {
             assert!(table.row_count() > 0);
         }
     }
+
+    #[test]
+    fn test_frequency_query() {
+        let ctx = DataFusionContext::new().unwrap();
+        let sql = super::super::frequency_query(
+            "SELECT * FROM (VALUES ('b'), ('a'), ('b'), (NULL)) AS t(v);",
+            "v",
+        );
+        let table = ctx.execute_sql(&sql).unwrap();
+        let names: Vec<&str> = table
+            .schema
+            .columns
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(names, ["v", "count"]);
+        assert_eq!(table.rows[0].values[0], Value::String("b".to_string()));
+        assert_eq!(table.rows[0].values[1], Value::Integer(2));
+        assert_eq!(table.row_count(), 3);
+    }
+
+    #[test]
+    fn test_execute_sql_async() {
+        let mut ctx = DataFusionContext::new().unwrap();
+        let users_csv = get_samples_path().join("users.csv");
+        ctx.register_csv("users", &users_csv).unwrap();
+
+        let handle = ctx.execute_sql_async("SELECT COUNT(*) FROM users");
+        let table = handle.wait().unwrap();
+        assert_eq!(table.row_count(), 1);
+    }
+
+    #[test]
+    fn test_concurrent_queries_can_be_awaited() {
+        let mut ctx = DataFusionContext::new().unwrap();
+        ctx.register_csv("users", &get_samples_path().join("users.csv"))
+            .unwrap();
+
+        let slow = ctx.execute_sql_async(
+            "SELECT value < 0 AS negative FROM generate_series(1, 100000000000)",
+        );
+        let preview = ctx.execute_sql_async("SELECT * FROM users LIMIT 3");
+        let count = ctx.execute_sql_async("SELECT COUNT(*) FROM users");
+        let (preview, count) = futures::executor::block_on(futures::future::join(preview, count));
+        assert_eq!(preview.unwrap().row_count(), 3);
+        assert_eq!(count.unwrap().rows[0].values[0], Value::Integer(10));
+
+        assert!(!slow.is_finished());
+        slow.cancel();
+        assert!(matches!(
+            futures::executor::block_on(slow),
+            Err(DataFusionError::QueryCancelled)
+        ));
+    }
+
+    #[test]
+    fn test_execute_sql_async_reports_progress() {
+        let mut ctx = DataFusionContext::new().unwrap();
+        let users_csv = get_samples_path().join("users.csv");
+        ctx.register_csv("users", &users_csv).unwrap();
+
+        let mut handle =
+            ctx.execute_sql_async("SELECT department, COUNT(*) FROM users GROUP BY department");
+        let table = loop {
+            if let Some(result) = handle.try_result() {
+                break result.unwrap();
+            }
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        };
+
+        let progress = handle.progress();
+        assert_eq!(progress.rows_scanned, 10);
+        assert_eq!(progress.rows_produced, table.row_count() as u64);
+        assert!(progress.operator.is_some());
+    }
+
+    #[test]
+    fn test_refresh_table_picks_up_schema_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scores.csv");
+        std::fs::write(&path, "id,score\n1,10\n").unwrap();
+
+        let mut ctx = DataFusionContext::new().unwrap();
+        ctx.register_csv("scores", &path).unwrap();
+        assert_eq!(ctx.table_source("scores"), Some(path.as_path()));
+
+        std::fs::write(&path, "id,score,grade\n1,10,A\n2,7,B\n").unwrap();
+        ctx.refresh_table("scores").unwrap();
+
+        let table = ctx.execute_sql("SELECT grade FROM scores").unwrap();
+        assert_eq!(table.row_count(), 2);
+        assert_eq!(ctx.list_tables(), vec!["scores"]);
+    }
+
+    #[test]
+    fn test_register_csv_with_options() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("orders.csv");
+        std::fs::write(
+            &path,
+            "zip;ordered;amount\n00123;31.12.2024;1.234,5\n04567;01.02.2025;-3\n",
+        )
+        .unwrap();
+        let options = CsvOptions::default()
+            .with_delimiter(b';')
+            .with_date_format("%d.%m.%Y")
+            .with_decimal_separator(',')
+            .with_column_type("ZIP", crate::storage::DataType::String);
+
+        let mut ctx = DataFusionContext::new().unwrap();
+        ctx.register_csv_with_options("orders", &path, &options)
+            .unwrap();
+        let check = "SELECT zip, ordered, amount FROM orders WHERE ordered < DATE '2025-01-01'";
+        let table = ctx.execute_sql(check).unwrap();
+        assert_eq!(table.row_count(), 1);
+        assert_eq!(table.rows[0].values[0], Value::String("00123".to_string()));
+        assert_eq!(
+            table.rows[0].values[1],
+            Value::Date(chrono::NaiveDate::from_ymd_opt(2024, 12, 31).unwrap())
+        );
+        assert_eq!(table.rows[0].values[2], Value::Float(1234.5));
+
+        std::fs::write(&path, "zip;ordered;amount\n00123;2024-12-31;2\n").unwrap();
+        ctx.refresh_table("orders").unwrap();
+        let table = ctx.execute_sql("SELECT zip FROM orders").unwrap();
+        assert_eq!(table.rows[0].values[0], Value::String("00123".to_string()));
+
+        let typed = CsvOptions::default()
+            .with_delimiter(b';')
+            .with_column_type("zip", crate::storage::DataType::Date);
+        let err = ctx.register_csv_with_options("typed", &path, &typed);
+        assert!(matches!(err, Err(DataFusionError::Conversion(_))));
+        assert!(!ctx.list_tables().contains(&"typed".to_string()));
+    }
+
+    #[test]
+    fn test_refresh_keeps_table_when_source_is_gone() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scores.csv");
+        std::fs::write(&path, "id,score\n1,10\n").unwrap();
+
+        let mut ctx = DataFusionContext::new().unwrap();
+        ctx.register_csv("scores", &path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let err = ctx.refresh_table("scores").unwrap_err();
+        assert!(matches!(err, DataFusionError::FileNotFound(_)));
+        assert!(ctx.get_table_schema("scores").is_some());
+        assert!(matches!(
+            ctx.refresh_table("missing"),
+            Err(DataFusionError::TableNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_refresh_keeps_provenance_columns() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scores.csv");
+        std::fs::write(&path, "id,score\n1,10\n").unwrap();
+
+        let mut ctx = DataFusionContext::new().unwrap();
+        ctx.register_csv("scores", &path).unwrap();
+        ctx.add_provenance_columns("scores", true).unwrap();
+
+        std::fs::write(&path, "id,score\n1,10\n2,7\n").unwrap();
+        ctx.refresh_table("scores").unwrap();
+
+        let table = ctx
+            .execute_sql(
+                "SELECT MAX(_row_id) AS last, COUNT(DISTINCT _source_file) AS files FROM scores",
+            )
+            .unwrap();
+        assert_eq!(
+            table.rows[0].values,
+            vec![Value::Integer(2), Value::Integer(1)]
+        );
+        assert!(matches!(
+            ctx.add_provenance_columns("missing", false),
+            Err(DataFusionError::TableNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_session_settings() {
+        let mut ctx = DataFusionContext::new().unwrap();
+        ctx.register_csv("users", &get_samples_path().join("users.csv"))
+            .unwrap();
+
+        let set = ctx.execute_sql("SET max_rows = 3").unwrap();
+        assert_eq!(
+            set.rows[0].values,
+            vec![Value::String("max_rows".into()), Value::String("3".into())]
+        );
+        let result = ctx.execute_sql("SELECT * FROM users").unwrap();
+        assert_eq!(result.row_count(), 3);
+
+        ctx.execute_sql("SET timezone = 'Europe/Berlin'").unwrap();
+        assert_eq!(ctx.settings().timezone, "Europe/Berlin");
+        let all = ctx.execute_sql("SHOW ALL").unwrap();
+        let value = |name: &str| {
+            all.rows
+                .iter()
+                .find(|row| row.values[0] == Value::String(name.into()))
+                .map(|row| row.values[1].clone())
+        };
+        assert_eq!(
+            value("timezone"),
+            Some(Value::String("Europe/Berlin".into()))
+        );
+        assert_eq!(
+            value("datafusion.execution.time_zone"),
+            Some(Value::String("Europe/Berlin".into()))
+        );
+
+        assert!(matches!(
+            ctx.execute_sql("SET max_rows = lots"),
+            Err(DataFusionError::InvalidSetting(_))
+        ));
+        ctx.set_setting("max_rows", "none").unwrap();
+        assert!(ctx.execute_sql("SELECT * FROM users").unwrap().row_count() > 3);
+    }
+
+    #[test]
+    fn test_query_options_limit_one_query() {
+        let ctx = DataFusionContext::new().unwrap();
+        let sql = "SELECT value FROM generate_series(1, 10) ORDER BY value";
+        let options = QueryOptions {
+            max_rows: Some(3),
+            ..QueryOptions::default()
+        };
+        let limited = ctx.execute_sql_with_options(sql, options).unwrap();
+        assert_eq!(limited.row_count(), 3);
+        assert!(limited.truncated);
+        let all = ctx.execute_sql(sql).unwrap();
+        assert_eq!(all.row_count(), 10);
+        assert!(!all.truncated);
+        let options = QueryOptions {
+            max_rows: Some(10),
+            ..QueryOptions::default()
+        };
+        assert!(
+            !ctx.execute_sql_with_options(sql, options)
+                .unwrap()
+                .truncated
+        );
+
+        let options = QueryOptions {
+            timeout: Some(Duration::from_millis(50)),
+            ..QueryOptions::default()
+        };
+        let slow = "SELECT value < 0 AS negative FROM generate_series(1, 100000000000)";
+        assert!(matches!(
+            ctx.execute_sql_with_options(slow, options),
+            Err(DataFusionError::QueryTimedOut(_))
+        ));
+
+        let options = QueryOptions {
+            memory_limit: Some(64 * 1024),
+            ..QueryOptions::default()
+        };
+        let hungry = "SELECT array_agg(value) FROM generate_series(1, 1000000)";
+        let err = ctx.execute_sql_with_options(hungry, options).unwrap_err();
+        assert!(err.to_string().contains("Resources exhausted"), "{}", err);
+        assert!(ctx.execute_sql(hungry).is_ok());
+    }
+
+    #[test]
+    fn test_timeout_setting_applies_to_spawned_queries() {
+        let ctx = DataFusionContext::new().unwrap();
+        ctx.execute_sql("SET timeout_secs = 1").unwrap();
+        let handle = ctx.execute_sql_async(
+            "SELECT value < 0 AS negative FROM generate_series(1, 100000000000)",
+        );
+        assert!(matches!(
+            handle.wait(),
+            Err(DataFusionError::QueryTimedOut(_))
+        ));
+    }
+
+    #[test]
+    fn test_result_memory_limit_spills_to_disk() {
+        let ctx = DataFusionContext::new().unwrap();
+        ctx.execute_sql("SET result_memory_mb = 1").unwrap();
+
+        let table = ctx
+            .execute_sql("SELECT value AS n FROM generate_series(1, 300000) ORDER BY n")
+            .unwrap();
+        let spilled = table.spilled.clone().expect("result should spill");
+        assert_eq!(table.total_row_count(), 300_000);
+        assert_eq!(table.row_count(), SPILL_PAGE_ROWS);
+        assert_eq!(table.rows[0].values[0], Value::Integer(1));
+        let page = spilled.rows(250_000, 2).unwrap();
+        assert_eq!(page[1].values[0], Value::Integer(250_002));
+
+        let mut out = Vec::new();
+        crate::render::render_all(
+            &mut crate::render::CsvRenderer::new(),
+            &mut out,
+            &table,
+            &MaskRules::default(),
+        )
+        .unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        assert_eq!(csv.lines().count(), 300_001);
+        assert_eq!(csv.lines().last(), Some("300000"));
+
+        let small = ctx.execute_sql("SELECT 1").unwrap();
+        assert!(small.spilled.is_none());
+        assert!(matches!(
+            ctx.execute_sql("SET result_memory_mb = 0"),
+            Err(DataFusionError::InvalidSetting(_))
+        ));
+    }
+
+    #[test]
+    fn test_cache_table() {
+        let mut ctx = DataFusionContext::new().unwrap();
+        ctx.register_csv("users", &get_samples_path().join("users.csv"))
+            .unwrap();
+
+        let cached = ctx
+            .execute_sql(
+                "CACHE TABLE engineers AS SELECT * FROM users WHERE department = 'Engineering'",
+            )
+            .unwrap();
+        assert_eq!(
+            cached.rows[0].values,
+            vec![Value::String("engineers".into()), Value::Integer(5)]
+        );
+        assert_eq!(ctx.list_tables(), vec!["users", "engineers"]);
+
+        let result = ctx.execute_sql("SELECT COUNT(*) FROM engineers").unwrap();
+        assert_eq!(result.rows[0].values[0], Value::Integer(5));
+        assert!(matches!(
+            ctx.execute_sql("CACHE TABLE engineers AS SELECT 1"),
+            Err(DataFusionError::TableAlreadyExists(_))
+        ));
+
+        ctx.execute_sql("DROP TABLE engineers").unwrap();
+        assert_eq!(ctx.list_tables(), vec!["users"]);
+    }
+
+    #[test]
+    fn test_temp_tables() {
+        let mut ctx = DataFusionContext::new().unwrap();
+        ctx.register_csv("users", &get_samples_path().join("users.csv"))
+            .unwrap();
+
+        let created = ctx
+            .execute_sql(
+                "CREATE TEMP TABLE eng AS SELECT * FROM users WHERE department = 'Engineering'",
+            )
+            .unwrap();
+        assert_eq!(
+            created.rows[0].values,
+            vec![Value::String("eng".into()), Value::Integer(5)]
+        );
+        ctx.execute_sql("CREATE TEMPORARY TABLE eng_names AS SELECT name FROM eng")
+            .unwrap();
+        assert_eq!(ctx.list_tables(), vec!["users"]);
+        assert_eq!(ctx.temp_tables(), vec!["eng", "eng_names"]);
+
+        let result = ctx.execute_sql("SELECT COUNT(*) FROM eng_names").unwrap();
+        assert_eq!(result.rows[0].values[0], Value::Integer(5));
+        assert!(matches!(
+            ctx.execute_sql("CREATE TEMP TABLE users AS SELECT 1"),
+            Err(DataFusionError::TableAlreadyExists(_))
+        ));
+        assert!(ctx.execute_sql("CACHE TABLE eng AS SELECT 1").is_err());
+
+        ctx.execute_sql("DROP TABLE eng").unwrap();
+        assert_eq!(ctx.temp_tables(), vec!["eng_names"]);
+        ctx.clear_temp_tables().unwrap();
+        assert!(ctx.temp_tables().is_empty());
+        assert!(ctx.execute_sql("SELECT * FROM eng_names").is_err());
+        assert_eq!(ctx.list_tables(), vec!["users"]);
+    }
+
+    #[test]
+    fn test_views_follow_refreshed_tables() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scores.csv");
+        std::fs::write(&path, "id,score\n1,10\n").unwrap();
+        let mut ctx = DataFusionContext::new().unwrap();
+        ctx.register_csv("scores", &path).unwrap();
+        ctx.execute_sql("CREATE VIEW high AS SELECT id, score FROM scores WHERE score > 5")
+            .unwrap();
+        ctx.execute_sql("CREATE VIEW high_count AS SELECT COUNT(*) AS n FROM high")
+            .unwrap();
+        assert_eq!(ctx.list_tables(), vec!["scores", "high", "high_count"]);
+        let formats: Vec<Option<&str>> = ctx
+            .list_table_details()
+            .iter()
+            .map(|details| details.format)
+            .collect();
+        assert_eq!(formats, vec![Some("csv"), Some("view"), Some("view")]);
+
+        std::fs::write(&path, "id,score,team\n1,10,a\n2,20,b\n3,1,b\n").unwrap();
+        ctx.refresh_table("scores").unwrap();
+        let count = ctx.execute_sql("SELECT n FROM high_count").unwrap();
+        assert_eq!(count.rows[0].values[0], Value::Integer(2));
+    }
+
+    #[test]
+    fn test_query_manifest() {
+        let mut ctx = DataFusionContext::new().unwrap();
+        let path = get_samples_path().join("users.csv");
+        ctx.register_csv("users", &path).unwrap();
+        ctx.execute_sql("CREATE VIEW v AS SELECT 1").unwrap();
+
+        let manifest = ctx
+            .query_manifest(vec![ManifestQuery {
+                name: "query_1".to_string(),
+                sql: "SELECT * FROM users".to_string(),
+                row_count: 10,
+            }])
+            .unwrap();
+        assert_eq!(manifest.knowhere_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(manifest.sources.len(), 1);
+        assert_eq!(manifest.sources[0].table, "users");
+        assert_eq!(
+            manifest.sources[0].size_bytes,
+            std::fs::metadata(&path).unwrap().len()
+        );
+        assert_eq!(manifest.sources[0].sha256.len(), 64);
+    }
+
+    #[test]
+    fn test_count_star_uses_parquet_metadata() {
+        let mut ctx = DataFusionContext::new().unwrap();
+        ctx.register_csv("users", &get_samples_path().join("users.csv"))
+            .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("users.parquet");
+        ctx.export_sql(
+            "SELECT * FROM users",
+            &path,
+            ExportFormat::Parquet,
+            &MaskRules::default(),
+        )
+        .unwrap();
+        ctx.register_parquet("users_pq", &path).unwrap();
+
+        let plan = ctx
+            .execute_sql("EXPLAIN SELECT COUNT(*) FROM users_pq")
+            .unwrap();
+        let physical = plan.rows[1].values[1].to_string();
+        assert!(physical.contains("PlaceholderRowExec"), "{}", physical);
+        assert!(!physical.contains("DataSourceExec"), "{}", physical);
+
+        let result = ctx.execute_sql("SELECT COUNT(*) FROM users_pq").unwrap();
+        assert_eq!(result.rows[0].values[0], Value::Integer(10));
+    }
+
+    #[test]
+    fn test_pruning_stats() {
+        use arrow::array::Int64Array;
+        use arrow::datatypes::{DataType as ArrowType, Field, Schema as ArrowSchema};
+        use parquet::arrow::ArrowWriter;
+        use parquet::file::properties::WriterProperties;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ids.parquet");
+        let schema = Arc::new(ArrowSchema::new(vec![Field::new(
+            "id",
+            ArrowType::Int64,
+            false,
+        )]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int64Array::from_iter_values(1..=10))],
+        )
+        .unwrap();
+        let props = WriterProperties::builder()
+            .set_max_row_group_size(2)
+            .build();
+        let mut writer =
+            ArrowWriter::try_new(std::fs::File::create(&path).unwrap(), schema, Some(props))
+                .unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let mut ctx = DataFusionContext::new().unwrap();
+        ctx.register_parquet("ids", &path).unwrap();
+
+        let (result, pruning) = ctx
+            .execute_sql_with_pruning("SELECT id FROM ids WHERE id > 8")
+            .unwrap();
+        assert_eq!(result.row_count(), 2);
+        assert_eq!(pruning.files_scanned, 1);
+        assert_eq!(pruning.row_groups_matched, 1);
+        assert_eq!(pruning.row_groups_pruned_statistics, 4);
+        assert!(pruning.bytes_scanned > 0);
+
+        let plan = ctx
+            .execute_sql("EXPLAIN ANALYZE SELECT id FROM ids WHERE id > 8")
+            .unwrap();
+        let last = plan.rows.last().unwrap();
+        assert_eq!(last.values[0], Value::String("Pruning".into()));
+        assert_eq!(
+            last.values[1],
+            Value::String(format!(
+                "1 file scanned ({} bytes read); 4 of 5 row groups pruned (4 by statistics, 0 by bloom filters)",
+                pruning.bytes_scanned
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parquet_scans_read_only_needed_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("series.parquet");
+        let mut ctx = DataFusionContext::new().unwrap();
+        ctx.execute_sql(&format!(
+            "COPY (SELECT value AS n, md5(CAST(value AS VARCHAR)) AS hash \
+             FROM generate_series(1, 100000)) TO '{}' \
+             OPTIONS (format.max_row_group_size 5000)",
+            path.display()
+        ))
+        .unwrap();
+        ctx.register_parquet("series", &path).unwrap();
+        ctx.execute_sql("SET datafusion.execution.batch_size = 100")
+            .unwrap();
+
+        let (limited, pruning) = ctx
+            .execute_sql_with_pruning("SELECT * FROM series LIMIT 100")
+            .unwrap();
+        assert_eq!(limited.row_count(), 100);
+        let (_, full) = ctx
+            .execute_sql_with_pruning("SELECT * FROM series")
+            .unwrap();
+        assert!(pruning.bytes_scanned > 0);
+        assert!(pruning.bytes_scanned * 10 < full.bytes_scanned);
+
+        // Column chunks of columns that are not selected are never read
+        let (_, projected) = ctx
+            .execute_sql_with_pruning("SELECT n FROM series")
+            .unwrap();
+        assert!(projected.bytes_scanned > 0);
+        assert!(projected.bytes_scanned * 3 < full.bytes_scanned);
+    }
+
+    #[test]
+    fn test_export_sql_streams_to_files() {
+        let mut ctx = DataFusionContext::new().unwrap();
+        ctx.register_csv("users", &get_samples_path().join("users.csv"))
+            .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let sql = "SELECT id, name FROM users ORDER BY id LIMIT 3";
+
+        let csv = dir.path().join("out.csv");
+        let summary = ctx
+            .export_sql(sql, &csv, ExportFormat::Csv, &MaskRules::default())
+            .unwrap();
+        assert_eq!(summary.rows, 3);
+        assert!(!summary.truncated);
+        let text = std::fs::read_to_string(&csv).unwrap();
+        assert!(text.starts_with("id,name\n1,Alice Johnson\n"));
+
+        let json = dir.path().join("out.jsonl");
+        ctx.export_sql(sql, &json, ExportFormat::Json, &MaskRules::default())
+            .unwrap();
+        let text = std::fs::read_to_string(&json).unwrap();
+        assert_eq!(text.lines().count(), 3);
+
+        let parquet = dir.path().join("out.parquet");
+        ctx.export_sql(sql, &parquet, ExportFormat::Parquet, &MaskRules::default())
+            .unwrap();
+        ctx.register_parquet("exported", &parquet).unwrap();
+        let result = ctx.execute_sql("SELECT COUNT(*) FROM exported").unwrap();
+        assert_eq!(result.rows[0].values[0], Value::Integer(3));
+    }
+
+    #[test]
+    fn test_table_to_ipc_round_trips_a_result() {
+        let mut ctx = DataFusionContext::new().unwrap();
+        ctx.register_csv("users", &get_samples_path().join("users.csv"))
+            .unwrap();
+        let sql = "SELECT id, name, salary, active, DATE '2024-03-01' AS day, \
+                   TIMESTAMP '2024-03-01 12:30:00' AS at, NULL AS nothing \
+                   FROM users ORDER BY id";
+        let options = QueryOptions {
+            max_rows: Some(4),
+            ..QueryOptions::default()
+        };
+        let table = ctx
+            .execute_sql_async_with_options(sql, options)
+            .wait()
+            .unwrap();
+        assert!(table.truncated);
+
+        let bytes = table_to_ipc(&table, &MaskRules::default()).unwrap();
+        let reader =
+            arrow::ipc::reader::StreamReader::try_new(std::io::Cursor::new(bytes), None).unwrap();
+        let schema = reader.schema();
+        assert_eq!(
+            schema.metadata().get(IPC_TRUNCATED_KEY).map(String::as_str),
+            Some("true")
+        );
+        let batches: Vec<RecordBatch> = reader.map(|batch| batch.unwrap()).collect();
+        let decoded = record_batch_to_table("result", batches).unwrap();
+        let types = |table: &Table| -> Vec<DataType> {
+            table
+                .schema
+                .columns
+                .iter()
+                .map(|column| column.data_type.clone())
+                .collect()
+        };
+        assert_eq!(types(&decoded), types(&table));
+        let values = |table: &Table| -> Vec<Vec<Value>> {
+            table.rows.iter().map(|row| row.values.clone()).collect()
+        };
+        assert_eq!(values(&decoded), values(&table));
+        assert_eq!(decoded.row_count(), 4);
+    }
+
+    #[test]
+    fn test_export_sql_masks_batches() {
+        let mut ctx = DataFusionContext::new().unwrap();
+        ctx.register_csv("users", &get_samples_path().join("users.csv"))
+            .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let sql = "SELECT id, name, NULL AS notes FROM users ORDER BY id LIMIT 2";
+        let masks = MaskRules::for_columns("name:redact,notes:hash").unwrap();
+
+        let csv = dir.path().join("out.csv");
+        ctx.export_sql(sql, &csv, ExportFormat::Csv, &masks)
+            .unwrap();
+        let text = std::fs::read_to_string(&csv).unwrap();
+        assert_eq!(text, "id,name,notes\n1,***,\n2,***,\n");
+
+        let parquet = dir.path().join("out.parquet");
+        ctx.export_sql(sql, &parquet, ExportFormat::Parquet, &masks)
+            .unwrap();
+        ctx.register_parquet("exported", &parquet).unwrap();
+        let result = ctx
+            .execute_sql("SELECT DISTINCT name FROM exported")
+            .unwrap();
+        assert_eq!(result.rows[0].values[0], Value::String("***".to_string()));
+    }
+
+    #[test]
+    fn test_export_sql_applies_session_limits() {
+        let ctx = DataFusionContext::new().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let csv = dir.path().join("out.csv");
+
+        ctx.set_setting("max_rows", "5").unwrap();
+        let sql = "SELECT value FROM generate_series(1, 100) ORDER BY value";
+        let summary = ctx
+            .export_sql(sql, &csv, ExportFormat::Csv, &MaskRules::default())
+            .unwrap();
+        assert_eq!(summary.rows, 5);
+        assert!(summary.truncated);
+        assert_eq!(std::fs::read_to_string(&csv).unwrap().lines().count(), 6);
+        ctx.set_setting("max_rows", "none").unwrap();
+
+        let options = QueryOptions {
+            timeout: Some(Duration::from_millis(50)),
+            ..QueryOptions::default()
+        };
+        let slow = "SELECT value < 0 AS negative FROM generate_series(1, 100000000000)";
+        assert!(matches!(
+            ctx.export_sql_with_options(
+                slow,
+                &csv,
+                ExportFormat::Csv,
+                &MaskRules::default(),
+                options
+            ),
+            Err(DataFusionError::QueryTimedOut(_))
+        ));
+
+        ctx.set_setting("max_memory_mb", "1").unwrap();
+        let hungry = "SELECT array_agg(value) FROM generate_series(1, 1000000)";
+        let err = ctx
+            .export_sql(hungry, &csv, ExportFormat::Csv, &MaskRules::default())
+            .unwrap_err();
+        assert!(err.to_string().contains("Resources exhausted"), "{}", err);
+    }
+
+    #[test]
+    fn test_infer_result_schema_reads_no_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("orders.csv");
+        std::fs::write(&path, "id,amount\n1,9.5\n").unwrap();
+        let mut ctx = DataFusionContext::new().unwrap();
+        ctx.register_csv("orders", &path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let schema = ctx
+            .infer_result_schema("SELECT id, amount * 2 AS doubled, 'x' AS tag FROM orders")
+            .unwrap();
+        assert_eq!(schema.column_names(), vec!["id", "doubled", "tag"]);
+        let types: Vec<&DataType> = schema.columns.iter().map(|c| &c.data_type).collect();
+        assert_eq!(
+            types,
+            vec![&DataType::Integer, &DataType::Float, &DataType::String]
+        );
+        assert!(ctx.execute_sql("SELECT id FROM orders").is_err());
+
+        // DDL is planned but not applied
+        ctx.infer_result_schema("CREATE VIEW big AS SELECT * FROM orders")
+            .unwrap();
+        assert!(ctx.infer_result_schema("SELECT * FROM big").is_err());
+
+        assert!(matches!(
+            ctx.infer_result_schema("SELECT total FROM orders"),
+            Err(DataFusionError::ColumnNotFound { .. })
+        ));
+        assert!(matches!(
+            ctx.infer_result_schema("SUMMARIZE orders"),
+            Err(DataFusionError::ResultSchemaUnknown(_))
+        ));
+    }
+
+    #[test]
+    fn test_csv_tail_sees_appended_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.csv");
+        std::fs::write(&path, "level,message\ninfo,started\n").unwrap();
+
+        let mut ctx = DataFusionContext::new().unwrap();
+        ctx.register_csv_tail("log", &path).unwrap();
+        let count = |ctx: &DataFusionContext| {
+            ctx.execute_sql("SELECT COUNT(*) FROM log").unwrap().rows[0].values[0].clone()
+        };
+        assert_eq!(count(&ctx), Value::Integer(1));
+
+        let mut contents = std::fs::read_to_string(&path).unwrap();
+        contents.push_str("warn,slow\nerror,failed\n");
+        std::fs::write(&path, contents).unwrap();
+        assert_eq!(count(&ctx), Value::Integer(3));
+        assert_eq!(ctx.table_details("log").unwrap().format, Some("csv"));
+    }
+
+    #[test]
+    fn test_refresh_all() {
+        let mut ctx = DataFusionContext::new().unwrap();
+        let samples = get_samples_path();
+        ctx.register_csv("users", &samples.join("users.csv"))
+            .unwrap();
+        ctx.register_csv("orders", &samples.join("orders.csv"))
+            .unwrap();
+
+        let refreshed = ctx.refresh_all().unwrap();
+        assert_eq!(refreshed, vec!["users", "orders"]);
+        assert_eq!(ctx.table_count(), 2);
+    }
+
+    #[test]
+    fn test_table_details() {
+        let mut ctx = DataFusionContext::new().unwrap();
+        let users_csv = get_samples_path().join("users.csv");
+        ctx.register_csv("users", &users_csv).unwrap();
+
+        let details = ctx.table_details("users").unwrap();
+        assert_eq!(details.column_count, 7);
+        assert_eq!(details.format, Some("csv"));
+        assert_eq!(details.row_count, Some(10));
+        assert_eq!(
+            details.size_bytes,
+            Some(std::fs::metadata(&users_csv).unwrap().len())
+        );
+        assert!(ctx.table_details("missing").is_none());
+        assert_eq!(ctx.list_table_details().len(), 1);
+    }
+
+    #[test]
+    fn test_tables_overview_and_describe() {
+        let mut ctx = DataFusionContext::new().unwrap();
+        let samples = get_samples_path();
+        ctx.register_csv("users", &samples.join("users.csv"))
+            .unwrap();
+        ctx.register_json("departments", &samples.join("departments.json"))
+            .unwrap();
+
+        let overview = ctx.tables_overview();
+        assert_eq!(
+            overview.schema.column_names(),
+            vec!["table_name", "format", "columns", "rows", "size_bytes"]
+        );
+        assert_eq!(overview.row_count(), 2);
+        assert_eq!(
+            overview.rows[1].values[0],
+            Value::String("departments".into())
+        );
+        assert_eq!(overview.rows[1].values[3], Value::Integer(3));
+
+        let described = ctx.describe_table("users").unwrap();
+        assert_eq!(described.row_count(), 7);
+        assert_eq!(described.rows[0].values[0], Value::String("id".into()));
+        assert!(matches!(
+            ctx.describe_table("missing"),
+            Err(DataFusionError::TableNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_parquet_row_count_is_exact() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("users.parquet");
+        let mut ctx = DataFusionContext::new().unwrap();
+        ctx.register_csv("users", &get_samples_path().join("users.csv"))
+            .unwrap();
+        ctx.execute_sql(&format!(
+            "COPY (SELECT * FROM users) TO '{}' STORED AS PARQUET",
+            path.display()
+        ))
+        .unwrap();
+        ctx.register_parquet("users_pq", &path).unwrap();
+
+        let details = ctx.table_details("users_pq").unwrap();
+        assert_eq!(details.format, Some("parquet"));
+        assert_eq!(details.row_count, Some(10));
+        assert!(!details.row_count_estimated);
+    }
+
+    #[test]
+    fn test_export_writes_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ctx = DataFusionContext::new().unwrap();
+        ctx.register_csv("users", &get_samples_path().join("users.csv"))
+            .unwrap();
+
+        let lines = dir.path().join("users.jsonl");
+        let written = ctx
+            .execute_sql(&format!(
+                "EXPORT (SELECT id FROM users ORDER BY id FETCH FIRST 3 ROWS ONLY) TO '{}'",
+                lines.display()
+            ))
+            .unwrap();
+        assert_eq!(written.rows[0].values[0], Value::Integer(3));
+        assert_eq!(
+            std::fs::read_to_string(&lines).unwrap(),
+            "{\"id\":1}\n{\"id\":2}\n{\"id\":3}\n"
+        );
+
+        let parquet = dir.path().join("users.parquet");
+        ctx.execute_sql(&format!("COPY users TO '{}'", parquet.display()))
+            .unwrap();
+        ctx.register_parquet("users_pq", &parquet).unwrap();
+        let count = ctx.execute_sql("SELECT COUNT(*) FROM users_pq").unwrap();
+        assert_eq!(count.rows[0].values[0], Value::Integer(10));
+    }
 }
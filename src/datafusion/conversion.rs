@@ -1,15 +1,21 @@
+use std::sync::Arc;
+
 use arrow::array::{
-    Array, ArrayRef, BooleanArray, Date32Array, Date64Array, Float32Array, Float64Array,
-    Int16Array, Int32Array, Int64Array, Int8Array, LargeListArray, LargeStringArray, ListArray,
-    StringArray, StructArray, TimestampMicrosecondArray, TimestampMillisecondArray,
-    TimestampNanosecondArray, TimestampSecondArray, UInt16Array, UInt32Array, UInt64Array,
-    UInt8Array,
+    downcast_dictionary_array, downcast_run_array, new_null_array, Array, ArrayRef, BooleanArray,
+    Date32Array, Date64Array, FixedSizeListArray, Float32Array, Float64Array, Int16Array,
+    Int32Array, Int64Array, Int8Array, LargeListArray, LargeStringArray, ListArray, MapArray,
+    StringArray, StringViewArray, StructArray, TimestampMicrosecondArray,
+    TimestampMillisecondArray, TimestampNanosecondArray, TimestampSecondArray, UInt16Array,
+    UInt32Array, UInt64Array, UInt8Array,
+};
+use arrow::datatypes::{
+    ArrowNativeType, DataType as ArrowDataType, Field, Schema as ArrowSchema, TimeUnit,
 };
-use arrow::datatypes::{DataType as ArrowDataType, TimeUnit};
-use arrow::record_batch::RecordBatch;
+use arrow::record_batch::{RecordBatch, RecordBatchOptions};
+use arrow::util::display::{ArrayFormatter, FormatOptions};
 use chrono::{DateTime, NaiveDate, Utc};
 
-use crate::storage::table::{Column, DataType, Row, Schema, Table, Value};
+use crate::storage::table::{json_string, Column, DataType, Row, Schema, Table, Value};
 
 use super::error::{DataFusionError, Result};
 
@@ -42,19 +48,117 @@ pub fn record_batch_to_table(
     Ok(Table::with_rows(table_name, schema, rows))
 }
 
+/// `rows` of `schema` as one record batch, the reverse of
+/// [`record_batch_to_table`]. Integers become `Int64`, floats `Float64`,
+/// dates `Date32` and timestamps microsecond `Timestamp`s; a value that does
+/// not fit its column's type is written as NULL, except in string columns,
+/// which hold any value as text.
+pub fn rows_to_record_batch(schema: &Schema, rows: &[Row]) -> Result<RecordBatch> {
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap_or_default();
+    let mut fields = Vec::with_capacity(schema.columns.len());
+    let mut columns: Vec<ArrayRef> = Vec::with_capacity(schema.columns.len());
+    for (i, column) in schema.columns.iter().enumerate() {
+        let values = rows
+            .iter()
+            .map(move |row| row.values.get(i).unwrap_or(&Value::Null));
+        let array: ArrayRef = match column.data_type {
+            DataType::Integer => Arc::new(values.map(Value::as_integer).collect::<Int64Array>()),
+            DataType::Float => Arc::new(values.map(Value::as_float).collect::<Float64Array>()),
+            DataType::Boolean => Arc::new(values.map(Value::as_bool).collect::<BooleanArray>()),
+            DataType::String => Arc::new(
+                values
+                    .map(|value| (!value.is_null()).then(|| value.to_string()))
+                    .collect::<StringArray>(),
+            ),
+            DataType::Date => Arc::new(
+                values
+                    .map(|value| match value {
+                        Value::Date(date) => Some((*date - epoch).num_days() as i32),
+                        _ => None,
+                    })
+                    .collect::<Date32Array>(),
+            ),
+            DataType::Timestamp => Arc::new(
+                values
+                    .map(|value| {
+                        value
+                            .as_timestamp()
+                            .map(|ts| ts.and_utc().timestamp_micros())
+                    })
+                    .collect::<TimestampMicrosecondArray>(),
+            ),
+            DataType::Null => new_null_array(&ArrowDataType::Null, rows.len()),
+        };
+        fields.push(Field::new(&column.name, array.data_type().clone(), true));
+        columns.push(array);
+    }
+    let options = RecordBatchOptions::new().with_row_count(Some(rows.len()));
+    Ok(RecordBatch::try_new_with_options(
+        Arc::new(ArrowSchema::new(fields)),
+        columns,
+        &options,
+    )?)
+}
+
+/// Arrow field metadata key under which a table provider records the type
+/// a column is declared with in its source, such as `VARCHAR(20)` in
+/// SQLite. Columns without it get a SQL name for their Arrow type.
+pub(super) const SOURCE_TYPE_KEY: &str = "knowhere:source_type";
+
 pub fn convert_schema(arrow_schema: &arrow::datatypes::Schema) -> Result<Schema> {
     let columns = arrow_schema
         .fields()
         .iter()
         .map(|field| {
             let data_type = convert_data_type(field.data_type());
+            let source_type = field
+                .metadata()
+                .get(SOURCE_TYPE_KEY)
+                .cloned()
+                .unwrap_or_else(|| sql_type_name(field.data_type()));
             Column::new(field.name().clone(), data_type)
+                .with_nullable(field.is_nullable())
+                .with_source_type(source_type)
         })
         .collect();
 
     Ok(Schema::new(columns))
 }
 
+/// SQL spelling of an Arrow type, e.g. `DECIMAL(10,2)` or `BIGINT`. Types
+/// without a common SQL name use Arrow's own.
+fn sql_type_name(arrow_type: &ArrowDataType) -> String {
+    let name = match arrow_type {
+        ArrowDataType::Boolean => "BOOLEAN",
+        ArrowDataType::Int8 => "TINYINT",
+        ArrowDataType::Int16 => "SMALLINT",
+        ArrowDataType::Int32 => "INT",
+        ArrowDataType::Int64 => "BIGINT",
+        ArrowDataType::UInt8 => "TINYINT UNSIGNED",
+        ArrowDataType::UInt16 => "SMALLINT UNSIGNED",
+        ArrowDataType::UInt32 => "INT UNSIGNED",
+        ArrowDataType::UInt64 => "BIGINT UNSIGNED",
+        ArrowDataType::Float16 | ArrowDataType::Float32 => "FLOAT",
+        ArrowDataType::Float64 => "DOUBLE",
+        ArrowDataType::Utf8 | ArrowDataType::LargeUtf8 | ArrowDataType::Utf8View => "VARCHAR",
+        ArrowDataType::Binary
+        | ArrowDataType::LargeBinary
+        | ArrowDataType::BinaryView
+        | ArrowDataType::FixedSizeBinary(_) => "BLOB",
+        ArrowDataType::Date32 | ArrowDataType::Date64 => "DATE",
+        ArrowDataType::Timestamp(_, None) => "TIMESTAMP",
+        ArrowDataType::Timestamp(_, Some(_)) => "TIMESTAMP WITH TIME ZONE",
+        ArrowDataType::Decimal128(precision, scale)
+        | ArrowDataType::Decimal256(precision, scale) => {
+            return format!("DECIMAL({},{})", precision, scale)
+        }
+        ArrowDataType::Dictionary(_, value_type) => return sql_type_name(value_type),
+        ArrowDataType::RunEndEncoded(_, values) => return sql_type_name(values.data_type()),
+        other => return other.to_string(),
+    };
+    name.to_string()
+}
+
 fn convert_data_type(arrow_type: &ArrowDataType) -> DataType {
     match arrow_type {
         ArrowDataType::Int8
@@ -67,18 +171,50 @@ fn convert_data_type(arrow_type: &ArrowDataType) -> DataType {
         | ArrowDataType::UInt64 => DataType::Integer,
         ArrowDataType::Float32 | ArrowDataType::Float64 => DataType::Float,
         ArrowDataType::Boolean => DataType::Boolean,
-        ArrowDataType::Utf8 | ArrowDataType::LargeUtf8 => DataType::String,
-        ArrowDataType::Date32
-        | ArrowDataType::Date64
-        | ArrowDataType::Timestamp(_, _)
-        | ArrowDataType::Time32(_)
-        | ArrowDataType::Time64(_) => DataType::String, // Convert dates/timestamps to strings
+        ArrowDataType::Utf8 | ArrowDataType::LargeUtf8 | ArrowDataType::Utf8View => {
+            DataType::String
+        }
+        ArrowDataType::Date32 | ArrowDataType::Date64 => DataType::Date,
+        ArrowDataType::Timestamp(_, _) => DataType::Timestamp,
         ArrowDataType::Null => DataType::Null,
+        ArrowDataType::Dictionary(_, value_type) => convert_data_type(value_type),
+        ArrowDataType::RunEndEncoded(_, values) => convert_data_type(values.data_type()),
         _ => DataType::String, // Default to string for unsupported types
     }
 }
 
-fn convert_array_value(array: &ArrayRef, index: usize) -> Result<Value> {
+/// The value at `index` as a JSON literal for embedding in a rendered
+/// struct or list. Nested structs and lists are embedded as they are rather
+/// than quoted as strings.
+fn nested_json(array: &ArrayRef, index: usize) -> Result<String> {
+    let value = convert_array_value(array, index)?;
+    Ok(match value {
+        Value::String(json) if is_nested(array.data_type()) => json,
+        value => value.to_json(),
+    })
+}
+
+/// Whether values of `data_type` are converted to JSON strings.
+fn is_nested(data_type: &ArrowDataType) -> bool {
+    match data_type {
+        ArrowDataType::Struct(_)
+        | ArrowDataType::List(_)
+        | ArrowDataType::LargeList(_)
+        | ArrowDataType::FixedSizeList(_, _)
+        | ArrowDataType::Map(_, _) => true,
+        ArrowDataType::Dictionary(_, value_type) => is_nested(value_type),
+        ArrowDataType::RunEndEncoded(_, values) => is_nested(values.data_type()),
+        _ => false,
+    }
+}
+
+/// Every value of `slice` as a compact JSON array string.
+fn list_json(slice: &ArrayRef) -> Result<String> {
+    let parts: Result<Vec<String>> = (0..slice.len()).map(|i| nested_json(slice, i)).collect();
+    Ok(format!("[{}]", parts?.join(",")))
+}
+
+pub(super) fn convert_array_value(array: &ArrayRef, index: usize) -> Result<Value> {
     if array.is_null(index) {
         return Ok(Value::Null);
     }
@@ -137,6 +273,11 @@ fn convert_array_value(array: &ArrayRef, index: usize) -> Result<Value> {
             let arr = array.as_any().downcast_ref::<LargeStringArray>().unwrap();
             Value::String(arr.value(index).to_string())
         }
+        // The Parquet reader returns strings as views
+        ArrowDataType::Utf8View => {
+            let arr = array.as_any().downcast_ref::<StringViewArray>().unwrap();
+            Value::String(arr.value(index).to_string())
+        }
         // Nested JSON objects → Struct; render as a compact JSON object string
         ArrowDataType::Struct(fields) => {
             let arr = array.as_any().downcast_ref::<StructArray>().unwrap();
@@ -146,13 +287,9 @@ fn convert_array_value(array: &ArrayRef, index: usize) -> Result<Value> {
                 .enumerate()
                 .filter_map(|(i, field)| {
                     let child = arr.column(i);
-                    convert_array_value(child, index).ok().map(|v| {
-                        let rendered = match &v {
-                            Value::String(s) => format!("\"{}\"", s),
-                            _ => v.to_string(),
-                        };
-                        format!("\"{}\":{}", field.name(), rendered)
-                    })
+                    nested_json(child, index)
+                        .ok()
+                        .map(|json| format!("{}:{}", json_string(field.name()), json))
                 })
                 .collect();
             Value::String(format!("{{{}}}", parts.join(",")))
@@ -160,101 +297,137 @@ fn convert_array_value(array: &ArrayRef, index: usize) -> Result<Value> {
         // Nested JSON arrays → List; render as a compact JSON array string
         ArrowDataType::List(_) => {
             let arr = array.as_any().downcast_ref::<ListArray>().unwrap();
-            let slice = arr.value(index);
-            let parts: Result<Vec<String>> = (0..slice.len())
-                .map(|i| {
-                    convert_array_value(&slice, i).map(|v| match &v {
-                        Value::String(s) => format!("\"{}\"", s),
-                        _ => v.to_string(),
-                    })
-                })
-                .collect();
-            Value::String(format!("[{}]", parts?.join(",")))
+            Value::String(list_json(&arr.value(index))?)
         }
         ArrowDataType::LargeList(_) => {
             let arr = array.as_any().downcast_ref::<LargeListArray>().unwrap();
-            let slice = arr.value(index);
-            let parts: Result<Vec<String>> = (0..slice.len())
+            Value::String(list_json(&arr.value(index))?)
+        }
+        ArrowDataType::FixedSizeList(_, _) => {
+            let arr = array.as_any().downcast_ref::<FixedSizeListArray>().unwrap();
+            Value::String(list_json(&arr.value(index))?)
+        }
+        // Maps render as a JSON object, with keys as their display strings
+        ArrowDataType::Map(_, _) => {
+            let arr = array.as_any().downcast_ref::<MapArray>().unwrap();
+            let entries = arr.value(index);
+            let parts: Result<Vec<String>> = (0..entries.len())
                 .map(|i| {
-                    convert_array_value(&slice, i).map(|v| match &v {
-                        Value::String(s) => format!("\"{}\"", s),
-                        _ => v.to_string(),
-                    })
+                    let key = convert_array_value(entries.column(0), i)?;
+                    let value = nested_json(entries.column(1), i)?;
+                    Ok(format!("{}:{}", json_string(&key.to_string()), value))
                 })
                 .collect();
-            Value::String(format!("[{}]", parts?.join(",")))
+            Value::String(format!("{{{}}}", parts?.join(",")))
         }
+        // Dictionary-encoded columns hold the value at the row's key
+        ArrowDataType::Dictionary(_, _) => downcast_dictionary_array! {
+            array => {
+                let key = array.keys().value(index).as_usize();
+                convert_array_value(array.values(), key)?
+            }
+            t => {
+                return Err(DataFusionError::Conversion(format!(
+                    "unsupported dictionary type {}",
+                    t
+                )))
+            }
+        },
+        // Run-end encoded columns hold the value of the run the row is in
+        ArrowDataType::RunEndEncoded(_, _) => downcast_run_array! {
+            array => {
+                let run = array.get_physical_index(index);
+                convert_array_value(array.values(), run)?
+            }
+            t => {
+                return Err(DataFusionError::Conversion(format!(
+                    "unsupported run-end encoded type {}",
+                    t
+                )))
+            }
+        },
         ArrowDataType::Date32 => {
             let arr = array.as_any().downcast_ref::<Date32Array>().unwrap();
             let days = arr.value(index);
-            let date = NaiveDate::from_ymd_opt(1970, 1, 1)
+            NaiveDate::from_ymd_opt(1970, 1, 1)
                 .unwrap()
                 .checked_add_signed(chrono::Duration::days(days as i64))
-                .unwrap();
-            Value::String(date.format("%Y-%m-%d").to_string())
+                .map_or_else(|| out_of_range(array, index), Value::Date)
         }
         ArrowDataType::Date64 => {
             let arr = array.as_any().downcast_ref::<Date64Array>().unwrap();
             let millis = arr.value(index);
-            let datetime = DateTime::<Utc>::from_timestamp_millis(millis).unwrap();
-            Value::String(datetime.format("%Y-%m-%d").to_string())
+            DateTime::<Utc>::from_timestamp_millis(millis).map_or_else(
+                || out_of_range(array, index),
+                |dt| Value::Date(dt.date_naive()),
+            )
         }
         ArrowDataType::Timestamp(unit, _) => {
-            let timestamp_str = match unit {
+            let datetime = match unit {
                 TimeUnit::Second => {
                     let arr = array
                         .as_any()
                         .downcast_ref::<TimestampSecondArray>()
                         .unwrap();
-                    let seconds = arr.value(index);
-                    let datetime = DateTime::<Utc>::from_timestamp(seconds, 0).unwrap();
-                    datetime.format("%Y-%m-%d %H:%M:%S").to_string()
+                    DateTime::<Utc>::from_timestamp(arr.value(index), 0)
                 }
                 TimeUnit::Millisecond => {
                     let arr = array
                         .as_any()
                         .downcast_ref::<TimestampMillisecondArray>()
                         .unwrap();
-                    let millis = arr.value(index);
-                    let datetime = DateTime::<Utc>::from_timestamp_millis(millis).unwrap();
-                    datetime.format("%Y-%m-%d %H:%M:%S%.3f").to_string()
+                    DateTime::<Utc>::from_timestamp_millis(arr.value(index))
                 }
                 TimeUnit::Microsecond => {
                     let arr = array
                         .as_any()
                         .downcast_ref::<TimestampMicrosecondArray>()
                         .unwrap();
-                    let micros = arr.value(index);
-                    let datetime = DateTime::<Utc>::from_timestamp_micros(micros).unwrap();
-                    datetime.format("%Y-%m-%d %H:%M:%S%.6f").to_string()
+                    DateTime::<Utc>::from_timestamp_micros(arr.value(index))
                 }
                 TimeUnit::Nanosecond => {
                     let arr = array
                         .as_any()
                         .downcast_ref::<TimestampNanosecondArray>()
                         .unwrap();
-                    let nanos = arr.value(index);
-                    let datetime = DateTime::<Utc>::from_timestamp_nanos(nanos);
-                    datetime.format("%Y-%m-%d %H:%M:%S%.9f").to_string()
+                    Some(DateTime::<Utc>::from_timestamp_nanos(arr.value(index)))
                 }
             };
-            Value::String(timestamp_str)
+            datetime.map_or_else(
+                || out_of_range(array, index),
+                |dt| Value::Timestamp(dt.naive_utc()),
+            )
         }
         ArrowDataType::Null => Value::Null,
-        _ => {
-            // For unsupported types, convert to string representation
-            Value::String(format!("{:?}", array))
-        }
+        // Decimals, times, intervals, binary and the like use Arrow's display
+        // format; a type Arrow cannot display gets a placeholder rather than
+        // a dump of the whole array
+        data_type => match ArrayFormatter::try_new(array.as_ref(), &FormatOptions::default()) {
+            Ok(formatter) => Value::String(formatter.value(index).to_string()),
+            Err(_) => Value::String(format!("<unsupported: {}>", data_type)),
+        },
     };
 
     Ok(value)
 }
 
+/// A date or timestamp chrono cannot represent, in Arrow's display format.
+fn out_of_range(array: &ArrayRef, index: usize) -> Value {
+    match ArrayFormatter::try_new(array.as_ref(), &FormatOptions::default()) {
+        Ok(formatter) => Value::String(formatter.value(index).to_string()),
+        Err(_) => Value::String(format!("<unsupported: {}>", array.data_type())),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use arrow::array::{Int64Array, StringArray};
-    use arrow::datatypes::{Field, Schema as ArrowSchema};
+    use arrow::array::{
+        Decimal128Array, DictionaryArray, Int64Array, Int64Builder, MapBuilder, RunArray,
+        StringArray, StringBuilder,
+    };
+    use arrow::datatypes::{Field, Int32Type, Int64Type, Schema as ArrowSchema};
+    use std::collections::HashMap;
     use std::sync::Arc;
 
     #[test]
@@ -298,4 +471,155 @@ mod tests {
         assert_eq!(table.rows[1].values[0], Value::Null);
         assert_eq!(table.rows[2].values[0], Value::Integer(3));
     }
+
+    #[test]
+    fn test_schema_keeps_nullability_and_source_types() {
+        let declared = HashMap::from([(SOURCE_TYPE_KEY.to_string(), "VARCHAR(20)".to_string())]);
+        let arrow_schema = ArrowSchema::new(vec![
+            Field::new("price", ArrowDataType::Decimal128(10, 2), false),
+            Field::new("id", ArrowDataType::Int32, true),
+            Field::new("code", ArrowDataType::Utf8, true).with_metadata(declared),
+        ]);
+        let schema = convert_schema(&arrow_schema).unwrap();
+        let columns: Vec<(bool, Option<&str>)> = schema
+            .columns
+            .iter()
+            .map(|c| (c.nullable, c.source_type.as_deref()))
+            .collect();
+        assert_eq!(
+            columns,
+            vec![
+                (false, Some("DECIMAL(10,2)")),
+                (true, Some("INT")),
+                (true, Some("VARCHAR(20)")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dates_and_timestamps() {
+        let dates = Arc::new(Date32Array::from(vec![19737])) as ArrayRef;
+        assert_eq!(convert_data_type(dates.data_type()), DataType::Date);
+        assert_eq!(
+            convert_array_value(&dates, 0).unwrap(),
+            Value::Date(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap())
+        );
+
+        let expected = NaiveDate::from_ymd_opt(2024, 1, 15)
+            .unwrap()
+            .and_hms_milli_opt(9, 30, 0, 500)
+            .unwrap();
+        let millis = expected.and_utc().timestamp_millis();
+        let timestamps =
+            Arc::new(TimestampMillisecondArray::from(vec![millis]).with_timezone("+00:00"))
+                as ArrayRef;
+        assert_eq!(
+            convert_data_type(timestamps.data_type()),
+            DataType::Timestamp
+        );
+        assert_eq!(
+            convert_array_value(&timestamps, 0).unwrap(),
+            Value::Timestamp(expected)
+        );
+        let nanos = Arc::new(TimestampNanosecondArray::from(vec![millis * 1_000_000])) as ArrayRef;
+        assert_eq!(
+            convert_array_value(&nanos, 0).unwrap().to_string(),
+            "2024-01-15 09:30:00.500"
+        );
+    }
+
+    #[test]
+    fn test_values_without_a_native_conversion() {
+        let mut builder = MapBuilder::new(None, StringBuilder::new(), Int64Builder::new());
+        builder.keys().append_value("a");
+        builder.values().append_value(1);
+        builder.keys().append_value("b");
+        builder.values().append_null();
+        builder.append(true).unwrap();
+        let map = Arc::new(builder.finish()) as ArrayRef;
+        assert_eq!(
+            convert_array_value(&map, 0).unwrap(),
+            Value::String("{\"a\":1,\"b\":null}".to_string())
+        );
+
+        let dictionary: DictionaryArray<Int32Type> = vec!["x", "y", "x"].into_iter().collect();
+        let dictionary = Arc::new(dictionary) as ArrayRef;
+        assert_eq!(
+            convert_array_value(&dictionary, 2).unwrap(),
+            Value::String("x".to_string())
+        );
+        assert_eq!(convert_data_type(dictionary.data_type()), DataType::String);
+
+        let runs: RunArray<Int32Type> = vec![Some("a"), Some("a"), None, Some("b")]
+            .into_iter()
+            .collect();
+        let runs = Arc::new(runs.slice(1, 3)) as ArrayRef;
+        let values: Vec<Value> = (0..runs.len())
+            .map(|i| convert_array_value(&runs, i).unwrap())
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                Value::String("a".to_string()),
+                Value::Null,
+                Value::String("b".to_string())
+            ]
+        );
+        assert_eq!(convert_data_type(runs.data_type()), DataType::String);
+
+        let list = FixedSizeListArray::from_iter_primitive::<Int64Type, _, _>(
+            vec![Some(vec![Some(1), None])],
+            2,
+        );
+        assert_eq!(
+            convert_array_value(&(Arc::new(list) as ArrayRef), 0).unwrap(),
+            Value::String("[1,null]".to_string())
+        );
+
+        let decimal = Decimal128Array::from(vec![150])
+            .with_precision_and_scale(10, 2)
+            .unwrap();
+        assert_eq!(
+            convert_array_value(&(Arc::new(decimal) as ArrayRef), 0).unwrap(),
+            Value::String("1.50".to_string())
+        );
+    }
+
+    #[test]
+    fn test_nested_values_render_as_valid_json() {
+        let note = Arc::new(StringArray::from(vec![Some("say \"hi\"\n")])) as ArrayRef;
+        let score = Arc::new(Float64Array::from(vec![f64::NAN])) as ArrayRef;
+        let missing = Arc::new(Int64Array::from(vec![None::<i64>])) as ArrayRef;
+        let inner = Arc::new(StructArray::from(vec![(
+            Arc::new(Field::new("n", ArrowDataType::Int64, true)),
+            Arc::new(Int64Array::from(vec![1])) as ArrayRef,
+        )])) as ArrayRef;
+        let record = StructArray::from(vec![
+            (
+                Arc::new(Field::new("note", ArrowDataType::Utf8, true)),
+                note,
+            ),
+            (
+                Arc::new(Field::new("score", ArrowDataType::Float64, true)),
+                score,
+            ),
+            (
+                Arc::new(Field::new("missing", ArrowDataType::Int64, true)),
+                missing,
+            ),
+            (
+                Arc::new(Field::new("inner", inner.data_type().clone(), true)),
+                inner,
+            ),
+        ]);
+
+        let value = convert_array_value(&(Arc::new(record) as ArrayRef), 0).unwrap();
+        assert_eq!(
+            value,
+            Value::String(
+                "{\"note\":\"say \\\"hi\\\"\\n\",\"score\":null,\"missing\":null,\"inner\":{\"n\":1}}"
+                    .to_string()
+            )
+        );
+    }
 }
@@ -0,0 +1,449 @@
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Date32Array, Float64Array, TimestampNanosecondArray};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType as ArrowType, Field, Schema, TimeUnit};
+use arrow::record_batch::RecordBatch;
+use chrono::{NaiveDate, NaiveDateTime};
+use datafusion::prelude::CsvReadOptions;
+
+use super::error::{DataFusionError, Result};
+use super::refine::parse_grouped_number;
+use crate::storage::DataType;
+
+/// Rows read to infer the column types of a CSV file unless
+/// [`CsvOptions::infer_rows`] says otherwise.
+pub const DEFAULT_INFER_ROWS: usize = 1000;
+
+/// Formats tried for columns typed `date` with
+/// [`CsvOptions::with_column_type`], after the ones the options name.
+const DEFAULT_DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d"];
+
+/// Formats tried for columns typed `timestamp`, after the ones the options
+/// name. `%.f` also matches no fraction.
+const DEFAULT_TIMESTAMP_FORMATS: &[&str] = &["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"];
+
+/// How CSV files are read: their layout, and how the types of their columns
+/// are inferred. The default reads comma-separated files with a header and
+/// infers types from the first [`DEFAULT_INFER_ROWS`] rows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvOptions {
+    pub delimiter: u8,
+    pub has_header: bool,
+    /// Rows read to infer column types; `None` reads the whole file.
+    pub infer_rows: Option<usize>,
+    /// chrono formats such as `%d/%m/%Y` or `%d.%m.%Y %H:%M`. A text column
+    /// whose values all read with one of them becomes a date column, or a
+    /// timestamp column if the format has a time.
+    pub date_formats: Vec<String>,
+    /// `.` or `,`. With `,`, numbers are read as in `1.234,5`, where `.`
+    /// separates thousands, and text columns holding them become floats.
+    pub decimal_separator: char,
+    /// Types for columns by name, matched case-insensitively, in place of
+    /// the inferred ones. A value that does not read as the type is an
+    /// error. Files without the column are not affected.
+    pub column_types: Vec<(String, DataType)>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            has_header: true,
+            infer_rows: Some(DEFAULT_INFER_ROWS),
+            date_formats: Vec::new(),
+            decimal_separator: '.',
+            column_types: Vec::new(),
+        }
+    }
+}
+
+impl CsvOptions {
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn with_header(mut self, has_header: bool) -> Self {
+        self.has_header = has_header;
+        self
+    }
+
+    /// Infer column types from the first `rows` rows, or every row for
+    /// `None`.
+    pub fn with_infer_rows(mut self, rows: Option<usize>) -> Self {
+        self.infer_rows = rows;
+        self
+    }
+
+    /// Also look for dates and timestamps written in chrono `format`.
+    pub fn with_date_format(mut self, format: impl Into<String>) -> Self {
+        self.date_formats.push(format.into());
+        self
+    }
+
+    pub fn with_decimal_separator(mut self, separator: char) -> Self {
+        self.decimal_separator = separator;
+        self
+    }
+
+    /// Read `column` as `data_type` instead of inferring its type.
+    pub fn with_column_type(mut self, column: impl Into<String>, data_type: DataType) -> Self {
+        self.column_types.push((column.into(), data_type));
+        self
+    }
+
+    /// The options DataFusion reads the file with, before any type changes.
+    pub(super) fn read_options(&self) -> CsvReadOptions<'_> {
+        CsvReadOptions::new()
+            .delimiter(self.delimiter)
+            .has_header(self.has_header)
+            .schema_infer_max_records(self.infer_rows.unwrap_or(usize::MAX))
+    }
+
+    /// Whether the inferred schema may need changing before the file is
+    /// read, see [`read_schema`](Self::read_schema).
+    pub(super) fn changes_schema(&self) -> bool {
+        !self.column_types.is_empty() || self.decimal_comma()
+    }
+
+    /// Whether columns may need converting after the file is read, see
+    /// [`convert`](Self::convert).
+    pub(super) fn converts_columns(&self) -> bool {
+        !self.date_formats.is_empty()
+            || self.decimal_comma()
+            || self
+                .column_types
+                .iter()
+                .any(|(_, data_type)| matches!(data_type, DataType::Date | DataType::Timestamp))
+    }
+
+    /// `inferred` with the types of [`column_types`](Self::column_types).
+    /// Columns that DataFusion cannot parse as their final type, such as
+    /// dates in other formats or numbers with a decimal comma, are read as
+    /// text for [`convert`](Self::convert) to finish.
+    pub(super) fn read_schema(&self, inferred: &Schema) -> Result<Schema> {
+        let fields = inferred.fields().iter().map(|field| {
+            let read_type = match self.column_type(field.name()) {
+                Some(DataType::String | DataType::Date | DataType::Timestamp) => ArrowType::Utf8,
+                Some(DataType::Integer) => ArrowType::Int64,
+                Some(DataType::Float) if !self.decimal_comma() => ArrowType::Float64,
+                Some(DataType::Float) => ArrowType::Utf8,
+                Some(DataType::Boolean) => ArrowType::Boolean,
+                Some(DataType::Null) => {
+                    return Err(DataFusionError::InvalidColumnType(format!(
+                        "{}: columns cannot be typed null",
+                        field.name()
+                    )))
+                }
+                None if self.decimal_comma() && field.data_type() == &ArrowType::Float64 => {
+                    ArrowType::Utf8
+                }
+                None => field.data_type().clone(),
+            };
+            Ok(Field::new(field.name(), read_type, true))
+        });
+        Ok(Schema::new(fields.collect::<Result<Vec<_>>>()?))
+    }
+
+    /// Convert the text columns of `batches` that hold dates in
+    /// [`date_formats`](Self::date_formats), numbers with a decimal comma,
+    /// or values of a type given in [`column_types`](Self::column_types).
+    /// Returns `None` if no column changes.
+    pub(super) fn convert(&self, batches: &[RecordBatch]) -> Result<Option<Vec<RecordBatch>>> {
+        let Some(first) = batches.first() else {
+            return Ok(None);
+        };
+        let schema = first.schema();
+        let mut conversions = Vec::new();
+        for (i, field) in schema.fields().iter().enumerate() {
+            if field.data_type() != &ArrowType::Utf8 {
+                continue;
+            }
+            let mut values = Vec::new();
+            for batch in batches {
+                let strings = arrow::array::as_string_array(batch.column(i));
+                values.extend(strings.iter().flatten().map(str::trim));
+            }
+            if let Some(conversion) = self.conversion(field.name(), &values)? {
+                conversions.push((i, conversion));
+            }
+        }
+        if conversions.is_empty() {
+            return Ok(None);
+        }
+
+        let fields: Vec<Field> = schema
+            .fields()
+            .iter()
+            .enumerate()
+            .map(
+                |(i, field)| match conversions.iter().find(|(c, _)| *c == i) {
+                    Some((_, conversion)) => {
+                        Field::new(field.name(), conversion.arrow_type(), true)
+                    }
+                    None => field.as_ref().clone(),
+                },
+            )
+            .collect();
+        let converted_schema = Arc::new(Schema::new(fields));
+        let converted = batches
+            .iter()
+            .map(|batch| {
+                let mut columns = batch.columns().to_vec();
+                for (i, conversion) in &conversions {
+                    columns[*i] = conversion.apply(&columns[*i], self.decimal_separator)?;
+                }
+                Ok(RecordBatch::try_new(converted_schema.clone(), columns)?)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Some(converted))
+    }
+
+    /// How to convert the text column `name` holding `values`, if at all.
+    fn conversion(&self, name: &str, values: &[&str]) -> Result<Option<Conversion>> {
+        let named = self.date_formats.iter().map(String::as_str);
+        let found = match self.column_type(name) {
+            Some(DataType::Date) => {
+                let formats: Vec<&str> =
+                    named.chain(DEFAULT_DATE_FORMATS.iter().copied()).collect();
+                Some(date_format(values, &formats).ok_or_else(|| not_a(name, "date", values))?)
+            }
+            Some(DataType::Timestamp) => {
+                let formats: Vec<&str> = named
+                    .chain(DEFAULT_TIMESTAMP_FORMATS.iter().copied())
+                    .collect();
+                Some(
+                    timestamp_format(values, &formats)
+                        .ok_or_else(|| not_a(name, "timestamp", values))?,
+                )
+            }
+            Some(DataType::Float) => {
+                if let Some(value) = values
+                    .iter()
+                    .find(|v| parse_number(v, self.decimal_separator).is_none())
+                {
+                    return Err(DataFusionError::Conversion(format!(
+                        "column {} is typed float, but '{}' is not a number",
+                        name, value
+                    )));
+                }
+                Some(Conversion::Float)
+            }
+            Some(_) => None,
+            None if values.is_empty() => None,
+            None => {
+                let formats: Vec<&str> = named.collect();
+                let is_decimal = |v: &&str| {
+                    v.contains([',', '.']) || parse_number(v, self.decimal_separator).is_none()
+                };
+                timestamp_format(values, &formats)
+                    .or_else(|| date_format(values, &formats))
+                    .or_else(|| {
+                        (self.decimal_comma()
+                            && values.iter().any(is_decimal)
+                            && values
+                                .iter()
+                                .all(|v| parse_number(v, self.decimal_separator).is_some()))
+                        .then_some(Conversion::Float)
+                    })
+            }
+        };
+        Ok(found)
+    }
+
+    fn column_type(&self, name: &str) -> Option<&DataType> {
+        self.column_types
+            .iter()
+            .find(|(column, _)| column.eq_ignore_ascii_case(name))
+            .map(|(_, data_type)| data_type)
+    }
+
+    fn decimal_comma(&self) -> bool {
+        self.decimal_separator == ','
+    }
+}
+
+/// Parse a `column:type` override such as `zip:string`. The types are
+/// `string` (or `text`), `int` (`integer`, `bigint`), `float` (`double`,
+/// `decimal`), `bool` (`boolean`), `date` and `timestamp`.
+pub fn parse_column_type(spec: &str) -> Result<(String, DataType)> {
+    let invalid = || {
+        DataFusionError::InvalidColumnType(format!(
+            "{}: expected column:type with type string, int, float, bool, date or timestamp",
+            spec
+        ))
+    };
+    let (column, type_name) = spec.rsplit_once(':').ok_or_else(invalid)?;
+    let data_type = match type_name.trim().to_lowercase().as_str() {
+        "string" | "text" | "varchar" => DataType::String,
+        "int" | "integer" | "bigint" => DataType::Integer,
+        "float" | "double" | "decimal" => DataType::Float,
+        "bool" | "boolean" => DataType::Boolean,
+        "date" => DataType::Date,
+        "timestamp" | "datetime" => DataType::Timestamp,
+        _ => return Err(invalid()),
+    };
+    let column = column.trim();
+    if column.is_empty() {
+        return Err(invalid());
+    }
+    Ok((column.to_string(), data_type))
+}
+
+/// A change to the type of one text column.
+#[derive(Debug, Clone, PartialEq)]
+enum Conversion {
+    Date(String),
+    Timestamp(String),
+    Float,
+}
+
+impl Conversion {
+    fn arrow_type(&self) -> ArrowType {
+        match self {
+            Conversion::Date(_) => ArrowType::Date32,
+            Conversion::Timestamp(_) => ArrowType::Timestamp(TimeUnit::Nanosecond, None),
+            Conversion::Float => ArrowType::Float64,
+        }
+    }
+
+    fn apply(&self, column: &ArrayRef, decimal_separator: char) -> Result<ArrayRef> {
+        let strings = cast(column, &ArrowType::Utf8)?;
+        let values = arrow::array::as_string_array(&strings)
+            .iter()
+            .map(|v| v.map(str::trim));
+        Ok(match self {
+            Conversion::Date(format) => Arc::new(Date32Array::from_iter(values.map(|v| {
+                v.and_then(|v| NaiveDate::parse_from_str(v, format).ok())
+                    .map(|date| (date - NaiveDate::default()).num_days() as i32)
+            }))),
+            Conversion::Timestamp(format) => {
+                Arc::new(TimestampNanosecondArray::from_iter(values.map(|v| {
+                    v.and_then(|v| NaiveDateTime::parse_from_str(v, format).ok())
+                        .and_then(|dt| dt.and_utc().timestamp_nanos_opt())
+                })))
+            }
+            Conversion::Float => Arc::new(Float64Array::from_iter(
+                values.map(|v| v.and_then(|v| parse_number(v, decimal_separator))),
+            )),
+        })
+    }
+}
+
+/// The first of `formats` that reads every one of `values` as a date.
+fn date_format(values: &[&str], formats: &[&str]) -> Option<Conversion> {
+    if values.is_empty() {
+        return None;
+    }
+    formats
+        .iter()
+        .find(|format| {
+            values
+                .iter()
+                .all(|v| NaiveDate::parse_from_str(v, format).is_ok())
+        })
+        .map(|format| Conversion::Date(format.to_string()))
+}
+
+/// The first of `formats` that reads every one of `values` as a date and
+/// time.
+fn timestamp_format(values: &[&str], formats: &[&str]) -> Option<Conversion> {
+    if values.is_empty() {
+        return None;
+    }
+    formats
+        .iter()
+        .find(|format| {
+            values
+                .iter()
+                .all(|v| NaiveDateTime::parse_from_str(v, format).is_ok())
+        })
+        .map(|format| Conversion::Timestamp(format.to_string()))
+}
+
+/// Parse a number written with `decimal_separator`, where the other of `.`
+/// and `,` may separate thousands.
+fn parse_number(value: &str, decimal_separator: char) -> Option<f64> {
+    let value: String = if decimal_separator == ',' {
+        value
+            .chars()
+            .map(|c| match c {
+                ',' => '.',
+                '.' => ',',
+                c => c,
+            })
+            .collect()
+    } else {
+        value.to_string()
+    };
+    parse_grouped_number(&value)?.parse().ok()
+}
+
+fn not_a(column: &str, type_name: &str, values: &[&str]) -> DataFusionError {
+    let example = values.first().copied().unwrap_or_default();
+    DataFusionError::Conversion(format!(
+        "column {} is typed {}, but values such as '{}' match none of its formats; \
+         add one with --date-format",
+        column, type_name, example
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_column_type() {
+        assert_eq!(
+            parse_column_type("zip:string").unwrap(),
+            ("zip".to_string(), DataType::String)
+        );
+        assert_eq!(
+            parse_column_type("Order Date: DATE").unwrap(),
+            ("Order Date".to_string(), DataType::Date)
+        );
+        assert!(parse_column_type("zip").is_err());
+        assert!(parse_column_type("zip:money").is_err());
+        assert!(parse_column_type(":int").is_err());
+    }
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number("1.234,5", ','), Some(1234.5));
+        assert_eq!(parse_number("-3,25", ','), Some(-3.25));
+        assert_eq!(parse_number("1,234.5", '.'), Some(1234.5));
+        assert_eq!(parse_number("1.23.4", ','), None);
+    }
+
+    #[test]
+    fn test_conversion() {
+        let options = CsvOptions::default()
+            .with_date_format("%d/%m/%Y")
+            .with_date_format("%d/%m/%Y %H:%M")
+            .with_decimal_separator(',');
+
+        assert_eq!(
+            options
+                .conversion("day", &["31/12/2024", "01/02/2025"])
+                .unwrap(),
+            Some(Conversion::Date("%d/%m/%Y".to_string()))
+        );
+        assert_eq!(
+            options.conversion("at", &["31/12/2024 08:30"]).unwrap(),
+            Some(Conversion::Timestamp("%d/%m/%Y %H:%M".to_string()))
+        );
+        assert_eq!(
+            options.conversion("price", &["3,5", "12"]).unwrap(),
+            Some(Conversion::Float)
+        );
+        // Numbers without separators may be codes such as zip codes
+        assert_eq!(options.conversion("zip", &["00123", "456"]).unwrap(), None);
+
+        let options = options.with_column_type("zip", DataType::Date);
+        assert!(matches!(
+            options.conversion("zip", &["00123"]),
+            Err(DataFusionError::Conversion(_))
+        ));
+    }
+}
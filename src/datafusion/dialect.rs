@@ -0,0 +1,153 @@
+use std::ops::ControlFlow;
+
+use datafusion::error::DataFusionError as DFError;
+use datafusion::sql::parser::Statement as DFStatement;
+use datafusion::sql::sqlparser::ast::{
+    Expr, Query, SetExpr, TopQuantity, Value as SqlValue, VisitMut, VisitorMut,
+};
+use datafusion::sql::sqlparser::dialect::GenericDialect;
+use datafusion::sql::sqlparser::keywords::Keyword;
+use datafusion::sql::sqlparser::tokenizer::{Token, Tokenizer};
+
+/// Translate syntax borrowed from other databases into what DataFusion
+/// plans, returning one note per kind of syntax found, naming it and its
+/// standard form:
+///
+/// - MySQL backtick-quoted identifiers are read as double-quoted ones.
+/// - MySQL `LIMIT offset, count` is read as `LIMIT count OFFSET offset`.
+/// - T-SQL `SELECT TOP n` is planned as `LIMIT n`.
+///
+/// The first two are already accepted by the parser and only produce a
+/// note; `TOP` is rewritten in `statement`. `sql` is the text `statement`
+/// was parsed from.
+pub(super) fn translate(statement: &mut DFStatement, sql: &str) -> Result<Vec<String>, DFError> {
+    let mut notes = token_notes(sql);
+
+    let mut top = TopToLimit::default();
+    match statement {
+        DFStatement::Statement(inner) => {
+            if let ControlFlow::Break(err) = inner.visit(&mut top) {
+                return Err(err);
+            }
+        }
+        DFStatement::Explain(explain) => {
+            let mut explained = translate(&mut explain.statement, "")?;
+            notes.append(&mut explained);
+        }
+        _ => {}
+    }
+    if top.found {
+        notes.push("T-SQL SELECT TOP n; the standard form is LIMIT n".to_string());
+    }
+    Ok(notes)
+}
+
+/// Notes for foreign syntax that is only visible in the tokens, because the
+/// parser accepts it without recording how it was written.
+fn token_notes(sql: &str) -> Vec<String> {
+    let Ok(tokens) = Tokenizer::new(&GenericDialect {}, sql).tokenize() else {
+        return Vec::new();
+    };
+    let tokens: Vec<&Token> = tokens
+        .iter()
+        .filter(|token| !matches!(token, Token::Whitespace(_)))
+        .collect();
+
+    let mut notes = Vec::new();
+    if tokens
+        .iter()
+        .any(|token| matches!(token, Token::Word(word) if word.quote_style == Some('`')))
+    {
+        notes.push(
+            "MySQL `backtick` identifiers; the standard form is \"double quotes\"".to_string(),
+        );
+    }
+    if tokens.windows(3).any(|window| {
+        matches!(
+            window,
+            [Token::Word(word), Token::Number(..), Token::Comma] if word.keyword == Keyword::LIMIT
+        )
+    }) {
+        notes.push(
+            "MySQL LIMIT offset, count; the standard form is LIMIT count OFFSET offset".to_string(),
+        );
+    }
+    notes
+}
+
+/// Moves `SELECT TOP n` into the query's `LIMIT`.
+#[derive(Default)]
+struct TopToLimit {
+    found: bool,
+}
+
+impl VisitorMut for TopToLimit {
+    type Break = DFError;
+
+    fn post_visit_query(&mut self, query: &mut Query) -> ControlFlow<DFError> {
+        let SetExpr::Select(select) = query.body.as_mut() else {
+            return ControlFlow::Continue(());
+        };
+        let Some(top) = select.top.take() else {
+            return ControlFlow::Continue(());
+        };
+        self.found = true;
+
+        if top.with_ties || top.percent {
+            return ControlFlow::Break(DFError::NotImplemented(format!(
+                "{} is not supported; use TOP n or LIMIT n",
+                top
+            )));
+        }
+        if query.limit.is_some() || query.fetch.is_some() {
+            return ControlFlow::Break(DFError::Plan(
+                "TOP cannot be combined with LIMIT or FETCH".to_string(),
+            ));
+        }
+        query.limit = match top.quantity {
+            Some(TopQuantity::Expr(expr)) => Some(expr),
+            Some(TopQuantity::Constant(n)) => {
+                Some(Expr::Value(SqlValue::Number(n.to_string(), false).into()))
+            }
+            None => return ControlFlow::Break(DFError::Plan("TOP needs a row count".to_string())),
+        };
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    fn translate_sql(sql: &str) -> Result<(String, Vec<String>), DFError> {
+        let session = SessionContext::new();
+        let mut statement = session.state().sql_to_statement(sql, "generic")?;
+        let notes = translate(&mut statement, sql)?;
+        Ok((statement.to_string(), notes))
+    }
+
+    #[test]
+    fn test_top_becomes_limit() {
+        let (sql, notes) = translate_sql("SELECT TOP 3 name FROM users ORDER BY age").unwrap();
+        assert_eq!(sql, "SELECT name FROM users ORDER BY age LIMIT 3");
+        assert_eq!(notes.len(), 1);
+
+        let (sql, _) = translate_sql("SELECT TOP (1 + 1) name FROM users").unwrap();
+        assert_eq!(sql, "SELECT name FROM users LIMIT 1 + 1");
+
+        assert!(translate_sql("SELECT TOP 3 name FROM users LIMIT 2").is_err());
+        assert!(translate_sql("SELECT TOP 10 PERCENT name FROM users").is_err());
+    }
+
+    #[test]
+    fn test_mysql_syntax_notes() {
+        let (_, notes) = translate_sql("SELECT `name` FROM users LIMIT 5, 10").unwrap();
+        assert_eq!(notes.len(), 2);
+        assert!(notes[0].contains("backtick"));
+        assert!(notes[1].contains("OFFSET"));
+
+        let (_, notes) = translate_sql("SELECT \"name\" FROM users LIMIT 10 OFFSET 5").unwrap();
+        assert!(notes.is_empty());
+    }
+}
@@ -3,7 +3,7 @@ use thiserror::Error;
 #[derive(Error, Debug)]
 pub enum DataFusionError {
     #[error("DataFusion error: {0}")]
-    DataFusion(#[from] datafusion::error::DataFusionError),
+    DataFusion(#[source] datafusion::error::DataFusionError),
 
     #[error("Arrow error: {0}")]
     Arrow(#[from] arrow::error::ArrowError),
@@ -11,18 +11,49 @@ pub enum DataFusionError {
     #[error("Parquet error: {0}")]
     Parquet(#[from] parquet::errors::ParquetError),
 
+    #[cfg(feature = "sqlite")]
     #[error("SQLite error: {0}")]
     Sqlite(#[from] rusqlite::Error),
 
+    #[cfg(feature = "xlsx")]
+    #[error("Excel error: {0}")]
+    Excel(#[from] calamine::Error),
+
+    #[cfg(feature = "xlsx")]
+    #[error("Worksheet not found: {0}")]
+    SheetNotFound(String),
+
+    #[cfg(feature = "delta")]
     #[error("Delta Lake error: {0}")]
     Delta(#[from] deltalake::DeltaTableError),
 
+    /// The table's protocol requires reader features, such as deletion
+    /// vectors or column mapping, that the Delta reader does not implement.
+    /// Reading it anyway could return deleted rows or physical column names.
+    #[cfg(feature = "delta")]
+    #[error(
+        "Delta table '{table}' needs reader features knowhere cannot apply yet: {}. \
+         Drop them with `ALTER TABLE ... DROP FEATURE` in Spark or Databricks, \
+         or rewrite the table without them",
+        .features.join(", ")
+    )]
+    UnsupportedDeltaFeatures {
+        table: String,
+        /// Feature names as the protocol spells them, e.g. `deletionVectors`.
+        features: Vec<String>,
+    },
+
+    #[cfg(feature = "iceberg")]
     #[error("Iceberg error: {0}")]
     Iceberg(String),
 
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
+    #[cfg(feature = "remote")]
+    #[error("Object store error: {0}")]
+    ObjectStore(#[from] object_store::Error),
+
     #[error("File not found: {0}")]
     FileNotFound(String),
 
@@ -38,11 +69,104 @@ pub enum DataFusionError {
     #[error("Table not found: {0}")]
     TableNotFound(String),
 
+    #[error("Schema mismatch: {0}")]
+    SchemaMismatch(String),
+
+    #[error("Column not found: {column}{}", column_hint(.suggestions, .available, *.alias))]
+    ColumnNotFound {
+        column: String,
+        /// Closest available names, best first.
+        suggestions: Vec<String>,
+        available: Vec<String>,
+        /// Whether `column` is a SELECT alias used where aliases are not
+        /// visible, such as WHERE.
+        alias: bool,
+    },
+
+    #[error("Syntax from another SQL dialect: {0}")]
+    ForeignSyntax(String),
+
     #[error("Conversion error: {0}")]
     Conversion(String),
 
     #[error("SQLite table not found: {0}")]
     SqliteTableNotFound(String),
+
+    #[error("Result schema is only known after running the statement: {0}")]
+    ResultSchemaUnknown(String),
+
+    #[error("Query timed out after {:.1} seconds", .0.as_secs_f64())]
+    QueryTimedOut(std::time::Duration),
+
+    #[error("Query cancelled")]
+    QueryCancelled,
+
+    #[error("Environment variable not set: {0}")]
+    EnvVarNotSet(String),
+
+    #[error("Invalid setting: {0}")]
+    InvalidSetting(String),
+
+    #[error("Invalid column type: {0}")]
+    InvalidColumnType(String),
+}
+
+impl From<datafusion::error::DataFusionError> for DataFusionError {
+    fn from(err: datafusion::error::DataFusionError) -> Self {
+        let err = match err {
+            datafusion::error::DataFusionError::Context(rule, inner)
+                if super::coercion::RULE_NAMES.contains(&rule.as_str()) =>
+            {
+                *inner
+            }
+            err => err,
+        };
+        #[cfg(feature = "iceberg")]
+        if let Some(message) = unsupported_iceberg_deletes(&err) {
+            return DataFusionError::Iceberg(message);
+        }
+        DataFusionError::DataFusion(err)
+    }
+}
+
+/// The Iceberg reader applies position deletes, but fails on equality delete
+/// files with an internal "Missing predicate" error. Explain what that means
+/// and how to get a readable table.
+#[cfg(feature = "iceberg")]
+fn unsupported_iceberg_deletes(err: &datafusion::error::DataFusionError) -> Option<String> {
+    let message = err.to_string();
+    let (_, rest) = message.split_once("Missing predicate for equality delete file '")?;
+    let path = rest.split('\'').next()?;
+    Some(format!(
+        "equality delete file '{}' cannot be applied: reading Iceberg tables with equality \
+         deletes is not supported yet. Compact the table (for example with Spark's \
+         rewrite_data_files) so the deletes are folded into its data files",
+        path
+    ))
+}
+
+fn column_hint(suggestions: &[String], available: &[String], alias: bool) -> String {
+    let mut hint = String::new();
+    if alias {
+        hint.push_str(
+            ". It is a SELECT alias, which WHERE cannot see; \
+             repeat the expression or select from a subquery",
+        );
+    }
+    match suggestions {
+        [] => {}
+        [one] => hint.push_str(&format!(". Did you mean {}?", one)),
+        many => hint.push_str(&format!(". Did you mean one of {}?", many.join(", "))),
+    }
+    if !available.is_empty() {
+        let separator = if hint.ends_with('?') { " " } else { ". " };
+        hint.push_str(&format!(
+            "{}Available columns: {}",
+            separator,
+            available.join(", ")
+        ));
+    }
+    hint
 }
 
 pub type Result<T> = std::result::Result<T, DataFusionError>;
@@ -0,0 +1,180 @@
+use rusqlite::{params_from_iter, Connection};
+use std::path::Path;
+
+use crate::render::MaskRules;
+use crate::storage::table::{DataType, Row, Table, Value};
+
+use super::error::{DataFusionError, Result};
+use super::names::quote_identifier;
+
+/// Write `table` into the SQLite database at `path` as `table_name`.
+///
+/// The database file is created if it does not exist. An existing table with
+/// the same name is dropped and recreated so repeated exports overwrite the
+/// previous result. Returns the number of rows written.
+///
+/// Every row of a spilled result is written, reading the rows that are only
+/// on disk back a page at a time and masking them with `masks`; the rows
+/// held in memory are expected to be masked already.
+pub fn write_sqlite(
+    table: &Table,
+    path: &Path,
+    table_name: &str,
+    masks: &MaskRules,
+) -> Result<usize> {
+    if table_name.trim().is_empty() {
+        return Err(DataFusionError::InvalidTableName(
+            "SQLite table name must not be empty".to_string(),
+        ));
+    }
+
+    let mut conn = Connection::open(path)?;
+    let tx = conn.transaction()?;
+    let quoted_table = quote_identifier(table_name);
+
+    tx.execute(&format!("DROP TABLE IF EXISTS {}", quoted_table), [])?;
+
+    let column_defs: Vec<String> = table
+        .schema
+        .columns
+        .iter()
+        .map(|col| {
+            format!(
+                "{} {}",
+                quote_identifier(&col.name),
+                sqlite_type(&col.data_type)
+            )
+        })
+        .collect();
+    tx.execute(
+        &format!("CREATE TABLE {} ({})", quoted_table, column_defs.join(", ")),
+        [],
+    )?;
+
+    if table.column_count() > 0 {
+        let placeholders = vec!["?"; table.column_count()].join(", ");
+        let mut stmt = tx.prepare(&format!(
+            "INSERT INTO {} VALUES ({})",
+            quoted_table, placeholders
+        ))?;
+        let mut insert = |rows: &[Row]| -> Result<()> {
+            for row in rows {
+                stmt.execute(params_from_iter(row.values.iter().map(to_sql_value)))?;
+            }
+            Ok(())
+        };
+        insert(&table.rows)?;
+        for page in table.spilled_pages() {
+            let mut page = page?;
+            masks.apply(&mut page);
+            insert(&page.rows)?;
+        }
+    }
+
+    tx.commit()?;
+    Ok(table.total_row_count())
+}
+
+fn sqlite_type(data_type: &DataType) -> &'static str {
+    match data_type {
+        DataType::Integer => "INTEGER",
+        DataType::Float => "REAL",
+        DataType::Boolean => "BOOLEAN",
+        DataType::Date => "DATE",
+        DataType::Timestamp => "TIMESTAMP",
+        DataType::String | DataType::Null => "TEXT",
+    }
+}
+
+fn to_sql_value(value: &Value) -> rusqlite::types::Value {
+    use rusqlite::types::Value as SqlValue;
+
+    match value {
+        Value::Integer(i) => SqlValue::Integer(*i),
+        Value::Float(f) => SqlValue::Real(*f),
+        Value::Boolean(b) => SqlValue::Integer(*b as i64),
+        Value::String(s) => SqlValue::Text(s.clone()),
+        Value::Date(_) | Value::Timestamp(_) => SqlValue::Text(value.to_string()),
+        Value::Null => SqlValue::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::table::{Column, Row, Schema};
+
+    fn sample_table() -> Table {
+        let schema = Schema::new(vec![
+            Column::new("id", DataType::Integer),
+            Column::new("name", DataType::String),
+            Column::new("score", DataType::Float),
+            Column::new("active", DataType::Boolean),
+        ]);
+        Table::with_rows(
+            "result",
+            schema,
+            vec![
+                Row::new(vec![
+                    Value::Integer(1),
+                    Value::String("Alice".to_string()),
+                    Value::Float(9.5),
+                    Value::Boolean(true),
+                ]),
+                Row::new(vec![
+                    Value::Integer(2),
+                    Value::Null,
+                    Value::Float(7.25),
+                    Value::Boolean(false),
+                ]),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_write_sqlite_creates_table() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("results.db");
+
+        let written =
+            write_sqlite(&sample_table(), &db_path, "results", &MaskRules::default()).unwrap();
+        assert_eq!(written, 2);
+
+        let conn = Connection::open(&db_path).unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM results", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 2);
+
+        let name: Option<String> = conn
+            .query_row("SELECT name FROM results WHERE id = 2", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(name, None);
+    }
+
+    #[test]
+    fn test_write_sqlite_overwrites_existing_table() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("results.db");
+
+        write_sqlite(&sample_table(), &db_path, "results", &MaskRules::default()).unwrap();
+        write_sqlite(&sample_table(), &db_path, "results", &MaskRules::default()).unwrap();
+
+        let conn = Connection::open(&db_path).unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM results", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_write_sqlite_rejects_empty_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("results.db");
+
+        let result = write_sqlite(&sample_table(), &db_path, "  ", &MaskRules::default());
+        assert!(matches!(result, Err(DataFusionError::InvalidTableName(_))));
+    }
+}
@@ -0,0 +1,30 @@
+use super::names::quote_identifier;
+
+/// Most common values a frequency table lists.
+pub const FREQUENCY_LIMIT: usize = 50;
+
+/// A query counting the rows of `query` per value of `column`, most common
+/// first, limited to [`FREQUENCY_LIMIT`] values. The value column keeps the
+/// name `column`, so masking rules for it still apply to the result.
+pub fn frequency_query(query: &str, column: &str) -> String {
+    let column = quote_identifier(column);
+    format!(
+        "SELECT {column}, COUNT(*) AS count FROM ({}) AS source GROUP BY {column} \
+         ORDER BY count DESC, {column} LIMIT {FREQUENCY_LIMIT}",
+        query.trim().trim_end_matches(';').trim_end()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frequency_query() {
+        assert_eq!(
+            frequency_query("SELECT * FROM orders;\n", "Order Status"),
+            "SELECT \"Order Status\", COUNT(*) AS count FROM (SELECT * FROM orders) AS source \
+             GROUP BY \"Order Status\" ORDER BY count DESC, \"Order Status\" LIMIT 50"
+        );
+    }
+}
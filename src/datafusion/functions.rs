@@ -0,0 +1,133 @@
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Float64Array, Int64Array};
+use arrow::datatypes::DataType;
+use datafusion::common::cast::{as_float64_array, as_int64_array};
+use datafusion::common::{exec_err, ScalarValue};
+use datafusion::error::Result as DFResult;
+use datafusion::logical_expr::{create_udf, ColumnarValue, Volatility};
+use datafusion::prelude::SessionContext;
+
+/// Register knowhere's own scalar functions on `session`.
+pub(super) fn register_functions(session: &SessionContext) {
+    session.register_udf(create_udf(
+        "safe_divide",
+        vec![DataType::Float64, DataType::Float64],
+        DataType::Float64,
+        Volatility::Immutable,
+        Arc::new(safe_divide),
+    ));
+    session.register_udf(create_udf(
+        "div",
+        vec![DataType::Int64, DataType::Int64],
+        DataType::Int64,
+        Volatility::Immutable,
+        Arc::new(integer_divide),
+    ));
+}
+
+/// `SAFE_DIVIDE(a, b)`: floating point `a / b`, or NULL when `b` is zero or
+/// either argument is NULL.
+fn safe_divide(args: &[ColumnarValue]) -> DFResult<ColumnarValue> {
+    let arrays = ColumnarValue::values_to_arrays(args)?;
+    let dividend = as_float64_array(&arrays[0])?;
+    let divisor = as_float64_array(&arrays[1])?;
+
+    let result: Float64Array = dividend
+        .iter()
+        .zip(divisor.iter())
+        .map(|(a, b)| match (a, b) {
+            (Some(a), Some(b)) if b != 0.0 => Some(a / b),
+            _ => None,
+        })
+        .collect();
+    to_columnar(args, Arc::new(result))
+}
+
+/// `DIV(a, b)`: integer division truncating toward zero, as the SQL standard
+/// defines it for integers. Dividing by zero is an error, like `/`.
+fn integer_divide(args: &[ColumnarValue]) -> DFResult<ColumnarValue> {
+    let arrays = ColumnarValue::values_to_arrays(args)?;
+    let dividend = as_int64_array(&arrays[0])?;
+    let divisor = as_int64_array(&arrays[1])?;
+
+    let mut result = Int64Array::builder(dividend.len());
+    for (a, b) in dividend.iter().zip(divisor.iter()) {
+        match (a, b) {
+            (Some(_), Some(0)) => return exec_err!("Division by zero in DIV"),
+            (Some(a), Some(b)) => match a.checked_div(b) {
+                Some(quotient) => result.append_value(quotient),
+                None => return exec_err!("Integer overflow in DIV({}, {})", a, b),
+            },
+            _ => result.append_null(),
+        }
+    }
+    to_columnar(args, Arc::new(result.finish()))
+}
+
+/// Return a scalar when every argument was a scalar, so constant expressions
+/// keep their shape.
+fn to_columnar(args: &[ColumnarValue], result: ArrayRef) -> DFResult<ColumnarValue> {
+    if args
+        .iter()
+        .all(|arg| matches!(arg, ColumnarValue::Scalar(_)))
+    {
+        Ok(ColumnarValue::Scalar(ScalarValue::try_from_array(
+            &result, 0,
+        )?))
+    } else {
+        Ok(ColumnarValue::Array(result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datafusion::DataFusionContext;
+    use crate::storage::table::Value;
+
+    fn query_values(sql: &str) -> Vec<Value> {
+        let ctx = DataFusionContext::new().unwrap();
+        let table = ctx.execute_sql(sql).unwrap();
+        table.rows[0].values.clone()
+    }
+
+    #[test]
+    fn test_safe_divide() {
+        let values =
+            query_values("SELECT safe_divide(7, 2), safe_divide(1, 0), safe_divide(NULL, 2)");
+        assert_eq!(values[0], Value::Float(3.5));
+        assert_eq!(values[1], Value::Null);
+        assert_eq!(values[2], Value::Null);
+    }
+
+    #[test]
+    fn test_safe_divide_over_columns() {
+        let ctx = DataFusionContext::new().unwrap();
+        let table = ctx
+            .execute_sql(
+                "SELECT safe_divide(a, b) AS r FROM (VALUES (6, 3), (1, 0), (5, NULL)) AS t(a, b)",
+            )
+            .unwrap();
+        let results: Vec<Value> = table.rows.iter().map(|r| r.values[0].clone()).collect();
+        assert_eq!(results, vec![Value::Float(2.0), Value::Null, Value::Null]);
+    }
+
+    #[test]
+    fn test_div_truncates_toward_zero() {
+        let values = query_values("SELECT div(7, 2), div(-7, 2), div(NULL, 2), 7 / 2");
+        assert_eq!(values[0], Value::Integer(3));
+        assert_eq!(values[1], Value::Integer(-3));
+        assert_eq!(values[2], Value::Null);
+        // `/` on integers already truncates; DIV makes the intent explicit
+        assert_eq!(values[3], Value::Integer(3));
+    }
+
+    #[test]
+    fn test_div_by_zero_is_an_error() {
+        let ctx = DataFusionContext::new().unwrap();
+        let err = ctx
+            .execute_sql("SELECT div(a, b) FROM (VALUES (1, 0)) AS t(a, b)")
+            .unwrap_err();
+        assert!(err.to_string().contains("Division by zero"));
+    }
+}
@@ -0,0 +1,345 @@
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow::array::{
+    ArrayRef, BooleanArray, Date32Array, Float64Array, Int64Array, StringArray,
+    TimestampMicrosecondArray,
+};
+use arrow::datatypes::{DataType as ArrowType, Field, Schema as ArrowSchema, TimeUnit};
+use arrow::record_batch::RecordBatch;
+
+use super::error::{DataFusionError, Result};
+use super::stream::{BatchWriter, ExportFormat};
+
+/// Rows generated per batch, so memory use stays flat for large datasets.
+const BATCH_ROWS: u64 = 8192;
+
+/// 2020-01-01 as days since the Unix epoch; generated dates and timestamps
+/// fall in the five years after it.
+const EPOCH_2020_DAYS: i64 = 18262;
+const SPAN_DAYS: i64 = 1827;
+
+const WORDS: [&str; 16] = [
+    "alpha", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel", "india", "juliet",
+    "kilo", "lima", "mike", "november", "oscar", "papa",
+];
+
+/// The type of a generated column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeneratedType {
+    Int,
+    Float,
+    String,
+    Bool,
+    Date,
+    Timestamp,
+}
+
+/// How the values of a generated column are spread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Distribution {
+    /// Evenly over the type's range: 0–1000 for numbers, a 16-word
+    /// vocabulary for strings, 2020–2024 for dates and timestamps.
+    Uniform,
+    /// 1, 2, 3, ... for integers; one day or second apart for dates and
+    /// timestamps.
+    Sequence,
+    /// Over the same range as `Uniform`, but heavily favouring low values
+    /// and the first words, like real-world category columns.
+    Skewed,
+    /// Normally distributed floats around 100 with a standard deviation
+    /// of 15.
+    Normal,
+    /// A distinct string per row.
+    Unique,
+}
+
+/// A column of a [`DatasetSpec`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedColumn {
+    pub name: String,
+    pub kind: GeneratedType,
+    pub distribution: Distribution,
+}
+
+/// The columns of a synthetic dataset, parsed from a spec such as
+/// `id:int:seq,name:string,amount:float:normal,ts:timestamp`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatasetSpec {
+    pub columns: Vec<GeneratedColumn>,
+}
+
+impl DatasetSpec {
+    /// Parse comma-separated `name:type[:distribution]` columns. Types are
+    /// `int`, `float`, `string`, `bool`, `date` and `timestamp`;
+    /// distributions are `uniform` (the default), `seq`, `skewed`, `normal`
+    /// and `unique`, where the type supports them.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let columns = spec
+            .split(',')
+            .map(str::trim)
+            .filter(|column| !column.is_empty())
+            .map(parse_column)
+            .collect::<Result<Vec<_>>>()?;
+        if columns.is_empty() {
+            return Err(invalid("the schema has no columns"));
+        }
+        Ok(Self { columns })
+    }
+
+    fn arrow_schema(&self) -> Arc<ArrowSchema> {
+        let fields: Vec<Field> = self
+            .columns
+            .iter()
+            .map(|column| {
+                let data_type = match column.kind {
+                    GeneratedType::Int => ArrowType::Int64,
+                    GeneratedType::Float => ArrowType::Float64,
+                    GeneratedType::String => ArrowType::Utf8,
+                    GeneratedType::Bool => ArrowType::Boolean,
+                    GeneratedType::Date => ArrowType::Date32,
+                    GeneratedType::Timestamp => ArrowType::Timestamp(TimeUnit::Microsecond, None),
+                };
+                Field::new(&column.name, data_type, false)
+            })
+            .collect();
+        Arc::new(ArrowSchema::new(fields))
+    }
+}
+
+fn parse_column(column: &str) -> Result<GeneratedColumn> {
+    let mut parts = column.split(':').map(str::trim);
+    let name = parts.next().unwrap_or_default();
+    let (Some(kind), distribution) = (parts.next(), parts.next()) else {
+        return Err(invalid(&format!("expected name:type, got '{}'", column)));
+    };
+    if name.is_empty() || parts.next().is_some() {
+        return Err(invalid(&format!("expected name:type, got '{}'", column)));
+    }
+
+    let kind = match kind.to_lowercase().as_str() {
+        "int" | "integer" | "bigint" => GeneratedType::Int,
+        "float" | "double" => GeneratedType::Float,
+        "string" | "text" | "varchar" => GeneratedType::String,
+        "bool" | "boolean" => GeneratedType::Bool,
+        "date" => GeneratedType::Date,
+        "timestamp" => GeneratedType::Timestamp,
+        other => return Err(invalid(&format!("unknown type '{}' for {}", other, name))),
+    };
+    let distribution = match distribution.map(str::to_lowercase).as_deref() {
+        None | Some("uniform") => Distribution::Uniform,
+        Some("seq") => Distribution::Sequence,
+        Some("skewed") => Distribution::Skewed,
+        Some("normal") => Distribution::Normal,
+        Some("unique") => Distribution::Unique,
+        Some(other) => {
+            return Err(invalid(&format!(
+                "unknown distribution '{}' for {}",
+                other, name
+            )))
+        }
+    };
+
+    let supported = match kind {
+        GeneratedType::Int => matches!(
+            distribution,
+            Distribution::Uniform | Distribution::Sequence | Distribution::Skewed
+        ),
+        GeneratedType::Float => matches!(
+            distribution,
+            Distribution::Uniform | Distribution::Skewed | Distribution::Normal
+        ),
+        GeneratedType::String => matches!(
+            distribution,
+            Distribution::Uniform | Distribution::Skewed | Distribution::Unique
+        ),
+        GeneratedType::Bool => distribution == Distribution::Uniform,
+        GeneratedType::Date | GeneratedType::Timestamp => {
+            matches!(distribution, Distribution::Uniform | Distribution::Sequence)
+        }
+    };
+    if !supported {
+        return Err(invalid(&format!(
+            "{:?} values cannot be {:?}",
+            kind, distribution
+        )));
+    }
+
+    Ok(GeneratedColumn {
+        name: name.to_string(),
+        kind,
+        distribution,
+    })
+}
+
+/// Parse a row count such as `1000`, `1_000_000` or `1e6`.
+pub fn parse_row_count(rows: &str) -> Result<u64> {
+    let rows = rows.trim().replace('_', "");
+    rows.parse::<u64>()
+        .ok()
+        .or_else(|| {
+            rows.parse::<f64>()
+                .ok()
+                .filter(|n| n.is_finite() && *n >= 0.0 && n.fract() == 0.0 && *n <= u64::MAX as f64)
+                .map(|n| n as u64)
+        })
+        .ok_or_else(|| invalid(&format!("invalid row count '{}'", rows)))
+}
+
+/// Write `rows` rows of synthetic data matching `spec` to `path`. The same
+/// `seed` always produces the same data. Returns the number of rows written.
+pub fn generate_dataset(
+    spec: &DatasetSpec,
+    rows: u64,
+    seed: u64,
+    path: &Path,
+    format: ExportFormat,
+) -> Result<u64> {
+    let schema = spec.arrow_schema();
+    let mut writer = BatchWriter::new(File::create(path)?, schema.clone(), format)?;
+    let mut rng = SplitMix64(seed);
+
+    // Always write at least one batch, so an empty CSV still has a header
+    let mut start = 0;
+    loop {
+        let len = BATCH_ROWS.min(rows - start);
+        let columns: Vec<ArrayRef> = spec
+            .columns
+            .iter()
+            .map(|column| generate_column(column, start, len, &mut rng))
+            .collect();
+        writer.write(&RecordBatch::try_new(schema.clone(), columns)?)?;
+        start += len;
+        if start >= rows {
+            break;
+        }
+    }
+    writer.finish()?;
+    Ok(rows)
+}
+
+fn generate_column(
+    column: &GeneratedColumn,
+    start: u64,
+    len: u64,
+    rng: &mut SplitMix64,
+) -> ArrayRef {
+    let rows = start..start + len;
+    match (column.kind, column.distribution) {
+        (GeneratedType::Int, Distribution::Sequence) => {
+            Arc::new(Int64Array::from_iter_values(rows.map(|i| i as i64 + 1)))
+        }
+        (GeneratedType::Int, distribution) => Arc::new(Int64Array::from_iter_values(
+            rows.map(|_| (rng.sample(distribution) * 1001.0) as i64),
+        )),
+        (GeneratedType::Float, Distribution::Normal) => Arc::new(Float64Array::from_iter_values(
+            rows.map(|_| round2(100.0 + 15.0 * rng.normal())),
+        )),
+        (GeneratedType::Float, distribution) => Arc::new(Float64Array::from_iter_values(
+            rows.map(|_| round2(rng.sample(distribution) * 1000.0)),
+        )),
+        (GeneratedType::String, Distribution::Unique) => Arc::new(StringArray::from_iter_values(
+            rows.map(|i| format!("{}_{}", column.name, i + 1)),
+        )),
+        (GeneratedType::String, distribution) => {
+            Arc::new(StringArray::from_iter_values(rows.map(|_| {
+                WORDS[(rng.sample(distribution) * WORDS.len() as f64) as usize]
+            })))
+        }
+        (GeneratedType::Bool, _) => Arc::new(BooleanArray::from(
+            rows.map(|_| rng.next_f64() < 0.5).collect::<Vec<_>>(),
+        )),
+        (GeneratedType::Date, Distribution::Sequence) => Arc::new(Date32Array::from_iter_values(
+            rows.map(|i| (EPOCH_2020_DAYS + i as i64) as i32),
+        )),
+        (GeneratedType::Date, _) => {
+            Arc::new(Date32Array::from_iter_values(rows.map(|_| {
+                (EPOCH_2020_DAYS + (rng.next_f64() * SPAN_DAYS as f64) as i64) as i32
+            })))
+        }
+        (GeneratedType::Timestamp, distribution) => {
+            let start_micros = EPOCH_2020_DAYS * 86_400_000_000;
+            Arc::new(TimestampMicrosecondArray::from_iter_values(rows.map(
+                |i| match distribution {
+                    Distribution::Sequence => start_micros + i as i64 * 1_000_000,
+                    _ => {
+                        let seconds = (rng.next_f64() * (SPAN_DAYS * 86_400) as f64) as i64;
+                        start_micros + seconds * 1_000_000
+                    }
+                },
+            )))
+        }
+    }
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+fn invalid(message: &str) -> DataFusionError {
+    DataFusionError::Conversion(format!("Invalid dataset schema: {}", message))
+}
+
+/// A small, fast and reproducible pseudo-random generator; the data only
+/// has to look plausible, not be unpredictable.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// In `[0, 1)`, spread according to `distribution`.
+    fn sample(&mut self, distribution: Distribution) -> f64 {
+        let u = self.next_f64();
+        match distribution {
+            Distribution::Skewed => u * u * u,
+            _ => u,
+        }
+    }
+
+    /// Standard normal, by the Box-Muller transform.
+    fn normal(&mut self) -> f64 {
+        let u1 = 1.0 - self.next_f64();
+        let u2 = self.next_f64();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_spec() {
+        let spec = DatasetSpec::parse("id:int:seq, name:string, ts:timestamp").unwrap();
+        assert_eq!(spec.columns.len(), 3);
+        assert_eq!(spec.columns[0].distribution, Distribution::Sequence);
+        assert_eq!(spec.columns[1].kind, GeneratedType::String);
+        assert_eq!(spec.columns[2].distribution, Distribution::Uniform);
+
+        assert!(DatasetSpec::parse("").is_err());
+        assert!(DatasetSpec::parse("id").is_err());
+        assert!(DatasetSpec::parse("id:uuid").is_err());
+        assert!(DatasetSpec::parse("flag:bool:seq").is_err());
+    }
+
+    #[test]
+    fn test_parse_row_count() {
+        assert_eq!(parse_row_count("1000").unwrap(), 1000);
+        assert_eq!(parse_row_count("1e6").unwrap(), 1_000_000);
+        assert_eq!(parse_row_count("2_500").unwrap(), 2500);
+        assert!(parse_row_count("1.5").is_err());
+        assert!(parse_row_count("-3").is_err());
+    }
+}
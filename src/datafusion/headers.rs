@@ -0,0 +1,156 @@
+use std::collections::HashSet;
+use std::fmt;
+
+/// How column names are rewritten when a table is loaded. Every step is off
+/// by default; [`HeaderNormalization::all`] turns them all on, so a header
+/// such as `" Total Sales ($)"` becomes `total_sales`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HeaderNormalization {
+    /// Strip leading and trailing whitespace.
+    pub trim: bool,
+    /// Lowercase the name, so it can be written unquoted in SQL.
+    pub lowercase: bool,
+    /// Replace each run of spaces and other characters that are not letters,
+    /// digits or `_` with a single `_`, dropping any at either end. A name
+    /// left empty becomes `column_<n>` and one starting with a digit gets a
+    /// leading `_`.
+    pub replace_special: bool,
+    /// Suffix `_2`, `_3`, ... to names that would repeat an earlier one.
+    pub dedupe: bool,
+}
+
+impl HeaderNormalization {
+    /// Every normalization step.
+    pub fn all() -> Self {
+        Self {
+            trim: true,
+            lowercase: true,
+            replace_special: true,
+            dedupe: true,
+        }
+    }
+
+    /// Whether any step is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.trim || self.lowercase || self.replace_special || self.dedupe
+    }
+
+    /// The normalized form of each of `names`, in order.
+    pub fn apply(&self, names: &[String]) -> Vec<String> {
+        let mut seen = HashSet::new();
+        names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let mut name = name.clone();
+                if self.trim {
+                    name = name.trim().to_string();
+                }
+                if self.lowercase {
+                    name = name.to_lowercase();
+                }
+                if self.replace_special {
+                    name = replace_special(&name, || format!("column_{}", i + 1));
+                }
+                if self.dedupe && seen.contains(&name) {
+                    name = (2..)
+                        .map(|n| format!("{}_{}", name, n))
+                        .find(|candidate| !seen.contains(candidate) && !names.contains(candidate))
+                        .expect("unbounded suffixes");
+                }
+                seen.insert(name.clone());
+                name
+            })
+            .collect()
+    }
+}
+
+/// A column whose name was changed by [`HeaderNormalization`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenamedColumn {
+    /// The name in the source file.
+    pub original: String,
+    /// The name to use in queries.
+    pub name: String,
+}
+
+impl fmt::Display for RenamedColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\" -> {}", self.original, self.name)
+    }
+}
+
+/// `name` with each run of characters other than letters, digits and `_`
+/// replaced by a single `_`, trimmed of `_` at either end. A name left empty
+/// becomes `empty()`, and one starting with a digit gets a leading `_`.
+pub(super) fn replace_special(name: &str, empty: impl FnOnce() -> String) -> String {
+    let mut replaced = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_alphanumeric() || c == '_' {
+            replaced.push(c);
+        } else if !replaced.ends_with('_') {
+            replaced.push('_');
+        }
+    }
+    let replaced = replaced.trim_matches('_');
+    if replaced.is_empty() {
+        empty()
+    } else if replaced.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", replaced)
+    } else {
+        replaced.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalize(options: HeaderNormalization, names: &[&str]) -> Vec<String> {
+        let names: Vec<String> = names.iter().map(|n| n.to_string()).collect();
+        options.apply(&names)
+    }
+
+    #[test]
+    fn test_normalize_all() {
+        assert_eq!(
+            normalize(
+                HeaderNormalization::all(),
+                &[
+                    " Total Sales ($)",
+                    "Order-ID",
+                    "2024 Q1",
+                    "???",
+                    "total_sales"
+                ]
+            ),
+            vec![
+                "total_sales",
+                "order_id",
+                "_2024_q1",
+                "column_4",
+                "total_sales_2"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize_single_steps() {
+        let trim = HeaderNormalization {
+            trim: true,
+            ..Default::default()
+        };
+        assert_eq!(normalize(trim, &[" Name "]), vec!["Name"]);
+
+        let dedupe = HeaderNormalization {
+            dedupe: true,
+            ..Default::default()
+        };
+        // A suffix never collides with a later original name
+        assert_eq!(
+            normalize(dedupe, &["id", "id", "id_2"]),
+            vec!["id", "id_3", "id_2"]
+        );
+        assert!(!HeaderNormalization::default().is_enabled());
+    }
+}
@@ -1,38 +1,127 @@
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use crate::storage::table::Schema;
 
 use super::context::DataFusionContext;
 use super::error::{DataFusionError, Result};
+use super::headers::HeaderNormalization;
+use super::names::sanitize_table_name;
+use super::reader::{ReaderRegistry, SourceReader};
+use super::report::{ColumnRefinement, LoadReport, LoadedTable, RenamedHeader, SkippedFile};
+
+/// What the loader does about text columns that could be a more specific
+/// type. See [`DataFusionContext::refine_types`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TypeRefinementMode {
+    /// Leave columns alone without scanning them.
+    #[default]
+    Off,
+    /// Scan text columns and list the conversions in the load report.
+    Suggest,
+    /// Scan text columns and convert them.
+    Apply,
+}
 
 pub struct FileLoader {
     context: DataFusionContext,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[allow(dead_code)]
-pub enum FileFormat {
-    Csv,
-    Json,
-    Parquet,
-    Delta,
-    Sqlite,
-    Iceberg,
+    readers: ReaderRegistry,
+    provenance: bool,
+    headers: HeaderNormalization,
+    refine_types: TypeRefinementMode,
+    union: bool,
+    filter: Option<String>,
+    report: LoadReport,
 }
 
 impl FileLoader {
     pub fn new() -> Result<Self> {
         let context = DataFusionContext::new()?;
-        Ok(Self { context })
+        Ok(Self::from_context(context))
     }
 
     /// Initialise a loader that registers files into an existing context,
     /// preserving all previously loaded tables.
     pub fn from_context(context: DataFusionContext) -> Self {
-        Self { context }
+        Self {
+            context,
+            readers: ReaderRegistry::default(),
+            provenance: false,
+            headers: HeaderNormalization::default(),
+            refine_types: TypeRefinementMode::Off,
+            union: false,
+            filter: None,
+            report: LoadReport::default(),
+        }
+    }
+
+    /// Give every table loaded from now on a `_row_id` column, plus a
+    /// `_source_file` column for files loaded as part of a directory. See
+    /// [`DataFusionContext::add_provenance_columns`].
+    pub fn set_provenance_columns(&mut self, enabled: bool) {
+        self.provenance = enabled;
+    }
+
+    /// Rename the columns of tables loaded from now on as `headers`
+    /// describes. See [`DataFusionContext::normalize_headers`].
+    pub fn set_header_normalization(&mut self, headers: HeaderNormalization) {
+        self.headers = headers;
+    }
+
+    /// Choose what to do about text columns of tables loaded from now on
+    /// that hold dates, booleans or numbers with thousands separators.
+    pub fn set_type_refinement(&mut self, mode: TypeRefinementMode) {
+        self.refine_types = mode;
+    }
+
+    /// Load the files of directories and glob patterns loaded from now on as
+    /// one table instead of one per file. The table is named after the
+    /// directory, or the directory a pattern starts in, and the files must
+    /// have the same column names and types. See
+    /// [`DataFusionContext::union_tables`].
+    pub fn set_union_files(&mut self, enabled: bool) {
+        self.union = enabled;
+    }
+
+    /// Keep only the rows matching `filter`, a SQL predicate such as
+    /// `date >= '2024-01-01'`, of Delta and Iceberg tables loaded from now
+    /// on, so partitions and files it rules out are never read. Other tables
+    /// are loaded whole, with a warning. See
+    /// [`DataFusionContext::filter_table`].
+    pub fn set_load_filter(&mut self, filter: Option<String>) {
+        self.filter = filter;
+    }
+
+    /// Add support for another source format. The reader takes priority over
+    /// the built-in formats and any reader registered earlier.
+    pub fn register_reader(&mut self, reader: Box<dyn SourceReader>) {
+        self.readers.register(reader);
+    }
+
+    pub fn readers(&self) -> &ReaderRegistry {
+        &self.readers
+    }
+
+    /// Everything this loader has loaded, skipped or warned about so far.
+    pub fn load_report(&self) -> &LoadReport {
+        &self.report
     }
 
     pub fn load_file(&mut self, path: &Path) -> Result<Vec<String>> {
-        if !path.exists() {
+        self.load_single_file(path, false)
+    }
+
+    /// Load a file, or a Delta table if the URI has no file extension, at an
+    /// `s3://`, `gs://` or `http(s)://` URI. The table is named after the
+    /// last path segment, and credentials come from the environment; see
+    /// [`DataFusionContext::register_object_store`].
+    pub fn load_uri(&mut self, uri: &str) -> Result<Vec<String>> {
+        self.context.register_object_store(uri)?;
+        self.load_single_file(Path::new(uri), false)
+    }
+
+    fn load_single_file(&mut self, path: &Path, in_directory: bool) -> Result<Vec<String>> {
+        if !is_remote_path(path) && !path.exists() {
             return Err(DataFusionError::FileNotFound(
                 path.to_string_lossy().to_string(),
             ));
@@ -42,34 +131,89 @@ impl FileLoader {
             return self.load_directory(path);
         }
 
-        let format = detect_file_format(path)?;
-        let table_name = path
+        let reader = self.readers.find(path).ok_or_else(|| unsupported(path))?;
+        let source_name = path
             .file_stem()
             .and_then(|s| s.to_str())
-            .ok_or_else(|| DataFusionError::InvalidTableName("Invalid file name".to_string()))?
-            .to_string();
+            .ok_or_else(|| DataFusionError::InvalidTableName("Invalid file name".to_string()))?;
 
-        match format {
-            FileFormat::Csv => {
-                self.context.register_csv(&table_name, path)?;
-                Ok(vec![table_name])
+        let format = reader.name().to_string();
+        let table_name = sanitize_table_name(source_name);
+        let tables = reader.load(&mut self.context, path, &table_name)?;
+        self.finish_load(&format, path, source_name, tables, in_directory)
+    }
+
+    /// Normalize headers, add provenance columns and refine types if enabled,
+    /// and record the new tables in the load report. `source_name` is the
+    /// file or directory name the table name was derived from.
+    fn finish_load(
+        &mut self,
+        format: &str,
+        path: &Path,
+        source_name: &str,
+        tables: Vec<String>,
+        source_file: bool,
+    ) -> Result<Vec<String>> {
+        let sanitized = sanitize_table_name(source_name);
+        for table in &tables {
+            if let Some(filter) = &self.filter {
+                if matches!(format, "delta" | "iceberg") {
+                    self.context.filter_table(table, filter)?;
+                } else {
+                    self.report.warnings.push(format!(
+                        "Load filter not applied to {} table {}; it only applies to Delta and Iceberg tables",
+                        format, table
+                    ));
+                }
             }
-            FileFormat::Json => {
-                self.context.register_json(&table_name, path)?;
-                Ok(vec![table_name])
+            let coerced = self.context.lenient_number_columns(table).to_vec();
+            self.report
+                .refinements
+                .extend(coerced.into_iter().map(|refinement| ColumnRefinement {
+                    table: table.clone(),
+                    refinement,
+                    applied: true,
+                }));
+            if self.headers.is_enabled() {
+                let renamed = self.context.normalize_headers(table, self.headers)?;
+                self.report
+                    .renamed
+                    .extend(renamed.into_iter().map(|column| RenamedHeader {
+                        table: table.clone(),
+                        column,
+                    }));
             }
-            FileFormat::Parquet => {
-                self.context.register_parquet(&table_name, path)?;
-                Ok(vec![table_name])
+            if self.provenance {
+                self.context.add_provenance_columns(table, source_file)?;
             }
-            FileFormat::Sqlite => self.context.register_sqlite(path),
-            FileFormat::Delta => Err(DataFusionError::UnsupportedFormat(
-                "Delta Lake tables must be directories".to_string(),
-            )),
-            FileFormat::Iceberg => Err(DataFusionError::UnsupportedFormat(
-                "Iceberg tables must be directories".to_string(),
-            )),
+            let refinements = match self.refine_types {
+                TypeRefinementMode::Off => Vec::new(),
+                TypeRefinementMode::Suggest => self.context.detect_type_refinements(table)?,
+                TypeRefinementMode::Apply => self.context.refine_types(table)?,
+            };
+            self.report
+                .refinements
+                .extend(refinements.into_iter().map(|refinement| ColumnRefinement {
+                    table: table.clone(),
+                    refinement,
+                    applied: self.refine_types == TypeRefinementMode::Apply,
+                }));
+            let details = self.context.table_details(table);
+            self.report.loaded.push(LoadedTable {
+                name: table.clone(),
+                original_name: (*table == sanitized && table != source_name)
+                    .then(|| source_name.to_string()),
+                path: path.to_path_buf(),
+                format: format.to_string(),
+                row_count: details.as_ref().and_then(|d| d.row_count),
+                row_count_estimated: details.is_some_and(|d| d.row_count_estimated),
+                schema: self
+                    .context
+                    .get_table_schema(table)
+                    .unwrap_or_else(|| Schema::new(Vec::new())),
+            });
         }
+        Ok(tables)
     }
 
     pub fn load_directory(&mut self, path: &Path) -> Result<Vec<String>> {
@@ -80,59 +224,140 @@ impl FileLoader {
             )));
         }
 
-        // Check for Delta Lake
-        if is_delta_table(path) {
-            let table_name = path
-                .file_name()
-                .and_then(|s| s.to_str())
-                .ok_or_else(|| {
-                    DataFusionError::InvalidTableName("Invalid directory name".to_string())
-                })?
-                .to_string();
-            self.context.register_delta(&table_name, path)?;
-            return Ok(vec![table_name]);
-        }
-
-        // Check for Iceberg
-        if is_iceberg_table(path) {
-            let table_name = path
-                .file_name()
-                .and_then(|s| s.to_str())
-                .ok_or_else(|| {
-                    DataFusionError::InvalidTableName("Invalid directory name".to_string())
-                })?
-                .to_string();
-            self.context.register_iceberg(&table_name, path)?;
-            return Ok(vec![table_name]);
+        // Table formats such as Delta Lake and Iceberg are whole directories
+        if let Some(reader) = self.readers.find(path) {
+            let source_name = path.file_name().and_then(|s| s.to_str()).ok_or_else(|| {
+                DataFusionError::InvalidTableName("Invalid directory name".to_string())
+            })?;
+            let format = reader.name().to_string();
+            let table_name = sanitize_table_name(source_name);
+            let tables = reader.load(&mut self.context, path, &table_name)?;
+            return self.finish_load(&format, path, source_name, tables, false);
         }
 
         // Load all files in directory
-        let mut loaded_tables = Vec::new();
-        let entries = fs::read_dir(path)?;
-
-        for entry in entries {
-            let entry = entry?;
-            let entry_path = entry.path();
-
+        let mut files = Vec::new();
+        for entry in fs::read_dir(path)? {
+            let entry_path = entry?.path();
             if entry_path.is_file() {
-                match self.load_file(&entry_path) {
-                    Ok(mut tables) => loaded_tables.append(&mut tables),
-                    Err(e) => {
-                        eprintln!("Warning: Failed to load {}: {}", entry_path.display(), e);
-                    }
-                }
+                files.push(entry_path);
+            } else if entry_path.is_dir() {
+                self.report
+                    .warnings
+                    .push(format!("Ignored subdirectory {}", entry_path.display()));
             }
         }
+        if self.union {
+            files.sort();
+        }
 
+        let first_loaded = self.report.loaded.len();
+        let loaded_tables = self.load_files(files);
         if loaded_tables.is_empty() {
             return Err(DataFusionError::Conversion(
                 "No supported files found in directory".to_string(),
             ));
         }
 
+        if self.union {
+            let source_name = path.file_name().and_then(|s| s.to_str()).ok_or_else(|| {
+                DataFusionError::InvalidTableName("Invalid directory name".to_string())
+            })?;
+            return self.union_loaded(path, source_name, loaded_tables, first_loaded);
+        }
+        Ok(loaded_tables)
+    }
+
+    /// Load the files matching a glob pattern such as
+    /// `events/2024-*.parquet`, in path order, like the files of a
+    /// directory: files that cannot be loaded are skipped.
+    pub fn load_glob(&mut self, pattern: &str) -> Result<Vec<String>> {
+        let files: Vec<PathBuf> = glob::glob(pattern)
+            .map_err(|e| {
+                DataFusionError::Conversion(format!("Invalid glob pattern {}: {}", pattern, e))
+            })?
+            .filter_map(|path| path.ok())
+            .filter(|path| path.is_file())
+            .collect();
+        if files.is_empty() {
+            return Err(DataFusionError::FileNotFound(pattern.to_string()));
+        }
+
+        let first_loaded = self.report.loaded.len();
+        let loaded_tables = self.load_files(files);
+        if loaded_tables.is_empty() {
+            return Err(DataFusionError::Conversion(format!(
+                "No supported files match {}",
+                pattern
+            )));
+        }
+
+        if self.union {
+            let base = glob_base(pattern);
+            let source_name = base
+                .file_name()
+                .and_then(|s| s.to_str())
+                .map(str::to_string)
+                .ok_or_else(|| {
+                    DataFusionError::InvalidTableName(format!("No directory name in {}", pattern))
+                })?;
+            return self.union_loaded(
+                Path::new(pattern),
+                &source_name,
+                loaded_tables,
+                first_loaded,
+            );
+        }
         Ok(loaded_tables)
     }
 
+    /// Load each of `files` as part of a directory, recording the ones that
+    /// fail as skipped. Returns the tables loaded.
+    fn load_files(&mut self, files: Vec<PathBuf>) -> Vec<String> {
+        let mut loaded_tables = Vec::new();
+        for file in files {
+            match self.load_single_file(&file, true) {
+                Ok(mut tables) => loaded_tables.append(&mut tables),
+                Err(e) => self.report.skipped.push(SkippedFile {
+                    path: file,
+                    reason: e.to_string(),
+                }),
+            }
+        }
+        loaded_tables
+    }
+
+    /// Combine the tables just loaded from the files of `path` into one
+    /// table named after `source_name`, and list it in the load report in
+    /// place of the report entries from `first_loaded` on.
+    fn union_loaded(
+        &mut self,
+        path: &Path,
+        source_name: &str,
+        tables: Vec<String>,
+        first_loaded: usize,
+    ) -> Result<Vec<String>> {
+        let name = sanitize_table_name(source_name);
+        self.context.union_tables(&name, &tables)?;
+
+        let format = self.report.loaded[first_loaded].format.clone();
+        self.report.loaded.truncate(first_loaded);
+        let details = self.context.table_details(&name);
+        self.report.loaded.push(LoadedTable {
+            name: name.clone(),
+            original_name: (name != source_name).then(|| source_name.to_string()),
+            path: path.to_path_buf(),
+            format,
+            row_count: details.as_ref().and_then(|d| d.row_count),
+            row_count_estimated: details.is_some_and(|d| d.row_count_estimated),
+            schema: self
+                .context
+                .get_table_schema(&name)
+                .unwrap_or_else(|| Schema::new(Vec::new())),
+        });
+        Ok(vec![name])
+    }
+
     pub fn into_context(self) -> DataFusionContext {
         self.context
     }
@@ -146,36 +371,60 @@ impl FileLoader {
     }
 }
 
-fn detect_file_format(path: &Path) -> Result<FileFormat> {
-    let extension = path
-        .extension()
-        .and_then(|s| s.to_str())
-        .ok_or_else(|| DataFusionError::UnsupportedFormat("No file extension".to_string()))?
-        .to_lowercase();
-
-    match extension.as_str() {
-        "csv" => Ok(FileFormat::Csv),
-        "json" | "ndjson" | "jsonl" => Ok(FileFormat::Json),
-        "parquet" | "pq" => Ok(FileFormat::Parquet),
-        "db" | "sqlite" | "sqlite3" => Ok(FileFormat::Sqlite),
-        _ => Err(DataFusionError::UnsupportedFormat(format!(
-            "Unsupported file format: {}",
-            extension
-        ))),
-    }
+/// URI schemes that [`is_remote_uri`] accepts.
+pub(super) const REMOTE_SCHEMES: [&str; 4] = ["s3", "gs", "http", "https"];
+
+/// Whether `path` is an `s3://`, `gs://`, `http://` or `https://` URI rather
+/// than a local path. Loading one needs the `remote` feature.
+pub fn is_remote_uri(path: &str) -> bool {
+    path.split_once("://").is_some_and(|(scheme, _)| {
+        REMOTE_SCHEMES
+            .iter()
+            .any(|remote| scheme.eq_ignore_ascii_case(remote))
+    })
+}
+
+/// [`is_remote_uri`] for a path a table was registered from.
+pub(super) fn is_remote_path(path: &Path) -> bool {
+    path.to_str().is_some_and(is_remote_uri)
+}
+
+/// Whether `path` is a glob pattern rather than a plain path.
+pub fn is_glob_pattern(path: &str) -> bool {
+    path.contains(['*', '?', '['])
 }
 
-fn is_delta_table(path: &Path) -> bool {
-    path.join("_delta_log").is_dir()
+/// The directory a glob pattern starts in: its leading components without
+/// wildcards, resolved against the current directory, e.g. `data/events`
+/// for `data/events/2024-*.parquet`.
+fn glob_base(pattern: &str) -> PathBuf {
+    let base: PathBuf = Path::new(pattern)
+        .components()
+        .take_while(|component| !is_glob_pattern(&component.as_os_str().to_string_lossy()))
+        .collect();
+    let base = if base.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        base.as_path()
+    };
+    fs::canonicalize(base).unwrap_or_else(|_| base.to_path_buf())
 }
 
-fn is_iceberg_table(path: &Path) -> bool {
-    path.join("metadata").is_dir()
+fn unsupported(path: &Path) -> DataFusionError {
+    match path.extension().and_then(|s| s.to_str()) {
+        Some(extension) => DataFusionError::UnsupportedFormat(format!(
+            "Unsupported file format: {}",
+            extension.to_lowercase()
+        )),
+        None => DataFusionError::UnsupportedFormat("No file extension".to_string()),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::datafusion::quote_identifier;
+    use crate::datafusion::reader::CsvReader;
     use std::env;
     use std::path::PathBuf;
 
@@ -183,28 +432,403 @@ mod tests {
         PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("samples")
     }
 
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_header_detects_duckdb() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("analytics.db");
+        let mut header = vec![0u8; 8];
+        header.extend_from_slice(b"DUCK");
+        header.extend_from_slice(&[0u8; 52]);
+        fs::write(&path, header).unwrap();
+
+        let mut loader = FileLoader::new().unwrap();
+        let err = loader.load_file(&path).unwrap_err();
+        assert!(matches!(err, DataFusionError::UnsupportedFormat(_)));
+        assert!(err.to_string().contains("DuckDB"));
+    }
+
+    struct TsvReader;
+
+    impl SourceReader for TsvReader {
+        fn name(&self) -> &str {
+            "tsv"
+        }
+
+        fn detect(&self, path: &Path) -> bool {
+            crate::datafusion::has_extension(path, &["tsv"])
+        }
+
+        fn load(
+            &self,
+            context: &mut DataFusionContext,
+            path: &Path,
+            table_name: &str,
+        ) -> Result<Vec<String>> {
+            let contents = fs::read_to_string(path)?.replace('\t', ",");
+            let csv_path = path.with_extension("csv");
+            fs::write(&csv_path, contents)?;
+            context.register_csv(table_name, &csv_path)?;
+            Ok(vec![table_name.to_string()])
+        }
+    }
+
+    #[test]
+    fn test_register_custom_reader() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scores.tsv");
+        fs::write(&path, "name\tscore\nalice\t3\nbob\t5\n").unwrap();
+
+        let mut loader = FileLoader::new().unwrap();
+        assert!(matches!(
+            loader.load_file(&path),
+            Err(DataFusionError::UnsupportedFormat(_))
+        ));
+
+        loader.register_reader(Box::new(TsvReader));
+        assert_eq!(loader.readers().names()[0], "tsv");
+        assert_eq!(loader.load_file(&path).unwrap(), vec!["scores"]);
+
+        let result = loader
+            .context()
+            .execute_sql("SELECT SUM(score) AS total FROM scores")
+            .unwrap();
+        assert_eq!(result.row_count(), 1);
+    }
+
+    #[test]
+    fn test_type_refinement_modes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("accounts.csv");
+        fs::write(&path, "id,active,balance\n1,Y,\"1,200\"\n2,N,35\n").unwrap();
+
+        let mut loader = FileLoader::new().unwrap();
+        loader.set_type_refinement(TypeRefinementMode::Suggest);
+        loader.load_file(&path).unwrap();
+        assert_eq!(loader.load_report().refinements.len(), 2);
+        assert!(!loader.load_report().refinements[0].applied);
+        let schema = loader.context().get_table_schema("accounts").unwrap();
+        assert_eq!(schema.columns[1].data_type, crate::DataType::String);
+
+        let mut loader = FileLoader::new().unwrap();
+        loader.set_type_refinement(TypeRefinementMode::Apply);
+        loader.load_file(&path).unwrap();
+        let mut ctx = loader.into_context();
+        let result = ctx
+            .execute_sql("SELECT SUM(balance) FROM accounts WHERE active")
+            .unwrap();
+        assert_eq!(result.rows[0].values[0], crate::Value::Integer(1200));
+
+        ctx.refresh_table("accounts").unwrap();
+        let schema = ctx.get_table_schema("accounts").unwrap();
+        assert_eq!(schema.columns[1].data_type, crate::DataType::Boolean);
+    }
+
+    #[test]
+    fn test_header_normalization() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sales.csv");
+        fs::write(
+            &path,
+            " Region ,Total Sales ($),total_sales\nwest,10,1\neast,5,2\n",
+        )
+        .unwrap();
+
+        let mut loader = FileLoader::new().unwrap();
+        loader.set_header_normalization(HeaderNormalization::all());
+        loader.set_provenance_columns(true);
+        loader.load_file(&path).unwrap();
+        assert_eq!(
+            loader
+                .load_report()
+                .renamed
+                .iter()
+                .map(|r| r.column.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "\" Region \" -> region",
+                "\"Total Sales ($)\" -> total_sales",
+                "\"total_sales\" -> total_sales_2"
+            ]
+        );
+
+        let mut ctx = loader.into_context();
+        let result = ctx
+            .execute_sql("SELECT region, total_sales, _row_id FROM sales WHERE total_sales_2 = 2")
+            .unwrap();
+        assert_eq!(
+            result.rows[0].values[0],
+            crate::Value::String("east".into())
+        );
+        assert_eq!(result.rows[0].values[2], crate::Value::Integer(2));
+
+        ctx.refresh_table("sales").unwrap();
+        let described = ctx.describe_table("sales").unwrap();
+        assert_eq!(
+            described.schema.columns.last().unwrap().name,
+            "original_name"
+        );
+        let last = described.schema.column_count() - 1;
+        assert_eq!(
+            described.rows[1].values[last],
+            crate::Value::String("Total Sales ($)".into())
+        );
+        assert_eq!(
+            described.rows[3].values[last],
+            crate::Value::String("_row_id".into())
+        );
+    }
+
+    #[test]
+    fn test_lenient_number_reader() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ledger.csv");
+        fs::write(
+            &path,
+            "item,amount,rate\nrent,\"$1,234.50\",12%\nrefund,(500),7.5%\nfee,$20,0%\n",
+        )
+        .unwrap();
+
+        let mut loader = FileLoader::new().unwrap();
+        loader.register_reader(Box::new(CsvReader::lenient()));
+        loader.load_file(&path).unwrap();
+        let report = loader.load_report();
+        assert_eq!(
+            report
+                .refinements
+                .iter()
+                .map(|r| (r.refinement.column.as_str(), r.applied))
+                .collect::<Vec<_>>(),
+            vec![("amount", true), ("rate", true)]
+        );
+
+        let mut ctx = loader.into_context();
+        let result = ctx
+            .execute_sql("SELECT SUM(amount), MAX(rate) FROM ledger")
+            .unwrap();
+        assert_eq!(result.rows[0].values[0], crate::Value::Float(754.5));
+        assert_eq!(result.rows[0].values[1], crate::Value::Float(0.12));
+
+        ctx.refresh_table("ledger").unwrap();
+        assert_eq!(ctx.lenient_number_columns("ledger").len(), 2);
+        let schema = ctx.get_table_schema("ledger").unwrap();
+        assert_eq!(schema.columns[0].data_type, crate::DataType::String);
+        assert_eq!(schema.columns[1].data_type, crate::DataType::Float);
+    }
+
+    #[test]
+    fn test_provenance_columns_for_directory_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.csv");
+        fs::write(&path, "kind\nopen\nclose\n").unwrap();
+
+        let mut loader = FileLoader::new().unwrap();
+        loader.set_provenance_columns(true);
+        loader.load_directory(dir.path()).unwrap();
+
+        let result = loader
+            .context()
+            .execute_sql("SELECT kind, _row_id, _source_file FROM events WHERE kind = 'close'")
+            .unwrap();
+        assert_eq!(result.rows[0].values[1], crate::Value::Integer(2));
+        assert_eq!(
+            result.rows[0].values[2],
+            crate::Value::String(path.to_string_lossy().to_string())
+        );
+
+        let mut loader = FileLoader::new().unwrap();
+        loader.set_provenance_columns(true);
+        loader.load_file(&path).unwrap();
+        let schema = loader.context().get_table_schema("events").unwrap();
+        assert_eq!(schema.column_names(), vec!["kind", "_row_id"]);
+    }
+
     #[test]
-    fn test_detect_csv() {
-        let path = PathBuf::from("test.csv");
-        assert_eq!(detect_file_format(&path).unwrap(), FileFormat::Csv);
+    fn test_load_report_for_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("users.csv"), "id,name\n1,a\n2,b\n").unwrap();
+        fs::write(dir.path().join("notes.txt"), "not data").unwrap();
+        fs::create_dir(dir.path().join("archive")).unwrap();
+
+        let mut loader = FileLoader::new().unwrap();
+        loader.load_directory(dir.path()).unwrap();
+
+        let report = loader.load_report();
+        assert_eq!(report.loaded.len(), 1);
+        assert_eq!(report.loaded[0].name, "users");
+        assert_eq!(report.loaded[0].format, "csv");
+        assert_eq!(report.loaded[0].row_count, Some(2));
+        assert_eq!(report.loaded[0].schema.column_names(), vec!["id", "name"]);
+        assert_eq!(report.skipped.len(), 1);
+        assert!(report.skipped[0].path.ends_with("notes.txt"));
+        assert!(report.skipped[0].reason.contains("txt"));
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].contains("archive"));
     }
 
     #[test]
-    fn test_detect_parquet() {
-        let path = PathBuf::from("test.parquet");
-        assert_eq!(detect_file_format(&path).unwrap(), FileFormat::Parquet);
+    fn test_glob_patterns_and_union() {
+        let dir = tempfile::tempdir().unwrap();
+        let events = dir.path().join("events");
+        fs::create_dir(&events).unwrap();
+        fs::write(events.join("2024-01.csv"), "id,amount\n1,10\n2,20\n").unwrap();
+        fs::write(events.join("2024-02.csv"), "id,amount\n3,30\n").unwrap();
+        fs::write(events.join("2023-12.csv"), "id,amount\n9,90\n").unwrap();
+        let pattern = events.join("2024-*.csv");
+        let pattern = pattern.to_str().unwrap();
+        assert!(is_glob_pattern(pattern));
+
+        let mut loader = FileLoader::new().unwrap();
+        assert_eq!(loader.load_glob(pattern).unwrap(), ["_2024_01", "_2024_02"]);
+
+        let mut loader = FileLoader::new().unwrap();
+        loader.set_union_files(true);
+        assert_eq!(loader.load_glob(pattern).unwrap(), ["events"]);
+        let report = loader.load_report();
+        assert_eq!(report.loaded.len(), 1);
+        assert_eq!(report.loaded[0].row_count, Some(3));
+        let ctx = loader.context_mut();
+        assert_eq!(ctx.list_tables(), ["events"]);
+        let total = |ctx: &DataFusionContext| {
+            let result = ctx.execute_sql("SELECT SUM(amount) FROM events").unwrap();
+            result.rows[0].values[0].clone()
+        };
+        assert_eq!(total(ctx), crate::Value::Integer(60));
+        assert_eq!(ctx.table_details("events").unwrap().file_count, Some(2));
+
+        // A refresh re-reads every file
+        fs::write(events.join("2024-02.csv"), "id,amount\n3,30\n4,40\n").unwrap();
+        ctx.refresh_all().unwrap();
+        assert_eq!(ctx.list_tables(), ["events"]);
+        assert_eq!(total(ctx), crate::Value::Integer(100));
+
+        fs::write(events.join("2024-03.csv"), "id,amount,note\n5,50,x\n").unwrap();
+        let mut loader = FileLoader::new().unwrap();
+        loader.set_union_files(true);
+        let err = loader.load_directory(&events).unwrap_err();
+        assert!(matches!(err, DataFusionError::SchemaMismatch(_)), "{err:?}");
+        assert!(err.to_string().contains("2024-03.csv has columns"));
+    }
+
+    #[test]
+    fn test_special_characters_in_file_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("My Data (2024).csv");
+        fs::write(&path, "id\n1\n2\n").unwrap();
+
+        let mut loader = FileLoader::new().unwrap();
+        assert_eq!(loader.load_file(&path).unwrap(), vec!["my_data_2024"]);
+        assert_eq!(
+            loader.load_report().renamed_tables(),
+            vec![("My Data (2024)", "my_data_2024")]
+        );
+
+        let ctx = loader.into_context();
+        let result = ctx
+            .execute_sql("SELECT COUNT(*) FROM my_data_2024")
+            .unwrap();
+        assert_eq!(result.rows[0].values[0], crate::Value::Integer(2));
+        let result = ctx
+            .execute_sql(&format!(
+                "SELECT * FROM {}",
+                quote_identifier("my_data_2024")
+            ))
+            .unwrap();
+        assert_eq!(result.row_count(), 2);
+    }
+
+    /// Serve `body` at every path on a local port, answering HEAD and ranged
+    /// GET requests the way object stores are read. Returns the base URL.
+    #[cfg(feature = "remote")]
+    fn serve_http(body: &'static [u8]) -> String {
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut request = Vec::new();
+                for line in BufReader::new(&stream).lines() {
+                    let line = line.unwrap();
+                    if line.is_empty() {
+                        break;
+                    }
+                    request.push(line.to_lowercase());
+                }
+                let range = request.iter().find_map(|line| {
+                    let (start, end) = line.strip_prefix("range: bytes=")?.split_once('-')?;
+                    let start: usize = start.parse().ok()?;
+                    let end = end.parse().map_or(body.len(), |end: usize| end + 1);
+                    Some((start, end.min(body.len())))
+                });
+                let (status, (start, end)) = match range {
+                    Some(range) => ("206 Partial Content", range),
+                    None => ("200 OK", (0, body.len())),
+                };
+                let mut response = format!(
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+                    status,
+                    end - start
+                );
+                if range.is_some() {
+                    response.push_str(&format!(
+                        "Content-Range: bytes {}-{}/{}\r\n",
+                        start,
+                        end - 1,
+                        body.len()
+                    ));
+                }
+                response.push_str("\r\n");
+                stream.write_all(response.as_bytes()).unwrap();
+                if request[0].starts_with("get") {
+                    stream.write_all(&body[start..end]).unwrap();
+                }
+            }
+        });
+        format!("http://{}", address)
+    }
 
-        let path = PathBuf::from("test.pq");
-        assert_eq!(detect_file_format(&path).unwrap(), FileFormat::Parquet);
+    #[cfg(feature = "remote")]
+    #[test]
+    fn test_load_csv_over_http() {
+        let base = serve_http(b"id,name\n1,alpha\n2,beta\n3,gamma\n");
+        let uri = format!("{}/exports/users.csv", base);
+
+        let mut loader = FileLoader::new().unwrap();
+        assert_eq!(loader.load_uri(&uri).unwrap(), vec!["users"]);
+        assert_eq!(loader.load_report().loaded[0].format, "csv");
+
+        let mut ctx = loader.into_context();
+        let result = ctx
+            .execute_sql("SELECT name FROM users WHERE id = 2")
+            .unwrap();
+        assert_eq!(
+            result.rows[0].values[0],
+            crate::Value::String("beta".into())
+        );
+
+        // A remote source is re-read on refresh rather than reported missing
+        ctx.refresh_table("users").unwrap();
+        let result = ctx.execute_sql("SELECT COUNT(*) FROM users").unwrap();
+        assert_eq!(result.rows[0].values[0], crate::Value::Integer(3));
     }
 
     #[test]
-    fn test_detect_sqlite() {
-        let path = PathBuf::from("test.db");
-        assert_eq!(detect_file_format(&path).unwrap(), FileFormat::Sqlite);
+    fn test_is_remote_uri() {
+        assert!(is_remote_uri("s3://bucket/events.parquet"));
+        assert!(is_remote_uri("gs://bucket/tables/sales"));
+        assert!(is_remote_uri("HTTPS://example.com/data.csv"));
+        assert!(!is_remote_uri("data/events.parquet"));
+        assert!(!is_remote_uri("file:///tmp/events.parquet"));
+        assert!(!is_remote_uri("C:\\data\\events.parquet"));
+    }
 
-        let path = PathBuf::from("test.sqlite");
-        assert_eq!(detect_file_format(&path).unwrap(), FileFormat::Sqlite);
+    #[test]
+    fn test_load_uri_rejects_unknown_schemes() {
+        let mut loader = FileLoader::new().unwrap();
+        let err = loader.load_uri("ftp://example.com/users.csv").unwrap_err();
+        assert!(matches!(err, DataFusionError::UnsupportedFormat(_)));
     }
 
     #[test]
@@ -231,8 +855,7 @@ mod tests {
             let mut loader = FileLoader::new().unwrap();
             let result = loader.load_directory(&samples);
 
-            if result.is_ok() {
-                let tables = result.unwrap();
+            if let Ok(tables) = result {
                 assert!(!tables.is_empty());
             }
         }
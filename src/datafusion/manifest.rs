@@ -0,0 +1,183 @@
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{SecondsFormat, Utc};
+use sha2::{Digest, Sha256};
+
+use crate::storage::table::json_string;
+
+use super::error::Result;
+
+/// A record of how a result was produced: the queries that ran, the files
+/// they read and the knowhere version, so the result can be reproduced and
+/// checked against its inputs later.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryManifest {
+    pub knowhere_version: String,
+    /// When the manifest was created, as an RFC 3339 UTC timestamp.
+    pub created_at: String,
+    pub queries: Vec<ManifestQuery>,
+    pub sources: Vec<ManifestSource>,
+}
+
+/// A query recorded in a [`QueryManifest`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestQuery {
+    pub name: String,
+    pub sql: String,
+    /// Rows the query returned or wrote.
+    pub row_count: u64,
+}
+
+/// A loaded table's source recorded in a [`QueryManifest`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestSource {
+    pub table: String,
+    pub path: PathBuf,
+    /// Size of the file, or of every file under a directory source.
+    pub size_bytes: u64,
+    /// Hex SHA-256 of the file. For a directory it covers the relative path
+    /// and contents of every file under it, in path order.
+    pub sha256: String,
+}
+
+impl QueryManifest {
+    pub(super) fn new(queries: Vec<ManifestQuery>, sources: Vec<ManifestSource>) -> Self {
+        Self {
+            knowhere_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            queries,
+            sources,
+        }
+    }
+
+    /// The manifest as an indented JSON object.
+    pub fn to_json(&self) -> String {
+        let queries: Vec<String> = self
+            .queries
+            .iter()
+            .map(|query| {
+                format!(
+                    "    {{\n      \"name\": {},\n      \"sql\": {},\n      \"row_count\": {}\n    }}",
+                    json_string(&query.name),
+                    json_string(&query.sql),
+                    query.row_count
+                )
+            })
+            .collect();
+        let sources: Vec<String> = self
+            .sources
+            .iter()
+            .map(|source| {
+                format!(
+                    "    {{\n      \"table\": {},\n      \"path\": {},\n      \"size_bytes\": {},\n      \"sha256\": {}\n    }}",
+                    json_string(&source.table),
+                    json_string(&source.path.display().to_string()),
+                    source.size_bytes,
+                    json_string(&source.sha256)
+                )
+            })
+            .collect();
+
+        format!(
+            "{{\n  \"knowhere_version\": {},\n  \"created_at\": {},\n  \"queries\": {},\n  \"sources\": {}\n}}\n",
+            json_string(&self.knowhere_version),
+            json_string(&self.created_at),
+            json_array(&queries),
+            json_array(&sources)
+        )
+    }
+
+    /// Write the manifest as JSON to `path`.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_json())?;
+        Ok(())
+    }
+}
+
+fn json_array(items: &[String]) -> String {
+    if items.is_empty() {
+        "[]".to_string()
+    } else {
+        format!("[\n{}\n  ]", items.join(",\n"))
+    }
+}
+
+/// Size and hex SHA-256 of the file or directory at `path`.
+pub(super) fn fingerprint(path: &Path) -> Result<(u64, String)> {
+    let mut hasher = Sha256::new();
+    let size = if path.is_dir() {
+        let mut files = Vec::new();
+        collect_files(path, &mut files)?;
+        files.sort();
+        let mut size = 0;
+        for file in files {
+            let relative = file.strip_prefix(path).unwrap_or(&file);
+            hasher.update(relative.to_string_lossy().as_bytes());
+            hasher.update([0]);
+            size += io::copy(&mut File::open(&file)?, &mut hasher)?;
+        }
+        size
+    } else {
+        io::copy(&mut File::open(path)?, &mut hasher)?
+    };
+    Ok((size, format!("{:x}", hasher.finalize())))
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.csv");
+        fs::write(&file, "abc").unwrap();
+
+        let (size, sha256) = fingerprint(&file).unwrap();
+        assert_eq!(size, 3);
+        assert_eq!(
+            sha256,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+
+        fs::create_dir(dir.path().join("part")).unwrap();
+        fs::write(dir.path().join("part").join("b.csv"), "de").unwrap();
+        let (size, before) = fingerprint(dir.path()).unwrap();
+        assert_eq!(size, 5);
+        fs::write(dir.path().join("part").join("b.csv"), "df").unwrap();
+        assert_ne!(fingerprint(dir.path()).unwrap().1, before);
+    }
+
+    #[test]
+    fn test_manifest_json() {
+        let manifest = QueryManifest {
+            knowhere_version: "1.0.0".to_string(),
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            queries: vec![ManifestQuery {
+                name: "query_1".to_string(),
+                sql: "SELECT \"a\" FROM t".to_string(),
+                row_count: 2,
+            }],
+            sources: vec![],
+        };
+        let json = manifest.to_json();
+        assert!(json.contains("\"knowhere_version\": \"1.0.0\""));
+        assert!(json.contains("\"sql\": \"SELECT \\\"a\\\" FROM t\""));
+        assert!(json.contains("\"row_count\": 2"));
+        assert!(json.contains("\"sources\": []"));
+    }
+}
@@ -0,0 +1,367 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use super::csv_options::CsvOptions;
+use super::headers::{HeaderNormalization, RenamedColumn};
+#[cfg(feature = "sqlite")]
+use super::names::quote_identifier;
+use super::provenance::Provenance;
+use super::refine::TypeRefinement;
+
+/// Where a registered table was read from, so it can be re-read or described
+/// later.
+#[derive(Debug, Clone)]
+pub(super) struct TableSource {
+    pub(super) kind: SourceKind,
+    pub(super) path: PathBuf,
+    pub(super) provenance: Option<Provenance>,
+    /// Whether text columns were converted by
+    /// [`refine_types`](super::DataFusionContext::refine_types).
+    pub(super) refine_types: bool,
+    /// Columns converted by
+    /// [`coerce_lenient_numbers`](super::DataFusionContext::coerce_lenient_numbers),
+    /// or `None` if it was not called.
+    pub(super) lenient_numbers: Option<Vec<TypeRefinement>>,
+    /// How [`normalize_headers`](super::DataFusionContext::normalize_headers)
+    /// renamed the columns, if it was called.
+    pub(super) headers: Option<(HeaderNormalization, Vec<RenamedColumn>)>,
+    /// Predicate given to [`filter_table`](super::DataFusionContext::filter_table).
+    pub(super) filter: Option<String>,
+    /// Options a CSV file was registered with, if not the default.
+    pub(super) csv_options: Option<CsvOptions>,
+    /// Worksheet an Excel table was read from.
+    #[cfg(feature = "xlsx")]
+    pub(super) sheet: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum SourceKind {
+    Csv,
+    /// A CSV file registered with [`TailingCsvTable`](super::tail::TailingCsvTable).
+    CsvTail,
+    Parquet,
+    Json,
+    #[cfg(feature = "delta")]
+    Delta,
+    #[cfg(feature = "iceberg")]
+    Iceberg,
+    #[cfg(feature = "sqlite")]
+    Sqlite,
+    #[cfg(feature = "xlsx")]
+    Xlsx,
+}
+
+impl SourceKind {
+    pub(super) fn name(self) -> &'static str {
+        match self {
+            SourceKind::Csv | SourceKind::CsvTail => "csv",
+            SourceKind::Parquet => "parquet",
+            SourceKind::Json => "json",
+            #[cfg(feature = "delta")]
+            SourceKind::Delta => "delta",
+            #[cfg(feature = "iceberg")]
+            SourceKind::Iceberg => "iceberg",
+            #[cfg(feature = "sqlite")]
+            SourceKind::Sqlite => "sqlite",
+            #[cfg(feature = "xlsx")]
+            SourceKind::Xlsx => "xlsx",
+        }
+    }
+}
+
+/// Cheap summary of a registered table for sidebars and listings. Nothing
+/// here runs a query: sizes come from the filesystem and row counts from
+/// file metadata or a line count.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableDetails {
+    pub name: String,
+    pub column_count: usize,
+    /// Source format such as `"csv"` or `"delta"`, or `"view"` for a view
+    /// created with `CREATE VIEW`; `None` for other tables that were not
+    /// loaded from a file.
+    pub format: Option<&'static str>,
+    pub path: Option<PathBuf>,
+    /// Bytes on disk, summed over every file for directory-based tables.
+    pub size_bytes: Option<u64>,
+    pub row_count: Option<u64>,
+    /// Whether `row_count` is estimated (e.g. from CSV line counts, which
+    /// miscount quoted newlines) rather than read from metadata.
+    pub row_count_estimated: bool,
+    /// Columns the data files of a Delta or Iceberg table are partitioned
+    /// by. Iceberg transforms other than identity are shown as `day(ts)`.
+    pub partition_columns: Vec<String>,
+    /// Data files in the version of a Delta or Iceberg table being read.
+    pub file_count: Option<u64>,
+    /// Delta table version, or Iceberg snapshot id, being read.
+    pub version: Option<i64>,
+}
+
+impl TableDetails {
+    pub(super) fn new(name: &str, column_count: usize, source: Option<&TableSource>) -> Self {
+        let mut details = TableDetails {
+            name: name.to_string(),
+            column_count,
+            format: None,
+            path: None,
+            size_bytes: None,
+            row_count: None,
+            row_count_estimated: false,
+            partition_columns: Vec::new(),
+            file_count: None,
+            version: None,
+        };
+        let Some(source) = source else {
+            return details;
+        };
+
+        details.format = Some(source.kind.name());
+        details.path = Some(source.path.clone());
+        details.size_bytes = disk_size(&source.path).ok();
+        match source.kind {
+            SourceKind::Csv | SourceKind::CsvTail => {
+                details.row_count = count_lines(&source.path).ok().map(|n| n.saturating_sub(1));
+                details.row_count_estimated = true;
+            }
+            SourceKind::Json => {
+                details.row_count = count_lines(&source.path).ok();
+                details.row_count_estimated = true;
+            }
+            SourceKind::Parquet => details.row_count = parquet_row_count(&source.path),
+            #[cfg(feature = "sqlite")]
+            SourceKind::Sqlite => details.row_count = sqlite_row_count(&source.path, name),
+            #[cfg(any(feature = "delta", feature = "iceberg", feature = "xlsx"))]
+            _ => {}
+        }
+        details
+    }
+
+    /// Details of a table combined from the tables `parts` by
+    /// [`union_tables`](super::DataFusionContext::union_tables): the format
+    /// of the first part, and sizes and row counts summed over all of them.
+    pub(super) fn combined(
+        name: &str,
+        column_count: usize,
+        parts: &[(String, TableSource)],
+    ) -> Self {
+        let mut details = TableDetails::new(name, column_count, None);
+        details.file_count = Some(parts.len() as u64);
+        details.size_bytes = Some(0);
+        details.row_count = Some(0);
+        for (part, source) in parts {
+            let part = TableDetails::new(part, column_count, Some(source));
+            details.format = details.format.or(part.format);
+            details.size_bytes = details.size_bytes.zip(part.size_bytes).map(|(a, b)| a + b);
+            details.row_count = details.row_count.zip(part.row_count).map(|(a, b)| a + b);
+            details.row_count_estimated |= part.row_count_estimated;
+        }
+        details
+    }
+
+    /// Fill in the layout of a Delta table from its transaction log. Size
+    /// and row count then cover only the files of the current version, not
+    /// the log or files removed by earlier commits.
+    #[cfg(feature = "delta")]
+    pub(super) fn read_delta_layout(&mut self, table: &deltalake::DeltaTable) {
+        let Ok(snapshot) = table.snapshot() else {
+            return;
+        };
+        let mut files = 0;
+        let mut size = 0;
+        let mut rows = Some(0);
+        for file in snapshot.log_data() {
+            files += 1;
+            size += u64::try_from(file.size()).unwrap_or(0);
+            rows = rows
+                .zip(file.num_records())
+                .map(|(total, n)| total + n as u64);
+            // Deleted rows are still counted in the file's statistics
+            self.row_count_estimated |= file.deletion_vector().is_some();
+        }
+        self.version = Some(snapshot.version());
+        self.partition_columns = snapshot.metadata().partition_columns().clone();
+        self.file_count = Some(files);
+        self.size_bytes = Some(size);
+        self.row_count = rows;
+    }
+
+    /// Fill in the layout of an Iceberg table from the metadata file at
+    /// `metadata_path`, using the totals in its current snapshot's summary.
+    #[cfg(feature = "iceberg")]
+    pub(super) async fn read_iceberg_layout(&mut self, metadata_path: &str) {
+        use iceberg::io::FileIOBuilder;
+        use iceberg::spec::{TableMetadata, Transform};
+
+        let Ok(file_io) = FileIOBuilder::new_fs_io().build() else {
+            return;
+        };
+        let Ok(metadata) = TableMetadata::read_from(&file_io, metadata_path).await else {
+            return;
+        };
+        let schema = metadata.current_schema();
+        self.partition_columns = metadata
+            .default_partition_spec()
+            .fields()
+            .iter()
+            .map(|field| {
+                let source = schema
+                    .name_by_field_id(field.source_id)
+                    .unwrap_or(&field.name);
+                match field.transform {
+                    Transform::Identity => source.to_string(),
+                    transform => format!("{}({})", transform, source),
+                }
+            })
+            .collect();
+
+        let Some(snapshot) = metadata.current_snapshot() else {
+            // A table that has never been written to
+            self.file_count = Some(0);
+            self.size_bytes = Some(0);
+            self.row_count = Some(0);
+            return;
+        };
+        let summary = &snapshot.summary().additional_properties;
+        let total = |key: &str| summary.get(key).and_then(|n| n.parse::<u64>().ok());
+        self.version = Some(snapshot.snapshot_id());
+        self.file_count = total("total-data-files");
+        self.size_bytes = total("total-files-size").or(self.size_bytes);
+        self.row_count = total("total-records");
+    }
+
+    /// One line describing how a Delta or Iceberg table is laid out, e.g.
+    /// `delta version 3: 12 data files, partitioned by year, month`. `None`
+    /// for other tables.
+    pub fn layout_summary(&self) -> Option<String> {
+        let files = self.file_count?;
+        let mut summary = match (self.format, self.version) {
+            (Some("iceberg"), Some(snapshot)) => format!("iceberg snapshot {}", snapshot),
+            (Some("iceberg"), None) => "iceberg table without snapshots".to_string(),
+            (format, Some(version)) => {
+                format!("{} version {}", format.unwrap_or("table"), version)
+            }
+            (format, None) => format.unwrap_or("table").to_string(),
+        };
+        summary.push_str(&format!(
+            ": {} data file{}",
+            files,
+            if files == 1 { "" } else { "s" }
+        ));
+        if !self.partition_columns.is_empty() {
+            summary.push_str(&format!(
+                ", partitioned by {}",
+                self.partition_columns.join(", ")
+            ));
+        }
+        Some(summary)
+    }
+}
+
+fn disk_size(path: &Path) -> io::Result<u64> {
+    let metadata = fs::metadata(path)?;
+    if metadata.is_file() {
+        return Ok(metadata.len());
+    }
+    let mut total = 0;
+    for entry in fs::read_dir(path)? {
+        total += disk_size(&entry?.path())?;
+    }
+    Ok(total)
+}
+
+/// Number of non-empty lines, counting a final line without a newline.
+fn count_lines(path: &Path) -> io::Result<u64> {
+    let mut file = fs::File::open(path)?;
+    let mut buf = [0u8; 64 * 1024];
+    let mut lines = 0;
+    let mut line_has_content = false;
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        for &byte in &buf[..n] {
+            match byte {
+                b'\n' => {
+                    if line_has_content {
+                        lines += 1;
+                    }
+                    line_has_content = false;
+                }
+                b'\r' => {}
+                _ => line_has_content = true,
+            }
+        }
+    }
+    if line_has_content {
+        lines += 1;
+    }
+    Ok(lines)
+}
+
+fn parquet_row_count(path: &Path) -> Option<u64> {
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    let reader = SerializedFileReader::new(fs::File::open(path).ok()?).ok()?;
+    u64::try_from(reader.metadata().file_metadata().num_rows()).ok()
+}
+
+#[cfg(feature = "sqlite")]
+fn sqlite_row_count(path: &Path, table: &str) -> Option<u64> {
+    let conn =
+        rusqlite::Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .ok()?;
+    let sql = format!("SELECT COUNT(*) FROM {}", quote_identifier(table));
+    conn.query_row(&sql, [], |row| row.get::<_, i64>(0))
+        .ok()
+        .and_then(|n| u64::try_from(n).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.csv");
+
+        fs::write(&path, "a,b\n1,2\n3,4\n").unwrap();
+        assert_eq!(count_lines(&path).unwrap(), 3);
+
+        fs::write(&path, "a,b\r\n1,2\r\n\r\n3,4").unwrap();
+        assert_eq!(count_lines(&path).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_details_for_csv_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.csv");
+        fs::write(&path, "a,b\n1,2\n3,4\n").unwrap();
+
+        let source = TableSource {
+            kind: SourceKind::Csv,
+            path: path.clone(),
+            provenance: None,
+            refine_types: false,
+            lenient_numbers: None,
+            headers: None,
+            filter: None,
+            csv_options: None,
+            #[cfg(feature = "xlsx")]
+            sheet: None,
+        };
+        let details = TableDetails::new("data", 2, Some(&source));
+        assert_eq!(details.format, Some("csv"));
+        assert_eq!(details.size_bytes, Some(12));
+        assert_eq!(details.row_count, Some(2));
+        assert!(details.row_count_estimated);
+    }
+
+    #[test]
+    fn test_details_without_source() {
+        let details = TableDetails::new("adhoc", 3, None);
+        assert_eq!(details.format, None);
+        assert_eq!(details.row_count, None);
+    }
+}
@@ -1,9 +1,73 @@
+mod cache;
+mod coercion;
 mod context;
 mod conversion;
+mod csv_options;
+mod dialect;
 mod error;
+#[cfg(feature = "sqlite")]
+mod export;
+mod frequency;
+mod functions;
+mod generate;
+mod headers;
 mod loader;
+mod manifest;
+mod metadata;
+mod names;
+mod provenance;
+mod pruning;
+mod query;
+mod reader;
+mod refine;
+#[cfg(feature = "remote")]
+mod remote;
+mod report;
+mod rewrite;
+mod script;
+mod settings;
+mod spill;
+#[cfg(feature = "sqlite")]
 mod sqlite;
+mod stream;
+mod suggest;
+mod summarize;
+mod tail;
+mod temp;
+mod templates;
+#[cfg(feature = "xlsx")]
+mod xlsx;
 
 pub use context::DataFusionContext;
+pub use csv_options::{parse_column_type, CsvOptions, DEFAULT_INFER_ROWS};
 pub use error::{DataFusionError, Result};
-pub use loader::FileLoader;
+#[cfg(feature = "sqlite")]
+pub use export::write_sqlite;
+pub use frequency::{frequency_query, FREQUENCY_LIMIT};
+pub use generate::{
+    generate_dataset, parse_row_count, DatasetSpec, Distribution, GeneratedColumn, GeneratedType,
+};
+pub use headers::{HeaderNormalization, RenamedColumn};
+pub use loader::{is_glob_pattern, is_remote_uri, FileLoader, TypeRefinementMode};
+pub use manifest::{ManifestQuery, ManifestSource, QueryManifest};
+pub use metadata::TableDetails;
+pub use names::{quote_identifier, sanitize_table_name};
+pub use provenance::{ROW_ID_COLUMN, SOURCE_FILE_COLUMN};
+pub use pruning::PruningStats;
+pub use query::{QueryHandle, QueryOptions, QueryProgress};
+#[cfg(feature = "xlsx")]
+pub use reader::XlsxReader;
+pub use reader::{has_extension, CsvReader, CsvTailReader, ReaderRegistry, SourceReader};
+pub use refine::{RefinedType, TypeRefinement};
+pub use report::{ColumnRefinement, LoadReport, LoadedTable, RenamedHeader, SkippedFile};
+pub use rewrite::writes_to_file;
+pub use script::{
+    expand_env_vars, fill_template, named_scripts, named_statements, split_statements,
+    take_statements, template_params, NamedStatement, SessionRecorder,
+};
+pub use settings::SessionSettings;
+pub use spill::SpilledResult;
+pub use stream::{table_to_ipc, ExportFormat, ExportSummary, IPC_TRUNCATED_KEY};
+pub use tail::TailingCsvTable;
+#[cfg(feature = "xlsx")]
+pub use xlsx::Sheet;
@@ -0,0 +1,37 @@
+use super::headers::replace_special;
+
+/// The name a file or directory called `name` (without extension) is
+/// registered under: lowercased, with spaces and other special characters
+/// replaced as [`HeaderNormalization`] does for columns, so it can be
+/// written unquoted in SQL. `my data (2024)` becomes `my_data_2024`.
+///
+/// [`HeaderNormalization`]: super::HeaderNormalization
+pub fn sanitize_table_name(name: &str) -> String {
+    replace_special(&name.trim().to_lowercase(), || "table".to_string())
+}
+
+/// `name` as a double-quoted SQL identifier, with embedded quotes doubled,
+/// so any registered name can be referenced exactly as it is.
+pub fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_table_name() {
+        assert_eq!(sanitize_table_name("users"), "users");
+        assert_eq!(sanitize_table_name("my data (2024)"), "my_data_2024");
+        assert_eq!(sanitize_table_name("Sales-Report.v2"), "sales_report_v2");
+        assert_eq!(sanitize_table_name("2024 orders"), "_2024_orders");
+        assert_eq!(sanitize_table_name("(((  )))"), "table");
+    }
+
+    #[test]
+    fn test_quote_identifier() {
+        assert_eq!(quote_identifier("my data"), "\"my data\"");
+        assert_eq!(quote_identifier("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
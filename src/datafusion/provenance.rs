@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Int64Array, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+
+use super::error::Result;
+
+/// Pseudo-column numbering rows from 1 in the order they appear in the
+/// source.
+pub const ROW_ID_COLUMN: &str = "_row_id";
+/// Pseudo-column holding the path a row was read from.
+pub const SOURCE_FILE_COLUMN: &str = "_source_file";
+
+/// Which provenance pseudo-columns a table carries, remembered so a refresh
+/// can add them again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Provenance {
+    pub(super) source_file: bool,
+}
+
+/// Append `_row_id` and, when `source_file` is given, `_source_file` to
+/// every batch. Row ids continue across batches, so `batches` must be in
+/// source order.
+pub(super) fn add_provenance_columns(
+    batches: Vec<RecordBatch>,
+    source_file: Option<&str>,
+) -> Result<Vec<RecordBatch>> {
+    let Some(first) = batches.first() else {
+        return Ok(batches);
+    };
+
+    let mut fields: Vec<Field> = first
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| field.as_ref().clone())
+        .collect();
+    fields.push(Field::new(ROW_ID_COLUMN, DataType::Int64, false));
+    if source_file.is_some() {
+        fields.push(Field::new(SOURCE_FILE_COLUMN, DataType::Utf8, false));
+    }
+    let schema = Arc::new(Schema::new(fields));
+
+    let mut next_id = 1i64;
+    batches
+        .into_iter()
+        .map(|batch| {
+            let rows = batch.num_rows() as i64;
+            let mut columns = batch.columns().to_vec();
+            columns
+                .push(Arc::new(Int64Array::from_iter_values(next_id..next_id + rows)) as ArrayRef);
+            if let Some(path) = source_file {
+                columns.push(Arc::new(StringArray::from(vec![path; rows as usize])) as ArrayRef);
+            }
+            next_id += rows;
+            Ok(RecordBatch::try_new(schema.clone(), columns)?)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch(values: Vec<i64>) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![Field::new("n", DataType::Int64, false)]));
+        RecordBatch::try_new(schema, vec![Arc::new(Int64Array::from(values))]).unwrap()
+    }
+
+    #[test]
+    fn test_row_ids_continue_across_batches() {
+        let batches =
+            add_provenance_columns(vec![batch(vec![7, 8]), batch(vec![9])], Some("a.csv")).unwrap();
+
+        let ids = batches[1]
+            .column(1)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        assert_eq!(ids.value(0), 3);
+        assert_eq!(batches[0].schema().field(2).name(), SOURCE_FILE_COLUMN);
+    }
+
+    #[test]
+    fn test_without_source_file() {
+        let batches = add_provenance_columns(vec![batch(vec![1])], None).unwrap();
+        assert_eq!(batches[0].num_columns(), 2);
+        assert!(add_provenance_columns(Vec::new(), None).unwrap().is_empty());
+    }
+}
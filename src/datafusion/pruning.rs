@@ -0,0 +1,101 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use datafusion::datasource::physical_plan::FileScanConfig;
+use datafusion::datasource::source::DataSourceExec;
+use datafusion::physical_plan::ExecutionPlan;
+
+/// How much of the data files a query read, and how much Parquet metadata
+/// let it skip, totalled over every scan in an executed plan.
+///
+/// Files left out before planning, by partition values or Delta log
+/// statistics, are not counted as pruned; they only show up as fewer
+/// `files_scanned`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruningStats {
+    /// Distinct files opened by the scans.
+    pub files_scanned: usize,
+    /// Row groups whose min/max statistics could match the filters.
+    pub row_groups_matched: usize,
+    /// Row groups skipped because their min/max statistics ruled them out.
+    pub row_groups_pruned_statistics: usize,
+    /// Row groups skipped by bloom filters.
+    pub row_groups_pruned_bloom_filter: usize,
+    /// Rows skipped using the page index.
+    pub page_index_rows_pruned: usize,
+    /// Bytes of Parquet data read. Scans stream a batch at a time and stop
+    /// once a `LIMIT` is satisfied, so this can be far less than the size of
+    /// the files.
+    pub bytes_scanned: usize,
+}
+
+impl PruningStats {
+    /// Totals for `plan`, which must have been executed for the row group
+    /// counts to be filled in.
+    pub fn from_plan(plan: &dyn ExecutionPlan) -> Self {
+        let mut files = HashSet::new();
+        let mut stats = Self::default();
+        stats.add(plan, &mut files);
+        stats.files_scanned = files.len();
+        stats
+    }
+
+    /// Whether the plan read any files at all.
+    pub fn is_empty(&self) -> bool {
+        self.files_scanned == 0
+    }
+
+    fn add(&mut self, plan: &dyn ExecutionPlan, files: &mut HashSet<String>) {
+        if let Some(config) = plan
+            .as_any()
+            .downcast_ref::<DataSourceExec>()
+            .and_then(|exec| exec.data_source().as_any().downcast_ref::<FileScanConfig>())
+        {
+            for group in &config.file_groups {
+                for file in group.files() {
+                    files.insert(file.object_meta.location.to_string());
+                }
+            }
+        }
+        if let Some(metrics) = plan.metrics() {
+            let count = |name: &str| {
+                metrics
+                    .sum_by_name(name)
+                    .map_or(0, |value| value.as_usize())
+            };
+            self.row_groups_matched += count("row_groups_matched_statistics");
+            self.row_groups_pruned_statistics += count("row_groups_pruned_statistics");
+            self.row_groups_pruned_bloom_filter += count("row_groups_pruned_bloom_filter");
+            self.page_index_rows_pruned += count("page_index_rows_pruned");
+            self.bytes_scanned += count("bytes_scanned");
+        }
+        for child in plan.children() {
+            self.add(child.as_ref(), files);
+        }
+    }
+}
+
+impl fmt::Display for PruningStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pruned = self.row_groups_pruned_statistics + self.row_groups_pruned_bloom_filter;
+        write!(
+            f,
+            "{} file{} scanned ({} bytes read); {} of {} row groups pruned ({} by statistics, {} by bloom filters)",
+            self.files_scanned,
+            if self.files_scanned == 1 { "" } else { "s" },
+            self.bytes_scanned,
+            pruned,
+            self.row_groups_matched + self.row_groups_pruned_statistics,
+            self.row_groups_pruned_statistics,
+            self.row_groups_pruned_bloom_filter
+        )?;
+        if self.page_index_rows_pruned > 0 {
+            write!(
+                f,
+                "; {} rows skipped by the page index",
+                self.page_index_rows_pruned
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,252 @@
+use datafusion::execution::runtime_env::RuntimeEnvBuilder;
+use datafusion::execution::TaskContext;
+use datafusion::physical_plan::ExecutionPlan;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::runtime::Runtime;
+use tokio::task::JoinHandle;
+
+use crate::storage::table::Table;
+
+use super::error::{DataFusionError, Result};
+use super::pruning::PruningStats;
+use super::settings::SessionSettings;
+
+/// A query running in the background on a [`DataFusionContext`] runtime.
+///
+/// Several handles can be in flight at once. Poll one with
+/// [`try_result`](Self::try_result), block on it with [`wait`](Self::wait)
+/// or `.await` it from async code on any runtime.
+///
+/// [`DataFusionContext`]: super::DataFusionContext
+pub struct QueryHandle {
+    task: JoinHandle<Result<Table>>,
+    runtime: Arc<Runtime>,
+    started: Instant,
+    tracker: Arc<ProgressTracker>,
+}
+
+impl QueryHandle {
+    pub(super) fn new(
+        task: JoinHandle<Result<Table>>,
+        runtime: Arc<Runtime>,
+        tracker: Arc<ProgressTracker>,
+    ) -> Self {
+        Self {
+            task,
+            runtime,
+            started: Instant::now(),
+            tracker,
+        }
+    }
+
+    /// How far the query has got so far. Cheap enough to call on every
+    /// redraw.
+    pub fn progress(&self) -> QueryProgress {
+        self.tracker.snapshot()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Abort the query. A subsequent [`wait`](Self::wait) returns
+    /// [`DataFusionError::QueryCancelled`].
+    pub fn cancel(&self) {
+        self.task.abort();
+    }
+
+    /// Take the result if the query has finished, without blocking.
+    pub fn try_result(&mut self) -> Option<Result<Table>> {
+        if !self.task.is_finished() {
+            return None;
+        }
+        Some(
+            self.runtime
+                .block_on(&mut self.task)
+                .unwrap_or_else(join_error),
+        )
+    }
+
+    /// Block until the query finishes and return its result.
+    pub fn wait(self) -> Result<Table> {
+        self.runtime.block_on(self.task).unwrap_or_else(join_error)
+    }
+}
+
+impl Future for QueryHandle {
+    type Output = Result<Table>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.task)
+            .poll(cx)
+            .map(|result| result.unwrap_or_else(join_error))
+    }
+}
+
+/// Limits for running one query. A limit left as `None` falls back to the
+/// session's setting of the same name (see [`SessionSettings`]), so a
+/// frontend can tighten or lift a limit for one query only.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryOptions {
+    /// Cancel the query after this long; it then fails with
+    /// [`DataFusionError::QueryTimedOut`].
+    pub timeout: Option<Duration>,
+    /// Bytes of memory that the query's sorts, joins and aggregations may
+    /// use. Operators that can spill to disk do so at the limit and the
+    /// others fail the query.
+    pub memory_limit: Option<usize>,
+    /// Stop reading the result after this many rows.
+    pub max_rows: Option<usize>,
+}
+
+impl QueryOptions {
+    /// These options with every unset limit taken from `settings`.
+    pub(super) fn or_settings(self, settings: &SessionSettings) -> Self {
+        Self {
+            timeout: self
+                .timeout
+                .or(settings.timeout_secs.map(Duration::from_secs)),
+            memory_limit: self.memory_limit.or(settings
+                .max_memory_mb
+                .map(|mb| mb.saturating_mul(1024 * 1024))),
+            max_rows: self.max_rows.or(settings.max_rows),
+        }
+    }
+
+    /// `task_ctx` with its memory pool capped at `memory_limit`.
+    pub(super) fn limit_memory(&self, task_ctx: TaskContext) -> Result<TaskContext> {
+        let Some(limit) = self.memory_limit else {
+            return Ok(task_ctx);
+        };
+        let runtime = RuntimeEnvBuilder::from_runtime_env(&task_ctx.runtime_env())
+            .with_memory_limit(limit, 1.0)
+            .build_arc()?;
+        Ok(task_ctx.with_runtime(runtime))
+    }
+
+    /// Run `query`, giving up once `timeout` passes.
+    pub(super) async fn run_with_timeout<T>(
+        &self,
+        query: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        match self.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, query)
+                .await
+                .map_err(|_| DataFusionError::QueryTimedOut(timeout))?,
+            None => query.await,
+        }
+    }
+}
+
+/// A snapshot of a running query's progress, read from DataFusion's
+/// execution metrics.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryProgress {
+    /// Rows read by the scans at the leaves of the plan.
+    pub rows_scanned: u64,
+    /// Rows of the final result collected so far.
+    pub rows_produced: u64,
+    /// The operator that data has most recently flowed up to, such as
+    /// `AggregateExec` while input is still being aggregated. `None` until
+    /// planning has finished.
+    pub operator: Option<String>,
+}
+
+/// Shared between a running query and its handle.
+#[derive(Default)]
+pub(super) struct ProgressTracker {
+    plan: Mutex<Option<Arc<dyn ExecutionPlan>>>,
+    rows_produced: AtomicU64,
+}
+
+impl ProgressTracker {
+    pub(super) fn set_plan(&self, plan: Arc<dyn ExecutionPlan>) {
+        if let Ok(mut slot) = self.plan.lock() {
+            *slot = Some(plan);
+        }
+    }
+
+    pub(super) fn add_rows(&self, rows: usize) {
+        self.rows_produced.fetch_add(rows as u64, Ordering::Relaxed);
+    }
+
+    /// Pruning totals of the plan once it has run.
+    pub(super) fn pruning(&self) -> PruningStats {
+        match self.plan.lock() {
+            Ok(slot) => slot
+                .as_ref()
+                .map(|plan| PruningStats::from_plan(plan.as_ref()))
+                .unwrap_or_default(),
+            Err(_) => PruningStats::default(),
+        }
+    }
+
+    fn snapshot(&self) -> QueryProgress {
+        let mut progress = QueryProgress {
+            rows_produced: self.rows_produced.load(Ordering::Relaxed),
+            ..QueryProgress::default()
+        };
+        let plan = match self.plan.lock() {
+            Ok(slot) => slot.clone(),
+            Err(_) => None,
+        };
+        if let Some(plan) = plan {
+            progress.rows_scanned = scanned_rows(plan.as_ref());
+            progress.operator = Some(active_operator(&plan).name().to_string());
+        }
+        progress
+    }
+}
+
+fn output_rows(plan: &dyn ExecutionPlan) -> usize {
+    plan.metrics()
+        .and_then(|metrics| metrics.output_rows())
+        .unwrap_or(0)
+}
+
+fn scanned_rows(plan: &dyn ExecutionPlan) -> u64 {
+    let children = plan.children();
+    if children.is_empty() {
+        return output_rows(plan) as u64;
+    }
+    children
+        .iter()
+        .map(|child| scanned_rows(child.as_ref()))
+        .sum()
+}
+
+/// The shallowest operator that has emitted rows, or the first leaf if
+/// nothing has been emitted yet.
+fn active_operator(plan: &Arc<dyn ExecutionPlan>) -> &Arc<dyn ExecutionPlan> {
+    let mut level = vec![plan];
+    loop {
+        if let Some(active) = level.iter().find(|node| output_rows(node.as_ref()) > 0) {
+            return active;
+        }
+        let next: Vec<_> = level.iter().flat_map(|node| node.children()).collect();
+        if next.is_empty() {
+            return level[0];
+        }
+        level = next;
+    }
+}
+
+fn join_error(err: tokio::task::JoinError) -> Result<Table> {
+    if err.is_cancelled() {
+        Err(DataFusionError::QueryCancelled)
+    } else {
+        Err(DataFusionError::Conversion(format!(
+            "Query task failed: {}",
+            err
+        )))
+    }
+}
@@ -0,0 +1,435 @@
+use std::path::Path;
+
+use super::context::DataFusionContext;
+use super::csv_options::CsvOptions;
+use super::error::Result;
+#[cfg(feature = "xlsx")]
+use super::xlsx::Sheet;
+
+/// A data source format that [`FileLoader`] can register as tables.
+///
+/// Readers are consulted in registration order (most recent first), so a
+/// downstream crate can add a format, or override a built-in one, with
+/// [`FileLoader::register_reader`] without touching the loader itself.
+///
+/// [`FileLoader`]: super::FileLoader
+/// [`FileLoader::register_reader`]: super::FileLoader::register_reader
+pub trait SourceReader: Send + Sync {
+    /// Short name used in messages, e.g. `"csv"`.
+    fn name(&self) -> &str;
+
+    /// Whether this reader handles `path`. Called for both files and
+    /// directories; implementations may look at the extension, the layout of
+    /// a directory or the first bytes of a file.
+    fn detect(&self, path: &Path) -> bool;
+
+    /// Register `path` into `context` and return the names of the tables it
+    /// created. `table_name` is derived from the file stem or directory name;
+    /// readers that produce several tables may ignore it.
+    fn load(
+        &self,
+        context: &mut DataFusionContext,
+        path: &Path,
+        table_name: &str,
+    ) -> Result<Vec<String>>;
+}
+
+/// Ordered collection of [`SourceReader`]s.
+pub struct ReaderRegistry {
+    readers: Vec<Box<dyn SourceReader>>,
+}
+
+impl ReaderRegistry {
+    /// An empty registry with no formats.
+    pub fn empty() -> Self {
+        Self {
+            readers: Vec::new(),
+        }
+    }
+
+    /// Register a reader. It takes priority over every reader registered
+    /// before it.
+    pub fn register(&mut self, reader: Box<dyn SourceReader>) {
+        self.readers.push(reader);
+    }
+
+    /// Find the reader for `path`, if any.
+    pub fn find(&self, path: &Path) -> Option<&dyn SourceReader> {
+        self.readers
+            .iter()
+            .rev()
+            .find(|reader| reader.detect(path))
+            .map(|reader| reader.as_ref())
+    }
+
+    /// Names of the registered readers, highest priority first.
+    pub fn names(&self) -> Vec<&str> {
+        self.readers.iter().rev().map(|r| r.name()).collect()
+    }
+}
+
+impl Default for ReaderRegistry {
+    /// A registry with every built-in format.
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register(Box::new(CsvReader::default()));
+        registry.register(Box::new(JsonReader));
+        registry.register(Box::new(ParquetReader));
+        #[cfg(feature = "sqlite")]
+        registry.register(Box::new(SqliteReader));
+        #[cfg(feature = "xlsx")]
+        registry.register(Box::new(XlsxReader::default()));
+        // Delta is checked before Iceberg
+        #[cfg(feature = "iceberg")]
+        registry.register(Box::new(IcebergReader));
+        #[cfg(feature = "delta")]
+        registry.register(Box::new(DeltaReader));
+        registry
+    }
+}
+
+/// Case-insensitive extension check for file readers. Directories never
+/// match, even if their name looks like a file.
+pub fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    !path.is_dir()
+        && path
+            .extension()
+            .and_then(|s| s.to_str())
+            .map(|ext| extensions.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+            .unwrap_or(false)
+}
+
+/// Loads CSV files with DataFusion's CSV reader.
+#[derive(Default)]
+pub struct CsvReader {
+    lenient_numbers: bool,
+    options: CsvOptions,
+}
+
+impl CsvReader {
+    /// A reader that also converts text columns holding amounts such as
+    /// `$1,234.56`, `(500)` or `12%` to numbers, with
+    /// [`DataFusionContext::coerce_lenient_numbers`]. Register it with
+    /// [`FileLoader::register_reader`] to replace the default CSV reader.
+    ///
+    /// [`FileLoader::register_reader`]: super::FileLoader::register_reader
+    pub fn lenient() -> Self {
+        Self {
+            lenient_numbers: true,
+            ..Self::default()
+        }
+    }
+
+    /// Read files with `options` instead of the defaults, e.g. a `;`
+    /// delimiter with decimal commas.
+    pub fn with_options(mut self, options: CsvOptions) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+impl SourceReader for CsvReader {
+    fn name(&self) -> &str {
+        "csv"
+    }
+
+    fn detect(&self, path: &Path) -> bool {
+        has_extension(path, &["csv"])
+    }
+
+    fn load(
+        &self,
+        context: &mut DataFusionContext,
+        path: &Path,
+        table_name: &str,
+    ) -> Result<Vec<String>> {
+        context.register_csv_with_options(table_name, path, &self.options)?;
+        if self.lenient_numbers {
+            context.coerce_lenient_numbers(table_name)?;
+        }
+        Ok(vec![table_name.to_string()])
+    }
+}
+
+/// Loads CSV files as [`TailingCsvTable`]s that pick up appended rows on
+/// every query. Not registered by default; add it with
+/// [`FileLoader::register_reader`] to tail CSV files instead of reading them
+/// with DataFusion's CSV reader.
+///
+/// [`TailingCsvTable`]: super::TailingCsvTable
+/// [`FileLoader::register_reader`]: super::FileLoader::register_reader
+pub struct CsvTailReader;
+
+impl SourceReader for CsvTailReader {
+    fn name(&self) -> &str {
+        "csv-tail"
+    }
+
+    fn detect(&self, path: &Path) -> bool {
+        has_extension(path, &["csv"])
+    }
+
+    fn load(
+        &self,
+        context: &mut DataFusionContext,
+        path: &Path,
+        table_name: &str,
+    ) -> Result<Vec<String>> {
+        context.register_csv_tail(table_name, path)?;
+        Ok(vec![table_name.to_string()])
+    }
+}
+
+pub struct JsonReader;
+
+impl SourceReader for JsonReader {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn detect(&self, path: &Path) -> bool {
+        has_extension(path, &["json", "ndjson", "jsonl"])
+    }
+
+    fn load(
+        &self,
+        context: &mut DataFusionContext,
+        path: &Path,
+        table_name: &str,
+    ) -> Result<Vec<String>> {
+        context.register_json(table_name, path)?;
+        Ok(vec![table_name.to_string()])
+    }
+}
+
+pub struct ParquetReader;
+
+impl SourceReader for ParquetReader {
+    fn name(&self) -> &str {
+        "parquet"
+    }
+
+    fn detect(&self, path: &Path) -> bool {
+        has_extension(path, &["parquet", "pq"])
+    }
+
+    fn load(
+        &self,
+        context: &mut DataFusionContext,
+        path: &Path,
+        table_name: &str,
+    ) -> Result<Vec<String>> {
+        context.register_parquet(table_name, path)?;
+        Ok(vec![table_name.to_string()])
+    }
+}
+
+#[cfg(feature = "sqlite")]
+pub struct SqliteReader;
+
+#[cfg(feature = "sqlite")]
+impl SourceReader for SqliteReader {
+    fn name(&self) -> &str {
+        "sqlite"
+    }
+
+    fn detect(&self, path: &Path) -> bool {
+        has_extension(path, &["db", "sqlite", "sqlite3"])
+    }
+
+    fn load(
+        &self,
+        context: &mut DataFusionContext,
+        path: &Path,
+        _table_name: &str,
+    ) -> Result<Vec<String>> {
+        check_sqlite_header(path)?;
+        context.register_sqlite(path)
+    }
+}
+
+/// Loads one worksheet of an Excel workbook as a table, the first sheet
+/// unless another is picked with [`XlsxReader::with_sheet`].
+#[cfg(feature = "xlsx")]
+#[derive(Default)]
+pub struct XlsxReader {
+    sheet: Sheet,
+}
+
+#[cfg(feature = "xlsx")]
+impl XlsxReader {
+    /// A reader that loads `sheet` of every workbook. Register it with
+    /// [`FileLoader::register_reader`] to replace the default Excel reader.
+    ///
+    /// [`FileLoader::register_reader`]: super::FileLoader::register_reader
+    pub fn with_sheet(sheet: Sheet) -> Self {
+        Self { sheet }
+    }
+}
+
+#[cfg(feature = "xlsx")]
+impl SourceReader for XlsxReader {
+    fn name(&self) -> &str {
+        "xlsx"
+    }
+
+    fn detect(&self, path: &Path) -> bool {
+        has_extension(path, &["xlsx", "xlsm"])
+    }
+
+    fn load(
+        &self,
+        context: &mut DataFusionContext,
+        path: &Path,
+        table_name: &str,
+    ) -> Result<Vec<String>> {
+        context.register_xlsx(table_name, path, &self.sheet)?;
+        Ok(vec![table_name.to_string()])
+    }
+}
+
+#[cfg(feature = "delta")]
+pub struct DeltaReader;
+
+#[cfg(feature = "delta")]
+impl SourceReader for DeltaReader {
+    fn name(&self) -> &str {
+        "delta"
+    }
+
+    fn detect(&self, path: &Path) -> bool {
+        // Remote directories cannot be inspected without a request, so a
+        // remote URI without a file extension is taken to be a Delta table
+        path.join("_delta_log").is_dir()
+            || (super::loader::is_remote_path(path) && path.extension().is_none())
+    }
+
+    fn load(
+        &self,
+        context: &mut DataFusionContext,
+        path: &Path,
+        table_name: &str,
+    ) -> Result<Vec<String>> {
+        context.register_delta(table_name, path)?;
+        Ok(vec![table_name.to_string()])
+    }
+}
+
+#[cfg(feature = "iceberg")]
+pub struct IcebergReader;
+
+#[cfg(feature = "iceberg")]
+impl SourceReader for IcebergReader {
+    fn name(&self) -> &str {
+        "iceberg"
+    }
+
+    fn detect(&self, path: &Path) -> bool {
+        path.join("metadata").is_dir()
+    }
+
+    fn load(
+        &self,
+        context: &mut DataFusionContext,
+        path: &Path,
+        table_name: &str,
+    ) -> Result<Vec<String>> {
+        context.register_iceberg(table_name, path)?;
+        Ok(vec![table_name.to_string()])
+    }
+}
+
+#[cfg(feature = "sqlite")]
+const SQLITE_MAGIC: &[u8; 16] = b"SQLite format 3\0";
+#[cfg(feature = "sqlite")]
+const DUCKDB_MAGIC: &[u8; 4] = b"DUCK";
+
+/// Probe the header of a `.db`-style file so that non-SQLite databases get a
+/// clear error instead of an opaque rusqlite failure. Empty files are accepted
+/// because SQLite treats them as a valid, empty database.
+#[cfg(feature = "sqlite")]
+pub(super) fn check_sqlite_header(path: &Path) -> Result<()> {
+    use super::error::DataFusionError;
+    use std::io::Read;
+
+    let mut header = Vec::with_capacity(SQLITE_MAGIC.len());
+    std::fs::File::open(path)?
+        .take(SQLITE_MAGIC.len() as u64)
+        .read_to_end(&mut header)?;
+
+    if header.is_empty() || header.as_slice() == SQLITE_MAGIC {
+        return Ok(());
+    }
+
+    // DuckDB stores its magic bytes after an 8-byte checksum
+    if header.get(8..12) == Some(DUCKDB_MAGIC.as_slice()) {
+        return Err(DataFusionError::UnsupportedFormat(format!(
+            "{} is a DuckDB database; only SQLite databases are supported",
+            path.display()
+        )));
+    }
+
+    Err(DataFusionError::UnsupportedFormat(format!(
+        "{} is not a SQLite database (unrecognised file header)",
+        path.display()
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn detect(path: &str) -> Option<String> {
+        ReaderRegistry::default()
+            .find(&PathBuf::from(path))
+            .map(|reader| reader.name().to_string())
+    }
+
+    #[test]
+    fn test_detect_csv() {
+        assert_eq!(detect("test.csv"), Some("csv".to_string()));
+        assert_eq!(detect("TEST.CSV"), Some("csv".to_string()));
+    }
+
+    #[test]
+    fn test_detect_parquet() {
+        assert_eq!(detect("test.parquet"), Some("parquet".to_string()));
+        assert_eq!(detect("test.pq"), Some("parquet".to_string()));
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_detect_sqlite() {
+        assert_eq!(detect("test.db"), Some("sqlite".to_string()));
+        assert_eq!(detect("test.sqlite"), Some("sqlite".to_string()));
+    }
+
+    #[test]
+    fn test_detect_unknown() {
+        assert_eq!(detect("test.xyz"), None);
+        assert_eq!(detect("test"), None);
+    }
+
+    #[cfg(feature = "delta")]
+    #[test]
+    fn test_detect_table_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = ReaderRegistry::default();
+        assert!(registry.find(dir.path()).is_none());
+
+        std::fs::create_dir(dir.path().join("_delta_log")).unwrap();
+        assert_eq!(registry.find(dir.path()).unwrap().name(), "delta");
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_header_rejects_unknown_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.db");
+        std::fs::write(&path, "just some text, not a database").unwrap();
+
+        let err = check_sqlite_header(&path).unwrap_err();
+        assert!(err.to_string().contains("not a SQLite database"));
+    }
+}
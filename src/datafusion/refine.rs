@@ -0,0 +1,372 @@
+use std::fmt;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, BooleanArray, Date32Array, Float64Array, Int64Array};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use chrono::NaiveDate;
+
+use super::error::Result;
+
+/// Spellings read as `true` and `false` by boolean refinement, compared
+/// case-insensitively.
+const TRUE_VALUES: &[&str] = &["y", "yes", "true", "t"];
+const FALSE_VALUES: &[&str] = &["n", "no", "false", "f"];
+
+/// Date formats recognised by date refinement.
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d"];
+
+/// Currency symbols stripped by lenient number parsing.
+const CURRENCY_SYMBOLS: &[char] = &['$', '€', '£', '¥', '₹'];
+
+/// A text column whose values all read as a more specific type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeRefinement {
+    pub column: String,
+    pub refined: RefinedType,
+}
+
+/// The type a text column can be refined to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefinedType {
+    /// `2024-01-15` or `2024/01/15`.
+    Date,
+    /// `Y`/`N`, `yes`/`no`, `true`/`false` or `t`/`f`.
+    Boolean,
+    /// Whole numbers with thousands separators, e.g. `1,234`.
+    Integer,
+    /// Decimals with thousands separators, e.g. `1,234.50`.
+    Float,
+    /// Amounts with currency symbols, percentages or accounting-style
+    /// negatives, e.g. `$1,234.56`, `12%` (0.12) or `(500)` (-500).
+    Lenient,
+}
+
+impl fmt::Display for RefinedType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RefinedType::Date => "Date",
+            RefinedType::Boolean => "Boolean",
+            RefinedType::Integer => "Integer",
+            RefinedType::Float => "Float",
+            RefinedType::Lenient => "Float (currency/percent)",
+        })
+    }
+}
+
+impl fmt::Display for TypeRefinement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: text -> {}", self.column, self.refined)
+    }
+}
+
+/// Find the string columns of `batches` whose non-null values all read as
+/// one of the [`RefinedType`]s. Columns without any non-null value are left
+/// alone.
+pub(super) fn detect_refinements(
+    schema: &Schema,
+    batches: &[RecordBatch],
+) -> Result<Vec<TypeRefinement>> {
+    detect_with(schema, batches, |values| {
+        classify(values.iter().map(String::as_str))
+    })
+}
+
+/// Find the string columns of `batches` holding amounts written with
+/// currency symbols, percent signs or parentheses for negatives, which
+/// [`RefinedType::Lenient`] converts to floats. Every non-null value must
+/// be a number and at least one must use such notation.
+pub(super) fn detect_lenient_numbers(
+    schema: &Schema,
+    batches: &[RecordBatch],
+) -> Result<Vec<TypeRefinement>> {
+    detect_with(schema, batches, |values| {
+        let lenient = !values.is_empty()
+            && values.iter().all(|v| parse_lenient_number(v).is_some())
+            && values
+                .iter()
+                .any(|v| v.contains(['%', '(', ',']) || v.contains(CURRENCY_SYMBOLS));
+        lenient.then_some(RefinedType::Lenient)
+    })
+}
+
+/// Run `classify` over the trimmed non-null values of each string column.
+fn detect_with(
+    schema: &Schema,
+    batches: &[RecordBatch],
+    classify: impl Fn(&[String]) -> Option<RefinedType>,
+) -> Result<Vec<TypeRefinement>> {
+    let mut refinements = Vec::new();
+    for (i, field) in schema.fields().iter().enumerate() {
+        if !matches!(
+            field.data_type(),
+            DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View
+        ) {
+            continue;
+        }
+        let mut values = Vec::new();
+        for batch in batches {
+            let strings = cast(batch.column(i), &DataType::Utf8)?;
+            let strings = arrow::array::as_string_array(&strings);
+            values.extend(strings.iter().flatten().map(|v| v.trim().to_string()));
+        }
+        if let Some(refined) = classify(&values) {
+            refinements.push(TypeRefinement {
+                column: field.name().clone(),
+                refined,
+            });
+        }
+    }
+    Ok(refinements)
+}
+
+fn classify<'a>(values: impl Iterator<Item = &'a str> + Clone) -> Option<RefinedType> {
+    let mut values = values.peekable();
+    values.peek()?;
+
+    if values.clone().all(|v| parse_bool(v).is_some()) {
+        return Some(RefinedType::Boolean);
+    }
+    if values.clone().all(|v| parse_date(v).is_some()) {
+        return Some(RefinedType::Date);
+    }
+    if values.clone().all(|v| parse_grouped_number(v).is_some())
+        && values.clone().any(|v| v.contains(','))
+    {
+        return Some(if values.clone().any(|v| v.contains('.')) {
+            RefinedType::Float
+        } else {
+            RefinedType::Integer
+        });
+    }
+    None
+}
+
+/// Convert the columns named in `refinements`, keeping the other columns
+/// as they are.
+pub(super) fn apply_refinements(
+    batches: Vec<RecordBatch>,
+    refinements: &[TypeRefinement],
+) -> Result<Vec<RecordBatch>> {
+    let Some(first) = batches.first() else {
+        return Ok(batches);
+    };
+    let schema = first.schema();
+    let refined_at = |i: usize| {
+        refinements
+            .iter()
+            .find(|r| r.column == *schema.field(i).name())
+            .map(|r| r.refined)
+    };
+
+    let fields: Vec<Field> = schema
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(i, field)| match refined_at(i) {
+            Some(refined) => Field::new(field.name(), arrow_type(refined), true),
+            None => field.as_ref().clone(),
+        })
+        .collect();
+    let refined_schema = Arc::new(Schema::new(fields));
+
+    batches
+        .iter()
+        .map(|batch| {
+            let columns = (0..batch.num_columns())
+                .map(|i| match refined_at(i) {
+                    Some(refined) => convert(batch.column(i), refined),
+                    None => Ok(batch.column(i).clone()),
+                })
+                .collect::<Result<Vec<ArrayRef>>>()?;
+            Ok(RecordBatch::try_new(refined_schema.clone(), columns)?)
+        })
+        .collect()
+}
+
+fn arrow_type(refined: RefinedType) -> DataType {
+    match refined {
+        RefinedType::Date => DataType::Date32,
+        RefinedType::Boolean => DataType::Boolean,
+        RefinedType::Integer => DataType::Int64,
+        RefinedType::Float | RefinedType::Lenient => DataType::Float64,
+    }
+}
+
+fn convert(column: &ArrayRef, refined: RefinedType) -> Result<ArrayRef> {
+    let strings = cast(column, &DataType::Utf8)?;
+    let values = arrow::array::as_string_array(&strings)
+        .iter()
+        .map(|v| v.map(str::trim));
+
+    Ok(match refined {
+        RefinedType::Date => Arc::new(Date32Array::from_iter(values.map(|v| {
+            v.and_then(parse_date)
+                .map(|date| (date - NaiveDate::default()).num_days() as i32)
+        }))),
+        RefinedType::Boolean => Arc::new(BooleanArray::from_iter(
+            values.map(|v| v.and_then(parse_bool)),
+        )),
+        RefinedType::Integer => Arc::new(Int64Array::from_iter(values.map(|v| {
+            v.and_then(parse_grouped_number)
+                .and_then(|n| n.parse::<i64>().ok())
+        }))),
+        RefinedType::Float => Arc::new(Float64Array::from_iter(values.map(|v| {
+            v.and_then(parse_grouped_number)
+                .and_then(|n| n.parse::<f64>().ok())
+        }))),
+        RefinedType::Lenient => Arc::new(Float64Array::from_iter(
+            values.map(|v| v.and_then(parse_lenient_number)),
+        )),
+    })
+}
+
+pub(super) fn parse_bool(value: &str) -> Option<bool> {
+    let value = value.to_lowercase();
+    if TRUE_VALUES.contains(&value.as_str()) {
+        Some(true)
+    } else if FALSE_VALUES.contains(&value.as_str()) {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+}
+
+/// `value` without its thousands separators, if it is a number whose
+/// separators (if any) split the integer part into groups of three.
+pub(super) fn parse_grouped_number(value: &str) -> Option<String> {
+    let unsigned = value.strip_prefix('-').unwrap_or(value);
+    let (integer, fraction) = match unsigned.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (unsigned, None),
+    };
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+
+    let mut groups = integer.split(',');
+    let first = groups.next()?;
+    if !digits(first) || (integer.contains(',') && first.len() > 3) {
+        return None;
+    }
+    if !groups.all(|group| group.len() == 3 && digits(group)) {
+        return None;
+    }
+    if fraction.is_some_and(|f| !digits(f)) {
+        return None;
+    }
+    Some(value.replace(',', ""))
+}
+
+/// Parse an amount such as `$1,234.56`, `-€5`, `12.5%` or `(500)`.
+/// Currency symbols and thousands separators are dropped, a trailing `%`
+/// divides by 100 and surrounding parentheses negate.
+fn parse_lenient_number(value: &str) -> Option<f64> {
+    let (value, negate) = match value.strip_prefix('(').and_then(|v| v.strip_suffix(')')) {
+        Some(inner) => (inner.trim(), true),
+        None => (value, false),
+    };
+    let (value, negate) = match value.strip_prefix('-') {
+        Some(rest) if !negate => (rest.trim_start(), true),
+        Some(_) => return None,
+        None => (value, negate),
+    };
+    let (value, percent) = match value.strip_suffix('%') {
+        Some(rest) => (rest.trim_end(), true),
+        None => (value, false),
+    };
+    let value = value
+        .trim_start_matches(CURRENCY_SYMBOLS)
+        .trim_end_matches(CURRENCY_SYMBOLS)
+        .trim();
+
+    let number: f64 = parse_grouped_number(value)?.parse().ok()?;
+    let number = if percent { number / 100.0 } else { number };
+    Some(if negate { -number } else { number })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Array, StringArray};
+
+    #[test]
+    fn test_classify() {
+        let classify_all = |values: &[&str]| classify(values.iter().copied());
+
+        assert_eq!(classify_all(&["Y", "n", "yes"]), Some(RefinedType::Boolean));
+        assert_eq!(
+            classify_all(&["2024-01-15", "2024/2/3"]),
+            Some(RefinedType::Date)
+        );
+        assert_eq!(classify_all(&["1,234", "56"]), Some(RefinedType::Integer));
+        assert_eq!(
+            classify_all(&["-1,234.50", "7.25"]),
+            Some(RefinedType::Float)
+        );
+        // Numbers without separators may be codes such as zip codes
+        assert_eq!(classify_all(&["00123", "456"]), None);
+        assert_eq!(classify_all(&["12,34"]), None);
+        assert_eq!(classify_all(&["Y", "maybe"]), None);
+        assert_eq!(classify_all(&[]), None);
+    }
+
+    #[test]
+    fn test_parse_lenient_number() {
+        assert_eq!(parse_lenient_number("$1,234.56"), Some(1234.56));
+        assert_eq!(parse_lenient_number("(500)"), Some(-500.0));
+        assert_eq!(parse_lenient_number("($1,000)"), Some(-1000.0));
+        assert_eq!(parse_lenient_number("-€5"), Some(-5.0));
+        assert_eq!(parse_lenient_number("12%"), Some(0.12));
+        assert_eq!(parse_lenient_number("7 €"), Some(7.0));
+        assert_eq!(parse_lenient_number("42"), Some(42.0));
+        assert_eq!(parse_lenient_number("-(5)"), None);
+        assert_eq!(parse_lenient_number("$"), None);
+        assert_eq!(parse_lenient_number("12 apples"), None);
+    }
+
+    #[test]
+    fn test_apply_refinements() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("active", DataType::Utf8, true),
+            Field::new("amount", DataType::Utf8, true),
+            Field::new("note", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec![Some("Y"), None])),
+                Arc::new(StringArray::from(vec![Some("1,200"), Some("35")])),
+                Arc::new(StringArray::from(vec![Some("a"), Some("b")])),
+            ],
+        )
+        .unwrap();
+
+        let refinements = detect_refinements(&schema, std::slice::from_ref(&batch)).unwrap();
+        assert_eq!(
+            refinements
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec!["active: text -> Boolean", "amount: text -> Integer"]
+        );
+
+        let refined = apply_refinements(vec![batch], &refinements).unwrap();
+        let schema = refined[0].schema();
+        assert_eq!(schema.field(0).data_type(), &DataType::Boolean);
+        assert_eq!(schema.field(1).data_type(), &DataType::Int64);
+        assert_eq!(schema.field(2).data_type(), &DataType::Utf8);
+        let amounts = refined[0]
+            .column(1)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        assert_eq!(amounts.values(), &[1200, 35]);
+        assert!(refined[0].column(0).is_null(1));
+    }
+}
@@ -0,0 +1,182 @@
+//! Object stores for tables loaded from `s3://`, `gs://` and `http(s)://`
+//! URIs instead of local paths.
+//!
+//! Credentials and regions are read from the environment the way each
+//! provider's own tools read them, e.g. `AWS_ACCESS_KEY_ID`, `AWS_REGION`
+//! and `AWS_ENDPOINT` for S3 and `GOOGLE_SERVICE_ACCOUNT` for Google Cloud
+//! Storage.
+
+use std::sync::Arc;
+
+use object_store::aws::AmazonS3Builder;
+use object_store::gcp::GoogleCloudStorageBuilder;
+use object_store::http::HttpBuilder;
+use object_store::{ClientOptions, ObjectStore};
+use url::Url;
+
+use super::error::{DataFusionError, Result};
+use super::loader::REMOTE_SCHEMES;
+
+/// Parse `uri`, rejecting anything [`is_remote_uri`] does not accept.
+///
+/// [`is_remote_uri`]: super::is_remote_uri
+pub(super) fn parse_remote_uri(uri: &str) -> Result<Url> {
+    let url = Url::parse(uri)
+        .map_err(|e| DataFusionError::Conversion(format!("Invalid URI {}: {}", uri, e)))?;
+    if !REMOTE_SCHEMES.contains(&url.scheme()) || url.host_str().is_none() {
+        return Err(DataFusionError::UnsupportedFormat(format!(
+            "Unsupported URI {}: expected s3://bucket/path, gs://bucket/path or http(s)://host/path",
+            uri
+        )));
+    }
+    Ok(url)
+}
+
+/// The part of `url` an object store is registered under: the scheme and
+/// the bucket or host, e.g. `s3://my-bucket` for `s3://my-bucket/a/b.parquet`.
+pub(super) fn store_url(url: &Url) -> Url {
+    let mut base = url.clone();
+    base.set_path("");
+    base.set_query(None);
+    base.set_fragment(None);
+    base
+}
+
+/// An object store for the bucket or host of `url`, configured from the
+/// environment. Building it does not contact the store.
+pub(super) fn object_store_for(url: &Url) -> Result<Arc<dyn ObjectStore>> {
+    let host = url.host_str().unwrap_or_default();
+    let store: Arc<dyn ObjectStore> = match url.scheme() {
+        "s3" => Arc::new(AmazonS3Builder::from_env().with_bucket_name(host).build()?),
+        "gs" => Arc::new(
+            GoogleCloudStorageBuilder::from_env()
+                .with_bucket_name(host)
+                .build()?,
+        ),
+        "http" | "https" => Arc::new(
+            HttpBuilder::new()
+                .with_url(store_url(url))
+                .with_client_options(ClientOptions::new().with_allow_http(url.scheme() == "http"))
+                .build()?,
+        ),
+        scheme => {
+            return Err(DataFusionError::UnsupportedFormat(format!(
+                "Unsupported URI scheme: {}",
+                scheme
+            )))
+        }
+    };
+    Ok(store)
+}
+
+/// Teach deltalake, which only opens local tables by itself, to open Delta
+/// tables at remote URIs with the same stores DataFusion uses.
+#[cfg(feature = "delta")]
+pub(super) fn register_delta_schemes() {
+    use std::collections::HashMap;
+    use std::sync::Once;
+
+    use deltalake::logstore::{
+        default_logstore, logstore_factories, object_store_factories, LogStore, LogStoreFactory,
+        ObjectStoreFactory, ObjectStoreRef, StorageConfig,
+    };
+    use deltalake::{DeltaResult, DeltaTableError};
+    use object_store::path::Path as StorePath;
+    use object_store::RetryConfig;
+    use tokio::runtime::Handle;
+
+    struct RemoteStores;
+
+    impl ObjectStoreFactory for RemoteStores {
+        fn parse_url_opts(
+            &self,
+            url: &Url,
+            _options: &HashMap<String, String>,
+            _retry: &RetryConfig,
+            _handle: Option<Handle>,
+        ) -> DeltaResult<(ObjectStoreRef, StorePath)> {
+            let store =
+                object_store_for(url).map_err(|e| DeltaTableError::Generic(e.to_string()))?;
+            let prefix = StorePath::from_url_path(url.path())?;
+            Ok((store, prefix))
+        }
+    }
+
+    impl LogStoreFactory for RemoteStores {
+        fn with_options(
+            &self,
+            prefixed_store: ObjectStoreRef,
+            root_store: ObjectStoreRef,
+            location: &Url,
+            options: &StorageConfig,
+        ) -> DeltaResult<Arc<dyn LogStore>> {
+            Ok(default_logstore(
+                prefixed_store,
+                root_store,
+                location,
+                options,
+            ))
+        }
+    }
+
+    static REGISTERED: Once = Once::new();
+    REGISTERED.call_once(|| {
+        let factory = Arc::new(RemoteStores);
+        for scheme in REMOTE_SCHEMES {
+            let Ok(url) = Url::parse(&format!("{}://", scheme)) else {
+                continue;
+            };
+            object_store_factories().insert(url.clone(), factory.clone());
+            logstore_factories().insert(url, factory.clone());
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_url() {
+        let url = parse_remote_uri("s3://bucket/a/b.parquet").unwrap();
+        assert_eq!(store_url(&url).as_str(), "s3://bucket");
+        let url = parse_remote_uri("http://localhost:8080/data.csv?x=1").unwrap();
+        assert_eq!(store_url(&url).as_str(), "http://localhost:8080/");
+    }
+
+    #[test]
+    fn test_register_bucket_stores() {
+        // Building the stores reads the environment but sends no requests
+        let ctx = crate::datafusion::DataFusionContext::new().unwrap();
+        ctx.register_object_store("s3://bucket/events.parquet")
+            .unwrap();
+        ctx.register_object_store("gs://bucket/tables/sales")
+            .unwrap();
+    }
+
+    #[cfg(feature = "delta")]
+    #[test]
+    fn test_remote_uri_without_extension_is_delta() {
+        let readers = crate::datafusion::ReaderRegistry::default();
+        let reader = |uri: &str| {
+            readers
+                .find(std::path::Path::new(uri))
+                .map(|r| r.name().to_string())
+        };
+        assert_eq!(reader("s3://bucket/tables/sales").as_deref(), Some("delta"));
+        assert_eq!(
+            reader("s3://bucket/tables/sales/").as_deref(),
+            Some("delta")
+        );
+        assert_eq!(
+            reader("gs://bucket/events.parquet").as_deref(),
+            Some("parquet")
+        );
+    }
+
+    #[test]
+    fn test_parse_remote_uri_rejects_other_schemes() {
+        assert!(parse_remote_uri("ftp://host/data.csv").is_err());
+        assert!(parse_remote_uri("s3:///no-bucket.parquet").is_err());
+    }
+}
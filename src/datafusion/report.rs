@@ -0,0 +1,195 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::storage::table::Schema;
+
+use super::headers::RenamedColumn;
+use super::refine::TypeRefinement;
+
+/// What happened while a [`FileLoader`] loaded its paths: the tables it
+/// created, the files it skipped and why, and anything else worth a look.
+///
+/// [`FileLoader`]: super::FileLoader
+#[derive(Debug, Clone, Default)]
+pub struct LoadReport {
+    pub loaded: Vec<LoadedTable>,
+    pub skipped: Vec<SkippedFile>,
+    pub warnings: Vec<String>,
+    pub refinements: Vec<ColumnRefinement>,
+    pub renamed: Vec<RenamedHeader>,
+}
+
+/// A table registered by the loader.
+#[derive(Debug, Clone)]
+pub struct LoadedTable {
+    pub name: String,
+    /// The file or directory name the table was named after, when it had to
+    /// be sanitized into `name`.
+    pub original_name: Option<String>,
+    pub path: PathBuf,
+    /// Name of the reader that loaded it, e.g. `"csv"`.
+    pub format: String,
+    pub row_count: Option<u64>,
+    pub row_count_estimated: bool,
+    pub schema: Schema,
+}
+
+/// A file in a directory that could not be loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedFile {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+/// A text column of a loaded table whose values all read as a more
+/// specific type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnRefinement {
+    pub table: String,
+    pub refinement: TypeRefinement,
+    /// Whether the column was converted, or only found to be convertible.
+    pub applied: bool,
+}
+
+/// A column of a loaded table renamed by header normalization.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenamedHeader {
+    pub table: String,
+    pub column: RenamedColumn,
+}
+
+impl LoadReport {
+    pub fn is_empty(&self) -> bool {
+        self.loaded.is_empty()
+            && self.skipped.is_empty()
+            && self.warnings.is_empty()
+            && self.refinements.is_empty()
+            && self.renamed.is_empty()
+    }
+
+    /// Loaded tables whose name had to be sanitized, as
+    /// `(original, name)` pairs.
+    pub fn renamed_tables(&self) -> Vec<(&str, &str)> {
+        self.loaded
+            .iter()
+            .filter_map(|table| Some((table.original_name.as_deref()?, table.name.as_str())))
+            .collect()
+    }
+
+    /// Whether anything was skipped or warned about.
+    pub fn has_problems(&self) -> bool {
+        !self.skipped.is_empty() || !self.warnings.is_empty()
+    }
+
+    /// One line such as `loaded 3 tables, skipped 1 file, 1 warning`.
+    pub fn summary(&self) -> String {
+        format!(
+            "loaded {}, skipped {}, {}",
+            plural(self.loaded.len(), "table"),
+            plural(self.skipped.len(), "file"),
+            plural(self.warnings.len(), "warning")
+        )
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+impl fmt::Display for LoadReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Load report: {}", self.summary())?;
+        for table in &self.loaded {
+            let rows = match (table.row_count, table.row_count_estimated) {
+                (Some(n), false) => format!("{} rows", n),
+                (Some(n), true) => format!("~{} rows", n),
+                (None, _) => "unknown rows".to_string(),
+            };
+            writeln!(
+                f,
+                "  loaded  {} ({}, {}) from {}",
+                table.name,
+                table.format,
+                rows,
+                table.path.display()
+            )?;
+            if let Some(original) = &table.original_name {
+                writeln!(f, "          named after \"{}\"", original)?;
+            }
+            let columns: Vec<String> = table
+                .schema
+                .columns
+                .iter()
+                .map(|c| format!("{}: {:?}", c.name, c.data_type))
+                .collect();
+            writeln!(f, "          {}", columns.join(", "))?;
+        }
+        for skipped in &self.skipped {
+            writeln!(
+                f,
+                "  skipped {}: {}",
+                skipped.path.display(),
+                skipped.reason
+            )?;
+        }
+        for warning in &self.warnings {
+            writeln!(f, "  warning {}", warning)?;
+        }
+        for renamed in &self.renamed {
+            writeln!(f, "  renamed {}: {}", renamed.table, renamed.column)?;
+        }
+        for refined in &self.refinements {
+            let (label, note) = match refined.applied {
+                true => ("refined", ""),
+                false => ("suggest", " (not applied)"),
+            };
+            writeln!(
+                f,
+                "  {} {}.{}{}",
+                label, refined.table, refined.refinement, note
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::table::{Column, DataType};
+
+    #[test]
+    fn test_summary_and_display() {
+        let mut report = LoadReport::default();
+        assert!(report.is_empty());
+
+        report.loaded.push(LoadedTable {
+            name: "users".to_string(),
+            original_name: Some("Users".to_string()),
+            path: PathBuf::from("data/users.csv"),
+            format: "csv".to_string(),
+            row_count: Some(10),
+            row_count_estimated: true,
+            schema: Schema::new(vec![Column::new("id", DataType::Integer)]),
+        });
+        report.skipped.push(SkippedFile {
+            path: PathBuf::from("data/notes.txt"),
+            reason: "Unsupported file format: txt".to_string(),
+        });
+
+        assert!(report.has_problems());
+        assert_eq!(
+            report.summary(),
+            "loaded 1 table, skipped 1 file, 0 warnings"
+        );
+        let text = report.to_string();
+        assert!(text.contains("users (csv, ~10 rows) from data/users.csv"));
+        assert!(text.contains("named after \"Users\""));
+        assert!(text.contains("id: Integer"));
+        assert!(text.contains("skipped data/notes.txt: Unsupported file format: txt"));
+    }
+}
@@ -0,0 +1,471 @@
+use std::borrow::Cow;
+use std::ops::ControlFlow;
+use std::path::Path;
+
+use datafusion::dataframe::DataFrame;
+use datafusion::error::DataFusionError as DFError;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::LogicalPlan;
+use datafusion::sql::parser::{CopyToSource, CopyToStatement, Statement as DFStatement};
+use datafusion::sql::sqlparser::ast::{
+    BinaryOperator, Expr, Query, UnaryOperator, Value as SqlValue, VisitMut, VisitorMut,
+};
+
+use super::cache::strip_keyword;
+use super::dialect::translate;
+use super::error::{DataFusionError, Result};
+use super::suggest::{column_not_found, select_aliases};
+use super::templates::ExpandTemplates;
+
+/// Plan `sql` like [`SessionContext::sql`], after rewriting syntax that
+/// DataFusion parses but would otherwise ignore or reject.
+///
+/// Digit separators in numbers are removed first, so `1_000_000` is
+/// one million rather than `1 AS _000_000`; see [`strip_digit_separators`].
+/// `FETCH FIRST n ROWS ONLY` is planned as `LIMIT n`; DataFusion's planner
+/// drops the FETCH clause and would return every row. The `top_n` and
+/// `dedupe` templates are expanded as described on [`ExpandTemplates`],
+/// including in the query of a `COPY ... TO` statement. `EXPORT` is
+/// accepted in place of `COPY`, and a `.jsonl` or `.ndjson` target is
+/// written as JSON lines.
+///
+/// Syntax from other databases is translated as described on
+/// [`translate`]; with `strict` set it is rejected with
+/// [`ForeignSyntax`](super::DataFusionError::ForeignSyntax) instead.
+///
+/// A reference to a missing column fails with
+/// [`ColumnNotFound`](super::DataFusionError::ColumnNotFound).
+pub(super) async fn sql_to_dataframe(
+    session: &SessionContext,
+    sql: &str,
+    strict: bool,
+) -> Result<DataFrame> {
+    let plan = sql_to_plan(session, sql, strict).await?;
+    Ok(session.execute_logical_plan(plan).await?)
+}
+
+/// Plan `sql` the way [`sql_to_dataframe`] does, without creating the
+/// DataFrame. DDL such as `CREATE VIEW` is planned but not applied.
+pub(super) async fn sql_to_plan(
+    session: &SessionContext,
+    sql: &str,
+    strict: bool,
+) -> Result<LogicalPlan> {
+    let state = session.state();
+    let dialect = state.config().options().sql_parser.dialect.clone();
+    let sql = strip_digit_separators(sql);
+    let sql = export_as_copy(&sql);
+    let sql = show_as_query(&sql);
+    let mut statement = state.sql_to_statement(&sql, &dialect)?;
+    let notes = translate(&mut statement, &sql)?;
+    if strict && !notes.is_empty() {
+        return Err(DataFusionError::ForeignSyntax(notes.join("; ")));
+    }
+    rewrite_statement(&mut statement)?;
+    let aliases = select_aliases(&statement);
+
+    let plan = state
+        .statement_to_plan(statement)
+        .await
+        .map_err(|e| column_not_found(e, &aliases))?;
+    Ok(plan)
+}
+
+/// `sql` without the underscores between digits of numeric literals, as in
+/// `1_000_000` or `0.000_001`. Underscores in identifiers, quoted
+/// identifiers, strings and comments are kept, as is one that is not
+/// between two digits.
+pub(super) fn strip_digit_separators(sql: &str) -> Cow<'_, str> {
+    if !sql.contains('_') {
+        return Cow::Borrowed(sql);
+    }
+    let chars: Vec<char> = sql.chars().collect();
+    let mut out = String::with_capacity(sql.len());
+    let mut changed = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        // Copy up to and including the end of a quoted or commented span
+        let end = match (c, next) {
+            ('\'', _) | ('"', _) | ('`', _) => Some(c.to_string()),
+            ('-', Some('-')) => Some("\n".to_string()),
+            ('/', Some('*')) => Some("*/".to_string()),
+            _ => None,
+        };
+        if let Some(end) = end {
+            let start = i;
+            i += if c == '-' || c == '/' { 2 } else { 1 };
+            let end: Vec<char> = end.chars().collect();
+            while i < chars.len() && !chars[i..].starts_with(&end) {
+                i += 1;
+            }
+            i = (i + end.len()).min(chars.len());
+            out.extend(&chars[start..i]);
+            continue;
+        }
+
+        if c.is_alphabetic() || c == '_' || c == '$' {
+            while i < chars.len() && is_word_char(chars[i]) {
+                out.push(chars[i]);
+                i += 1;
+            }
+        } else if c.is_ascii_digit() {
+            while i < chars.len() && (is_word_char(chars[i]) || chars[i] == '.') {
+                let separator = chars[i] == '_'
+                    && chars[i - 1].is_ascii_digit()
+                    && chars.get(i + 1).is_some_and(char::is_ascii_digit);
+                if separator {
+                    changed = true;
+                } else {
+                    out.push(chars[i]);
+                }
+                i += 1;
+            }
+        } else {
+            out.push(c);
+            i += 1;
+        }
+    }
+    if changed {
+        Cow::Owned(out)
+    } else {
+        Cow::Borrowed(sql)
+    }
+}
+
+/// `sql` with a leading `EXPORT` keyword replaced by `COPY`, so
+/// `EXPORT (SELECT ...) TO 'out.csv'` runs as DataFusion's `COPY ... TO`.
+pub(super) fn export_as_copy(sql: &str) -> Cow<'_, str> {
+    let text = sql.trim_start();
+    let is_export = text
+        .get(..6)
+        .is_some_and(|head| head.eq_ignore_ascii_case("EXPORT"))
+        && text[6..].starts_with(|c: char| c.is_whitespace() || c == '(');
+    if is_export {
+        Cow::Owned(format!("COPY{}", &text[6..]))
+    } else {
+        Cow::Borrowed(sql)
+    }
+}
+
+/// Whether `sql` is a `COPY ... TO` or `EXPORT ... TO` statement, which
+/// writes its result to a file instead of returning it.
+pub fn writes_to_file(sql: &str) -> bool {
+    let text = skip_leading_comments(sql);
+    ["COPY", "EXPORT"].iter().any(|keyword| {
+        text.get(..keyword.len())
+            .is_some_and(|head| head.eq_ignore_ascii_case(keyword))
+            && text[keyword.len()..].starts_with(|c: char| c.is_whitespace() || c == '(')
+    })
+}
+
+/// `SHOW TABLES` as a query listing only the loaded and created tables,
+/// not the `information_schema` views, and `SHOW COLUMNS FROM t` (or `IN t`)
+/// as `DESCRIBE t`. DataFusion's own forms also repeat the catalog and
+/// schema in every row. Other `SHOW` statements are left to DataFusion.
+pub(super) fn show_as_query(sql: &str) -> Cow<'_, str> {
+    let text = skip_leading_comments(sql)
+        .trim_end()
+        .trim_end_matches(';')
+        .trim_end();
+    let Some(rest) = strip_keyword(text, "SHOW") else {
+        return Cow::Borrowed(sql);
+    };
+    if rest.eq_ignore_ascii_case("TABLES") {
+        return Cow::Borrowed(
+            "SELECT table_name, table_type FROM information_schema.tables \
+             WHERE table_schema <> 'information_schema' ORDER BY table_name",
+        );
+    }
+    let table = strip_keyword(rest, "COLUMNS")
+        .and_then(|rest| strip_keyword(rest, "FROM").or_else(|| strip_keyword(rest, "IN")));
+    match table {
+        Some(table) if is_single_name(table) => Cow::Owned(format!("DESCRIBE {}", table)),
+        _ => Cow::Borrowed(sql),
+    }
+}
+
+/// `sql` from its first token on, past whitespace and `--` and `/* */`
+/// comments.
+fn skip_leading_comments(sql: &str) -> &str {
+    let mut text = sql.trim_start();
+    loop {
+        if let Some(rest) = text.strip_prefix("--") {
+            text = rest.split_once('\n').map_or("", |(_, rest)| rest);
+        } else if let Some(rest) = text.strip_prefix("/*") {
+            text = rest.split_once("*/").map_or("", |(_, rest)| rest);
+        } else {
+            return text;
+        }
+        text = text.trim_start();
+    }
+}
+
+/// Whether `text` is one table name, possibly qualified or double-quoted,
+/// rather than a name followed by a clause such as `LIKE`.
+fn is_single_name(text: &str) -> bool {
+    let mut quoted = false;
+    !text.is_empty()
+        && text.chars().all(|c| {
+            if c == '"' {
+                quoted = !quoted;
+            }
+            quoted || !c.is_whitespace()
+        })
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+fn rewrite_statement(statement: &mut DFStatement) -> std::result::Result<(), DFError> {
+    match statement {
+        DFStatement::Statement(inner) => rewrite_node(inner.as_mut()),
+        DFStatement::Explain(explain) => rewrite_statement(&mut explain.statement),
+        DFStatement::CopyTo(copy) => {
+            json_lines_target(copy);
+            match &mut copy.source {
+                CopyToSource::Query(query) => rewrite_node(query.as_mut()),
+                CopyToSource::Relation(_) => Ok(()),
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
+fn rewrite_node<T: VisitMut>(node: &mut T) -> std::result::Result<(), DFError> {
+    if let ControlFlow::Break(err) = node.visit(&mut ExpandTemplates) {
+        return Err(err);
+    }
+    if let ControlFlow::Break(err) = node.visit(&mut FetchToLimit) {
+        return Err(err);
+    }
+    match node.visit(&mut NonNegativeLimits) {
+        ControlFlow::Break(err) => Err(err),
+        ControlFlow::Continue(()) => Ok(()),
+    }
+}
+
+/// Store a `COPY` target ending in `.jsonl` or `.ndjson` as JSON, which
+/// DataFusion writes one object per line but only recognises by a `.json`
+/// extension.
+fn json_lines_target(copy: &mut CopyToStatement) {
+    if copy.stored_as.is_some() {
+        return;
+    }
+    let extension = Path::new(&copy.target)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+    if matches!(extension.as_deref(), Some("jsonl" | "ndjson")) {
+        copy.stored_as = Some("JSON".to_string());
+    }
+}
+
+/// Turns `FETCH FIRST|NEXT [n] ROW[S] ONLY` into `LIMIT n` on every query,
+/// including subqueries and CTEs.
+struct FetchToLimit;
+
+impl VisitorMut for FetchToLimit {
+    type Break = DFError;
+
+    fn post_visit_query(&mut self, query: &mut Query) -> ControlFlow<DFError> {
+        let Some(fetch) = query.fetch.take() else {
+            return ControlFlow::Continue(());
+        };
+        if fetch.with_ties || fetch.percent {
+            return ControlFlow::Break(DFError::NotImplemented(format!(
+                "{} is not supported; use FETCH FIRST n ROWS ONLY or LIMIT n",
+                fetch
+            )));
+        }
+        if query.limit.is_some() {
+            return ControlFlow::Break(DFError::Plan(
+                "LIMIT and FETCH cannot be used in the same query".to_string(),
+            ));
+        }
+        // FETCH FIRST ROW ONLY fetches a single row
+        let quantity = fetch
+            .quantity
+            .unwrap_or_else(|| Expr::Value(SqlValue::Number("1".to_string(), false).into()));
+        query.limit = Some(quantity);
+        ControlFlow::Continue(())
+    }
+}
+
+/// Rejects a `LIMIT` or `OFFSET` that is a negative number, or arithmetic
+/// on numbers that comes out negative, before DataFusion's optimizer fails
+/// on it with a message naming one of its rules.
+struct NonNegativeLimits;
+
+impl VisitorMut for NonNegativeLimits {
+    type Break = DFError;
+
+    fn post_visit_query(&mut self, query: &mut Query) -> ControlFlow<DFError> {
+        let clauses = [
+            ("LIMIT", query.limit.as_ref()),
+            ("OFFSET", query.offset.as_ref().map(|offset| &offset.value)),
+        ];
+        for (clause, expr) in clauses {
+            if let Some(value) = expr.and_then(constant_integer).filter(|value| *value < 0) {
+                return ControlFlow::Break(DFError::Plan(format!(
+                    "{} must be >= 0 (got {})",
+                    clause, value
+                )));
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+/// The value of `expr` if it is an integer or `+`, `-` and `*` on integers.
+fn constant_integer(expr: &Expr) -> Option<i128> {
+    match expr {
+        Expr::Value(value) => match &value.value {
+            SqlValue::Number(number, _) => number.parse().ok(),
+            _ => None,
+        },
+        Expr::Nested(inner) => constant_integer(inner),
+        Expr::UnaryOp { op, expr } => match op {
+            UnaryOperator::Minus => constant_integer(expr)?.checked_neg(),
+            UnaryOperator::Plus => constant_integer(expr),
+            _ => None,
+        },
+        Expr::BinaryOp { left, op, right } => {
+            let (left, right) = (constant_integer(left)?, constant_integer(right)?);
+            match op {
+                BinaryOperator::Plus => left.checked_add(right),
+                BinaryOperator::Minus => left.checked_sub(right),
+                BinaryOperator::Multiply => left.checked_mul(right),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rewrite(sql: &str) -> std::result::Result<String, DFError> {
+        let session = SessionContext::new();
+        let state = session.state();
+        let mut statement = state.sql_to_statement(sql, "generic")?;
+        rewrite_statement(&mut statement)?;
+        Ok(statement.to_string())
+    }
+
+    #[test]
+    fn test_fetch_becomes_limit() {
+        assert_eq!(
+            rewrite("SELECT a FROM t ORDER BY a FETCH FIRST 3 ROWS ONLY").unwrap(),
+            "SELECT a FROM t ORDER BY a LIMIT 3"
+        );
+        assert_eq!(
+            rewrite("SELECT a FROM t OFFSET 2 ROWS FETCH NEXT ROW ONLY").unwrap(),
+            "SELECT a FROM t LIMIT 1 OFFSET 2 ROWS"
+        );
+        assert_eq!(
+            rewrite("SELECT * FROM (SELECT a FROM t FETCH FIRST 1 ROWS ONLY) AS s").unwrap(),
+            "SELECT * FROM (SELECT a FROM t LIMIT 1) AS s"
+        );
+    }
+
+    #[test]
+    fn test_copy_statements() {
+        assert_eq!(
+            rewrite(&export_as_copy(
+                "EXPORT (SELECT a FROM t FETCH FIRST 2 ROWS ONLY) TO 'out.jsonl'"
+            ))
+            .unwrap(),
+            "COPY (SELECT a FROM t LIMIT 2) TO out.jsonl STORED AS JSON"
+        );
+        assert_eq!(
+            rewrite(&export_as_copy("export t TO 'out.csv'")).unwrap(),
+            "COPY t TO out.csv"
+        );
+        assert!(matches!(
+            export_as_copy("SELECT * FROM exports"),
+            Cow::Borrowed(_)
+        ));
+        assert!(matches!(export_as_copy("EXPORTS"), Cow::Borrowed(_)));
+
+        assert!(writes_to_file("-- save\ncopy (SELECT 1) TO 'out.csv'"));
+        assert!(writes_to_file("EXPORT(SELECT 1) TO 'out.csv'"));
+        assert!(!writes_to_file("SELECT * FROM copy_log"));
+        assert!(!writes_to_file("COPYRIGHT"));
+    }
+
+    #[test]
+    fn test_show_statements() {
+        assert!(show_as_query("show tables;").starts_with("SELECT table_name"));
+        assert_eq!(show_as_query("SHOW COLUMNS FROM users"), "DESCRIBE users");
+        assert_eq!(
+            show_as_query("show columns in \"my table\";"),
+            "DESCRIBE \"my table\""
+        );
+        assert!(matches!(
+            show_as_query("SHOW COLUMNS FROM users LIKE 'a%'"),
+            Cow::Borrowed(_)
+        ));
+        assert_eq!(
+            show_as_query("-- columns\n/* of t */ SHOW COLUMNS FROM t"),
+            "DESCRIBE t"
+        );
+        assert!(matches!(show_as_query("SHOW ALL"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_strip_digit_separators() {
+        assert_eq!(
+            strip_digit_separators("SELECT 1_000_000, 0.000_001, 1_5e1_0 FROM t"),
+            "SELECT 1000000, 0.000001, 15e10 FROM t"
+        );
+        let kept = "SELECT col_1_2, \"a 1_2\", '1_2' FROM t_1 -- 1_2\nWHERE x = 1_ /* 3_4 */";
+        assert!(matches!(strip_digit_separators(kept), Cow::Borrowed(_)));
+        assert_eq!(
+            strip_digit_separators("SELECT 1__0, 1_a"),
+            "SELECT 1__0, 1_a"
+        );
+    }
+
+    #[test]
+    fn test_unsupported_fetch_forms() {
+        assert!(matches!(
+            rewrite("SELECT a FROM t ORDER BY a FETCH FIRST 3 ROWS WITH TIES"),
+            Err(DFError::NotImplemented(_))
+        ));
+        assert!(matches!(
+            rewrite("SELECT a FROM t FETCH FIRST 10 PERCENT ROWS ONLY"),
+            Err(DFError::NotImplemented(_))
+        ));
+        assert!(matches!(
+            rewrite("SELECT a FROM t LIMIT 2 FETCH FIRST 3 ROWS ONLY"),
+            Err(DFError::Plan(_))
+        ));
+    }
+
+    #[test]
+    fn test_negative_limits() {
+        let message = |sql: &str| match rewrite(sql) {
+            Err(DFError::Plan(message)) => message,
+            other => panic!("expected a plan error, got {:?}", other),
+        };
+        assert_eq!(
+            message("SELECT a FROM t LIMIT -1"),
+            "LIMIT must be >= 0 (got -1)"
+        );
+        assert_eq!(
+            message("SELECT * FROM (SELECT a FROM t OFFSET 1 - 3) AS s"),
+            "OFFSET must be >= 0 (got -2)"
+        );
+        assert_eq!(
+            message("SELECT a FROM t LIMIT -(2 * 2)"),
+            "LIMIT must be >= 0 (got -4)"
+        );
+        assert!(rewrite("SELECT a FROM t LIMIT 3 - 1 OFFSET 0").is_ok());
+        assert!(rewrite("SELECT a FROM t LIMIT (SELECT -1)").is_ok());
+    }
+}
@@ -0,0 +1,359 @@
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+
+use super::error::{DataFusionError, Result};
+
+/// One statement from a SQL script, with the name its result is reported
+/// under.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamedStatement {
+    pub name: String,
+    pub sql: String,
+}
+
+/// Split a script into statements on semicolons, ignoring semicolons inside
+/// string literals, quoted identifiers and comments. Statements that contain
+/// nothing but whitespace and comments are dropped.
+pub fn split_statements(script: &str) -> Vec<String> {
+    let (mut statements, rest) = split_terminated(script);
+    if let Some(rest) = rest {
+        statements.push(rest.trim().to_string());
+    }
+    statements
+}
+
+/// Remove the statements that end in a semicolon from the start of
+/// `buffer` and return them, leaving the unterminated rest, as for input
+/// read a line at a time. The rest is cleared if it holds nothing but
+/// whitespace and comments.
+pub fn take_statements(buffer: &mut String) -> Vec<String> {
+    let (statements, rest) = split_terminated(buffer);
+    *buffer = rest
+        .map(|rest| rest.trim_start().to_string())
+        .unwrap_or_default();
+    statements
+}
+
+/// The statements of `script` that end in a semicolon, and the text after
+/// the last one, if it holds any code.
+fn split_terminated(script: &str) -> (Vec<String>, Option<String>) {
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut has_code = false;
+    let mut chars = script.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' | '"' | '`' => {
+                has_code = true;
+                current.push(c);
+                // A doubled quote is an escaped quote, which this loop reads
+                // as closing and immediately reopening the literal.
+                for inner in chars.by_ref() {
+                    current.push(inner);
+                    if inner == c {
+                        break;
+                    }
+                }
+            }
+            '-' if chars.peek() == Some(&'-') => {
+                current.push(c);
+                for inner in chars.by_ref() {
+                    current.push(inner);
+                    if inner == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                current.push(c);
+                current.push(chars.next().unwrap_or('*'));
+                let mut prev = '\0';
+                for inner in chars.by_ref() {
+                    current.push(inner);
+                    if prev == '*' && inner == '/' {
+                        break;
+                    }
+                    prev = inner;
+                }
+            }
+            ';' => {
+                if has_code {
+                    statements.push(current.trim().to_string());
+                }
+                current.clear();
+                has_code = false;
+            }
+            _ => {
+                has_code |= !c.is_whitespace();
+                current.push(c);
+            }
+        }
+    }
+    let rest = has_code.then_some(current);
+    (statements, rest)
+}
+
+/// Split `script` and name each statement. A `-- name: <name>` comment
+/// before a statement names it; otherwise statements are numbered
+/// `query_1`, `query_2`, ... by position.
+pub fn named_statements(script: &str) -> Vec<NamedStatement> {
+    named_scripts(&[script])
+}
+
+/// Split each of `scripts` on its own and name the statements as
+/// [`named_statements`] does, numbering them across all scripts. A comment
+/// left open at the end of one script does not run into the next.
+pub fn named_scripts<S: AsRef<str>>(scripts: &[S]) -> Vec<NamedStatement> {
+    scripts
+        .iter()
+        .flat_map(|script| split_statements(script.as_ref()))
+        .enumerate()
+        .map(|(i, sql)| NamedStatement {
+            name: statement_name(&sql).unwrap_or_else(|| format!("query_{}", i + 1)),
+            sql,
+        })
+        .collect()
+}
+
+fn statement_name(sql: &str) -> Option<String> {
+    sql.lines()
+        .map(str::trim)
+        .take_while(|line| line.starts_with("--") || line.is_empty())
+        .find_map(|line| {
+            let name = line.trim_start_matches('-').trim().strip_prefix("name:")?;
+            let name = name.trim();
+            (!name.is_empty()).then(|| name.to_string())
+        })
+}
+
+/// Writes the statements of a session to a script, in the order they ran,
+/// so that `--replay` can run them again, e.g. against next month's data.
+pub struct SessionRecorder {
+    file: File,
+}
+
+impl SessionRecorder {
+    /// Start a recording at `path`, replacing any earlier one.
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            file: File::create(path)?,
+        })
+    }
+
+    /// Append `sql` as the next statement. It is written straight to the
+    /// file, so a session that ends abruptly keeps what ran before.
+    pub fn record(&self, sql: &str) -> io::Result<()> {
+        let sql = sql.trim().trim_end_matches(';').trim_end();
+        // A semicolon after a trailing `--` comment would be commented out
+        let ends_in_comment = sql.lines().last().is_some_and(|line| line.contains("--"));
+        let terminator = if ends_in_comment { "\n;" } else { ";" };
+        (&self.file).write_all(format!("{}{}\n\n", sql, terminator).as_bytes())
+    }
+}
+
+/// Replace `${NAME}` with the value of environment variable `NAME`, or with
+/// `default` for `${NAME:-default}` when `NAME` is unset or empty. A `$` not
+/// followed by `{`, and an unterminated `${`, are left as they are.
+pub fn expand_env_vars(text: &str) -> Result<String> {
+    expand_vars(text, |name| std::env::var(name).ok())
+}
+
+fn expand_vars(text: &str, lookup: impl Fn(&str) -> Option<String>) -> Result<String> {
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start + 2..].find('}') else {
+            break;
+        };
+        expanded.push_str(&rest[..start]);
+
+        let reference = &rest[start + 2..start + 2 + len];
+        let (name, default) = match reference.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (reference, None),
+        };
+        match (lookup(name).filter(|v| !v.is_empty()), default) {
+            (Some(value), _) => expanded.push_str(&value),
+            (None, Some(default)) => expanded.push_str(default),
+            (None, None) => return Err(DataFusionError::EnvVarNotSet(name.to_string())),
+        }
+        rest = &rest[start + 3 + len..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Names of the `{{name}}` placeholders in `sql`, in order of first use.
+/// Whitespace inside the braces is ignored, and braces holding anything but
+/// a word are not placeholders.
+pub fn template_params(sql: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    replace_placeholders(sql, |name| {
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+        None
+    });
+    names
+}
+
+/// Replace each `{{name}}` placeholder with its entry in `values`. Values are
+/// inserted as typed, so strings need their own quotes. Placeholders without
+/// a value are left as they are.
+pub fn fill_template(sql: &str, values: &[(String, String)]) -> String {
+    replace_placeholders(sql, |name| {
+        values
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.clone())
+    })
+}
+
+/// Call `replace` with the name of every placeholder in `text` and return
+/// `text` with each one it returns a value for replaced.
+fn replace_placeholders(text: &str, mut replace: impl FnMut(&str) -> Option<String>) -> String {
+    let mut filled = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let end = start + 4 + len;
+        let name = rest[start + 2..start + 2 + len].trim();
+        let is_word = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+        filled.push_str(&rest[..start]);
+        match is_word.then(|| replace(name)).flatten() {
+            Some(value) => filled.push_str(&value),
+            None => filled.push_str(&rest[start..end]),
+        }
+        rest = &rest[end..];
+    }
+    filled.push_str(rest);
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_statements() {
+        let statements = split_statements("SELECT 1; SELECT 2;\n\nSELECT 3");
+        assert_eq!(statements, vec!["SELECT 1", "SELECT 2", "SELECT 3"]);
+    }
+
+    #[test]
+    fn test_split_ignores_quoted_and_commented_semicolons() {
+        let script = "SELECT 'a;b', \"c;d\" FROM t; -- trailing; comment\n\
+                      /* block; */ SELECT 'it''s;'; -- only a comment;";
+        let statements = split_statements(script);
+        assert_eq!(statements.len(), 2);
+        assert_eq!(statements[0], "SELECT 'a;b', \"c;d\" FROM t");
+        assert!(statements[1].ends_with("SELECT 'it''s;'"));
+    }
+
+    #[test]
+    fn test_take_statements() {
+        let mut buffer = "SELECT 1; SELECT 'a;".to_string();
+        assert_eq!(take_statements(&mut buffer), vec!["SELECT 1"]);
+        assert_eq!(buffer, "SELECT 'a;");
+
+        buffer.push_str("b' FROM t\n");
+        assert!(take_statements(&mut buffer).is_empty());
+        buffer.push_str(";\n-- done\n");
+        assert_eq!(take_statements(&mut buffer), vec!["SELECT 'a;b' FROM t"]);
+        assert_eq!(buffer, "");
+    }
+
+    #[test]
+    fn test_named_statements() {
+        let script = "-- name: user_count\nSELECT COUNT(*) FROM users;\nSELECT 2;";
+        let statements = named_statements(script);
+        assert_eq!(statements[0].name, "user_count");
+        assert_eq!(statements[1].name, "query_2");
+    }
+
+    #[test]
+    fn test_named_scripts_keep_trailing_comments_apart() {
+        let scripts = [
+            "SELECT 1 -- first",
+            "SELECT 2; SELECT 3",
+            "-- name: last\nSELECT 4",
+        ];
+        let statements = named_scripts(&scripts);
+        let sql: Vec<&str> = statements.iter().map(|s| s.sql.as_str()).collect();
+        assert_eq!(
+            sql,
+            vec![
+                "SELECT 1 -- first",
+                "SELECT 2",
+                "SELECT 3",
+                "-- name: last\nSELECT 4"
+            ]
+        );
+        assert_eq!(statements[2].name, "query_3");
+        assert_eq!(statements[3].name, "last");
+    }
+
+    #[test]
+    fn test_recorded_session_replays_statements() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.kql");
+        let recorder = SessionRecorder::create(&path).unwrap();
+        recorder.record("SET max_rows = 10 -- small").unwrap();
+        recorder
+            .record("-- name: greeting\nSELECT 'a;b' AS text;\n")
+            .unwrap();
+
+        let statements = named_statements(&std::fs::read_to_string(&path).unwrap());
+        assert_eq!(statements.len(), 2);
+        assert_eq!(statements[0].sql, "SET max_rows = 10 -- small");
+        assert_eq!(statements[1].name, "greeting");
+        assert!(statements[1].sql.ends_with("SELECT 'a;b' AS text"));
+
+        let ctx = crate::datafusion::DataFusionContext::new().unwrap();
+        for statement in &statements {
+            ctx.execute_sql(&statement.sql).unwrap();
+        }
+        assert_eq!(ctx.settings().max_rows, Some(10));
+    }
+
+    #[test]
+    fn test_expand_vars() {
+        let lookup = |name: &str| match name {
+            "START" => Some("2024-01-01".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        };
+        let sql = "WHERE d >= '${START}' AND d < '${END:-2024-02-01}' AND c = '$5'";
+        assert_eq!(
+            expand_vars(sql, lookup).unwrap(),
+            "WHERE d >= '2024-01-01' AND d < '2024-02-01' AND c = '$5'"
+        );
+        assert_eq!(expand_vars("${EMPTY:-x} ${", lookup).unwrap(), "x ${");
+        assert!(matches!(
+            expand_vars("${MISSING}", lookup),
+            Err(DataFusionError::EnvVarNotSet(name)) if name == "MISSING"
+        ));
+    }
+
+    #[test]
+    fn test_template_params() {
+        let sql = "SELECT * FROM t WHERE region = {{ region }} AND d >= {{start}} \
+                   AND r = {{region}} AND x = '{{not a param}}'";
+        assert_eq!(template_params(sql), vec!["region", "start"]);
+
+        let values = vec![
+            ("region".to_string(), "'EU'".to_string()),
+            ("start".to_string(), "DATE '2024-01-01'".to_string()),
+        ];
+        assert_eq!(
+            fill_template(sql, &values),
+            "SELECT * FROM t WHERE region = 'EU' AND d >= DATE '2024-01-01' \
+             AND r = 'EU' AND x = '{{not a param}}'"
+        );
+        assert_eq!(fill_template("{{missing}} {{", &values), "{{missing}} {{");
+    }
+}
@@ -0,0 +1,401 @@
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use arrow::array::timezone::Tz;
+use datafusion::execution::context::SessionContext;
+
+use crate::render::DEFAULT_MAX_COL_WIDTH;
+use crate::storage::table::{Column, DataType, Row, Schema, Table, Value};
+
+use super::conversion::record_batch_to_table;
+use super::error::{DataFusionError, Result};
+
+/// Names of the settings, in the order `SHOW ALL` lists them.
+const SETTING_NAMES: [&str; 6] = [
+    "max_rows",
+    "timezone",
+    "max_col_width",
+    "result_memory_mb",
+    "timeout_secs",
+    "max_memory_mb",
+];
+
+/// Per-session settings changed with `SET name = value` and listed with
+/// `SHOW ALL`. The context applies `max_rows`, `timezone`,
+/// `result_memory_mb`, `timeout_secs` and `max_memory_mb` when running
+/// queries, unless a query's [`QueryOptions`](super::QueryOptions) say
+/// otherwise; frontends read `max_col_width` when rendering results.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionSettings {
+    /// Stop reading a query result after this many rows. `None` means no
+    /// limit.
+    pub max_rows: Option<usize>,
+    /// Time zone for `now()` and timestamps with a time zone, as a name
+    /// such as `Europe/Berlin` or an offset such as `+02:00`.
+    pub timezone: String,
+    /// Width at which rendered values are truncated.
+    pub max_col_width: usize,
+    /// Spill a result to a temporary file once its Arrow data passes this
+    /// many megabytes, keeping only its first rows in memory; see
+    /// [`SpilledResult`](super::SpilledResult). `None` keeps every result
+    /// in memory.
+    pub result_memory_mb: Option<usize>,
+    /// Cancel a query that runs for longer than this many seconds. `None`
+    /// means no limit.
+    pub timeout_secs: Option<u64>,
+    /// Memory that a query's sorts, joins and aggregations may use, in
+    /// megabytes. Operators that can spill to disk do so at the limit and
+    /// the others fail the query. `None` means no limit.
+    pub max_memory_mb: Option<usize>,
+}
+
+impl Default for SessionSettings {
+    fn default() -> Self {
+        Self {
+            max_rows: None,
+            timezone: "+00:00".to_string(),
+            max_col_width: DEFAULT_MAX_COL_WIDTH,
+            result_memory_mb: None,
+            timeout_secs: None,
+            max_memory_mb: None,
+        }
+    }
+}
+
+impl SessionSettings {
+    /// Change setting `name` to `value`, parsed as that setting's type.
+    /// `DEFAULT` restores the default, as does `NONE` for `max_rows`,
+    /// `result_memory_mb`, `timeout_secs` and `max_memory_mb`.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<()> {
+        let name = name.to_lowercase();
+        let reset = value.eq_ignore_ascii_case("default");
+        let invalid = || DataFusionError::InvalidSetting(format!("{} = {}", name, value));
+        match name.as_str() {
+            "max_rows" if reset || value.eq_ignore_ascii_case("none") => self.max_rows = None,
+            "max_rows" => self.max_rows = Some(value.parse().map_err(|_| invalid())?),
+            "timezone" if reset => self.timezone = Self::default().timezone,
+            "timezone" => {
+                Tz::from_str(value).map_err(|_| invalid())?;
+                self.timezone = value.to_string();
+            }
+            "max_col_width" if reset => self.max_col_width = DEFAULT_MAX_COL_WIDTH,
+            "max_col_width" => {
+                self.max_col_width = value
+                    .parse()
+                    .ok()
+                    .filter(|width| *width > 0)
+                    .ok_or_else(invalid)?
+            }
+            "result_memory_mb" if reset || value.eq_ignore_ascii_case("none") => {
+                self.result_memory_mb = None
+            }
+            "result_memory_mb" => {
+                self.result_memory_mb = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|mb| *mb > 0)
+                        .ok_or_else(invalid)?,
+                )
+            }
+            "timeout_secs" if reset || value.eq_ignore_ascii_case("none") => {
+                self.timeout_secs = None
+            }
+            "timeout_secs" => {
+                self.timeout_secs = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|secs| *secs > 0)
+                        .ok_or_else(invalid)?,
+                )
+            }
+            "max_memory_mb" if reset || value.eq_ignore_ascii_case("none") => {
+                self.max_memory_mb = None
+            }
+            "max_memory_mb" => {
+                self.max_memory_mb = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|mb| *mb > 0)
+                        .ok_or_else(invalid)?,
+                )
+            }
+            _ => {
+                return Err(DataFusionError::InvalidSetting(format!(
+                    "unknown setting {}; available: {}",
+                    name,
+                    SETTING_NAMES.join(", ")
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    /// The value of setting `name`, or `None` if there is no such setting.
+    /// A setting without a limit, such as `max_rows = NONE`, is
+    /// `Value::Null`.
+    pub fn get(&self, name: &str) -> Option<Value> {
+        match name.to_lowercase().as_str() {
+            "max_rows" => Some(
+                self.max_rows
+                    .map_or(Value::Null, |rows| Value::Integer(rows as i64)),
+            ),
+            "timezone" => Some(Value::String(self.timezone.clone())),
+            "max_col_width" => Some(Value::Integer(self.max_col_width as i64)),
+            "result_memory_mb" => Some(
+                self.result_memory_mb
+                    .map_or(Value::Null, |mb| Value::Integer(mb as i64)),
+            ),
+            "timeout_secs" => Some(
+                self.timeout_secs
+                    .map_or(Value::Null, |secs| Value::Integer(secs as i64)),
+            ),
+            "max_memory_mb" => Some(
+                self.max_memory_mb
+                    .map_or(Value::Null, |mb| Value::Integer(mb as i64)),
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// A `SET` or `SHOW` statement about a knowhere setting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum SessionStatement {
+    Set {
+        name: String,
+        value: String,
+    },
+    Show(String),
+    /// `SHOW ALL`, which also lists DataFusion's own settings.
+    ShowAll,
+}
+
+/// Recognise `SET name = value` (or `TO value`), `SHOW name` and `SHOW ALL`
+/// for the settings of [`SessionSettings`]. Other names, such as
+/// `datafusion.execution.batch_size`, are left for DataFusion to handle.
+/// Values may be single-quoted, and comments are ignored.
+pub(super) fn parse_session_statement(sql: &str) -> Option<SessionStatement> {
+    let sql = strip_comments(sql);
+    let sql = sql.trim().trim_end_matches(';').trim_end();
+    let (keyword, rest) = sql.split_once(char::is_whitespace)?;
+    let rest = rest.trim();
+
+    if keyword.eq_ignore_ascii_case("show") {
+        if rest.eq_ignore_ascii_case("all") {
+            return Some(SessionStatement::ShowAll);
+        }
+        let name = rest.to_lowercase();
+        return SETTING_NAMES
+            .contains(&name.as_str())
+            .then_some(SessionStatement::Show(name));
+    }
+    if !keyword.eq_ignore_ascii_case("set") {
+        return None;
+    }
+
+    let end = rest
+        .find(|c: char| c == '=' || c.is_whitespace())
+        .unwrap_or(rest.len());
+    let name = rest[..end].to_lowercase();
+    if name.contains('.') || !SETTING_NAMES.contains(&name.as_str()) {
+        return None;
+    }
+    let value = rest[end..].trim_start();
+    let value = match value.strip_prefix('=') {
+        Some(value) => value,
+        None => value
+            .get(..2)
+            .filter(|to| to.eq_ignore_ascii_case("to"))
+            .map(|_| &value[2..])?,
+    };
+    let value = value.trim();
+    let value = value
+        .strip_prefix('\'')
+        .and_then(|v| v.strip_suffix('\''))
+        .unwrap_or(value);
+    (!value.is_empty()).then(|| SessionStatement::Set {
+        name,
+        value: value.to_string(),
+    })
+}
+
+/// `sql` with its `--` and `/* */` comments replaced by spaces, leaving
+/// quoted text alone.
+fn strip_comments(sql: &str) -> String {
+    let mut out = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' | '"' => {
+                out.push(c);
+                for inner in chars.by_ref() {
+                    out.push(inner);
+                    if inner == c {
+                        break;
+                    }
+                }
+            }
+            '-' if chars.peek() == Some(&'-') => {
+                chars.by_ref().find(|&c| c == '\n');
+                out.push(' ');
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = ' ';
+                for inner in chars.by_ref() {
+                    if prev == '*' && inner == '/' {
+                        break;
+                    }
+                    prev = inner;
+                }
+                out.push(' ');
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Change setting `name` to `value` in `settings`, forwarding the time zone
+/// to DataFusion. `settings` is left as it was if either step fails.
+pub(super) async fn apply_setting(
+    session: &SessionContext,
+    settings: &Arc<Mutex<SessionSettings>>,
+    name: &str,
+    value: &str,
+) -> Result<()> {
+    let mut updated = settings.lock().map_err(|_| poisoned())?.clone();
+    updated.set(name, value)?;
+    if name.eq_ignore_ascii_case("timezone") {
+        let sql = format!(
+            "SET datafusion.execution.time_zone = '{}'",
+            updated.timezone.replace('\'', "''")
+        );
+        session.sql(&sql).await?.collect().await?;
+    }
+    *settings.lock().map_err(|_| poisoned())? = updated;
+    Ok(())
+}
+
+/// Run a settings statement against `settings`. `SET` also forwards the
+/// time zone to DataFusion. Returns `name`/`value` rows like DataFusion's
+/// own `SHOW`.
+pub(super) async fn run_session_statement(
+    session: &SessionContext,
+    settings: &Arc<Mutex<SessionSettings>>,
+    statement: SessionStatement,
+) -> Result<Table> {
+    let current = |name: &str| -> Result<Row> {
+        let settings = settings.lock().map_err(|_| poisoned())?;
+        let value = match settings.get(name) {
+            Some(Value::Null) | None => Value::Null,
+            Some(value) => Value::String(value.to_string()),
+        };
+        Ok(Row::new(vec![Value::String(name.to_string()), value]))
+    };
+
+    let rows = match statement {
+        SessionStatement::Set { name, value } => {
+            apply_setting(session, settings, &name, &value).await?;
+            vec![current(&name)?]
+        }
+        SessionStatement::Show(name) => vec![current(&name)?],
+        SessionStatement::ShowAll => {
+            let mut rows = SETTING_NAMES
+                .iter()
+                .map(|name| current(name))
+                .collect::<Result<Vec<_>>>()?;
+            let batches = session.sql("SHOW ALL").await?.collect().await?;
+            rows.extend(record_batch_to_table("settings", batches)?.rows);
+            rows
+        }
+    };
+
+    let schema = Schema::new(vec![
+        Column::new("name", DataType::String),
+        Column::new("value", DataType::String),
+    ]);
+    Ok(Table::with_rows("settings", schema, rows))
+}
+
+fn poisoned() -> DataFusionError {
+    DataFusionError::InvalidSetting("settings are unavailable after a panic".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_session_statement() {
+        assert_eq!(
+            parse_session_statement("SET max_rows = 1000;"),
+            Some(SessionStatement::Set {
+                name: "max_rows".to_string(),
+                value: "1000".to_string()
+            })
+        );
+        assert_eq!(
+            parse_session_statement("set TimeZone TO 'Europe/Berlin'"),
+            Some(SessionStatement::Set {
+                name: "timezone".to_string(),
+                value: "Europe/Berlin".to_string()
+            })
+        );
+        assert_eq!(
+            parse_session_statement("SET max_rows = 10 -- small"),
+            Some(SessionStatement::Set {
+                name: "max_rows".to_string(),
+                value: "10".to_string()
+            })
+        );
+        assert_eq!(
+            parse_session_statement("/* tz */ SET timezone = '+01:00' /* ; */;"),
+            Some(SessionStatement::Set {
+                name: "timezone".to_string(),
+                value: "+01:00".to_string()
+            })
+        );
+        assert_eq!(
+            parse_session_statement("show all"),
+            Some(SessionStatement::ShowAll)
+        );
+        assert_eq!(
+            parse_session_statement("SHOW max_col_width"),
+            Some(SessionStatement::Show("max_col_width".to_string()))
+        );
+
+        assert!(parse_session_statement("SET datafusion.execution.batch_size = 10").is_none());
+        assert!(parse_session_statement("SHOW TABLES").is_none());
+        assert!(parse_session_statement("SET max_rows").is_none());
+        assert!(parse_session_statement("SELECT 1").is_none());
+    }
+
+    #[test]
+    fn test_settings_values() {
+        let mut settings = SessionSettings::default();
+        settings.set("max_rows", "5").unwrap();
+        settings.set("timezone", "Europe/Berlin").unwrap();
+        settings.set("max_col_width", "12").unwrap();
+        assert_eq!(settings.max_rows, Some(5));
+        assert_eq!(settings.timezone, "Europe/Berlin");
+        assert_eq!(settings.get("max_col_width"), Some(Value::Integer(12)));
+        settings.set("timeout_secs", "30").unwrap();
+        assert_eq!(settings.timeout_secs, Some(30));
+
+        settings.set("max_rows", "none").unwrap();
+        settings.set("max_col_width", "DEFAULT").unwrap();
+        assert_eq!(settings.get("max_rows"), Some(Value::Null));
+        assert_eq!(settings.max_col_width, DEFAULT_MAX_COL_WIDTH);
+
+        assert!(settings.set("max_rows", "-1").is_err());
+        assert!(settings.set("timezone", "Mars/Olympus").is_err());
+        assert!(settings.set("max_col_width", "0").is_err());
+        assert!(settings.set("timeout_secs", "0").is_err());
+        assert!(settings.set("max_memory_mb", "lots").is_err());
+        assert!(settings.set("colour", "blue").is_err());
+    }
+}
@@ -0,0 +1,185 @@
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+
+use arrow::datatypes::SchemaRef;
+use arrow::ipc::reader::FileReader;
+use arrow::ipc::writer::FileWriter;
+use arrow::record_batch::RecordBatch;
+use tempfile::TempPath;
+
+use crate::storage::table::{Row, Schema, SpilledRows};
+
+use super::conversion::{convert_schema, record_batch_to_table};
+use super::error::Result;
+use super::stream::{BatchWriter, ExportFormat};
+
+/// A query result too large to hold in memory, kept in a temporary Arrow
+/// IPC file instead; see the `result_memory_mb` setting. Rows are read
+/// back a range at a time. The file is deleted when the last reference to
+/// the result is dropped.
+#[derive(Debug)]
+pub struct SpilledResult {
+    path: TempPath,
+    schema: Schema,
+    /// Row index at which each batch of the file starts, followed by the
+    /// total row count.
+    batch_starts: Vec<usize>,
+}
+
+impl SpilledRows for SpilledResult {
+    fn row_count(&self) -> usize {
+        self.batch_starts.last().copied().unwrap_or(0)
+    }
+
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    fn rows(&self, start: usize, count: usize) -> io::Result<Vec<Row>> {
+        self.read_rows(start, count).map_err(io::Error::other)
+    }
+}
+
+impl SpilledResult {
+    /// The temporary file holding the rows.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Up to `count` rows starting at row `start`, reading only the batches
+    /// that hold them.
+    fn read_rows(&self, start: usize, count: usize) -> Result<Vec<Row>> {
+        let end = start.saturating_add(count).min(self.row_count());
+        if start >= end {
+            return Ok(Vec::new());
+        }
+        let mut reader = FileReader::try_new(File::open(&self.path)?, None)?;
+        // The last batch starting at or before `start`
+        let first = self.batch_starts.partition_point(|&s| s <= start) - 1;
+        let mut batches = Vec::new();
+        for index in first..self.batch_starts.len() - 1 {
+            let batch_start = self.batch_starts[index];
+            if batch_start >= end {
+                break;
+            }
+            reader.set_index(index)?;
+            let Some(batch) = reader.next() else {
+                break;
+            };
+            let batch = batch?;
+            let offset = start.saturating_sub(batch_start);
+            let len = (end - batch_start).min(batch.num_rows()) - offset;
+            batches.push(batch.slice(offset, len));
+        }
+        Ok(record_batch_to_table("page", batches)?.rows)
+    }
+
+    /// Write every row to `path` in `format`, a batch at a time. Returns the
+    /// number of rows written.
+    pub fn export(&self, path: &Path, format: ExportFormat) -> Result<u64> {
+        let reader = FileReader::try_new(File::open(&self.path)?, None)?;
+        let mut writer = BatchWriter::new(File::create(path)?, reader.schema(), format)?;
+        for batch in reader {
+            writer.write(&batch?)?;
+        }
+        writer.finish()?;
+        Ok(self.row_count() as u64)
+    }
+}
+
+/// Writes the batches of a result to a new spill file.
+pub(super) struct SpillWriter {
+    path: TempPath,
+    writer: FileWriter<BufWriter<File>>,
+    schema: SchemaRef,
+    batch_starts: Vec<usize>,
+}
+
+impl SpillWriter {
+    /// Create the file with a random name that no other file has, so a
+    /// file or link planted at a predictable path is never written through.
+    pub(super) fn new(schema: SchemaRef) -> Result<Self> {
+        let (file, path) = tempfile::Builder::new()
+            .prefix("knowhere-spill-")
+            .suffix(".arrow")
+            .tempfile()?
+            .into_parts();
+        let writer = FileWriter::try_new(BufWriter::new(file), &schema)?;
+        Ok(Self {
+            path,
+            writer,
+            schema,
+            batch_starts: vec![0],
+        })
+    }
+
+    pub(super) fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        if batch.num_rows() == 0 {
+            return Ok(());
+        }
+        self.writer.write(batch)?;
+        let rows = self.batch_starts.last().copied().unwrap_or(0) + batch.num_rows();
+        self.batch_starts.push(rows);
+        Ok(())
+    }
+
+    pub(super) fn finish(mut self) -> Result<SpilledResult> {
+        // Dropping `self.path` removes the file if finishing fails
+        self.writer.finish()?;
+        Ok(SpilledResult {
+            path: self.path,
+            schema: convert_schema(&self.schema)?,
+            batch_starts: self.batch_starts,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::Int64Array;
+    use arrow::datatypes::{DataType as ArrowDataType, Field, Schema as ArrowSchema};
+
+    use crate::storage::table::Value;
+
+    use super::*;
+
+    #[test]
+    fn test_spilled_rows() {
+        let schema = Arc::new(ArrowSchema::new(vec![Field::new(
+            "n",
+            ArrowDataType::Int64,
+            false,
+        )]));
+        let mut writer = SpillWriter::new(schema.clone()).unwrap();
+        for start in [0, 4, 8] {
+            let values = Int64Array::from_iter_values(start..start + 4);
+            let batch = RecordBatch::try_new(schema.clone(), vec![Arc::new(values)]).unwrap();
+            writer.write(&batch).unwrap();
+        }
+        let spilled = writer.finish().unwrap();
+        assert_eq!(spilled.row_count(), 12);
+
+        let values: Vec<Value> = spilled
+            .rows(3, 6)
+            .unwrap()
+            .into_iter()
+            .map(|row| row.values[0].clone())
+            .collect();
+        assert_eq!(values, (3..9).map(Value::Integer).collect::<Vec<_>>());
+        assert_eq!(spilled.rows(10, 100).unwrap().len(), 2);
+        assert!(spilled.rows(12, 1).unwrap().is_empty());
+
+        let dir = tempfile::tempdir().unwrap();
+        let csv = dir.path().join("all.csv");
+        assert_eq!(spilled.export(&csv, ExportFormat::Csv).unwrap(), 12);
+        assert_eq!(std::fs::read_to_string(&csv).unwrap().lines().count(), 13);
+
+        let path = spilled.path().to_path_buf();
+        assert!(path.exists());
+        drop(spilled);
+        assert!(!path.exists());
+    }
+}
@@ -12,10 +12,13 @@ use datafusion::physical_plan::ExecutionPlan;
 use datafusion_datasource::memory::MemorySourceConfig;
 use rusqlite::Connection;
 use std::any::Any;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use super::conversion::SOURCE_TYPE_KEY;
 use super::error::{DataFusionError, Result};
+use super::names::quote_identifier;
 
 #[derive(Debug)]
 pub struct SqliteTableProvider {
@@ -90,7 +93,12 @@ impl SqliteTableProvider {
                     _ => ArrowDataType::Utf8,
                 };
 
-                Ok(Field::new(name, arrow_type, not_null == 0))
+                let field = Field::new(name, arrow_type, not_null == 0);
+                Ok(if type_name.is_empty() {
+                    field
+                } else {
+                    field.with_metadata(HashMap::from([(SOURCE_TYPE_KEY.to_string(), type_name)]))
+                })
             })?
             .collect::<std::result::Result<Vec<_>, _>>()?;
 
@@ -99,7 +107,7 @@ impl SqliteTableProvider {
 
     fn read_table_data(&self) -> Result<Vec<RecordBatch>> {
         let conn = Connection::open(&self.db_path)?;
-        let query = format!("SELECT * FROM {}", self.table_name);
+        let query = format!("SELECT * FROM {}", quote_identifier(&self.table_name));
         let mut stmt = conn.prepare(&query)?;
 
         let _column_count = stmt.column_count();
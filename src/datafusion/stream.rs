@@ -0,0 +1,255 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, StringArray};
+use arrow::csv::WriterBuilder as CsvWriterBuilder;
+use arrow::datatypes::{DataType as ArrowDataType, Field, Schema as ArrowSchema, SchemaRef};
+use arrow::ipc::writer::StreamWriter;
+use arrow::json::LineDelimitedWriter;
+use arrow::record_batch::RecordBatch;
+use datafusion::execution::context::SessionContext;
+use datafusion::physical_plan::execute_stream;
+use futures::StreamExt;
+use parquet::arrow::ArrowWriter;
+
+use crate::render::MaskRules;
+use crate::storage::table::{Table, Value, SPILL_PAGE_ROWS};
+
+use super::conversion::{convert_array_value, rows_to_record_batch};
+use super::error::Result;
+use super::query::QueryOptions;
+use super::rewrite::sql_to_dataframe;
+
+/// File formats query results can be streamed into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    /// Newline-delimited JSON, one object per row.
+    Json,
+    Parquet,
+}
+
+impl ExportFormat {
+    /// The format for a file extension (`csv`, `json`, `jsonl`, `ndjson`,
+    /// `parquet`), if it is one.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_lowercase().as_str() {
+            "csv" => Some(ExportFormat::Csv),
+            "json" | "jsonl" | "ndjson" => Some(ExportFormat::Json),
+            "parquet" => Some(ExportFormat::Parquet),
+            _ => None,
+        }
+    }
+}
+
+/// What an export wrote.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExportSummary {
+    pub rows: u64,
+    /// Set when the query produced more rows than its `max_rows` limit and
+    /// only the first `max_rows` were written.
+    pub truncated: bool,
+}
+
+/// Run `sql` under the limits of `options` and write its batches to `path`
+/// as they are produced, so memory use stays flat however large the result
+/// is. Columns matched by `masks` are masked batch by batch.
+pub(super) async fn export_stream(
+    session: &SessionContext,
+    sql: &str,
+    strict: bool,
+    path: &Path,
+    format: ExportFormat,
+    options: QueryOptions,
+    masks: &MaskRules,
+) -> Result<ExportSummary> {
+    let df = sql_to_dataframe(session, sql, strict).await?;
+    let task_ctx = options.limit_memory(df.task_ctx())?;
+    let plan = df.create_physical_plan().await?;
+    let schema = plan.schema();
+
+    let file = File::create(path)?;
+    let mut writer = BatchWriter::masked(file, schema.clone(), format, masks)?;
+    let mut stream = execute_stream(plan, Arc::new(task_ctx))?;
+    let max_rows = options.max_rows.map_or(u64::MAX, |rows| rows as u64);
+    let mut rows = 0;
+    let mut truncated = false;
+    while !truncated {
+        // Let a timeout take effect between batches that are already ready
+        tokio::task::consume_budget().await;
+        let Some(batch) = stream.next().await else {
+            break;
+        };
+        let batch = batch?;
+        truncated = batch.num_rows() as u64 > max_rows - rows;
+        let batch = batch.slice(0, batch.num_rows().min((max_rows - rows) as usize));
+        rows += batch.num_rows() as u64;
+        if batch.num_rows() > 0 {
+            writer.write(&batch)?;
+        }
+    }
+    if rows == 0 {
+        // So a CSV export still gets its header row
+        writer.write(&RecordBatch::new_empty(schema))?;
+    }
+    writer.finish()?;
+    Ok(ExportSummary { rows, truncated })
+}
+
+/// Schema metadata key set to `true` in [`table_to_ipc`] output when the
+/// query stopped at its `max_rows` limit.
+pub const IPC_TRUNCATED_KEY: &str = "knowhere:truncated";
+
+/// `table` in the Arrow IPC stream format, which readers such as arrow-js
+/// decode without converting every cell to JSON. Rows are written
+/// [`SPILL_PAGE_ROWS`] to a batch. The rows of a spilled result that are
+/// only on disk are read back a page at a time and masked with `masks`; the
+/// rows held in memory are expected to be masked already.
+pub fn table_to_ipc(table: &Table, masks: &MaskRules) -> Result<Vec<u8>> {
+    let empty = rows_to_record_batch(&table.schema, &[])?;
+    let mut schema = empty.schema().as_ref().clone();
+    if table.truncated {
+        schema
+            .metadata
+            .insert(IPC_TRUNCATED_KEY.to_string(), "true".to_string());
+    }
+    let schema = Arc::new(schema);
+
+    let mut writer = StreamWriter::try_new(Vec::new(), &schema)?;
+    for rows in table.rows.chunks(SPILL_PAGE_ROWS) {
+        let batch = rows_to_record_batch(&table.schema, rows)?;
+        writer.write(&batch.with_schema(schema.clone())?)?;
+    }
+    for page in table.spilled_pages() {
+        let mut page = page?;
+        masks.apply(&mut page);
+        let batch = rows_to_record_batch(&table.schema, &page.rows)?;
+        writer.write(&batch.with_schema(schema.clone())?)?;
+    }
+    Ok(writer.into_inner()?)
+}
+
+/// Writes record batches to a file in an [`ExportFormat`].
+pub(super) enum BatchWriter {
+    Csv(Box<arrow::csv::Writer<BufWriter<File>>>),
+    Json(LineDelimitedWriter<BufWriter<File>>),
+    Parquet(Box<ArrowWriter<File>>),
+    /// Masks each batch with the rules before writing it.
+    Masked(Box<BatchWriter>, MaskRules),
+}
+
+impl BatchWriter {
+    pub(super) fn new(file: File, schema: SchemaRef, format: ExportFormat) -> Result<Self> {
+        Ok(match format {
+            ExportFormat::Csv => BatchWriter::Csv(Box::new(
+                CsvWriterBuilder::new()
+                    .with_header(true)
+                    .build(BufWriter::new(file)),
+            )),
+            ExportFormat::Json => BatchWriter::Json(LineDelimitedWriter::new(BufWriter::new(file))),
+            ExportFormat::Parquet => {
+                BatchWriter::Parquet(Box::new(ArrowWriter::try_new(file, schema, None)?))
+            }
+        })
+    }
+
+    /// A writer that masks the columns of each batch matched by `masks`,
+    /// which are written as strings. Without rules this is [`new`](Self::new).
+    pub(super) fn masked(
+        file: File,
+        schema: SchemaRef,
+        format: ExportFormat,
+        masks: &MaskRules,
+    ) -> Result<Self> {
+        if masks.is_empty() {
+            return Self::new(file, schema, format);
+        }
+        let writer = Self::new(file, masked_schema(&schema, masks), format)?;
+        Ok(BatchWriter::Masked(Box::new(writer), masks.clone()))
+    }
+
+    pub(super) fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        match self {
+            BatchWriter::Csv(writer) => writer.write(batch)?,
+            BatchWriter::Json(writer) => writer.write(batch)?,
+            BatchWriter::Parquet(writer) => writer.write(batch)?,
+            BatchWriter::Masked(writer, masks) => writer.write(&mask_batch(batch, masks)?)?,
+        }
+        Ok(())
+    }
+
+    pub(super) fn finish(self) -> Result<()> {
+        match self {
+            BatchWriter::Csv(writer) => writer.into_inner().flush()?,
+            BatchWriter::Json(mut writer) => {
+                writer.finish()?;
+                writer.into_inner().flush()?;
+            }
+            BatchWriter::Parquet(writer) => {
+                writer.close()?;
+            }
+            BatchWriter::Masked(writer, _) => writer.finish()?,
+        }
+        Ok(())
+    }
+}
+
+/// `schema` with the columns matched by `masks` turned into strings.
+fn masked_schema(schema: &SchemaRef, masks: &MaskRules) -> SchemaRef {
+    let fields: Vec<Field> = schema
+        .fields()
+        .iter()
+        .map(|field| match masks.masker(field.name()) {
+            Some(_) => Field::new(field.name(), ArrowDataType::Utf8, true),
+            None => field.as_ref().clone(),
+        })
+        .collect();
+    Arc::new(ArrowSchema::new(fields))
+}
+
+/// Mask the columns of `batch` matched by `masks` the way
+/// [`MaskRules::apply`] masks a table, leaving NULLs as they are.
+fn mask_batch(batch: &RecordBatch, masks: &MaskRules) -> Result<RecordBatch> {
+    let schema = batch.schema();
+    let mut columns = Vec::with_capacity(batch.num_columns());
+    for (field, array) in schema.fields().iter().zip(batch.columns()) {
+        let Some(mask) = masks.masker(field.name()) else {
+            columns.push(array.clone());
+            continue;
+        };
+        let values = (0..array.len())
+            .map(|i| {
+                Ok(match convert_array_value(array, i)? {
+                    Value::Null => None,
+                    value => Some(mask(&value.to_string())),
+                })
+            })
+            .collect::<Result<StringArray>>()?;
+        columns.push(Arc::new(values) as ArrayRef);
+    }
+    Ok(RecordBatch::try_new(
+        masked_schema(&schema, masks),
+        columns,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_from_extension() {
+        assert_eq!(ExportFormat::from_extension("CSV"), Some(ExportFormat::Csv));
+        assert_eq!(
+            ExportFormat::from_extension("ndjson"),
+            Some(ExportFormat::Json)
+        );
+        assert_eq!(
+            ExportFormat::from_extension("parquet"),
+            Some(ExportFormat::Parquet)
+        );
+        assert_eq!(ExportFormat::from_extension("db"), None);
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::HashSet;
+use std::ops::ControlFlow;
+
+use datafusion::common::{Column, SchemaError};
+use datafusion::error::DataFusionError as DFError;
+use datafusion::sql::parser::Statement as DFStatement;
+use datafusion::sql::sqlparser::ast::{Ident, Query, SelectItem, SetExpr, Visit, Visitor};
+
+use super::error::DataFusionError;
+
+/// Most suggestions offered for a missing column.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Turn DataFusion's "no field named" planning error into
+/// [`DataFusionError::ColumnNotFound`], with the closest available column
+/// names as suggestions. `aliases` are the SELECT aliases of the statement,
+/// used to explain references to an alias where it is not visible.
+pub(super) fn column_not_found(err: DFError, aliases: &HashSet<String>) -> DataFusionError {
+    let DFError::SchemaError(
+        SchemaError::FieldNotFound {
+            field,
+            valid_fields,
+        },
+        _,
+    ) = err.find_root()
+    else {
+        return err.into();
+    };
+
+    let column = field.flat_name();
+    let alias = aliases.contains(&field.name);
+    let suggestions = suggest(field, valid_fields);
+    let mut available: Vec<String> = valid_fields.iter().map(Column::flat_name).collect();
+    available.dedup();
+
+    DataFusionError::ColumnNotFound {
+        column,
+        suggestions,
+        available,
+        alias,
+    }
+}
+
+/// Column names within a small edit distance of `field`, closest first. A
+/// qualified reference, or a name that appears in more than one table, is
+/// suggested in its qualified form so it can be pasted back as is.
+fn suggest(field: &Column, valid_fields: &[Column]) -> Vec<String> {
+    let wanted = field.name.to_lowercase();
+    let max_distance = (wanted.chars().count() / 3).max(1);
+
+    let mut scored: Vec<(usize, String)> = valid_fields
+        .iter()
+        .filter(|c| field.relation.is_none() || c.relation == field.relation)
+        .filter_map(|c| {
+            let distance = edit_distance(&wanted, &c.name.to_lowercase());
+            if distance > max_distance {
+                return None;
+            }
+            let ambiguous = valid_fields
+                .iter()
+                .filter(|other| other.name == c.name)
+                .count()
+                > 1;
+            let name = if field.relation.is_some() || ambiguous {
+                c.flat_name()
+            } else {
+                c.name.clone()
+            };
+            Some((distance, name))
+        })
+        .collect();
+    scored.sort();
+
+    let mut suggestions: Vec<String> = Vec::new();
+    for (_, name) in scored {
+        if !suggestions.contains(&name) {
+            suggestions.push(name);
+        }
+    }
+    suggestions.truncate(MAX_SUGGESTIONS);
+    suggestions
+}
+
+/// Edit distance between `a` and `b` in characters, where swapping two
+/// adjacent characters counts as one edit (optimal string alignment).
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(d[i - 2][j - 2] + 1);
+            }
+            d[i][j] = best;
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Aliases given to SELECT items anywhere in `statement`, normalized the way
+/// DataFusion normalizes column names.
+pub(super) fn select_aliases(statement: &DFStatement) -> HashSet<String> {
+    let mut collector = AliasCollector::default();
+    match statement {
+        DFStatement::Statement(inner) => {
+            let _ = inner.visit(&mut collector);
+        }
+        DFStatement::Explain(explain) => return select_aliases(&explain.statement),
+        _ => {}
+    }
+    collector.aliases
+}
+
+#[derive(Default)]
+struct AliasCollector {
+    aliases: HashSet<String>,
+}
+
+impl Visitor for AliasCollector {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<()> {
+        if let SetExpr::Select(select) = query.body.as_ref() {
+            for item in &select.projection {
+                if let SelectItem::ExprWithAlias { alias, .. } = item {
+                    self.aliases.insert(normalize(alias));
+                }
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+fn normalize(ident: &Ident) -> String {
+    match ident.quote_style {
+        Some(_) => ident.value.clone(),
+        None => ident.value.to_lowercase(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn columns(names: &[(&str, &str)]) -> Vec<Column> {
+        names
+            .iter()
+            .map(|(table, name)| Column::new(Some(*table), *name))
+            .collect()
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("name", "name"), 0);
+        assert_eq!(edit_distance("nmae", "name"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("salry", "salary"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_suggest_unqualified() {
+        let valid = columns(&[("u", "id"), ("u", "salary"), ("o", "id"), ("o", "status")]);
+        assert_eq!(
+            suggest(&Column::new_unqualified("salry"), &valid),
+            vec!["salary"]
+        );
+        // `id` exists on both sides, so it is suggested qualified
+        assert_eq!(
+            suggest(&Column::new_unqualified("idd"), &valid),
+            vec!["o.id", "u.id"]
+        );
+        assert!(suggest(&Column::new_unqualified("zzzzzz"), &valid).is_empty());
+    }
+
+    #[test]
+    fn test_suggest_qualified_stays_in_relation() {
+        let valid = columns(&[("u", "status"), ("o", "stats")]);
+        assert_eq!(
+            suggest(&Column::new(Some("o"), "statu"), &valid),
+            vec!["o.stats"]
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use datafusion::execution::context::SessionContext;
+
+use crate::storage::table::{Column, DataType, Row, Schema, Table, Value};
+
+use super::cache::strip_keyword;
+use super::conversion::record_batch_to_table;
+use super::error::Result;
+use super::names::quote_identifier;
+use super::rewrite::sql_to_dataframe;
+
+/// Quantiles reported per numeric column, as column name and fraction.
+const QUANTILES: [(&str, f64); 3] = [("p25", 0.25), ("p50", 0.5), ("p75", 0.75)];
+
+/// Recognise `SUMMARIZE table` and `SUMMARIZE query`, returning the query
+/// whose result to summarize.
+pub(super) fn parse_summarize(sql: &str) -> Option<String> {
+    let rest = strip_keyword(sql.trim_start(), "SUMMARIZE")?;
+    let rest = rest.trim().trim_end_matches(';').trim_end();
+    if rest.is_empty() {
+        return None;
+    }
+    let is_query = rest.starts_with('(')
+        || ["SELECT", "WITH", "VALUES"]
+            .iter()
+            .any(|keyword| strip_keyword(rest, keyword).is_some());
+    Some(if is_query {
+        rest.to_string()
+    } else {
+        format!("SELECT * FROM {}", rest)
+    })
+}
+
+/// One row per column of `query`'s result, like pandas' `describe()`: the
+/// count of non-null values, the null count, the minimum and maximum and,
+/// for numeric columns, the mean, standard deviation and quartiles. The
+/// quartiles are approximate, computed with `approx_percentile_cont`.
+pub(super) async fn summarize(
+    session: &SessionContext,
+    query: &str,
+    strict: bool,
+) -> Result<Table> {
+    let df = sql_to_dataframe(session, query, strict).await?;
+    let fields: Vec<_> = df.schema().fields().iter().cloned().collect();
+
+    let mut aggregates = vec!["COUNT(*)".to_string()];
+    for field in &fields {
+        let column = quote_identifier(field.name());
+        let value = format!("CAST({} AS DOUBLE)", column);
+        let numeric = field.data_type().is_numeric();
+        let ordered = numeric
+            || field.data_type().is_temporal()
+            || matches!(
+                field.data_type(),
+                arrow::datatypes::DataType::Utf8
+                    | arrow::datatypes::DataType::LargeUtf8
+                    | arrow::datatypes::DataType::Utf8View
+                    | arrow::datatypes::DataType::Boolean
+            );
+        let numeric_aggregate = |aggregate: String| {
+            if numeric {
+                aggregate
+            } else {
+                "CAST(NULL AS DOUBLE)".to_string()
+            }
+        };
+        let bound = |function: &str| {
+            if ordered {
+                format!("CAST({}({}) AS VARCHAR)", function, column)
+            } else {
+                "CAST(NULL AS VARCHAR)".to_string()
+            }
+        };
+
+        aggregates.push(format!("COUNT({})", column));
+        aggregates.push(numeric_aggregate(format!("AVG({})", value)));
+        aggregates.push(numeric_aggregate(format!("STDDEV({})", value)));
+        aggregates.push(bound("MIN"));
+        aggregates.push(bound("MAX"));
+    }
+
+    // Aliased because the NULL placeholders would otherwise share a name
+    let aggregates: Vec<String> = aggregates
+        .iter()
+        .enumerate()
+        .map(|(i, aggregate)| format!("{} AS s{}", aggregate, i))
+        .collect();
+    let sql = format!(
+        "SELECT {} FROM ({}) AS source",
+        aggregates.join(", "),
+        query
+    );
+    let batches = sql_to_dataframe(session, &sql, false)
+        .await?
+        .collect()
+        .await?;
+    let totals = record_batch_to_table("summary", batches)?;
+    let values = totals
+        .rows
+        .into_iter()
+        .next()
+        .map(|row| row.values)
+        .unwrap_or_default();
+
+    let total = match values.first() {
+        Some(Value::Integer(n)) => *n,
+        _ => 0,
+    };
+    let mut rows = Vec::with_capacity(fields.len());
+    for (i, field) in fields.iter().enumerate() {
+        let stats = &values[1 + i * 5..1 + (i + 1) * 5];
+        let count = match stats[0] {
+            Value::Integer(n) => n,
+            _ => 0,
+        };
+        let mut row = vec![
+            Value::String(field.name().clone()),
+            Value::String(field.data_type().to_string()),
+            Value::Integer(count),
+            Value::Integer(total - count),
+        ];
+        row.extend_from_slice(&stats[1..4]);
+        if field.data_type().is_numeric() {
+            row.extend(quantiles(session, query, field.name()).await?);
+        } else {
+            row.extend(QUANTILES.iter().map(|_| Value::Null));
+        }
+        row.push(stats[4].clone());
+        rows.push(Row::new(row));
+    }
+
+    let mut columns = vec![
+        Column::new("column_name", DataType::String),
+        Column::new("data_type", DataType::String),
+        Column::new("count", DataType::Integer),
+        Column::new("null_count", DataType::Integer),
+        Column::new("mean", DataType::Float),
+        Column::new("std", DataType::Float),
+        Column::new("min", DataType::String),
+    ];
+    columns.extend(
+        QUANTILES
+            .iter()
+            .map(|(name, _)| Column::new(*name, DataType::Float)),
+    );
+    columns.push(Column::new("max", DataType::String));
+    Ok(Table::with_rows("summary", Schema::new(columns), rows))
+}
+
+/// The [`QUANTILES`] of numeric column `name` of `query`'s result. They get
+/// a query of their own because DataFusion cannot compute ordered-set
+/// aggregates over different columns in one query.
+async fn quantiles(session: &SessionContext, query: &str, name: &str) -> Result<Vec<Value>> {
+    let value = format!("CAST({} AS DOUBLE)", quote_identifier(name));
+    let aggregates: Vec<String> = QUANTILES
+        .iter()
+        .map(|(alias, fraction)| {
+            format!(
+                "approx_percentile_cont({}) WITHIN GROUP (ORDER BY {}) AS {}",
+                fraction, value, alias
+            )
+        })
+        .collect();
+    let sql = format!(
+        "SELECT {} FROM ({}) AS source",
+        aggregates.join(", "),
+        query
+    );
+    let batches = sql_to_dataframe(session, &sql, false)
+        .await?
+        .collect()
+        .await?;
+    let table = record_batch_to_table("quantiles", batches)?;
+    Ok(table
+        .rows
+        .into_iter()
+        .next()
+        .map_or_else(|| vec![Value::Null; QUANTILES.len()], |row| row.values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_summarize() {
+        assert_eq!(
+            parse_summarize("summarize users;").as_deref(),
+            Some("SELECT * FROM users")
+        );
+        assert_eq!(
+            parse_summarize("SUMMARIZE SELECT age FROM users").as_deref(),
+            Some("SELECT age FROM users")
+        );
+        assert_eq!(
+            parse_summarize("SUMMARIZE \"my table\"").as_deref(),
+            Some("SELECT * FROM \"my table\"")
+        );
+        assert!(parse_summarize("SUMMARIZE").is_none());
+        assert!(parse_summarize("SELECT * FROM summarize").is_none());
+    }
+}
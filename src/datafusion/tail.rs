@@ -0,0 +1,167 @@
+use arrow::csv::reader::Format;
+use arrow::csv::ReaderBuilder;
+use arrow::datatypes::SchemaRef;
+use arrow::record_batch::RecordBatch;
+use datafusion::catalog::Session;
+use datafusion::datasource::TableProvider;
+use datafusion::error::DataFusionError as DFError;
+use datafusion::error::Result as DFResult;
+use datafusion::logical_expr::TableType;
+use datafusion::physical_plan::ExecutionPlan;
+use datafusion_datasource::memory::MemorySourceConfig;
+use std::any::Any;
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use super::error::{DataFusionError, Result};
+
+/// Rows read to infer the schema when the table is registered.
+const INFER_SCHEMA_ROWS: usize = 1000;
+
+/// A CSV file that is still being written to, such as a log or an export in
+/// progress.
+///
+/// Rows already parsed are kept in memory together with the byte offset they
+/// end at. Each scan parses only the complete lines appended since the
+/// previous one, so queries see the latest writes without re-reading the
+/// whole file. A trailing line without a newline is left for the next scan.
+/// If the file shrinks, e.g. after log rotation, it is read again from the
+/// start. The schema is inferred once at registration; refresh the table to
+/// infer it again.
+#[derive(Debug)]
+pub struct TailingCsvTable {
+    path: PathBuf,
+    schema: SchemaRef,
+    state: Mutex<TailState>,
+}
+
+#[derive(Debug, Default)]
+struct TailState {
+    offset: u64,
+    batches: Vec<RecordBatch>,
+}
+
+impl TailingCsvTable {
+    pub fn try_new(path: &Path) -> Result<Self> {
+        let file = BufReader::new(File::open(path)?);
+        let (schema, _) = Format::default()
+            .with_header(true)
+            .infer_schema(file, Some(INFER_SCHEMA_ROWS))?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            schema: Arc::new(schema),
+            state: Mutex::new(TailState::default()),
+        })
+    }
+
+    /// Parse whatever was appended since the last call and return every row
+    /// read so far.
+    fn read_appended(&self) -> Result<Vec<RecordBatch>> {
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|_| DataFusionError::Conversion("CSV tail state poisoned".to_string()))?;
+
+        let mut file = File::open(&self.path)?;
+        if file.metadata()?.len() < state.offset {
+            *state = TailState::default();
+        }
+
+        file.seek(SeekFrom::Start(state.offset))?;
+        let mut appended = Vec::new();
+        file.read_to_end(&mut appended)?;
+        let Some(last_newline) = appended.iter().rposition(|&b| b == b'\n') else {
+            return Ok(state.batches.clone());
+        };
+        let complete = &appended[..=last_newline];
+
+        let reader = ReaderBuilder::new(self.schema.clone())
+            .with_header(state.offset == 0)
+            .build(Cursor::new(complete))?;
+        for batch in reader {
+            state.batches.push(batch?);
+        }
+        state.offset += complete.len() as u64;
+
+        Ok(state.batches.clone())
+    }
+}
+
+#[async_trait::async_trait]
+impl TableProvider for TailingCsvTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        _state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        _filters: &[datafusion::prelude::Expr],
+        _limit: Option<usize>,
+    ) -> DFResult<Arc<dyn ExecutionPlan>> {
+        let batches = self
+            .read_appended()
+            .map_err(|e| DFError::External(Box::new(e)))?;
+
+        let exec =
+            MemorySourceConfig::try_new_exec(&[batches], self.schema.clone(), projection.cloned())?;
+
+        Ok(exec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn row_count(table: &TailingCsvTable) -> usize {
+        let batches = table.read_appended().unwrap();
+        batches.iter().map(|batch| batch.num_rows()).sum()
+    }
+
+    #[test]
+    fn test_reads_only_complete_appended_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.csv");
+        std::fs::write(&path, "id,kind\n1,open\n").unwrap();
+
+        let table = TailingCsvTable::try_new(&path).unwrap();
+        assert_eq!(row_count(&table), 1);
+
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        file.write_all(b"2,close\n3,op").unwrap();
+        assert_eq!(row_count(&table), 2);
+
+        file.write_all(b"en\n").unwrap();
+        assert_eq!(row_count(&table), 3);
+    }
+
+    #[test]
+    fn test_rereads_truncated_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.csv");
+        std::fs::write(&path, "id,kind\n1,open\n2,close\n").unwrap();
+
+        let table = TailingCsvTable::try_new(&path).unwrap();
+        assert_eq!(row_count(&table), 2);
+
+        std::fs::write(&path, "id,kind\n9,open\n").unwrap();
+        assert_eq!(row_count(&table), 1);
+    }
+}
@@ -0,0 +1,145 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datafusion::catalog::{MemorySchemaProvider, SchemaProvider, TableProvider};
+use datafusion::common::{exec_err, TableReference};
+use datafusion::error::Result as DFResult;
+use datafusion::execution::context::SessionContext;
+
+use crate::storage::table::Table;
+
+use super::cache::{parse_name_as_query, query_to_memtable, stored_table_summary, strip_keyword};
+use super::error::{DataFusionError, Result};
+
+/// A parsed `CREATE TEMP TABLE <name> AS <query>` statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct CreateTempTable<'a> {
+    pub(super) name: TableReference,
+    pub(super) query: &'a str,
+}
+
+/// Recognise `CREATE TEMP TABLE name AS query` (or `TEMPORARY`), which
+/// DataFusion rejects. Names follow the rules of `CACHE TABLE`.
+pub(super) fn parse_create_temp_table(sql: &str) -> Option<CreateTempTable<'_>> {
+    let rest = strip_keyword(sql.trim_start(), "CREATE")?;
+    let rest = strip_keyword(rest, "TEMP").or_else(|| strip_keyword(rest, "TEMPORARY"))?;
+    let rest = strip_keyword(rest, "TABLE")?;
+    parse_name_as_query(rest).map(|(name, query)| CreateTempTable { name, query })
+}
+
+/// Run `create.query` and keep its result as the temporary table
+/// `create.name`. Returns a one-row table with the name and row count.
+pub(super) async fn create_temp_table(
+    session: &SessionContext,
+    create: CreateTempTable<'_>,
+    strict: bool,
+) -> Result<Table> {
+    if session.table_exist(create.name.clone())? {
+        return Err(DataFusionError::TableAlreadyExists(create.name.to_string()));
+    }
+
+    let temp = temp_schema(session).ok_or_else(|| {
+        DataFusionError::Conversion("session has no schema for temporary tables".to_string())
+    })?;
+    let (table, rows) = query_to_memtable(session, create.query, strict).await?;
+    temp.register_table(create.name.table().to_string(), Arc::new(table))?;
+    Ok(stored_table_summary(create.name.table(), rows))
+}
+
+/// The schema holding the temporary tables of `session`, if its default
+/// schema is a [`SessionSchema`].
+pub(super) fn temp_schema(session: &SessionContext) -> Option<Arc<MemorySchemaProvider>> {
+    let state = session.state();
+    let defaults = &state.config().options().catalog;
+    let schema = session
+        .catalog(&defaults.default_catalog)?
+        .schema(&defaults.default_schema)?;
+    schema
+        .as_any()
+        .downcast_ref::<SessionSchema>()
+        .map(|schema| schema.temp.clone())
+}
+
+/// The session's default schema: the tables loaded from files and created
+/// with SQL, overlaid with the temporary tables of `CREATE TEMP TABLE`.
+///
+/// Temporary tables resolve by their bare name like any other table and can
+/// be dropped with `DROP TABLE`, but they are kept in their own schema and
+/// left out of [`table_names`](SchemaProvider::table_names), so they never
+/// show up in the table list.
+#[derive(Debug)]
+pub(super) struct SessionSchema {
+    tables: MemorySchemaProvider,
+    temp: Arc<MemorySchemaProvider>,
+}
+
+impl SessionSchema {
+    pub(super) fn new() -> Self {
+        Self {
+            tables: MemorySchemaProvider::new(),
+            temp: Arc::new(MemorySchemaProvider::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl SchemaProvider for SessionSchema {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn table_names(&self) -> Vec<String> {
+        self.tables.table_names()
+    }
+
+    async fn table(&self, name: &str) -> DFResult<Option<Arc<dyn TableProvider>>> {
+        match self.temp.table(name).await? {
+            Some(table) => Ok(Some(table)),
+            None => self.tables.table(name).await,
+        }
+    }
+
+    fn register_table(
+        &self,
+        name: String,
+        table: Arc<dyn TableProvider>,
+    ) -> DFResult<Option<Arc<dyn TableProvider>>> {
+        if self.temp.table_exist(&name) {
+            return exec_err!("The table {name} already exists as a temporary table");
+        }
+        self.tables.register_table(name, table)
+    }
+
+    fn deregister_table(&self, name: &str) -> DFResult<Option<Arc<dyn TableProvider>>> {
+        if self.temp.table_exist(name) {
+            return self.temp.deregister_table(name);
+        }
+        self.tables.deregister_table(name)
+    }
+
+    fn table_exist(&self, name: &str) -> bool {
+        self.temp.table_exist(name) || self.tables.table_exist(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_create_temp_table() {
+        let create =
+            parse_create_temp_table("create temp table Recent AS SELECT * FROM orders;").unwrap();
+        assert_eq!(create.name.table(), "recent");
+        assert_eq!(create.query, "SELECT * FROM orders");
+
+        let create =
+            parse_create_temp_table("CREATE TEMPORARY TABLE \"My T\" AS SELECT 1").unwrap();
+        assert_eq!(create.name.table(), "My T");
+
+        assert!(parse_create_temp_table("CREATE TABLE t AS SELECT 1").is_none());
+        assert!(parse_create_temp_table("CREATE TEMP VIEW v AS SELECT 1").is_none());
+        assert!(parse_create_temp_table("CREATE TEMP TABLE t").is_none());
+    }
+}
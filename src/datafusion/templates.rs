@@ -0,0 +1,269 @@
+use std::ops::ControlFlow;
+
+use datafusion::error::DataFusionError as DFError;
+use datafusion::sql::sqlparser::ast::{
+    Expr, FunctionArg, FunctionArgExpr, TableFactor, Value as SqlValue, VisitorMut,
+};
+use datafusion::sql::sqlparser::dialect::GenericDialect;
+use datafusion::sql::sqlparser::parser::Parser;
+
+/// Name of the row number column the templates filter on. It is excluded
+/// from their output.
+const RANK_COLUMN: &str = "__knowhere_rank";
+
+/// Expands the query templates used as table functions in FROM into
+/// window-function subqueries:
+///
+/// - `top_n(table, group_cols, order_col, n)` keeps the `n` rows with the
+///   highest `order_col` in each group.
+/// - `dedupe(table, key_cols [, order_col])` keeps one row per key, the one
+///   with the highest `order_col`.
+/// - `histogram(table, column, n_buckets)` splits the range of a numeric
+///   column into `n_buckets` equal-width buckets and counts the rows in
+///   each, including empty ones. Like any table, its rows are unordered
+///   without an `ORDER BY bucket`.
+///
+/// `table` and single columns may be identifiers or strings. Several
+/// columns are given as a string (`'region, city'`) or a parenthesized list
+/// (`(region, city)`); `''` means no grouping. An `order_col` string may end
+/// in `ASC` to pick the lowest values instead.
+pub(super) struct ExpandTemplates;
+
+impl VisitorMut for ExpandTemplates {
+    type Break = DFError;
+
+    fn pre_visit_table_factor(&mut self, factor: &mut TableFactor) -> ControlFlow<DFError> {
+        let TableFactor::Table {
+            name,
+            alias,
+            args: Some(args),
+            ..
+        } = factor
+        else {
+            return ControlFlow::Continue(());
+        };
+        let template = name.to_string().to_lowercase();
+        let sql = match template.as_str() {
+            "top_n" => top_n(&args.args),
+            "dedupe" => dedupe(&args.args),
+            "histogram" => histogram(&args.args),
+            _ => return ControlFlow::Continue(()),
+        };
+        let subquery = match sql.and_then(|sql| parse_query(&sql)) {
+            Ok(subquery) => subquery,
+            Err(err) => return ControlFlow::Break(err),
+        };
+
+        *factor = TableFactor::Derived {
+            lateral: false,
+            subquery: Box::new(subquery),
+            alias: alias.take(),
+        };
+        ControlFlow::Continue(())
+    }
+}
+
+fn top_n(args: &[FunctionArg]) -> Result<String, DFError> {
+    let [table, group, order, n] = args else {
+        return Err(usage("top_n(table, group_cols, order_col, n)"));
+    };
+    let n = arg_expr(n, "n")?;
+    Ok(ranked(
+        &single(table, "table")?,
+        &columns(group, "group_cols")?,
+        Some(&order_by(order)?),
+        &n.to_string(),
+    ))
+}
+
+fn dedupe(args: &[FunctionArg]) -> Result<String, DFError> {
+    let (table, keys, order) = match args {
+        [table, keys] => (table, keys, None),
+        [table, keys, order] => (table, keys, Some(order_by(order)?)),
+        _ => return Err(usage("dedupe(table, key_cols [, order_col])")),
+    };
+    let keys = columns(keys, "key_cols")?;
+    if keys.is_empty() {
+        return Err(DFError::Plan(
+            "dedupe needs at least one key column".to_string(),
+        ));
+    }
+    Ok(ranked(
+        &single(table, "table")?,
+        &keys,
+        order.as_deref(),
+        "1",
+    ))
+}
+
+fn histogram(args: &[FunctionArg]) -> Result<String, DFError> {
+    let [table, column, buckets] = args else {
+        return Err(usage("histogram(table, column, n_buckets)"));
+    };
+    let table = single(table, "table")?;
+    let column = single(column, "column")?;
+    let buckets = match arg_expr(buckets, "n_buckets")? {
+        Expr::Value(value) => match &value.value {
+            SqlValue::Number(n, _) => n.parse::<u32>().ok().filter(|n| *n > 0),
+            _ => None,
+        },
+        _ => None,
+    }
+    .ok_or_else(|| {
+        DFError::Plan(format!(
+            "Invalid n_buckets argument: {} (expected a positive integer)",
+            buckets
+        ))
+    })?;
+
+    let value = format!("CAST({} AS DOUBLE)", column);
+    let stats = format!(
+        "SELECT MIN({value}) AS lo, (MAX({value}) - MIN({value})) / {buckets} AS width FROM {table}"
+    );
+    // Values equal to the maximum belong to the last bucket, and every value
+    // to the first when they are all equal
+    let bucket = format!(
+        "CASE WHEN s.width = 0 THEN 1 \
+         ELSE LEAST(CAST(FLOOR((v.x - s.lo) / s.width) AS BIGINT) + 1, {buckets}) END"
+    );
+    Ok(format!(
+        "SELECT b.bucket, s.lo + (b.bucket - 1) * s.width AS lower, \
+         s.lo + b.bucket * s.width AS upper, COALESCE(c.count, 0) AS count \
+         FROM ({stats}) AS s \
+         CROSS JOIN (SELECT unnest(range(1, {end})) AS bucket) AS b \
+         LEFT JOIN (SELECT {bucket} AS bucket, COUNT(*) AS count \
+         FROM (SELECT {value} AS x FROM {table} WHERE {column} IS NOT NULL) AS v \
+         CROSS JOIN ({stats}) AS s GROUP BY 1) AS c ON c.bucket = b.bucket",
+        end = buckets + 1,
+    ))
+}
+
+/// Rows of `table` numbered within each `partition` by `order`, keeping
+/// those numbered up to `limit`.
+fn ranked(table: &str, partition: &[String], order: Option<&str>, limit: &str) -> String {
+    let mut window = Vec::new();
+    if !partition.is_empty() {
+        window.push(format!("PARTITION BY {}", partition.join(", ")));
+    }
+    if let Some(order) = order {
+        window.push(format!("ORDER BY {}", order));
+    }
+    format!(
+        "SELECT * EXCLUDE ({rank}) FROM \
+         (SELECT *, ROW_NUMBER() OVER ({window}) AS {rank} FROM {table}) \
+         WHERE {rank} <= {limit}",
+        rank = RANK_COLUMN,
+        window = window.join(" "),
+    )
+}
+
+fn usage(signature: &str) -> DFError {
+    DFError::Plan(format!("Usage: {}", signature))
+}
+
+fn arg_expr<'a>(arg: &'a FunctionArg, what: &str) -> Result<&'a Expr, DFError> {
+    match arg {
+        FunctionArg::Unnamed(FunctionArgExpr::Expr(expr)) => Ok(expr),
+        _ => Err(DFError::Plan(format!("Invalid {} argument: {}", what, arg))),
+    }
+}
+
+/// A table or column name given as an identifier or a string.
+fn single(arg: &FunctionArg, what: &str) -> Result<String, DFError> {
+    match arg_expr(arg, what)? {
+        Expr::Identifier(_) | Expr::CompoundIdentifier(_) => Ok(arg.to_string()),
+        Expr::Value(value) => match &value.value {
+            SqlValue::SingleQuotedString(s) if !s.trim().is_empty() => Ok(s.trim().to_string()),
+            _ => Err(DFError::Plan(format!("Invalid {} argument: {}", what, arg))),
+        },
+        _ => Err(DFError::Plan(format!("Invalid {} argument: {}", what, arg))),
+    }
+}
+
+/// Column names given as an identifier, a parenthesized list or a
+/// comma-separated string.
+fn columns(arg: &FunctionArg, what: &str) -> Result<Vec<String>, DFError> {
+    match arg_expr(arg, what)? {
+        Expr::Tuple(items) => Ok(items.iter().map(ToString::to_string).collect()),
+        Expr::Nested(item) => Ok(vec![item.to_string()]),
+        Expr::Value(value) => match &value.value {
+            SqlValue::SingleQuotedString(s) => Ok(s
+                .split(',')
+                .map(str::trim)
+                .filter(|c| !c.is_empty())
+                .map(str::to_string)
+                .collect()),
+            _ => Err(DFError::Plan(format!("Invalid {} argument: {}", what, arg))),
+        },
+        _ => single(arg, what).map(|c| vec![c]),
+    }
+}
+
+/// An ORDER BY item, descending unless the string says otherwise.
+fn order_by(arg: &FunctionArg) -> Result<String, DFError> {
+    let order = single(arg, "order_col")?;
+    let upper = order.to_uppercase();
+    if upper.ends_with(" ASC") || upper.ends_with(" DESC") {
+        Ok(order)
+    } else {
+        Ok(format!("{} DESC", order))
+    }
+}
+
+fn parse_query(sql: &str) -> Result<datafusion::sql::sqlparser::ast::Query, DFError> {
+    Parser::new(&GenericDialect {})
+        .try_with_sql(sql)
+        .and_then(|mut parser| parser.parse_query())
+        .map(|query| *query)
+        .map_err(|e| DFError::SQL(e, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::sql::sqlparser::ast::VisitMut;
+
+    fn expand(sql: &str) -> Result<String, DFError> {
+        let mut statements = Parser::parse_sql(&GenericDialect {}, sql).unwrap();
+        if let ControlFlow::Break(err) = statements[0].visit(&mut ExpandTemplates) {
+            return Err(err);
+        }
+        Ok(statements[0].to_string())
+    }
+
+    #[test]
+    fn test_top_n_expansion() {
+        assert_eq!(
+            expand("SELECT * FROM top_n(users, department, salary, 2) AS t").unwrap(),
+            "SELECT * FROM (SELECT * EXCLUDE (__knowhere_rank) FROM \
+             (SELECT *, ROW_NUMBER() OVER (PARTITION BY department ORDER BY salary DESC) \
+             AS __knowhere_rank FROM users) WHERE __knowhere_rank <= 2) AS t"
+        );
+        assert!(expand("SELECT * FROM top_n(users, '', 'age ASC', 1)")
+            .unwrap()
+            .contains("OVER (ORDER BY age ASC)"));
+    }
+
+    #[test]
+    fn test_dedupe_expansion() {
+        let sql =
+            expand("SELECT * FROM dedupe(orders, (user_id, product_id), order_date)").unwrap();
+        assert!(sql.contains("OVER (PARTITION BY user_id, product_id ORDER BY order_date DESC)"));
+        assert!(sql.contains("__knowhere_rank <= 1"));
+
+        assert!(expand("SELECT * FROM dedupe(orders, 'user_id')").is_ok());
+        assert!(expand("SELECT * FROM dedupe(orders, '')").is_err());
+        assert!(expand("SELECT * FROM top_n(users, department)").is_err());
+    }
+
+    #[test]
+    fn test_histogram_expansion() {
+        let sql = expand("SELECT * FROM histogram(users, salary, 5)").unwrap();
+        assert!(sql.contains("(MAX(CAST(salary AS DOUBLE)) - MIN(CAST(salary AS DOUBLE))) / 5"));
+        assert!(sql.contains("unnest(range(1, 6))"));
+
+        assert!(expand("SELECT * FROM histogram(users, salary, 0)").is_err());
+        assert!(expand("SELECT * FROM histogram(users, salary, 'five')").is_err());
+        assert!(expand("SELECT * FROM histogram(users, salary)").is_err());
+    }
+}
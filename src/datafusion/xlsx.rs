@@ -0,0 +1,308 @@
+//! Excel workbooks as tables: one worksheet becomes one table, with the
+//! first row as column names and column types inferred from the cells.
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use arrow::array::{
+    ArrayRef, BooleanBuilder, Date32Builder, Float64Builder, Int64Builder, StringBuilder,
+    TimestampMicrosecondBuilder,
+};
+use arrow::datatypes::{DataType as ArrowDataType, Field, Schema as ArrowSchema, TimeUnit};
+use arrow::record_batch::RecordBatch;
+use calamine::{open_workbook_auto, Data, DataType, Range, Reader};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+use super::error::{DataFusionError, Result};
+
+/// Which worksheet of a workbook to load.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sheet {
+    /// Zero-based position in the workbook.
+    Index(usize),
+    /// Sheet name, matched exactly.
+    Name(String),
+}
+
+impl Default for Sheet {
+    /// The first worksheet.
+    fn default() -> Self {
+        Sheet::Index(0)
+    }
+}
+
+impl FromStr for Sheet {
+    type Err = std::convert::Infallible;
+
+    /// A sheet name, or a position counting from 1 such as `2` for the
+    /// second sheet.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(match s.parse::<usize>() {
+            Ok(position) if position > 0 => Sheet::Index(position - 1),
+            _ => Sheet::Name(s.to_string()),
+        })
+    }
+}
+
+impl fmt::Display for Sheet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Sheet::Index(index) => write!(f, "sheet {}", index + 1),
+            Sheet::Name(name) => write!(f, "sheet '{}'", name),
+        }
+    }
+}
+
+/// Read `sheet` of the workbook at `path`. Returns the sheet's name with
+/// its rows.
+pub(super) fn read_sheet(path: &Path, sheet: &Sheet) -> Result<(String, RecordBatch)> {
+    let mut workbook = open_workbook_auto(path)?;
+    let names = workbook.sheet_names();
+    let name = match sheet {
+        Sheet::Index(index) => names.get(*index),
+        Sheet::Name(name) => names.iter().find(|candidate| *candidate == name),
+    }
+    .cloned()
+    .ok_or_else(|| {
+        DataFusionError::SheetNotFound(format!(
+            "{} in {}; the workbook has {}",
+            sheet,
+            path.display(),
+            names.join(", ")
+        ))
+    })?;
+    let range = workbook.worksheet_range(&name)?;
+    Ok((name, sheet_to_batch(&range)?))
+}
+
+/// The rows of a worksheet below its header row. A sheet without any cells
+/// has no columns.
+fn sheet_to_batch(range: &Range<Data>) -> Result<RecordBatch> {
+    let mut rows = range.rows();
+    let Some(header) = rows.next() else {
+        return Ok(RecordBatch::new_empty(Arc::new(ArrowSchema::empty())));
+    };
+    let rows: Vec<&[Data]> = rows.collect();
+
+    let mut fields = Vec::new();
+    let mut columns = Vec::new();
+    for (index, name) in column_names(header).into_iter().enumerate() {
+        let cells: Vec<&Data> = rows
+            .iter()
+            .map(|row| row.get(index).unwrap_or(&Data::Empty))
+            .collect();
+        let (data_type, column) = build_column(&cells);
+        fields.push(Field::new(name, data_type, true));
+        columns.push(column);
+    }
+    Ok(RecordBatch::try_new(
+        Arc::new(ArrowSchema::new(fields)),
+        columns,
+    )?)
+}
+
+/// Header cells as column names. Blank headers are named `column_N` after
+/// their position and repeated names get a `_2`, `_3`, ... suffix.
+fn column_names(header: &[Data]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::with_capacity(header.len());
+    for (index, cell) in header.iter().enumerate() {
+        let text = cell_text(cell).map(|text| text.trim().to_string());
+        let base = match text {
+            Some(text) if !text.is_empty() => text,
+            _ => format!("column_{}", index + 1),
+        };
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while names.contains(&name) {
+            name = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+        names.push(name);
+    }
+    names
+}
+
+/// Column types in order of preference. Cells of different kinds widen a
+/// column: integers to floats, dates to timestamps and anything else to
+/// text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CellType {
+    Empty,
+    Boolean,
+    Integer,
+    Float,
+    Date,
+    Timestamp,
+    Text,
+}
+
+impl CellType {
+    fn of(cell: &Data) -> Self {
+        match cell {
+            Data::Empty | Data::Error(_) => CellType::Empty,
+            Data::Bool(_) => CellType::Boolean,
+            Data::Int(_) => CellType::Integer,
+            Data::Float(value) if is_whole(*value) => CellType::Integer,
+            Data::Float(_) => CellType::Float,
+            Data::DateTime(value) if value.is_duration() => CellType::Text,
+            Data::DateTime(_) | Data::DateTimeIso(_) => match cell.as_datetime() {
+                Some(value) if value.time() == NaiveTime::MIN => CellType::Date,
+                Some(_) => CellType::Timestamp,
+                None => CellType::Text,
+            },
+            Data::String(_) | Data::DurationIso(_) => CellType::Text,
+        }
+    }
+
+    fn widen(self, other: Self) -> Self {
+        use CellType::*;
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Empty, other) | (other, Empty) => other,
+            (Integer, Float) | (Float, Integer) => Float,
+            (Date, Timestamp) | (Timestamp, Date) => Timestamp,
+            _ => Text,
+        }
+    }
+}
+
+fn is_whole(value: f64) -> bool {
+    value.fract() == 0.0 && value.abs() < i64::MAX as f64
+}
+
+/// Infer the type of a column from its cells, like CSV schema inference
+/// does, and build it. Columns without any values are text.
+fn build_column(cells: &[&Data]) -> (ArrowDataType, ArrayRef) {
+    let cell_type = cells.iter().fold(CellType::Empty, |column, cell| {
+        column.widen(CellType::of(cell))
+    });
+    match cell_type {
+        CellType::Boolean => {
+            let mut builder = BooleanBuilder::with_capacity(cells.len());
+            for cell in cells {
+                builder.append_option(cell.get_bool());
+            }
+            (ArrowDataType::Boolean, Arc::new(builder.finish()))
+        }
+        CellType::Integer => {
+            let mut builder = Int64Builder::with_capacity(cells.len());
+            for cell in cells {
+                builder.append_option(cell.as_i64());
+            }
+            (ArrowDataType::Int64, Arc::new(builder.finish()))
+        }
+        CellType::Float => {
+            let mut builder = Float64Builder::with_capacity(cells.len());
+            for cell in cells {
+                builder.append_option(cell.as_f64());
+            }
+            (ArrowDataType::Float64, Arc::new(builder.finish()))
+        }
+        CellType::Date => {
+            let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap_or_default();
+            let mut builder = Date32Builder::with_capacity(cells.len());
+            for cell in cells {
+                builder.append_option(
+                    datetime(cell).map(|value| (value.date() - epoch).num_days() as i32),
+                );
+            }
+            (ArrowDataType::Date32, Arc::new(builder.finish()))
+        }
+        CellType::Timestamp => {
+            let mut builder = TimestampMicrosecondBuilder::with_capacity(cells.len());
+            for cell in cells {
+                builder
+                    .append_option(datetime(cell).map(|value| value.and_utc().timestamp_micros()));
+            }
+            (
+                ArrowDataType::Timestamp(TimeUnit::Microsecond, None),
+                Arc::new(builder.finish()),
+            )
+        }
+        CellType::Empty | CellType::Text => {
+            let mut builder = StringBuilder::with_capacity(cells.len(), cells.len() * 8);
+            for cell in cells {
+                builder.append_option(cell_text(cell));
+            }
+            (ArrowDataType::Utf8, Arc::new(builder.finish()))
+        }
+    }
+}
+
+/// A date or date-time cell's value. Durations are not points in time.
+fn datetime(cell: &Data) -> Option<NaiveDateTime> {
+    match cell {
+        Data::DateTime(value) if value.is_duration() => None,
+        _ => cell.as_datetime(),
+    }
+}
+
+/// A cell as text, with dates written the way the table view shows them
+/// rather than as Excel serial numbers. Empty and error cells have none.
+fn cell_text(cell: &Data) -> Option<String> {
+    match cell {
+        Data::Empty | Data::Error(_) => None,
+        Data::String(text) => Some(text.clone()),
+        Data::Float(value) if is_whole(*value) => Some((*value as i64).to_string()),
+        Data::DateTime(_) => Some(match datetime(cell) {
+            Some(value) if value.time() == NaiveTime::MIN => value.format("%Y-%m-%d").to_string(),
+            Some(value) => value.format("%Y-%m-%d %H:%M:%S").to_string(),
+            None => cell.to_string(),
+        }),
+        other => Some(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sheet() {
+        assert_eq!("2".parse::<Sheet>().unwrap(), Sheet::Index(1));
+        assert_eq!("Q3".parse::<Sheet>().unwrap(), Sheet::Name("Q3".into()));
+        assert_eq!("0".parse::<Sheet>().unwrap(), Sheet::Name("0".into()));
+    }
+
+    #[test]
+    fn test_column_names() {
+        let header = [
+            Data::String("id".into()),
+            Data::Empty,
+            Data::String(" name ".into()),
+            Data::String("name".into()),
+            Data::Float(2024.0),
+        ];
+        assert_eq!(
+            column_names(&header),
+            vec!["id", "column_2", "name", "name_2", "2024"]
+        );
+    }
+
+    #[test]
+    fn test_cell_types_widen() {
+        let column = |cells: &[Data]| {
+            let cells: Vec<&Data> = cells.iter().collect();
+            build_column(&cells).0
+        };
+        assert_eq!(
+            column(&[Data::Float(1.0), Data::Empty, Data::Int(3)]),
+            ArrowDataType::Int64
+        );
+        assert_eq!(
+            column(&[Data::Float(1.0), Data::Float(2.5)]),
+            ArrowDataType::Float64
+        );
+        assert_eq!(
+            column(&[Data::Bool(true), Data::Error(calamine::CellErrorType::NA)]),
+            ArrowDataType::Boolean
+        );
+        assert_eq!(
+            column(&[Data::Int(1), Data::String("n/a".into())]),
+            ArrowDataType::Utf8
+        );
+        assert_eq!(column(&[Data::Empty]), ArrowDataType::Utf8);
+    }
+}
@@ -1,7 +1,38 @@
+//! Query CSV, JSON, Parquet, Delta, Iceberg, SQLite and Excel files with SQL.
+//!
+//! A [`FileLoader`] registers files as tables in a [`DataFusionContext`],
+//! the session that queries run in, and results come back as a [`Table`]
+//! that the [`render`] module can print in several formats. The
+//! [`prelude`] imports all of these at once:
+//!
+//! ```no_run
+//! use knowhere::prelude::*;
+//! use std::path::Path;
+//!
+//! let mut loader = FileLoader::new()?;
+//! loader.load_file(Path::new("sales.csv"))?;
+//! let ctx = loader.into_context();
+//! let table = ctx.execute_sql("SELECT region, SUM(amount) FROM sales GROUP BY region")?;
+//! print!("{}", render_to_string(&mut TableRenderer::new(40), &table)?);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+// Used by the knowhere binary; not part of the library API
+#[doc(hidden)]
 pub mod cli;
 pub mod datafusion;
+pub mod prelude;
+pub mod render;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod repl;
+// Replaced by the datafusion module; empty, and kept only so existing
+// `use knowhere::sql` lines still compile
+#[doc(hidden)]
 pub mod sql;
 pub mod storage;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
 pub mod tui;
 
 pub use datafusion::{DataFusionContext, FileLoader, Result as DataFusionResult};
@@ -1,4 +1,6 @@
-use std::io::stdout;
+use std::io::{stdout, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
 use crossterm::{
     execute,
@@ -6,178 +8,729 @@ use crossterm::{
 };
 use ratatui::prelude::*;
 
-use knowhere::cli::{Cli, OutputFormat};
-use knowhere::datafusion::{DataFusionContext, FileLoader};
-use knowhere::storage::table::Table;
-use knowhere::tui::{app::App, input::handle_events, ui::draw};
+use knowhere::cli::{Cli, Command, OutputFormat, SampleArgs};
+#[cfg(feature = "sqlite")]
+use knowhere::datafusion::write_sqlite;
+#[cfg(feature = "xlsx")]
+use knowhere::datafusion::XlsxReader;
+use knowhere::datafusion::{
+    expand_env_vars, generate_dataset, is_glob_pattern, is_remote_uri, named_scripts,
+    parse_column_type, parse_row_count, quote_identifier, split_statements, take_statements,
+    CsvOptions, CsvReader, CsvTailReader, DataFusionContext, DatasetSpec, ExportFormat, FileLoader,
+    HeaderNormalization, ManifestQuery, NamedStatement, SessionRecorder, TypeRefinementMode,
+};
+use knowhere::render::{
+    render_all, CsvRenderer, JsonRenderer, MaskRules, NdjsonRenderer, ResultRenderer,
+};
+use knowhere::repl::{LineEditor, ReadLine};
+use knowhere::storage::table::json_string;
+use knowhere::tui::{
+    app::{App, DEFAULT_MAX_ROWS},
+    history::default_history_path,
+    input::handle_events,
+    ui::{draw, results_page_size},
+};
+use knowhere::Table;
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let cli = Cli::parse_args();
+fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cli = Cli::parse_args();
+    if let Some(command) = &cli.command {
+        return run_command(command);
+    }
+    if cli.expand_env {
+        expand_cli_env(&mut cli)?;
+    }
 
     // Load data into execution context
-    let ctx = load_data(&cli)?;
+    let mut ctx = load_data(&cli)?;
+    ctx.set_strict_sql(cli.strict_sql);
+    ctx.set_setting("max_col_width", &cli.max_col_width.to_string())?;
+    if let Some(secs) = cli.timeout {
+        ctx.set_setting("timeout_secs", &secs.to_string())?;
+    }
+    if let Some(mb) = cli.max_memory_mb {
+        ctx.set_setting("max_memory_mb", &mb.to_string())?;
+    }
+    if let Some(rows) = cli.max_rows {
+        ctx.set_setting("max_rows", &rows.to_string())?;
+    }
+    let recorder = match &cli.record {
+        Some(path) => Some(
+            SessionRecorder::create(path)
+                .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?,
+        ),
+        None => None,
+    };
+    let statements = collect_statements(&cli)?;
+    for statement in &statements {
+        for note in ctx.dialect_notes(&statement.sql) {
+            eprintln!("Warning: translated {}", note);
+        }
+    }
+    let masks = match &cli.mask {
+        Some(path) => MaskRules::from_file(path)
+            .map_err(|e| format!("Failed to read mask rules {}: {}", path.display(), e))?,
+        None => MaskRules::default(),
+    };
 
-    if let Some(query) = &cli.query {
+    if cli.list_tables {
+        print_table(&ctx, &ctx.tables_overview(), &masks, cli.format)?;
+    } else if let Some(table) = &cli.describe {
+        print_table(&ctx, &ctx.describe_table(table)?, &masks, cli.format)?;
+        if let Some(layout) = ctx.table_details(table).and_then(|d| d.layout_summary()) {
+            eprintln!("{}", layout);
+        }
+    } else if cli.repl {
+        run_repl(&ctx, &masks, &cli, recorder.as_ref())?;
+    } else if !statements.is_empty() {
         // Non-interactive mode
-        run_query(&ctx, query, cli.format)?;
+        let row_counts = match (&cli.output, statements.as_slice()) {
+            (Some(output), [statement]) => vec![export_query(
+                &ctx,
+                &statement.sql,
+                &masks,
+                output,
+                &cli.output_table,
+                recorder.as_ref(),
+            )?],
+            (Some(_), _) => return Err("--output supports a single query".into()),
+            (None, [statement]) => vec![run_query(
+                &ctx,
+                &statement.sql,
+                &masks,
+                cli.format,
+                cli.verbose,
+                recorder.as_ref(),
+            )?],
+            (None, _) => run_statements(
+                &ctx,
+                &statements,
+                &masks,
+                cli.format,
+                cli.verbose,
+                recorder.as_ref(),
+            )?,
+        };
+        if let Some(path) = &cli.manifest {
+            let queries = statements
+                .iter()
+                .zip(row_counts)
+                .map(|(statement, row_count)| ManifestQuery {
+                    name: statement.name.clone(),
+                    sql: statement.sql.clone(),
+                    row_count,
+                })
+                .collect();
+            ctx.query_manifest(queries)?.write(path)?;
+        }
     } else {
         // Interactive TUI mode
-        run_tui(ctx)?;
+        run_tui(ctx, masks, recorder, &cli)?;
+    }
+
+    Ok(())
+}
+
+fn run_command(command: &Command) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        Command::Generate(args) => {
+            let spec = DatasetSpec::parse(&args.schema)?;
+            let rows = parse_row_count(&args.rows)?;
+            let format = args
+                .output
+                .extension()
+                .and_then(|s| s.to_str())
+                .and_then(ExportFormat::from_extension)
+                .ok_or_else(|| format!("Unsupported output format: {}", args.output.display()))?;
+            generate_dataset(&spec, rows, args.seed, &args.output, format)?;
+            eprintln!("Wrote {} rows to {}", rows, args.output.display());
+            Ok(())
+        }
+        Command::Sample(args) => sample(args),
+    }
+}
+
+/// Pick `args.rows` random rows of a table, anonymize the requested
+/// columns and write them to `args.output`.
+fn sample(args: &SampleArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut loader = FileLoader::new()?;
+    if args.input.is_dir() {
+        loader.load_directory(&args.input)?;
+    } else {
+        loader.load_file(&args.input)?;
+    }
+    let ctx = loader.into_context();
+
+    let tables = ctx.list_tables();
+    let table = match (&args.table, tables.as_slice()) {
+        (Some(table), _) => table.clone(),
+        (None, [table]) => table.clone(),
+        (None, []) => return Err("No valid data files found".into()),
+        (None, _) => {
+            return Err(format!(
+                "{} holds several tables; pick one with --table: {}",
+                args.input.display(),
+                tables.join(", ")
+            )
+            .into())
+        }
+    };
+
+    let mut result = ctx.execute_sql(&format!(
+        "SELECT * FROM {} ORDER BY random() LIMIT {}",
+        quote_identifier(&table),
+        args.rows
+    ))?;
+    let masks = match &args.anonymize {
+        Some(columns) => MaskRules::for_columns(columns)?,
+        None => MaskRules::default(),
+    };
+    let unmatched = masks.unmatched(&result.schema);
+    if !unmatched.is_empty() {
+        return Err(format!(
+            "No column matches {} in table {}",
+            unmatched.join(", "),
+            table
+        )
+        .into());
+    }
+    masks.apply(&mut result);
+
+    let extension = args
+        .output
+        .extension()
+        .and_then(|s| s.to_str())
+        .map(|s| s.to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "csv" => render_to_file(&mut CsvRenderer::new(), &result, &masks, &args.output)?,
+        "json" | "jsonl" | "ndjson" => {
+            render_to_file(&mut NdjsonRenderer::new(), &result, &masks, &args.output)?
+        }
+        #[cfg(feature = "sqlite")]
+        "db" | "sqlite" | "sqlite3" => {
+            write_sqlite(&result, &args.output, &table, &masks)?;
+        }
+        _ => return Err(format!("Unsupported output format: {}", args.output.display()).into()),
     }
+    eprintln!(
+        "Wrote {} rows of {} to {}",
+        result.total_row_count(),
+        table,
+        args.output.display()
+    );
+    Ok(())
+}
 
+fn render_to_file(
+    renderer: &mut dyn ResultRenderer,
+    table: &Table,
+    masks: &MaskRules,
+    path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+    render_all(renderer, &mut out, table, masks)?;
+    out.flush()?;
     Ok(())
 }
 
 fn load_data(cli: &Cli) -> Result<DataFusionContext, Box<dyn std::error::Error>> {
     let mut loader = FileLoader::new()?;
-    let path = &cli.path;
+    loader.set_provenance_columns(cli.provenance);
+    if cli.normalize_headers {
+        loader.set_header_normalization(HeaderNormalization::all());
+    }
+    loader.set_type_refinement(if cli.refine_types {
+        TypeRefinementMode::Apply
+    } else if cli.verbose {
+        TypeRefinementMode::Suggest
+    } else {
+        TypeRefinementMode::Off
+    });
+    let csv = csv_options(cli)?;
+    if cli.lenient_numbers || csv != CsvOptions::default() {
+        let reader = if cli.lenient_numbers {
+            CsvReader::lenient()
+        } else {
+            CsvReader::default()
+        };
+        loader.register_reader(Box::new(reader.with_options(csv.clone())));
+    }
+    if cli.tail {
+        loader.register_reader(Box::new(CsvTailReader));
+    }
+    #[cfg(feature = "xlsx")]
+    if let Some(sheet) = &cli.sheet {
+        loader.register_reader(Box::new(XlsxReader::with_sheet(sheet.parse()?)));
+    }
+    loader.set_union_files(cli.union);
+    loader.set_load_filter(cli.filter.clone());
+    let path = cli.path.as_deref().ok_or("No data path given")?;
+    let remote = path.to_str().filter(|path| is_remote_uri(path));
+    let pattern = path.to_str().filter(|path| is_glob_pattern(path));
 
-    if path.is_file() {
-        loader.load_file(path)?;
+    let loaded = if let Some(uri) = remote {
+        loader.load_uri(uri)
+    } else if path.is_file() {
+        loader.load_file(path)
     } else if path.is_dir() {
-        loader.load_directory(path)?;
+        loader.load_directory(path)
+    } else if let Some(pattern) = pattern {
+        loader.load_glob(pattern)
     } else {
         return Err(format!("Path does not exist: {}", path.display()).into());
+    };
+
+    let report = loader.load_report();
+    if cli.verbose {
+        eprint!("{}", report);
+    } else {
+        for (original, name) in report.renamed_tables() {
+            eprintln!("Note: \"{}\" is loaded as table {}", original, name);
+        }
+        if report.has_problems() {
+            eprintln!(
+                "Warning: {} (run with --verbose for details)",
+                report.summary()
+            );
+        }
     }
+    loaded?;
 
     let ctx = loader.into_context();
 
     if ctx.table_count() == 0 {
         return Err("No valid data files found".into());
     }
+    // Normalized headers no longer match the names the overrides were given
+    if !cli.normalize_headers {
+        warn_unused_column_types(&ctx, &csv);
+    }
 
     Ok(ctx)
 }
 
-fn run_query(
+/// How CSV files are read, from the `--delimiter`, `--no-header`,
+/// `--infer-rows`, `--date-format`, `--decimal-separator` and
+/// `--column-type` flags.
+fn csv_options(cli: &Cli) -> Result<CsvOptions, Box<dyn std::error::Error>> {
+    if !cli.delimiter.is_ascii() {
+        return Err(format!(
+            "The CSV delimiter must be an ASCII character: {}",
+            cli.delimiter
+        )
+        .into());
+    }
+    let mut options = CsvOptions::default()
+        .with_delimiter(cli.delimiter as u8)
+        .with_header(!cli.no_header)
+        .with_infer_rows(Some(cli.infer_rows).filter(|rows| *rows > 0))
+        .with_decimal_separator(cli.decimal_separator.chars().next().unwrap_or('.'));
+    for format in &cli.date_format {
+        options = options.with_date_format(format);
+    }
+    for spec in &cli.column_type {
+        let (column, data_type) = parse_column_type(spec)?;
+        options = options.with_column_type(column, data_type);
+    }
+    Ok(options)
+}
+
+/// Warn about `--column-type` overrides for columns that no table has,
+/// which are most likely misspelled.
+fn warn_unused_column_types(ctx: &DataFusionContext, csv: &CsvOptions) {
+    let columns: Vec<String> = ctx
+        .list_tables()
+        .iter()
+        .filter_map(|table| ctx.get_table_schema(table))
+        .flat_map(|schema| schema.columns.into_iter().map(|column| column.name))
+        .collect();
+    for (name, _) in &csv.column_types {
+        if !columns
+            .iter()
+            .any(|column| column.eq_ignore_ascii_case(name))
+        {
+            eprintln!("Warning: --column-type {}: no table has this column", name);
+        }
+    }
+}
+
+/// Statements from every `-q` flag, each split as a script of its own, or
+/// from the `--script` or `--replay` file.
+fn collect_statements(cli: &Cli) -> Result<Vec<NamedStatement>, Box<dyn std::error::Error>> {
+    let scripts = match cli.script.as_ref().or(cli.replay.as_ref()) {
+        Some(path) => vec![std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read script {}: {}", path.display(), e))?],
+        None => cli.query.clone(),
+    };
+    let scripts = if cli.expand_env {
+        scripts
+            .iter()
+            .map(|script| expand_env_vars(script))
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        scripts
+    };
+    Ok(named_scripts(&scripts))
+}
+
+/// Expand environment variables in every path argument. Queries are
+/// expanded with the script text in `collect_statements`.
+fn expand_cli_env(cli: &mut Cli) -> Result<(), Box<dyn std::error::Error>> {
+    fn expand_path(path: &mut PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(text) = path.to_str() {
+            *path = PathBuf::from(expand_env_vars(text)?);
+        }
+        Ok(())
+    }
+
+    for path in cli
+        .path
+        .iter_mut()
+        .chain(cli.script.iter_mut())
+        .chain(cli.replay.iter_mut())
+        .chain(cli.record.iter_mut())
+        .chain(cli.output.iter_mut())
+        .chain(cli.manifest.iter_mut())
+    {
+        expand_path(path)?;
+    }
+    if let Some(filter) = &mut cli.filter {
+        *filter = expand_env_vars(filter)?;
+    }
+    Ok(())
+}
+
+/// Run several statements in order and print each result. With `--format
+/// json` or `json-pretty` the results are combined into one array of
+/// `{"name", "rows"}` objects. Statements that return no columns, such as
+/// `CREATE VIEW`, run without printing anything. Results are rendered with
+/// the settings in effect after the last statement. Returns the row count
+/// of each statement.
+fn run_statements(
     ctx: &DataFusionContext,
-    query: &str,
+    statements: &[NamedStatement],
+    masks: &MaskRules,
     format: OutputFormat,
+    verbose: bool,
+    recorder: Option<&SessionRecorder>,
+) -> Result<Vec<u64>, Box<dyn std::error::Error>> {
+    let mut results = Vec::new();
+    let mut row_counts = Vec::new();
+    for statement in statements {
+        let mut result = execute(ctx, &statement.sql, masks, verbose, recorder)
+            .map_err(|e| format!("{}: {}", statement.name, e))?;
+        masks.apply(&mut result);
+        row_counts.push(result.total_row_count() as u64);
+        if result.column_count() > 0 {
+            results.push((statement.name.as_str(), result));
+        }
+    }
+
+    let stdout = stdout();
+    let mut out = stdout.lock();
+    if matches!(format, OutputFormat::Json) {
+        writeln!(out, "[")?;
+        for (i, (name, result)) in results.iter().enumerate() {
+            let rows = render_json(&mut JsonRenderer::new(), result, masks)?;
+            let separator = if i + 1 < results.len() { "," } else { "" };
+            writeln!(
+                out,
+                "{{\"name\":{},\"rows\":{}}}{}",
+                json_string(name),
+                rows.trim_end(),
+                separator
+            )?;
+        }
+        writeln!(out, "]")?;
+        return Ok(row_counts);
+    }
+    if matches!(format, OutputFormat::JsonPretty) {
+        writeln!(out, "[")?;
+        for (i, (name, result)) in results.iter().enumerate() {
+            let rows = render_json(&mut JsonRenderer::pretty(), result, masks)?;
+            let separator = if i + 1 < results.len() { "," } else { "" };
+            writeln!(
+                out,
+                "  {{\n    \"name\": {},\n    \"rows\": {}\n  }}{}",
+                json_string(name),
+                rows.trim_end().replace('\n', "\n    "),
+                separator
+            )?;
+        }
+        writeln!(out, "]")?;
+        return Ok(row_counts);
+    }
+
+    let max_col_width = ctx.settings().max_col_width;
+    for (i, (name, result)) in results.iter().enumerate() {
+        if i > 0 {
+            writeln!(out)?;
+        }
+        if matches!(format, OutputFormat::Table | OutputFormat::Bars) {
+            writeln!(out, "-- {}", name)?;
+        }
+        render_all(
+            format.renderer(max_col_width).as_mut(),
+            &mut out,
+            result,
+            masks,
+        )?;
+    }
+    Ok(row_counts)
+}
+
+/// `table` rendered as a JSON array, including spilled rows.
+fn render_json(
+    renderer: &mut JsonRenderer,
+    table: &Table,
+    masks: &MaskRules,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut buf = Vec::new();
+    render_all(renderer, &mut buf, table, masks)?;
+    Ok(String::from_utf8(buf)?)
+}
+
+/// Read and run statements until `.quit`, Ctrl+D or the end of input.
+/// Failing statements are reported and the session goes on; with piped
+/// input the exit status still reports the failures.
+fn run_repl(
+    ctx: &DataFusionContext,
+    masks: &MaskRules,
+    cli: &Cli,
+    recorder: Option<&SessionRecorder>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let result = ctx.execute_sql(query)?;
+    let mut editor = LineEditor::new();
+    if editor.is_interactive() {
+        eprintln!(
+            "knowhere {}: end statements with ;  .tables lists tables, .quit or Ctrl+D exits",
+            env!("CARGO_PKG_VERSION")
+        );
+    }
 
-    match format {
-        OutputFormat::Table => print_table(&result),
-        OutputFormat::Csv => print_csv(&result),
-        OutputFormat::Json => print_json(&result),
+    let mut buffer = String::new();
+    let mut failures = 0;
+    loop {
+        let prompt = if buffer.is_empty() {
+            "knowhere> "
+        } else {
+            "       -> "
+        };
+        let line = match editor.read_line(prompt)? {
+            ReadLine::Line(line) => line,
+            ReadLine::Interrupted => {
+                buffer.clear();
+                continue;
+            }
+            ReadLine::Eof => break,
+        };
+        if buffer.is_empty() {
+            match line.trim() {
+                ".quit" | ".exit" => return Ok(()),
+                ".tables" => {
+                    print_table(ctx, &ctx.tables_overview(), masks, cli.format)?;
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        buffer.push_str(&line);
+        buffer.push('\n');
+        for sql in take_statements(&mut buffer) {
+            failures += usize::from(!run_repl_statement(ctx, &sql, masks, cli, recorder));
+        }
     }
 
+    // Piped input may end without a semicolon after the last statement
+    if !editor.is_interactive() {
+        for sql in split_statements(&buffer) {
+            failures += usize::from(!run_repl_statement(ctx, &sql, masks, cli, recorder));
+        }
+        if failures > 0 {
+            let plural = if failures == 1 { "" } else { "s" };
+            return Err(format!("{} statement{} failed", failures, plural).into());
+        }
+    }
     Ok(())
 }
 
-fn print_table(table: &Table) {
-    if table.row_count() == 0 {
-        println!("(0 rows)");
-        return;
+/// Run one REPL statement and print its result, or the error. Returns
+/// whether it succeeded.
+fn run_repl_statement(
+    ctx: &DataFusionContext,
+    sql: &str,
+    masks: &MaskRules,
+    cli: &Cli,
+    recorder: Option<&SessionRecorder>,
+) -> bool {
+    for note in ctx.dialect_notes(sql) {
+        eprintln!("Warning: translated {}", note);
     }
+    let printed = execute(ctx, sql, masks, cli.verbose, recorder).and_then(|mut result| {
+        masks.apply(&mut result);
+        if result.column_count() > 0 {
+            print_table(ctx, &result, masks, cli.format)?;
+        }
+        Ok(())
+    });
+    match printed {
+        Ok(()) => true,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            false
+        }
+    }
+}
 
-    // Calculate column widths
-    let widths: Vec<usize> = table
-        .schema
-        .columns
-        .iter()
-        .enumerate()
-        .map(|(i, col)| {
-            let header_width = col.name.len();
-            let max_value_width = table
-                .rows
-                .iter()
-                .map(|row| row.values.get(i).map(|v| v.to_string().len()).unwrap_or(0))
-                .max()
-                .unwrap_or(0);
-            header_width.max(max_value_width)
-        })
-        .collect();
+/// Run `query` and print its result. Returns the number of rows.
+fn run_query(
+    ctx: &DataFusionContext,
+    query: &str,
+    masks: &MaskRules,
+    format: OutputFormat,
+    verbose: bool,
+    recorder: Option<&SessionRecorder>,
+) -> Result<u64, Box<dyn std::error::Error>> {
+    let mut result = execute(ctx, query, masks, verbose, recorder)?;
+    masks.apply(&mut result);
+    print_table(ctx, &result, masks, format)?;
+    Ok(result.total_row_count() as u64)
+}
 
-    // Print header
-    let header: Vec<String> = table
-        .schema
-        .columns
-        .iter()
-        .enumerate()
-        .map(|(i, col)| format!("{:width$}", col.name, width = widths[i]))
-        .collect();
-    println!("{}", header.join(" | "));
+/// Run `sql`, reporting file and row group pruning on stderr when
+/// `verbose`, and record it if it succeeds. Statements that would write
+/// unmasked rows to a file are refused while `masks` are active.
+fn execute(
+    ctx: &DataFusionContext,
+    sql: &str,
+    masks: &MaskRules,
+    verbose: bool,
+    recorder: Option<&SessionRecorder>,
+) -> Result<Table, Box<dyn std::error::Error>> {
+    masks.check_statement(sql)?;
+    let result = if verbose {
+        let (result, pruning) = ctx.execute_sql_with_pruning(sql)?;
+        if !pruning.is_empty() {
+            eprintln!("Pruning: {}", pruning);
+        }
+        result
+    } else {
+        ctx.execute_sql(sql)?
+    };
+    record(recorder, sql);
+    warn_truncated(result.truncated, result.total_row_count() as u64);
+    Ok(result)
+}
 
-    // Print separator
-    let sep: Vec<String> = widths.iter().map(|&w| "-".repeat(w)).collect();
-    println!("{}", sep.join("-+-"));
+/// Say on stderr that a result stopped at `max_rows`, so that a cut-off
+/// result is not mistaken for the whole one.
+fn warn_truncated(truncated: bool, rows: u64) {
+    if truncated {
+        eprintln!(
+            "Warning: stopped after {} rows (max_rows); raise --max-rows or SET max_rows = NONE for the rest",
+            rows
+        );
+    }
+}
 
-    // Print rows
-    for row in &table.rows {
-        let values: Vec<String> = row
-            .values
-            .iter()
-            .enumerate()
-            .map(|(i, v)| format!("{:width$}", v, width = widths[i]))
-            .collect();
-        println!("{}", values.join(" | "));
+/// Add `sql` to the `--record` recording, if there is one. A statement that
+/// cannot be written is reported without failing the query.
+fn record(recorder: Option<&SessionRecorder>, sql: &str) {
+    if let Some(Err(e)) = recorder.map(|recorder| recorder.record(sql)) {
+        eprintln!("Warning: could not record statement: {}", e);
     }
+}
 
-    println!("({} rows)", table.row_count());
+/// Print every row of `table`, reading a spilled result back from disk and
+/// masking the rows that were not held in memory with `masks`.
+fn print_table(
+    ctx: &DataFusionContext,
+    table: &Table,
+    masks: &MaskRules,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let stdout = stdout();
+    let mut out = stdout.lock();
+    let mut renderer = format.renderer(ctx.settings().max_col_width);
+    render_all(renderer.as_mut(), &mut out, table, masks)?;
+    Ok(())
 }
 
-fn print_csv(table: &Table) {
-    // Header
-    let header: Vec<&str> = table
-        .schema
-        .columns
-        .iter()
-        .map(|c| c.name.as_str())
-        .collect();
-    println!("{}", header.join(","));
+/// Run `query` and write its result to `output`. Returns the number of rows
+/// written.
+fn export_query(
+    ctx: &DataFusionContext,
+    query: &str,
+    masks: &MaskRules,
+    output: &Path,
+    table_name: &str,
+    recorder: Option<&SessionRecorder>,
+) -> Result<u64, Box<dyn std::error::Error>> {
+    let extension = output
+        .extension()
+        .and_then(|s| s.to_str())
+        .map(|s| s.to_lowercase())
+        .unwrap_or_default();
+    masks.check_statement(query)?;
 
-    // Rows
-    for row in &table.rows {
-        let values: Vec<String> = row
-            .values
-            .iter()
-            .map(|v| {
-                let s = v.to_string();
-                if s.contains(',') || s.contains('"') || s.contains('\n') {
-                    format!("\"{}\"", s.replace('"', "\"\""))
-                } else {
-                    s
-                }
-            })
-            .collect();
-        println!("{}", values.join(","));
+    if let Some(format) = ExportFormat::from_extension(&extension) {
+        let summary = ctx.export_sql(query, output, format, masks)?;
+        record(recorder, query);
+        eprintln!("Wrote {} rows to {}", summary.rows, output.display());
+        warn_truncated(summary.truncated, summary.rows);
+        return Ok(summary.rows);
     }
-}
 
-fn print_json(table: &Table) {
-    print!("[");
-    for (i, row) in table.rows.iter().enumerate() {
-        if i > 0 {
-            print!(",");
+    match extension.as_str() {
+        #[cfg(feature = "sqlite")]
+        "db" | "sqlite" | "sqlite3" => {
+            let mut result = ctx.execute_sql(query)?;
+            record(recorder, query);
+            masks.apply(&mut result);
+            let rows = write_sqlite(&result, output, table_name, masks)?;
+            eprintln!(
+                "Wrote {} rows to table '{}' in {}",
+                rows,
+                table_name,
+                output.display()
+            );
+            warn_truncated(result.truncated, rows as u64);
+            Ok(rows as u64)
         }
-        print!("{{");
-        for (j, (col, val)) in table
-            .schema
-            .columns
-            .iter()
-            .zip(row.values.iter())
-            .enumerate()
-        {
-            if j > 0 {
-                print!(",");
-            }
-            let val_str = match val {
-                knowhere::storage::table::Value::String(s) => {
-                    format!("\"{}\"", s.replace('"', "\\\""))
-                }
-                knowhere::storage::table::Value::Null => "null".to_string(),
-                knowhere::storage::table::Value::Boolean(b) => b.to_string(),
-                _ => val.to_string(),
-            };
-            print!("\"{}\":{}", col.name, val_str);
+        #[cfg(not(feature = "sqlite"))]
+        "db" | "sqlite" | "sqlite3" => {
+            let _ = (ctx, query, masks, table_name, recorder);
+            Err("SQLite export requires the `sqlite` feature".into())
         }
-        print!("}}");
+        _ => Err(format!("Unsupported output format: {}", output.display()).into()),
     }
-    println!("]");
 }
 
-fn run_tui(ctx: DataFusionContext) -> Result<(), Box<dyn std::error::Error>> {
+fn run_tui(
+    ctx: DataFusionContext,
+    masks: MaskRules,
+    recorder: Option<SessionRecorder>,
+    cli: &Cli,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Results past this size are spilled to disk rather than held in memory
+    if cli.result_memory_mb > 0 {
+        ctx.set_setting("result_memory_mb", &cli.result_memory_mb.to_string())?;
+    }
+    // Cap results before they are read rather than warning afterwards
+    if cli.max_rows.is_none() {
+        ctx.set_setting("max_rows", &DEFAULT_MAX_ROWS.to_string())?;
+    }
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = stdout();
@@ -187,9 +740,19 @@ fn run_tui(ctx: DataFusionContext) -> Result<(), Box<dyn std::error::Error>> {
 
     // Create app
     let mut app = App::new(ctx);
+    app.memory_threshold = cli.memory_warning_mb * 1024 * 1024;
+    app.row_limit = cli.row_limit;
+    app.masks = masks;
+    app.recorder = recorder;
+    if let Some(path) = cli.history_file.clone().or_else(default_history_path) {
+        app.load_history(path);
+    }
 
     // Main loop
     loop {
+        app.poll_query();
+        app.sample_memory();
+        app.page_size = results_page_size(terminal.size()?.height);
         terminal.draw(|frame| draw(frame, &app))?;
 
         if handle_events(&mut app)? {
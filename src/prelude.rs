@@ -0,0 +1,19 @@
+//! The types most programs that embed knowhere need, in one import:
+//!
+//! ```
+//! use knowhere::prelude::*;
+//! ```
+//!
+//! Items are added here as the library grows, but not renamed or removed
+//! within a release series. Everything else stays reachable through its
+//! module, such as [`crate::datafusion`] for less common loading options.
+
+pub use crate::datafusion::{
+    CsvOptions, CsvReader, DataFusionContext, DataFusionError, FileLoader, QueryHandle,
+    QueryOptions, Result, SessionSettings, SourceReader,
+};
+pub use crate::render::{
+    render_all, render_to_string, renderer_for, CsvRenderer, HtmlRenderer, JsonRenderer,
+    MarkdownRenderer, MaskRules, NdjsonRenderer, ResultRenderer, TableRenderer,
+};
+pub use crate::storage::{Column, DataType, Row, Schema, Table, Value};
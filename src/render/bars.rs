@@ -0,0 +1,166 @@
+use std::io::{self, Write};
+
+use crate::storage::table::{Row, Schema, Table, Value};
+
+use super::layout::{display_width, fit};
+use super::ResultRenderer;
+
+/// Longest bar drawn by default, in characters.
+pub const DEFAULT_BAR_WIDTH: usize = 40;
+
+const BAR: char = '█';
+
+/// A horizontal bar chart of the last column, one labelled bar per row,
+/// scaled to the largest value. Meant for the output of `histogram(...)`,
+/// whose rows are labelled with their `lower` – `upper` range, and for
+/// frequency tables, labelled with the other columns' values.
+pub struct BarRenderer {
+    max_bar_width: usize,
+    max_value: f64,
+    label_width: usize,
+    value_width: usize,
+}
+
+impl Default for BarRenderer {
+    fn default() -> Self {
+        Self::new(DEFAULT_BAR_WIDTH)
+    }
+}
+
+impl BarRenderer {
+    pub fn new(max_bar_width: usize) -> Self {
+        Self {
+            max_bar_width,
+            max_value: 0.0,
+            label_width: 0,
+            value_width: 0,
+        }
+    }
+}
+
+/// The row's label: its bucket range when the result has `lower` and
+/// `upper` columns, otherwise every value but the last.
+fn label(schema: &Schema, row: &Row) -> String {
+    let index = |name: &str| schema.columns.iter().position(|c| c.name == name);
+    if let (Some(lower), Some(upper)) = (index("lower"), index("upper")) {
+        return format!(
+            "{} – {}",
+            bound(&row.values[lower]),
+            bound(&row.values[upper])
+        );
+    }
+    let values = &row.values[..row.values.len().saturating_sub(1)];
+    let labels: Vec<String> = values.iter().map(ToString::to_string).collect();
+    labels.join(", ")
+}
+
+/// A bucket bound with at most two decimals and no trailing zeros.
+fn bound(value: &Value) -> String {
+    match value {
+        Value::Float(f) => {
+            let s = format!("{:.2}", f);
+            s.trim_end_matches('0').trim_end_matches('.').to_string()
+        }
+        other => other.to_string(),
+    }
+}
+
+fn bar_value(row: &Row) -> f64 {
+    match row.values.last() {
+        Some(Value::Integer(n)) => *n as f64,
+        Some(Value::Float(f)) if f.is_finite() => *f,
+        _ => 0.0,
+    }
+}
+
+impl ResultRenderer for BarRenderer {
+    fn prepare(&mut self, table: &Table) {
+        for row in &table.rows {
+            self.max_value = self.max_value.max(bar_value(row));
+            self.label_width = self
+                .label_width
+                .max(display_width(&label(&table.schema, row)));
+            let value = row.values.last().map(ToString::to_string);
+            self.value_width = self.value_width.max(value.map_or(0, |v| display_width(&v)));
+        }
+    }
+
+    fn render_header(&mut self, _out: &mut dyn Write, _schema: &Schema) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn render_row(&mut self, out: &mut dyn Write, schema: &Schema, row: &Row) -> io::Result<()> {
+        let value = bar_value(row);
+        let length = if self.max_value > 0.0 && value > 0.0 {
+            ((value / self.max_value) * self.max_bar_width as f64).ceil() as usize
+        } else {
+            0
+        };
+        let shown = row
+            .values
+            .last()
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        let line = format!(
+            "{} │ {} {}",
+            fit(&label(schema, row), self.label_width, false),
+            fit(&shown, self.value_width, true),
+            BAR.to_string().repeat(length)
+        );
+        writeln!(out, "{}", line.trim_end())
+    }
+
+    fn render_footer(&mut self, _out: &mut dyn Write, _row_count: usize) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::render_to_string;
+    use crate::storage::table::{Column, DataType};
+
+    #[test]
+    fn test_bar_renderer_histogram() {
+        let schema = Schema::new(vec![
+            Column::new("bucket", DataType::Integer),
+            Column::new("lower", DataType::Float),
+            Column::new("upper", DataType::Float),
+            Column::new("count", DataType::Integer),
+        ]);
+        let row = |bucket, lower, upper, count| {
+            Row::new(vec![
+                Value::Integer(bucket),
+                Value::Float(lower),
+                Value::Float(upper),
+                Value::Integer(count),
+            ])
+        };
+        let table = Table::with_rows(
+            "histogram",
+            schema,
+            vec![
+                row(1, 0.0, 2.5, 4),
+                row(2, 2.5, 5.0, 0),
+                row(3, 5.0, 7.5, 10),
+            ],
+        );
+        let out = render_to_string(&mut BarRenderer::new(10), &table).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "0 – 2.5 │  4 ████");
+        assert_eq!(lines[1], "2.5 – 5 │  0");
+        assert_eq!(lines[2], "5 – 7.5 │ 10 ██████████");
+    }
+
+    #[test]
+    fn test_bar_renderer_frequencies() {
+        let out = render_to_string(
+            &mut BarRenderer::new(4),
+            &crate::render::test_support::sample_table(),
+        )
+        .unwrap();
+        assert!(out.starts_with("1, Alice     │  9.5 ████\n"));
+        assert!(out.contains("│ NULL\n"));
+    }
+}
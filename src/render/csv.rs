@@ -0,0 +1,55 @@
+use std::io::{self, Write};
+
+use crate::storage::table::{Row, Schema};
+
+use super::ResultRenderer;
+
+/// RFC 4180 style CSV with a header line.
+#[derive(Default)]
+pub struct CsvRenderer;
+
+impl CsvRenderer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+fn escape(s: &str) -> String {
+    if s.contains(',') || s.contains('"') || s.contains('\n') || s.contains('\r') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+impl ResultRenderer for CsvRenderer {
+    fn render_header(&mut self, out: &mut dyn Write, schema: &Schema) -> io::Result<()> {
+        let header: Vec<String> = schema.columns.iter().map(|c| escape(&c.name)).collect();
+        writeln!(out, "{}", header.join(","))
+    }
+
+    fn render_row(&mut self, out: &mut dyn Write, _schema: &Schema, row: &Row) -> io::Result<()> {
+        let values: Vec<String> = row.values.iter().map(|v| escape(&v.to_string())).collect();
+        writeln!(out, "{}", values.join(","))
+    }
+
+    fn render_footer(&mut self, _out: &mut dyn Write, _row_count: usize) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::render_to_string;
+    use crate::render::test_support::sample_table;
+
+    #[test]
+    fn test_csv_renderer() {
+        let out = render_to_string(&mut CsvRenderer::new(), &sample_table()).unwrap();
+        assert_eq!(
+            out,
+            "id,name,score\n1,Alice,9.5\n2,\"Bob, \"\"Jr\"\"\",NULL\n"
+        );
+    }
+}
@@ -0,0 +1,68 @@
+use std::io::{self, Write};
+
+use crate::storage::table::{Row, Schema, Value};
+
+use super::ResultRenderer;
+
+/// A standalone `<table>` element with escaped cell contents.
+#[derive(Default)]
+pub struct HtmlRenderer;
+
+impl HtmlRenderer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl ResultRenderer for HtmlRenderer {
+    fn render_header(&mut self, out: &mut dyn Write, schema: &Schema) -> io::Result<()> {
+        writeln!(out, "<table>")?;
+        writeln!(out, "  <thead>")?;
+        write!(out, "    <tr>")?;
+        for col in &schema.columns {
+            write!(out, "<th>{}</th>", escape(&col.name))?;
+        }
+        writeln!(out, "</tr>")?;
+        writeln!(out, "  </thead>")?;
+        writeln!(out, "  <tbody>")
+    }
+
+    fn render_row(&mut self, out: &mut dyn Write, _schema: &Schema, row: &Row) -> io::Result<()> {
+        write!(out, "    <tr>")?;
+        for value in &row.values {
+            match value {
+                Value::Null => write!(out, "<td class=\"null\">NULL</td>")?,
+                _ => write!(out, "<td>{}</td>", escape(&value.to_string()))?,
+            }
+        }
+        writeln!(out, "</tr>")
+    }
+
+    fn render_footer(&mut self, out: &mut dyn Write, _row_count: usize) -> io::Result<()> {
+        writeln!(out, "  </tbody>")?;
+        writeln!(out, "</table>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::render_to_string;
+    use crate::render::test_support::sample_table;
+
+    #[test]
+    fn test_html_renderer() {
+        let out = render_to_string(&mut HtmlRenderer::new(), &sample_table()).unwrap();
+        assert!(out.starts_with("<table>\n"));
+        assert!(out.contains("<th>id</th><th>name</th><th>score</th>"));
+        assert!(out.contains("<td>Bob, &quot;Jr&quot;</td><td class=\"null\">NULL</td>"));
+        assert!(out.ends_with("</table>\n"));
+    }
+}
@@ -0,0 +1,153 @@
+use std::io::{self, Write};
+
+use crate::storage::table::{json_string, Row, Schema};
+
+use super::ResultRenderer;
+
+/// Indentation of one level in pretty-printed output.
+const INDENT: &str = "  ";
+
+fn row_object(schema: &Schema, row: &Row) -> String {
+    let fields: Vec<String> = schema
+        .columns
+        .iter()
+        .zip(row.values.iter())
+        .map(|(col, val)| format!("{}:{}", json_string(&col.name), val.to_json()))
+        .collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+/// `row` as an object with one key per line, indented for an array element.
+/// Keys are in column order.
+fn pretty_row_object(schema: &Schema, row: &Row) -> String {
+    if schema.columns.is_empty() {
+        return format!("{}{{}}", INDENT);
+    }
+    let fields: Vec<String> = schema
+        .columns
+        .iter()
+        .zip(row.values.iter())
+        .map(|(col, val)| {
+            format!(
+                "{}{}{}: {}",
+                INDENT,
+                INDENT,
+                json_string(&col.name),
+                val.to_json()
+            )
+        })
+        .collect();
+    format!("{i}{{\n{}\n{i}}}", fields.join(",\n"), i = INDENT)
+}
+
+/// A single JSON array containing one object per row, either on one line or
+/// pretty-printed with one key per line.
+#[derive(Default)]
+pub struct JsonRenderer {
+    pretty: bool,
+    rows_written: usize,
+}
+
+impl JsonRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A renderer that indents the array, putting each row object and each
+    /// of its keys on its own line.
+    pub fn pretty() -> Self {
+        Self {
+            pretty: true,
+            ..Self::default()
+        }
+    }
+}
+
+impl ResultRenderer for JsonRenderer {
+    fn render_header(&mut self, out: &mut dyn Write, _schema: &Schema) -> io::Result<()> {
+        self.rows_written = 0;
+        write!(out, "[")
+    }
+
+    fn render_row(&mut self, out: &mut dyn Write, schema: &Schema, row: &Row) -> io::Result<()> {
+        if self.rows_written > 0 {
+            write!(out, ",")?;
+        }
+        self.rows_written += 1;
+        if self.pretty {
+            write!(out, "\n{}", pretty_row_object(schema, row))
+        } else {
+            write!(out, "{}", row_object(schema, row))
+        }
+    }
+
+    fn render_footer(&mut self, out: &mut dyn Write, _row_count: usize) -> io::Result<()> {
+        if self.pretty && self.rows_written > 0 {
+            writeln!(out)?;
+        }
+        writeln!(out, "]")
+    }
+}
+
+/// Newline-delimited JSON: one object per line.
+#[derive(Default)]
+pub struct NdjsonRenderer;
+
+impl NdjsonRenderer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl ResultRenderer for NdjsonRenderer {
+    fn render_header(&mut self, _out: &mut dyn Write, _schema: &Schema) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn render_row(&mut self, out: &mut dyn Write, schema: &Schema, row: &Row) -> io::Result<()> {
+        writeln!(out, "{}", row_object(schema, row))
+    }
+
+    fn render_footer(&mut self, _out: &mut dyn Write, _row_count: usize) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::render_to_string;
+    use crate::render::test_support::sample_table;
+    use crate::storage::table::Table;
+
+    #[test]
+    fn test_json_renderer() {
+        let out = render_to_string(&mut JsonRenderer::new(), &sample_table()).unwrap();
+        assert_eq!(
+            out,
+            "[{\"id\":1,\"name\":\"Alice\",\"score\":9.5},{\"id\":2,\"name\":\"Bob, \\\"Jr\\\"\",\"score\":null}]\n"
+        );
+    }
+
+    #[test]
+    fn test_pretty_json_renderer() {
+        let out = render_to_string(&mut JsonRenderer::pretty(), &sample_table()).unwrap();
+        assert_eq!(
+            out,
+            "[\n  {\n    \"id\": 1,\n    \"name\": \"Alice\",\n    \"score\": 9.5\n  },\n  \
+             {\n    \"id\": 2,\n    \"name\": \"Bob, \\\"Jr\\\"\",\n    \"score\": null\n  }\n]\n"
+        );
+
+        let empty = Table::new("empty", sample_table().schema);
+        let out = render_to_string(&mut JsonRenderer::pretty(), &empty).unwrap();
+        assert_eq!(out, "[]\n");
+    }
+
+    #[test]
+    fn test_ndjson_renderer() {
+        let out = render_to_string(&mut NdjsonRenderer::new(), &sample_table()).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], "{\"id\":1,\"name\":\"Alice\",\"score\":9.5}");
+    }
+}
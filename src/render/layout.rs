@@ -0,0 +1,127 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::storage::table::{DataType, Table};
+
+const TRUNCATION_MARKER: char = '…';
+
+/// Display width of each column: the widest of the header and its values,
+/// capped at `max_width`.
+pub fn column_widths(table: &Table, max_width: usize) -> Vec<usize> {
+    table
+        .schema
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(i, col)| {
+            let header_width = display_width(&col.name);
+            let max_value_width = table
+                .rows
+                .iter()
+                .map(|row| {
+                    row.values
+                        .get(i)
+                        .map(|v| display_width(&v.to_string()))
+                        .unwrap_or(0)
+                })
+                .max()
+                .unwrap_or(0);
+            header_width.max(max_value_width).min(max_width.max(1))
+        })
+        .collect()
+}
+
+pub fn is_numeric(data_type: &DataType) -> bool {
+    matches!(data_type, DataType::Integer | DataType::Float)
+}
+
+/// Number of terminal columns `s` occupies; wide (e.g. CJK) characters count as two.
+pub fn display_width(s: &str) -> usize {
+    UnicodeWidthStr::width(s)
+}
+
+/// Shorten `s` to at most `max_width` terminal columns, marking truncated
+/// values with a trailing `…`. Cuts only on grapheme cluster boundaries.
+pub fn truncate(s: &str, max_width: usize) -> String {
+    if display_width(s) <= max_width {
+        return s.to_string();
+    }
+
+    let budget = max_width.saturating_sub(1);
+    let mut out = String::new();
+    let mut used = 0;
+    for grapheme in s.graphemes(true) {
+        let w = display_width(grapheme);
+        if used + w > budget {
+            break;
+        }
+        out.push_str(grapheme);
+        used += w;
+    }
+    if max_width > 0 {
+        out.push(TRUNCATION_MARKER);
+    }
+    out
+}
+
+/// Truncate and pad `s` to exactly `width` columns, right-aligning when requested.
+pub fn fit(s: &str, width: usize, right_align: bool) -> String {
+    let s = truncate(s, width);
+    let padding = " ".repeat(width.saturating_sub(display_width(&s)));
+    if right_align {
+        format!("{}{}", padding, s)
+    } else {
+        format!("{}{}", s, padding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::table::{Column, Row, Schema, Value};
+
+    #[test]
+    fn test_column_widths_are_capped() {
+        let schema = Schema::new(vec![
+            Column::new("id", DataType::Integer),
+            Column::new("description", DataType::String),
+        ]);
+        let table = Table::with_rows(
+            "t",
+            schema,
+            vec![Row::new(vec![
+                Value::Integer(12345),
+                Value::String("x".repeat(100)),
+            ])],
+        );
+
+        assert_eq!(column_widths(&table, 20), vec![5, 20]);
+    }
+
+    #[test]
+    fn test_fit_aligns_and_truncates() {
+        assert_eq!(fit("42", 5, true), "   42");
+        assert_eq!(fit("ab", 5, false), "ab   ");
+        assert_eq!(fit("abcdefgh", 5, false), "abcd…");
+    }
+
+    #[test]
+    fn test_wide_characters() {
+        assert_eq!(display_width("東京"), 4);
+        assert_eq!(fit("東京", 6, false), "東京  ");
+        // A wide character never straddles the cut
+        assert_eq!(truncate("東京都", 4), "東…");
+        assert_eq!(display_width(&truncate("東京都", 4)), 3);
+    }
+
+    #[test]
+    fn test_truncate_keeps_grapheme_clusters() {
+        let family = "👨‍👩‍👧 family";
+        let truncated = truncate(family, 4);
+        assert!(truncated.starts_with("👨‍👩‍👧"));
+        assert!(truncated.ends_with('…'));
+
+        // Combining accents stay attached to their base character
+        assert_eq!(truncate("e\u{301}e\u{301}e\u{301}", 2), "e\u{301}…");
+    }
+}
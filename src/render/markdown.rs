@@ -0,0 +1,65 @@
+use std::io::{self, Write};
+
+use crate::storage::table::{Row, Schema};
+
+use super::layout::is_numeric;
+use super::ResultRenderer;
+
+/// GitHub-flavoured markdown table; numeric columns are right-aligned.
+#[derive(Default)]
+pub struct MarkdownRenderer;
+
+impl MarkdownRenderer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', "<br>")
+}
+
+impl ResultRenderer for MarkdownRenderer {
+    fn render_header(&mut self, out: &mut dyn Write, schema: &Schema) -> io::Result<()> {
+        let names: Vec<String> = schema.columns.iter().map(|c| escape(&c.name)).collect();
+        writeln!(out, "| {} |", names.join(" | "))?;
+
+        let align: Vec<&str> = schema
+            .columns
+            .iter()
+            .map(|c| {
+                if is_numeric(&c.data_type) {
+                    "---:"
+                } else {
+                    "---"
+                }
+            })
+            .collect();
+        writeln!(out, "| {} |", align.join(" | "))
+    }
+
+    fn render_row(&mut self, out: &mut dyn Write, _schema: &Schema, row: &Row) -> io::Result<()> {
+        let values: Vec<String> = row.values.iter().map(|v| escape(&v.to_string())).collect();
+        writeln!(out, "| {} |", values.join(" | "))
+    }
+
+    fn render_footer(&mut self, _out: &mut dyn Write, _row_count: usize) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::render_to_string;
+    use crate::render::test_support::sample_table;
+
+    #[test]
+    fn test_markdown_renderer() {
+        let out = render_to_string(&mut MarkdownRenderer::new(), &sample_table()).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "| id | name | score |");
+        assert_eq!(lines[1], "| ---: | --- | ---: |");
+        assert_eq!(lines[2], "| 1 | Alice | 9.5 |");
+    }
+}
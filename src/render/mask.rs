@@ -0,0 +1,375 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::datafusion::writes_to_file;
+use crate::storage::table::{Column, DataType, Schema, Table, Value};
+
+/// Text that replaces a value masked with `redact`.
+pub const REDACTED: &str = "***";
+
+/// Names substituted for words by the `fake` action.
+const FAKE_NAMES: [&str; 24] = [
+    "Alex", "Avery", "Blake", "Casey", "Drew", "Emery", "Finley", "Hayden", "Jamie", "Jordan",
+    "Kendall", "Logan", "Morgan", "Parker", "Peyton", "Quinn", "Reese", "Riley", "Rowan", "Sage",
+    "Skyler", "Tatum", "Taylor", "Wren",
+];
+
+/// Masking rules applied to results before they are rendered or exported,
+/// so output from sensitive files can be shared.
+///
+/// Rules are read from a text file with one rule per line:
+///
+/// ```text
+/// # column      action   arguments
+/// email         hash
+/// *_ssn         regex    \d{3}-\d{2}   ***-**
+/// notes         redact
+/// phone         redact   [phone]
+/// ```
+///
+/// The column is matched case-insensitively against result column names and
+/// may contain `*` wildcards. The first matching rule wins. Actions:
+///
+/// - `redact [text]` replaces the value with `text` (default `***`).
+/// - `hash` replaces the value with a stable 16-digit hex hash, so equal
+///   values still group and join together.
+/// - `fake` replaces the value with a made-up one of the same shape: emails
+///   become `user_<hash>@example.com`, digits in phone numbers and IDs are
+///   replaced, and words become names. Like `hash`, equal values stay equal.
+/// - `regex <pattern> <replacement>` replaces every match of `pattern`;
+///   `$1`-style group references work in `replacement`.
+///
+/// NULLs are never masked. Masked columns become strings.
+#[derive(Debug, Clone, Default)]
+pub struct MaskRules {
+    rules: Vec<MaskRule>,
+}
+
+#[derive(Debug, Clone)]
+struct MaskRule {
+    column: String,
+    action: MaskAction,
+}
+
+#[derive(Debug, Clone)]
+enum MaskAction {
+    Redact(String),
+    Hash,
+    Fake,
+    Regex(Regex, String),
+}
+
+impl MaskRules {
+    pub fn from_file(path: &Path) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Parse rules in the format described on [`MaskRules`]. Errors name the
+    /// offending line.
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let mut rules = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = |message: String| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("mask rule on line {}: {}", i + 1, message),
+                )
+            };
+
+            let mut parts = line.split_whitespace();
+            let column = parts.next().unwrap_or_default().to_lowercase();
+            let action = match parts.next().map(str::to_lowercase).as_deref() {
+                Some("redact") => {
+                    let text: Vec<&str> = parts.collect();
+                    MaskAction::Redact(if text.is_empty() {
+                        REDACTED.to_string()
+                    } else {
+                        text.join(" ")
+                    })
+                }
+                Some("hash") => MaskAction::Hash,
+                Some("fake") => MaskAction::Fake,
+                Some("regex") => {
+                    let pattern = parts
+                        .next()
+                        .ok_or_else(|| invalid("regex needs a pattern".to_string()))?;
+                    let regex = Regex::new(pattern).map_err(|e| invalid(e.to_string()))?;
+                    MaskAction::Regex(regex, parts.collect::<Vec<_>>().join(" "))
+                }
+                Some(other) => return Err(invalid(format!("unknown action '{}'", other))),
+                None => return Err(invalid(format!("missing action for '{}'", column))),
+            };
+            rules.push(MaskRule { column, action });
+        }
+        Ok(Self { rules })
+    }
+
+    /// Rules from a comma-separated list of columns, each optionally
+    /// followed by `:action`, e.g. `email,name:hash,notes:redact`. Columns
+    /// without an action are faked.
+    pub fn for_columns(list: &str) -> io::Result<Self> {
+        let rules: Vec<String> = list
+            .split(',')
+            .map(str::trim)
+            .filter(|column| !column.is_empty())
+            .map(|column| match column.split_once(':') {
+                Some((column, action)) => format!("{} {}", column, action),
+                None => format!("{} fake", column),
+            })
+            .collect();
+        Self::parse(&rules.join("\n"))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Refuse `sql` while rules are active if it is a `COPY ... TO` or
+    /// `EXPORT ... TO` statement: DataFusion writes those rows to the file
+    /// itself, so they would never be masked.
+    pub fn check_statement(&self, sql: &str) -> io::Result<()> {
+        if self.is_empty() || !writes_to_file(sql) {
+            return Ok(());
+        }
+        Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "COPY and EXPORT are not allowed while mask rules are active, since the rows \
+             they write would not be masked",
+        ))
+    }
+
+    /// Column patterns of the rules that match no column of `schema`, e.g.
+    /// to reject a misspelt column instead of leaving it unmasked.
+    pub fn unmatched(&self, schema: &Schema) -> Vec<&str> {
+        self.rules
+            .iter()
+            .filter(|rule| {
+                !schema
+                    .columns
+                    .iter()
+                    .any(|column| wildcard_match(&rule.column, &column.name.to_lowercase()))
+            })
+            .map(|rule| rule.column.as_str())
+            .collect()
+    }
+
+    /// Mask every column of `table` that a rule matches.
+    pub fn apply(&self, table: &mut Table) {
+        let matches: Vec<(usize, &MaskAction)> = table
+            .schema
+            .columns
+            .iter()
+            .enumerate()
+            .filter_map(|(i, column)| Some((i, self.action_for(&column.name)?)))
+            .collect();
+        if matches.is_empty() {
+            return;
+        }
+
+        for row in &mut table.rows {
+            for &(i, action) in &matches {
+                if let Some(value) = row.values.get_mut(i) {
+                    if !value.is_null() {
+                        *value = Value::String(action.mask(&value.to_string()));
+                    }
+                }
+            }
+        }
+
+        let mut columns = table.schema.columns.clone();
+        for &(i, _) in &matches {
+            columns[i] = Column::new(columns[i].name.clone(), DataType::String);
+        }
+        table.schema = Schema::new(columns);
+    }
+
+    /// The masking function of the first rule matching `column`, for
+    /// masking values that are not held in a [`Table`], such as the batches
+    /// of a streamed export.
+    pub fn masker(&self, column: &str) -> Option<impl Fn(&str) -> String + '_> {
+        let action = self.action_for(column)?;
+        Some(move |value: &str| action.mask(value))
+    }
+
+    fn action_for(&self, column: &str) -> Option<&MaskAction> {
+        let column = column.to_lowercase();
+        self.rules
+            .iter()
+            .find(|rule| wildcard_match(&rule.column, &column))
+            .map(|rule| &rule.action)
+    }
+}
+
+impl MaskAction {
+    fn mask(&self, value: &str) -> String {
+        match self {
+            MaskAction::Redact(text) => text.clone(),
+            MaskAction::Hash => format!("{:016x}", fnv1a(value.as_bytes())),
+            MaskAction::Fake => fake(value),
+            MaskAction::Regex(regex, replacement) => {
+                regex.replace_all(value, replacement.as_str()).into_owned()
+            }
+        }
+    }
+}
+
+/// A made-up value shaped like `value`, chosen by its hash.
+fn fake(value: &str) -> String {
+    let hash = fnv1a(value.as_bytes());
+    if value.contains('@') {
+        return format!("user_{:08x}@example.com", hash as u32);
+    }
+
+    let is_number_like = |c: char| c.is_ascii_digit() || " -+().".contains(c);
+    if value.chars().any(|c| c.is_ascii_digit()) && value.chars().all(is_number_like) {
+        let mut state = hash;
+        return value
+            .chars()
+            .map(|c| {
+                if !c.is_ascii_digit() {
+                    return c;
+                }
+                state = state
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1_442_695_040_888_963_407);
+                char::from(b'0' + ((state >> 33) % 10) as u8)
+            })
+            .collect();
+    }
+
+    let is_word_like = |c: char| c.is_alphabetic() || " -'.".contains(c);
+    if value.chars().any(char::is_alphabetic) && value.chars().all(is_word_like) {
+        return value
+            .split_whitespace()
+            .enumerate()
+            .map(|(i, _)| FAKE_NAMES[(hash.rotate_left(8 * i as u32) % 24) as usize])
+            .collect::<Vec<_>>()
+            .join(" ");
+    }
+
+    format!("{:016x}", hash)
+}
+
+/// 64-bit FNV-1a, used instead of `DefaultHasher` because its output must
+/// not change between Rust releases.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Match `text` against `pattern`, where `*` matches any run of characters.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::test_support::sample_table;
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("email", "email"));
+        assert!(!wildcard_match("email", "emails"));
+        assert!(wildcard_match("*_ssn", "user_ssn"));
+        assert!(wildcard_match("*", "anything"));
+        assert!(wildcard_match("a*b*c", "axxbyyc"));
+        assert!(!wildcard_match("a*b*c", "axxc"));
+    }
+
+    #[test]
+    fn test_apply_rules() {
+        let rules = MaskRules::parse(
+            "# sample rules\n\
+             NAME  regex  ^(\\w)\\w*  ${1}.\n\
+             score redact [hidden]\n\
+             id    hash\n",
+        )
+        .unwrap();
+        let mut table = sample_table();
+        rules.apply(&mut table);
+
+        assert_eq!(table.rows[0].values[1], Value::String("A.".to_string()));
+        assert_eq!(
+            table.rows[0].values[2],
+            Value::String("[hidden]".to_string())
+        );
+        assert_eq!(table.rows[1].values[2], Value::Null);
+        assert_eq!(table.schema.columns[0].data_type, DataType::String);
+
+        let hashed = table.rows[0].values[0].to_string();
+        assert_eq!(hashed.len(), 16);
+        assert_ne!(hashed, table.rows[1].values[0].to_string());
+    }
+
+    #[test]
+    fn test_fake_values() {
+        assert!(fake("ann@corp.com").ends_with("@example.com"));
+        assert_eq!(fake("ann@corp.com"), fake("ann@corp.com"));
+
+        let phone = fake("(555) 123-4567");
+        assert_eq!(phone.len(), "(555) 123-4567".len());
+        assert!(phone.starts_with('(') && phone.contains(") ") && phone.contains('-'));
+
+        let name = fake("Alice Johnson");
+        assert_eq!(name.split(' ').count(), 2);
+        assert!(name.split(' ').all(|word| FAKE_NAMES.contains(&word)));
+        assert_eq!(fake("x_1/2").len(), 16);
+    }
+
+    #[test]
+    fn test_for_columns() {
+        let rules = MaskRules::for_columns("email, score:redact").unwrap();
+        let mut table = sample_table();
+        assert_eq!(rules.unmatched(&table.schema), vec!["email"]);
+        rules.apply(&mut table);
+        assert_eq!(table.rows[0].values[2], Value::String(REDACTED.to_string()));
+
+        assert!(MaskRules::for_columns("email:scramble").is_err());
+    }
+
+    #[test]
+    fn test_copy_refused_while_masking() {
+        let copy = "COPY (SELECT name, email FROM users) TO '/tmp/out.csv'";
+        assert!(MaskRules::default().check_statement(copy).is_ok());
+
+        let rules = MaskRules::parse("email redact").unwrap();
+        let err = rules.check_statement(copy).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        assert!(rules
+            .check_statement("export users TO 'out.parquet'")
+            .is_err());
+        assert!(rules.check_statement("SELECT email FROM users").is_ok());
+    }
+
+    #[test]
+    fn test_parse_errors_name_the_line() {
+        let err = MaskRules::parse("email hash\nssn scramble").unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+        assert!(MaskRules::parse("ssn regex (").is_err());
+        assert!(MaskRules::parse("email").is_err());
+    }
+}
@@ -0,0 +1,136 @@
+//! Result formatting shared by the CLI printers, the TUI and the GUI.
+//!
+//! Output formats implement [`ResultRenderer`]; downstream crates can add their
+//! own formats by implementing the trait and passing the renderer to
+//! [`render_to_string`] or calling [`ResultRenderer::render`] directly.
+
+mod bars;
+mod csv;
+mod html;
+mod json;
+mod layout;
+mod markdown;
+mod mask;
+mod table;
+
+use std::io::{self, Write};
+
+use crate::storage::table::{Row, Schema, Table};
+
+pub use self::bars::{BarRenderer, DEFAULT_BAR_WIDTH};
+pub use self::csv::CsvRenderer;
+pub use html::HtmlRenderer;
+pub use json::{JsonRenderer, NdjsonRenderer};
+pub use layout::{column_widths, display_width, fit, is_numeric, truncate};
+pub use markdown::MarkdownRenderer;
+pub use mask::{MaskRules, REDACTED};
+pub use table::TableRenderer;
+
+/// Default upper bound for a rendered column, in characters.
+pub const DEFAULT_MAX_COL_WIDTH: usize = 40;
+
+/// Writes a result set in a particular output format.
+///
+/// Rendering happens in three phases so formats with framing (JSON arrays,
+/// HTML tables) and formats without (CSV, NDJSON) share one interface.
+pub trait ResultRenderer {
+    /// Inspect the complete result before anything is written. Renderers that
+    /// need to measure every value up front, such as aligned tables, use this.
+    fn prepare(&mut self, _table: &Table) {}
+
+    fn render_header(&mut self, out: &mut dyn Write, schema: &Schema) -> io::Result<()>;
+
+    fn render_row(&mut self, out: &mut dyn Write, schema: &Schema, row: &Row) -> io::Result<()>;
+
+    fn render_footer(&mut self, out: &mut dyn Write, row_count: usize) -> io::Result<()>;
+
+    /// Render a whole table: prepare, header, every row, then footer.
+    fn render(&mut self, out: &mut dyn Write, table: &Table) -> io::Result<()> {
+        self.prepare(table);
+        self.render_header(out, &table.schema)?;
+        for row in &table.rows {
+            self.render_row(out, &table.schema, row)?;
+        }
+        self.render_footer(out, table.row_count())
+    }
+}
+
+/// Look up a built-in renderer by format name (`table`, `csv`, `json`,
+/// `json-pretty`, `ndjson`/`jsonl`, `markdown`/`md`, `html`, `bars`/`histogram`),
+/// case-insensitively.
+pub fn renderer_for(name: &str, max_col_width: usize) -> Option<Box<dyn ResultRenderer>> {
+    let renderer: Box<dyn ResultRenderer> = match name.to_lowercase().as_str() {
+        "table" => Box::new(TableRenderer::new(max_col_width)),
+        "csv" => Box::new(CsvRenderer::new()),
+        "json" => Box::new(JsonRenderer::new()),
+        "json-pretty" => Box::new(JsonRenderer::pretty()),
+        "ndjson" | "jsonl" => Box::new(NdjsonRenderer::new()),
+        "markdown" | "md" => Box::new(MarkdownRenderer::new()),
+        "html" => Box::new(HtmlRenderer::new()),
+        "bars" | "histogram" => Box::new(BarRenderer::default()),
+        _ => return None,
+    };
+    Some(renderer)
+}
+
+/// Render every row of `table` like [`ResultRenderer::render`], including
+/// the rows of a spilled result that are only on disk. Those are read back
+/// a page at a time and masked with `masks`, since only the rows held in
+/// memory were masked with the table. Renderers that measure values in
+/// `prepare` measure the rows in memory.
+pub fn render_all(
+    renderer: &mut dyn ResultRenderer,
+    out: &mut dyn Write,
+    table: &Table,
+    masks: &MaskRules,
+) -> io::Result<()> {
+    renderer.prepare(table);
+    renderer.render_header(out, &table.schema)?;
+    for row in &table.rows {
+        renderer.render_row(out, &table.schema, row)?;
+    }
+    for page in table.spilled_pages() {
+        let mut page = page?;
+        masks.apply(&mut page);
+        for row in &page.rows {
+            renderer.render_row(out, &table.schema, row)?;
+        }
+    }
+    renderer.render_footer(out, table.total_row_count())
+}
+
+/// Render `table` into a string with the given renderer.
+pub fn render_to_string(renderer: &mut dyn ResultRenderer, table: &Table) -> io::Result<String> {
+    let mut buf = Vec::new();
+    renderer.render(&mut buf, table)?;
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    use crate::storage::table::{Column, DataType, Row, Schema, Table, Value};
+
+    pub fn sample_table() -> Table {
+        let schema = Schema::new(vec![
+            Column::new("id", DataType::Integer),
+            Column::new("name", DataType::String),
+            Column::new("score", DataType::Float),
+        ]);
+        Table::with_rows(
+            "result",
+            schema,
+            vec![
+                Row::new(vec![
+                    Value::Integer(1),
+                    Value::String("Alice".to_string()),
+                    Value::Float(9.5),
+                ]),
+                Row::new(vec![
+                    Value::Integer(2),
+                    Value::String("Bob, \"Jr\"".to_string()),
+                    Value::Null,
+                ]),
+            ],
+        )
+    }
+}
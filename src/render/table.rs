@@ -0,0 +1,110 @@
+use std::io::{self, Write};
+
+use crate::storage::table::{Row, Schema, Table};
+
+use super::layout::{column_widths, fit, is_numeric};
+use super::ResultRenderer;
+
+/// Aligned plain-text table with a `(N rows)` footer.
+pub struct TableRenderer {
+    max_col_width: usize,
+    widths: Vec<usize>,
+    numeric: Vec<bool>,
+}
+
+impl TableRenderer {
+    pub fn new(max_col_width: usize) -> Self {
+        Self {
+            max_col_width,
+            widths: Vec::new(),
+            numeric: Vec::new(),
+        }
+    }
+
+    fn width(&self, index: usize) -> usize {
+        self.widths.get(index).copied().unwrap_or(0)
+    }
+
+    fn is_numeric(&self, index: usize) -> bool {
+        self.numeric.get(index).copied().unwrap_or(false)
+    }
+}
+
+impl Default for TableRenderer {
+    fn default() -> Self {
+        Self::new(super::DEFAULT_MAX_COL_WIDTH)
+    }
+}
+
+impl ResultRenderer for TableRenderer {
+    fn prepare(&mut self, table: &Table) {
+        self.widths = if table.row_count() == 0 {
+            Vec::new()
+        } else {
+            column_widths(table, self.max_col_width)
+        };
+        self.numeric = table
+            .schema
+            .columns
+            .iter()
+            .map(|col| is_numeric(&col.data_type))
+            .collect();
+    }
+
+    fn render_header(&mut self, out: &mut dyn Write, schema: &Schema) -> io::Result<()> {
+        // Empty results only print the footer
+        if self.widths.is_empty() {
+            return Ok(());
+        }
+
+        let header: Vec<String> = schema
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, col)| fit(&col.name, self.width(i), self.is_numeric(i)))
+            .collect();
+        writeln!(out, "{}", header.join(" | "))?;
+
+        let sep: Vec<String> = self.widths.iter().map(|&w| "-".repeat(w)).collect();
+        writeln!(out, "{}", sep.join("-+-"))
+    }
+
+    fn render_row(&mut self, out: &mut dyn Write, _schema: &Schema, row: &Row) -> io::Result<()> {
+        let values: Vec<String> = row
+            .values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| fit(&v.to_string(), self.width(i), self.is_numeric(i)))
+            .collect();
+        writeln!(out, "{}", values.join(" | "))
+    }
+
+    fn render_footer(&mut self, out: &mut dyn Write, row_count: usize) -> io::Result<()> {
+        writeln!(out, "({} rows)", row_count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::render_to_string;
+    use crate::render::test_support::sample_table;
+    use crate::storage::table::{Column, DataType};
+
+    #[test]
+    fn test_table_renderer() {
+        let out = render_to_string(&mut TableRenderer::new(40), &sample_table()).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "id | name      | score");
+        assert_eq!(lines[1], "---+-----------+------");
+        assert_eq!(lines[2], " 1 | Alice     |   9.5");
+        assert_eq!(lines[4], "(2 rows)");
+    }
+
+    #[test]
+    fn test_table_renderer_empty() {
+        let table = Table::new("t", Schema::new(vec![Column::new("id", DataType::Integer)]));
+        let out = render_to_string(&mut TableRenderer::default(), &table).unwrap();
+        assert_eq!(out, "(0 rows)\n");
+    }
+}
@@ -0,0 +1,224 @@
+use std::io::{self, stderr, stdin, BufRead, IsTerminal, Write};
+
+use crossterm::cursor::MoveToColumn;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::queue;
+use crossterm::style::Print;
+use crossterm::terminal::{self, Clear, ClearType};
+use unicode_width::UnicodeWidthStr;
+
+/// Most lines kept in the history.
+const MAX_HISTORY: usize = 1000;
+
+/// The outcome of [`LineEditor::read_line`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReadLine {
+    Line(String),
+    /// Ctrl+C was pressed; the line was discarded.
+    Interrupted,
+    /// Ctrl+D on an empty line, or the end of piped input.
+    Eof,
+}
+
+/// Reads lines for the `--repl` mode. On a terminal, lines are edited in
+/// place with the arrow keys, Home/End, Ctrl+A/E/U/W and Backspace/Delete,
+/// and Up/Down step through the lines entered earlier in the session. The
+/// prompt and line are drawn on stderr, so stdout only carries results.
+/// Piped input is read a line at a time without prompts.
+#[derive(Debug)]
+pub struct LineEditor {
+    history: Vec<String>,
+    interactive: bool,
+}
+
+impl Default for LineEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LineEditor {
+    pub fn new() -> Self {
+        Self {
+            history: Vec::new(),
+            interactive: stdin().is_terminal() && stderr().is_terminal(),
+        }
+    }
+
+    /// Whether input comes from a terminal rather than a pipe or file.
+    pub fn is_interactive(&self) -> bool {
+        self.interactive
+    }
+
+    /// Read one line, showing `prompt` first on a terminal.
+    pub fn read_line(&mut self, prompt: &str) -> io::Result<ReadLine> {
+        if !self.interactive {
+            let mut line = String::new();
+            if stdin().lock().read_line(&mut line)? == 0 {
+                return Ok(ReadLine::Eof);
+            }
+            let line = line.strip_suffix('\n').unwrap_or(&line);
+            return Ok(ReadLine::Line(
+                line.strip_suffix('\r').unwrap_or(line).to_string(),
+            ));
+        }
+
+        terminal::enable_raw_mode()?;
+        let result = self.edit_line(prompt);
+        terminal::disable_raw_mode()?;
+        let result = result?;
+        if let ReadLine::Line(line) = &result {
+            if !line.trim().is_empty() && self.history.last() != Some(line) {
+                self.history.push(line.clone());
+                if self.history.len() > MAX_HISTORY {
+                    self.history.remove(0);
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    fn edit_line(&self, prompt: &str) -> io::Result<ReadLine> {
+        let mut out = stderr();
+        let mut line = EditBuffer::default();
+        // Position in the history while browsing it, and the line being
+        // typed before browsing started
+        let mut browsing: Option<(usize, String)> = None;
+
+        loop {
+            redraw(&mut out, prompt, &line)?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let KeyEvent {
+                code, modifiers, ..
+            } = key;
+            let control = modifiers.contains(KeyModifiers::CONTROL);
+            match code {
+                KeyCode::Enter => {
+                    write!(out, "\r\n")?;
+                    return Ok(ReadLine::Line(line.text()));
+                }
+                KeyCode::Char('c') if control => {
+                    write!(out, "^C\r\n")?;
+                    return Ok(ReadLine::Interrupted);
+                }
+                KeyCode::Char('d') if control => {
+                    if line.chars.is_empty() {
+                        write!(out, "\r\n")?;
+                        return Ok(ReadLine::Eof);
+                    }
+                    line.delete();
+                }
+                KeyCode::Char('a') if control => line.cursor = 0,
+                KeyCode::Char('e') if control => line.cursor = line.chars.len(),
+                KeyCode::Char('u') if control => line.kill_to_start(),
+                KeyCode::Char('w') if control => line.kill_word(),
+                KeyCode::Char(c) if !control => line.insert(c),
+                KeyCode::Tab => line.insert(' '),
+                KeyCode::Backspace => line.backspace(),
+                KeyCode::Delete => line.delete(),
+                KeyCode::Left => line.cursor = line.cursor.saturating_sub(1),
+                KeyCode::Right => line.cursor = (line.cursor + 1).min(line.chars.len()),
+                KeyCode::Home => line.cursor = 0,
+                KeyCode::End => line.cursor = line.chars.len(),
+                KeyCode::Up => {
+                    let index = match &browsing {
+                        Some((index, _)) => index.checked_sub(1),
+                        None => self.history.len().checked_sub(1),
+                    };
+                    if let Some(index) = index {
+                        let draft = browsing.take().map_or_else(|| line.text(), |(_, d)| d);
+                        line = EditBuffer::from(self.history[index].as_str());
+                        browsing = Some((index, draft));
+                    }
+                }
+                KeyCode::Down => {
+                    if let Some((index, draft)) = browsing.take() {
+                        if index + 1 < self.history.len() {
+                            line = EditBuffer::from(self.history[index + 1].as_str());
+                            browsing = Some((index + 1, draft));
+                        } else {
+                            line = EditBuffer::from(draft.as_str());
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn redraw(out: &mut impl Write, prompt: &str, line: &EditBuffer) -> io::Result<()> {
+    let before: String = line.chars[..line.cursor].iter().collect();
+    let column = (prompt.width() + before.width()).min(u16::MAX as usize) as u16;
+    queue!(
+        out,
+        MoveToColumn(0),
+        Print(prompt),
+        Print(line.text()),
+        Clear(ClearType::UntilNewLine),
+        MoveToColumn(column)
+    )?;
+    out.flush()
+}
+
+/// The line being edited, with the cursor as a character index.
+#[derive(Debug, Default)]
+struct EditBuffer {
+    chars: Vec<char>,
+    cursor: usize,
+}
+
+impl From<&str> for EditBuffer {
+    fn from(text: &str) -> Self {
+        let chars: Vec<char> = text.chars().collect();
+        let cursor = chars.len();
+        Self { chars, cursor }
+    }
+}
+
+impl EditBuffer {
+    fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    fn insert(&mut self, c: char) {
+        self.chars.insert(self.cursor, c);
+        self.cursor += 1;
+    }
+
+    fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.chars.remove(self.cursor);
+        }
+    }
+
+    fn delete(&mut self) {
+        if self.cursor < self.chars.len() {
+            self.chars.remove(self.cursor);
+        }
+    }
+
+    fn kill_to_start(&mut self) {
+        self.chars.drain(..self.cursor);
+        self.cursor = 0;
+    }
+
+    /// Delete the word before the cursor, with any whitespace between them.
+    fn kill_word(&mut self) {
+        let mut start = self.cursor;
+        while start > 0 && self.chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+        while start > 0 && !self.chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+        self.chars.drain(start..self.cursor);
+        self.cursor = start;
+    }
+}
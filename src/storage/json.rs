@@ -0,0 +1,224 @@
+//! Reading JSON records into a [`Table`] without going through DataFusion.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde_json::{Map, Value as JsonValue};
+
+use super::table::{Column, DataType, Row, Schema, Table, Value};
+
+/// Reads a JSON array of objects, or newline-delimited JSON (one object per
+/// line, or any whitespace-separated sequence of objects), into a [`Table`].
+///
+/// The schema is inferred across all records: columns appear in the order
+/// their keys are first seen, and a key missing from a record is NULL there.
+/// Nested objects are flattened into dotted column names, so
+/// `{"address": {"city": "Oslo"}}` gives an `address.city` column. Arrays
+/// have no table type and are kept as JSON text.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonReader;
+
+impl JsonReader {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Read the file at `path` into a table named after its file stem.
+    pub fn read_file(&self, path: &Path) -> io::Result<Table> {
+        let text = fs::read_to_string(path)?;
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        self.read_str(&name, &text)
+    }
+
+    /// Read JSON `text` into a table called `name`. Malformed JSON and
+    /// records that are not objects are `InvalidData` errors.
+    pub fn read_str(&self, name: &str, text: &str) -> io::Result<Table> {
+        let values = serde_json::Deserializer::from_str(text)
+            .into_iter::<JsonValue>()
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| invalid(format!("Invalid JSON: {}", e)))?;
+        // A lone top-level array holds the records
+        let records = match <[JsonValue; 1]>::try_from(values) {
+            Ok([JsonValue::Array(items)]) => items,
+            Ok([value]) => vec![value],
+            Err(values) => values,
+        };
+
+        let mut flattened = Vec::with_capacity(records.len());
+        for (index, record) in records.into_iter().enumerate() {
+            let JsonValue::Object(object) = record else {
+                return Err(invalid(format!(
+                    "JSON record {} is not an object",
+                    index + 1
+                )));
+            };
+            let mut fields = Vec::new();
+            flatten("", object, &mut fields);
+            flattened.push(fields);
+        }
+        Ok(build_table(name, flattened))
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Append the leaves of `object` to `fields` as (dotted name, value) pairs.
+fn flatten(prefix: &str, object: Map<String, JsonValue>, fields: &mut Vec<(String, JsonValue)>) {
+    for (key, value) in object {
+        let name = if prefix.is_empty() {
+            key
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            JsonValue::Object(nested) if !nested.is_empty() => flatten(&name, nested, fields),
+            value => fields.push((name, value)),
+        }
+    }
+}
+
+/// Column type that holds every value seen so far: integers widen to
+/// floats, and any other mix of kinds to strings.
+fn widen(current: DataType, value: &JsonValue) -> DataType {
+    let kind = match value {
+        JsonValue::Null => return current,
+        JsonValue::Bool(_) => DataType::Boolean,
+        JsonValue::Number(n) if n.is_i64() => DataType::Integer,
+        JsonValue::Number(_) => DataType::Float,
+        _ => DataType::String,
+    };
+    match (current, kind) {
+        (DataType::Null, kind) => kind,
+        (current, kind) if current == kind => current,
+        (DataType::Integer, DataType::Float) | (DataType::Float, DataType::Integer) => {
+            DataType::Float
+        }
+        _ => DataType::String,
+    }
+}
+
+fn build_table(name: &str, records: Vec<Vec<(String, JsonValue)>>) -> Table {
+    let mut columns: Vec<Column> = Vec::new();
+    for fields in &records {
+        for (key, value) in fields {
+            let index = match columns.iter().position(|column| column.name == *key) {
+                Some(index) => index,
+                None => {
+                    columns.push(Column::new(key.clone(), DataType::Null));
+                    columns.len() - 1
+                }
+            };
+            columns[index].data_type = widen(columns[index].data_type.clone(), value);
+        }
+    }
+
+    let rows = records
+        .into_iter()
+        .map(|fields| {
+            let mut values = vec![Value::Null; columns.len()];
+            for (key, value) in fields {
+                if let Some(index) = columns.iter().position(|column| column.name == key) {
+                    values[index] = convert(value, &columns[index].data_type);
+                }
+            }
+            Row::new(values)
+        })
+        .collect();
+    Table::with_rows(name, Schema::new(columns), rows)
+}
+
+/// `value` as a value of a column of type `data_type`.
+fn convert(value: JsonValue, data_type: &DataType) -> Value {
+    match (value, data_type) {
+        (JsonValue::Null, _) => Value::Null,
+        (JsonValue::Bool(b), DataType::Boolean) => Value::Boolean(b),
+        (JsonValue::Number(n), DataType::Integer) => n.as_i64().map_or(Value::Null, Value::Integer),
+        (JsonValue::Number(n), DataType::Float) => n.as_f64().map_or(Value::Null, Value::Float),
+        (JsonValue::String(s), _) => Value::String(s),
+        (value, _) => Value::String(value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_read_json_array() {
+        let table = JsonReader::new()
+            .read_str(
+                "people",
+                r#"[{"id": 1, "name": "Ada"}, {"id": 2, "name": "Grace", "age": 36}]"#,
+            )
+            .unwrap();
+        assert_eq!(table.schema.column_names(), vec!["id", "name", "age"]);
+        assert_eq!(table.row_count(), 2);
+        assert_eq!(table.rows[0].values[2], Value::Null);
+        assert_eq!(table.rows[1].values[2], Value::Integer(36));
+    }
+
+    #[test]
+    fn test_read_ndjson_flattens_nested_objects() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("samples")
+            .join("employees_nested.json");
+        let table = JsonReader::new().read_file(&path).unwrap();
+        assert_eq!(table.name, "employees_nested");
+        assert_eq!(
+            table.schema.column_names(),
+            vec!["id", "name", "address.city", "address.country", "skills"]
+        );
+        let city = table.get_column_index("address.city").unwrap();
+        assert_eq!(
+            table.rows[1].values[city],
+            Value::String("London".to_string())
+        );
+        let skills = table.get_column_index("skills").unwrap();
+        assert_eq!(
+            table.rows[0].values[skills],
+            Value::String(r#"["Rust","Python","SQL"]"#.to_string())
+        );
+    }
+
+    #[test]
+    fn test_column_types_widen_across_records() {
+        let table = JsonReader::new()
+            .read_str(
+                "mixed",
+                "{\"a\": 1, \"b\": 1, \"c\": true, \"d\": null}\n\
+                 {\"a\": 2.5, \"b\": \"two\", \"c\": false, \"d\": null}\n",
+            )
+            .unwrap();
+        let types: Vec<&DataType> = table.schema.columns.iter().map(|c| &c.data_type).collect();
+        assert_eq!(
+            types,
+            vec![
+                &DataType::Float,
+                &DataType::String,
+                &DataType::Boolean,
+                &DataType::Null
+            ]
+        );
+        assert_eq!(table.rows[0].values[0], Value::Float(1.0));
+        assert_eq!(table.rows[0].values[1], Value::String("1".to_string()));
+    }
+
+    #[test]
+    fn test_records_must_be_objects() {
+        let err = JsonReader::new()
+            .read_str("bad", "{\"a\": 1}\n[1, 2]\n")
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("record 2"));
+
+        let err = JsonReader::new().read_str("bad", "{\"a\": ").unwrap_err();
+        assert!(err.to_string().starts_with("Invalid JSON"));
+    }
+}
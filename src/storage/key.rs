@@ -0,0 +1,163 @@
+use std::hash::{Hash, Hasher};
+
+use super::table::Value;
+
+/// Hashable view of a [`Value`] for grouping, deduplication and distinct
+/// counts.
+///
+/// `Value` itself only has `PartialEq` because of floats. `ValueKey` gives it
+/// total equality with the semantics SQL uses for grouping:
+///
+/// * `NULL` equals `NULL`, so all NULLs fall into one group.
+/// * Every `NaN` equals every other `NaN`.
+/// * Numbers compare by value: `1` and `1.0` are one key, and `0.0` equals
+///   `-0.0`.
+/// * Values of different kinds never match (`'1'`, `1` and `true` differ).
+#[derive(Debug, Clone, Copy)]
+pub struct ValueKey<'a>(pub &'a Value);
+
+/// Normalized form that equality and hashing are both defined on.
+#[derive(PartialEq, Eq, Hash)]
+enum Canonical<'a> {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    Float(u64),
+    String(&'a str),
+    Date(chrono::NaiveDate),
+    Timestamp(chrono::NaiveDateTime),
+}
+
+const CANONICAL_NAN: u64 = 0x7ff8_0000_0000_0000;
+
+impl<'a> ValueKey<'a> {
+    fn canonical(&self) -> Canonical<'a> {
+        match self.0 {
+            Value::Null => Canonical::Null,
+            Value::Boolean(b) => Canonical::Boolean(*b),
+            Value::Integer(i) => Canonical::Integer(*i),
+            Value::String(s) => Canonical::String(s),
+            Value::Date(date) => Canonical::Date(*date),
+            Value::Timestamp(ts) => Canonical::Timestamp(*ts),
+            Value::Float(f) => canonical_float(*f),
+        }
+    }
+}
+
+fn canonical_float(f: f64) -> Canonical<'static> {
+    // i64::MIN is exactly representable; i64::MAX rounds up to 2^63, so the
+    // upper bound must be exclusive.
+    const I64_LOWER: f64 = i64::MIN as f64;
+    const I64_UPPER: f64 = -(i64::MIN as f64);
+
+    if f.is_nan() {
+        Canonical::Float(CANONICAL_NAN)
+    } else if f.fract() == 0.0 && (I64_LOWER..I64_UPPER).contains(&f) {
+        // Also maps -0.0 to 0
+        Canonical::Integer(f as i64)
+    } else {
+        Canonical::Float(f.to_bits())
+    }
+}
+
+impl PartialEq for ValueKey<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.canonical() == other.canonical()
+    }
+}
+
+impl Eq for ValueKey<'_> {}
+
+impl Hash for ValueKey<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.canonical().hash(state);
+    }
+}
+
+/// Hashable view of a whole row, compared column by column with
+/// [`ValueKey`] semantics.
+#[derive(Debug, Clone, Copy)]
+pub struct RowKey<'a>(pub &'a [Value]);
+
+impl PartialEq for RowKey<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len()
+            && self
+                .0
+                .iter()
+                .zip(other.0)
+                .all(|(a, b)| ValueKey(a) == ValueKey(b))
+    }
+}
+
+impl Eq for RowKey<'_> {}
+
+impl Hash for RowKey<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.len().hash(state);
+        for value in self.0 {
+            ValueKey(value).hash(state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::HashSet;
+
+    fn hash_of(value: &Value) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        ValueKey(value).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_numeric_keys_compare_by_value() {
+        assert_eq!(ValueKey(&Value::Integer(1)), ValueKey(&Value::Float(1.0)));
+        assert_eq!(hash_of(&Value::Integer(1)), hash_of(&Value::Float(1.0)));
+        assert_eq!(ValueKey(&Value::Float(0.0)), ValueKey(&Value::Float(-0.0)));
+        assert_ne!(ValueKey(&Value::Integer(1)), ValueKey(&Value::Float(1.5)));
+    }
+
+    #[test]
+    fn test_null_and_nan_policies() {
+        assert_eq!(ValueKey(&Value::Null), ValueKey(&Value::Null));
+        assert_ne!(ValueKey(&Value::Null), ValueKey(&Value::Integer(0)));
+
+        let nan = Value::Float(f64::NAN);
+        let other_nan = Value::Float(-f64::NAN);
+        assert_eq!(ValueKey(&nan), ValueKey(&other_nan));
+        assert_eq!(hash_of(&nan), hash_of(&other_nan));
+    }
+
+    #[test]
+    fn test_kinds_never_match() {
+        let one = Value::Integer(1);
+        let text = Value::String("1".to_string());
+        let truth = Value::Boolean(true);
+        assert_ne!(ValueKey(&one), ValueKey(&text));
+        assert_ne!(ValueKey(&one), ValueKey(&truth));
+        // Debug syntax inside strings is just data
+        let debug_like = Value::String("Integer(1)".to_string());
+        assert_ne!(ValueKey(&one), ValueKey(&debug_like));
+    }
+
+    #[test]
+    fn test_float_extremes_do_not_wrap() {
+        let big = Value::Float(9.3e18);
+        assert_ne!(ValueKey(&big), ValueKey(&Value::Integer(i64::MAX)));
+        let min = Value::Float(i64::MIN as f64);
+        assert_eq!(ValueKey(&min), ValueKey(&Value::Integer(i64::MIN)));
+    }
+
+    #[test]
+    fn test_row_keys() {
+        let a = [Value::Integer(1), Value::Null];
+        let b = [Value::Float(1.0), Value::Null];
+        let c = [Value::Integer(1)];
+        let keys: HashSet<RowKey> = [RowKey(&a), RowKey(&b), RowKey(&c)].into();
+        assert_eq!(keys.len(), 2);
+    }
+}
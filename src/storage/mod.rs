@@ -1,3 +1,11 @@
+pub mod json;
+pub mod key;
 pub mod table;
+pub mod versioned;
 
-pub use table::{Column, DataType, Row, Schema, Table, Value};
+pub use json::JsonReader;
+pub use key::{RowKey, ValueKey};
+pub use table::{
+    Column, DataType, Row, RowError, Schema, SpilledRows, Table, Value, SPILL_PAGE_ROWS,
+};
+pub use versioned::{load_versioned, save_versioned, Migration};
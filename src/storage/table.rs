@@ -1,5 +1,12 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::io;
+use std::sync::Arc;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use thiserror::Error;
+
+use super::key::{RowKey, ValueKey};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum DataType {
@@ -7,15 +14,34 @@ pub enum DataType {
     Float,
     String,
     Boolean,
+    Date,
+    Timestamp,
     Null,
 }
 
+impl DataType {
+    /// Whether a column of this type can hold `value`. NULL fits every
+    /// column and integers fit float columns; otherwise the value's own
+    /// type must match.
+    pub fn accepts(&self, value: &Value) -> bool {
+        match (self, value) {
+            (_, Value::Null) => true,
+            (DataType::Float, Value::Integer(_)) => true,
+            (data_type, value) => value.data_type() == *data_type,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     Integer(i64),
     Float(f64),
     String(String),
     Boolean(bool),
+    Date(NaiveDate),
+    /// A point in time without a zone; timestamps with a time zone are
+    /// converted to UTC.
+    Timestamp(NaiveDateTime),
     Null,
 }
 
@@ -26,6 +52,8 @@ impl Value {
             Value::Float(_) => DataType::Float,
             Value::String(_) => DataType::String,
             Value::Boolean(_) => DataType::Boolean,
+            Value::Date(_) => DataType::Date,
+            Value::Timestamp(_) => DataType::Timestamp,
             Value::Null => DataType::Null,
         }
     }
@@ -60,21 +88,62 @@ impl Value {
         }
     }
 
+    /// The value as a timestamp, with dates at midnight.
+    pub fn as_timestamp(&self) -> Option<NaiveDateTime> {
+        match self {
+            Value::Timestamp(ts) => Some(*ts),
+            Value::Date(date) => Some(date.and_time(chrono::NaiveTime::MIN)),
+            _ => None,
+        }
+    }
+
     pub fn is_null(&self) -> bool {
         matches!(self, Value::Null)
     }
 
+    /// Encode the value as a JSON literal.
+    pub fn to_json(&self) -> String {
+        match self {
+            Value::Integer(i) => i.to_string(),
+            Value::Float(f) if f.is_finite() => f.to_string(),
+            Value::Float(_) | Value::Null => "null".to_string(),
+            Value::Boolean(b) => b.to_string(),
+            Value::String(s) => json_string(s),
+            Value::Date(_) | Value::Timestamp(_) => json_string(&self.to_string()),
+        }
+    }
+
     pub fn is_truthy(&self) -> bool {
         match self {
             Value::Boolean(b) => *b,
             Value::Integer(i) => *i != 0,
             Value::Float(f) => *f != 0.0,
             Value::String(s) => !s.is_empty(),
+            Value::Date(_) | Value::Timestamp(_) => true,
             Value::Null => false,
         }
     }
 }
 
+/// Quote and escape `s` as a JSON string literal.
+pub fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -82,6 +151,10 @@ impl fmt::Display for Value {
             Value::Float(fl) => write!(f, "{}", fl),
             Value::String(s) => write!(f, "{}", s),
             Value::Boolean(b) => write!(f, "{}", b),
+            Value::Date(date) => write!(f, "{}", date.format("%Y-%m-%d")),
+            // Fractional seconds only when there are any, with as many
+            // digits as they need
+            Value::Timestamp(ts) => write!(f, "{}", ts.format("%Y-%m-%d %H:%M:%S%.f")),
             Value::Null => write!(f, "NULL"),
         }
     }
@@ -96,6 +169,10 @@ impl PartialOrd for Value {
             (Value::Float(a), Value::Integer(b)) => a.partial_cmp(&(*b as f64)),
             (Value::String(a), Value::String(b)) => a.partial_cmp(b),
             (Value::Boolean(a), Value::Boolean(b)) => a.partial_cmp(b),
+            (Value::Date(a), Value::Date(b)) => a.partial_cmp(b),
+            (Value::Date(_) | Value::Timestamp(_), Value::Date(_) | Value::Timestamp(_)) => {
+                self.as_timestamp().partial_cmp(&other.as_timestamp())
+            }
             (Value::Null, Value::Null) => Some(std::cmp::Ordering::Equal),
             (Value::Null, _) => Some(std::cmp::Ordering::Less),
             (_, Value::Null) => Some(std::cmp::Ordering::Greater),
@@ -108,6 +185,13 @@ impl PartialOrd for Value {
 pub struct Column {
     pub name: String,
     pub data_type: DataType,
+    /// Whether the source allows NULLs in this column. Columns are nullable
+    /// unless the source declares otherwise.
+    pub nullable: bool,
+    /// The column's type as the source spells it, e.g. `DECIMAL(10,2)` in
+    /// Parquet or `TEXT` in SQLite, before it was mapped to `data_type`.
+    /// `None` for columns that were not read from a source.
+    pub source_type: Option<String>,
 }
 
 impl Column {
@@ -115,8 +199,20 @@ impl Column {
         Self {
             name: name.into(),
             data_type,
+            nullable: true,
+            source_type: None,
         }
     }
+
+    pub fn with_nullable(mut self, nullable: bool) -> Self {
+        self.nullable = nullable;
+        self
+    }
+
+    pub fn with_source_type(mut self, source_type: impl Into<String>) -> Self {
+        self.source_type = Some(source_type.into());
+        self
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -149,6 +245,52 @@ impl Schema {
     pub fn column_names(&self) -> Vec<&str> {
         self.columns.iter().map(|c| c.name.as_str()).collect()
     }
+
+    /// Check that `row`, the row at index `index`, has one value per column
+    /// and that each value fits its column's type (see
+    /// [`DataType::accepts`]).
+    pub fn validate_row(&self, index: usize, row: &Row) -> Result<(), RowError> {
+        if row.values.len() != self.columns.len() {
+            return Err(RowError::Arity {
+                row: index,
+                expected: self.columns.len(),
+                found: row.values.len(),
+            });
+        }
+        for (column, value) in self.columns.iter().zip(&row.values) {
+            if !column.data_type.accepts(value) {
+                return Err(RowError::Type {
+                    row: index,
+                    column: column.name.clone(),
+                    expected: column.data_type.clone(),
+                    found: value.data_type(),
+                    value: value.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A row that does not fit a table's schema, found by
+/// [`Schema::validate_row`]. `row` is the zero-based index of the row.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum RowError {
+    #[error("Row {row} has {found} values but the schema has {expected} columns")]
+    Arity {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+
+    #[error("Row {row}, column {column}: expected {expected:?}, found {found:?} value {value}")]
+    Type {
+        row: usize,
+        column: String,
+        expected: DataType,
+        found: DataType,
+        value: String,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -166,11 +308,32 @@ impl Row {
     }
 }
 
+/// Rows kept in memory for a spilled result, and read back at a time by
+/// frontends paging through it.
+pub const SPILL_PAGE_ROWS: usize = 10_000;
+
+/// The rows of a result too large to hold in memory, kept elsewhere (such
+/// as in a spill file) and read back a range at a time.
+pub trait SpilledRows: fmt::Debug + Send + Sync {
+    fn row_count(&self) -> usize;
+
+    fn schema(&self) -> &Schema;
+
+    /// Up to `count` rows starting at row `start`.
+    fn rows(&self, start: usize, count: usize) -> io::Result<Vec<Row>>;
+}
+
 #[derive(Debug, Clone)]
 pub struct Table {
     pub name: String,
     pub schema: Schema,
     pub rows: Vec<Row>,
+    /// Set when the result was too large to keep in memory: `rows` then
+    /// holds only the first rows, and every row is in the spill file.
+    pub spilled: Option<Arc<dyn SpilledRows>>,
+    /// Set when the query produced more rows than its `max_rows` limit and
+    /// only the first `max_rows` were read.
+    pub truncated: bool,
 }
 
 impl Table {
@@ -179,6 +342,8 @@ impl Table {
             name: name.into(),
             schema,
             rows: Vec::new(),
+            spilled: None,
+            truncated: false,
         }
     }
 
@@ -187,17 +352,76 @@ impl Table {
             name: name.into(),
             schema,
             rows,
+            spilled: None,
+            truncated: false,
         }
     }
 
+    /// Like [`with_rows`](Self::with_rows), but reject rows that do not fit
+    /// `schema`, reporting the first one.
+    pub fn try_with_rows(
+        name: impl Into<String>,
+        schema: Schema,
+        rows: Vec<Row>,
+    ) -> Result<Self, RowError> {
+        let table = Self::with_rows(name, schema, rows);
+        table.validate()?;
+        Ok(table)
+    }
+
+    /// Append a row without checking it against the schema. Use
+    /// [`try_add_row`](Self::try_add_row) for rows from untrusted input.
     pub fn add_row(&mut self, row: Row) {
         self.rows.push(row);
     }
 
+    /// Append `row` if it fits the schema; otherwise leave the table as it
+    /// is and return why.
+    pub fn try_add_row(&mut self, row: Row) -> Result<(), RowError> {
+        self.schema.validate_row(self.rows.len(), &row)?;
+        self.rows.push(row);
+        Ok(())
+    }
+
+    /// Check every row against the schema, returning the first that does
+    /// not fit.
+    pub fn validate(&self) -> Result<(), RowError> {
+        self.rows
+            .iter()
+            .enumerate()
+            .try_for_each(|(index, row)| self.schema.validate_row(index, row))
+    }
+
     pub fn row_count(&self) -> usize {
         self.rows.len()
     }
 
+    /// Rows in the whole result, including those only in the spill file.
+    pub fn total_row_count(&self) -> usize {
+        self.spilled
+            .as_ref()
+            .map_or(self.rows.len(), |spilled| spilled.row_count())
+    }
+
+    /// The rows of a spilled result that `rows` does not hold, read back
+    /// from the spill file up to [`SPILL_PAGE_ROWS`] at a time. Pages have
+    /// the schema of the spilled result, so masks applied to this table must
+    /// be applied to each page too. Empty for a result held in memory.
+    pub fn spilled_pages(&self) -> impl Iterator<Item = io::Result<Table>> + '_ {
+        self.spilled.iter().flat_map(move |spilled| {
+            (self.rows.len()..spilled.row_count())
+                .step_by(SPILL_PAGE_ROWS)
+                .map(move |start| {
+                    let rows = spilled.rows(start, SPILL_PAGE_ROWS)?;
+                    Ok(Table::with_rows(
+                        self.name.clone(),
+                        spilled.schema().clone(),
+                        rows,
+                    ))
+                })
+        })
+    }
+
     pub fn column_count(&self) -> usize {
         self.schema.column_count()
     }
@@ -209,6 +433,130 @@ impl Table {
     pub fn iter(&self) -> impl Iterator<Item = &Row> {
         self.rows.iter()
     }
+
+    /// Copy of the table with duplicate rows removed, keeping the first
+    /// occurrence of each. Rows are compared with [`RowKey`] semantics.
+    pub fn distinct(&self) -> Table {
+        let mut seen = HashSet::with_capacity(self.rows.len());
+        let rows = self
+            .rows
+            .iter()
+            .filter(|row| seen.insert(RowKey(&row.values)))
+            .cloned()
+            .collect();
+        Table::with_rows(self.name.clone(), self.schema.clone(), rows)
+    }
+
+    /// Number of distinct non-NULL values in `column`, like SQL
+    /// `COUNT(DISTINCT column)`. Returns `None` if the column does not exist.
+    pub fn count_distinct(&self, column: &str) -> Option<usize> {
+        let index = self.get_column_index(column)?;
+        let distinct: HashSet<ValueKey> = self
+            .rows
+            .iter()
+            .filter_map(|row| row.get(index))
+            .filter(|value| !value.is_null())
+            .map(ValueKey)
+            .collect();
+        Some(distinct.len())
+    }
+
+    /// Copy of the table with only its first `n` rows.
+    ///
+    /// Like the other row helpers below, this works on the rows held in
+    /// memory; the result of a spilled table is not spilled.
+    pub fn head(&self, n: usize) -> Table {
+        self.slice(0, n)
+    }
+
+    /// Copy of the table with only its last `n` rows.
+    pub fn tail(&self, n: usize) -> Table {
+        self.slice(self.rows.len().saturating_sub(n), n)
+    }
+
+    /// Copy of the table with up to `len` rows starting at row `offset`.
+    /// Offsets past the end give an empty table.
+    pub fn slice(&self, offset: usize, len: usize) -> Table {
+        let start = offset.min(self.rows.len());
+        let end = start.saturating_add(len).min(self.rows.len());
+        Table::with_rows(
+            self.name.clone(),
+            self.schema.clone(),
+            self.rows[start..end].to_vec(),
+        )
+    }
+
+    /// Copy of the table with only `columns`, in the order given. Names are
+    /// matched case-insensitively. Returns `None` if a column does not exist.
+    pub fn select_columns(&self, columns: &[&str]) -> Option<Table> {
+        let indices = columns
+            .iter()
+            .map(|name| self.get_column_index(name))
+            .collect::<Option<Vec<usize>>>()?;
+        let schema = Schema::new(
+            indices
+                .iter()
+                .map(|&i| self.schema.columns[i].clone())
+                .collect(),
+        );
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| Row::new(indices.iter().map(|&i| row.values[i].clone()).collect()))
+            .collect();
+        Some(Table::with_rows(self.name.clone(), schema, rows))
+    }
+
+    /// Copy of the table sorted by `keys`, pairs of a column name and
+    /// whether to sort it ascending. Later keys break ties in earlier ones,
+    /// rows that still tie keep their order, and NULLs sort first in
+    /// ascending order. Returns `None` if a column does not exist.
+    pub fn sort_by(&self, keys: &[(&str, bool)]) -> Option<Table> {
+        let keys = keys
+            .iter()
+            .map(|&(name, ascending)| Some((self.get_column_index(name)?, ascending)))
+            .collect::<Option<Vec<(usize, bool)>>>()?;
+        let mut rows = self.rows.clone();
+        rows.sort_by(|a, b| {
+            keys.iter()
+                .map(|&(i, ascending)| {
+                    let order = a.values[i]
+                        .partial_cmp(&b.values[i])
+                        .unwrap_or(std::cmp::Ordering::Equal);
+                    if ascending {
+                        order
+                    } else {
+                        order.reverse()
+                    }
+                })
+                .find(|order| order.is_ne())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        Some(Table::with_rows(
+            self.name.clone(),
+            self.schema.clone(),
+            rows,
+        ))
+    }
+
+    /// Approximate heap footprint of the rows in bytes, including string payloads.
+    pub fn estimated_size_bytes(&self) -> usize {
+        let value_size = std::mem::size_of::<Value>();
+        self.rows
+            .iter()
+            .map(|row| {
+                std::mem::size_of::<Row>()
+                    + row
+                        .values
+                        .iter()
+                        .map(|v| match v {
+                            Value::String(s) => value_size + s.capacity(),
+                            _ => value_size,
+                        })
+                        .sum::<usize>()
+            })
+            .sum()
+    }
 }
 
 #[cfg(test)]
@@ -220,6 +568,42 @@ mod tests {
         assert!(Value::Integer(1) < Value::Integer(2));
         assert!(Value::Float(1.5) < Value::Float(2.5));
         assert!(Value::String("a".to_string()) < Value::String("b".to_string()));
+
+        let date = |d| Value::Date(NaiveDate::from_ymd_opt(2024, 1, d).unwrap());
+        assert!(date(1) < date(15));
+        let noon = NaiveDate::from_ymd_opt(2024, 1, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        assert!(date(1) < Value::Timestamp(noon));
+        assert!(Value::Timestamp(noon) < date(2));
+        assert_eq!(
+            date(1).partial_cmp(&Value::String("2024-01-01".into())),
+            None
+        );
+    }
+
+    #[test]
+    fn test_date_and_timestamp_formatting() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 9).unwrap();
+        assert_eq!(Value::Date(date).to_string(), "2024-03-09");
+        assert_eq!(Value::Date(date).to_json(), "\"2024-03-09\"");
+
+        let ts = date.and_hms_opt(8, 5, 0).unwrap();
+        assert_eq!(Value::Timestamp(ts).to_string(), "2024-03-09 08:05:00");
+        let ts = date.and_hms_milli_opt(8, 5, 0, 250).unwrap();
+        assert_eq!(Value::Timestamp(ts).to_string(), "2024-03-09 08:05:00.250");
+    }
+
+    #[test]
+    fn test_value_to_json() {
+        assert_eq!(Value::Integer(3).to_json(), "3");
+        assert_eq!(Value::Float(f64::NAN).to_json(), "null");
+        assert_eq!(Value::Null.to_json(), "null");
+        assert_eq!(
+            Value::String("say \"hi\"\n\u{1}".to_string()).to_json(),
+            r#""say \"hi\"\n\u0001""#
+        );
     }
 
     #[test]
@@ -254,4 +638,165 @@ mod tests {
         assert_eq!(table.row_count(), 2);
         assert_eq!(table.column_count(), 2);
     }
+
+    #[test]
+    fn test_distinct_and_count_distinct() {
+        let schema = Schema::new(vec![
+            Column::new("id", DataType::Float),
+            Column::new("tag", DataType::String),
+        ]);
+        let table = Table::with_rows(
+            "test",
+            schema,
+            vec![
+                Row::new(vec![Value::Integer(1), Value::String("a".to_string())]),
+                Row::new(vec![Value::Float(1.0), Value::String("a".to_string())]),
+                Row::new(vec![Value::Float(f64::NAN), Value::Null]),
+                Row::new(vec![Value::Float(f64::NAN), Value::Null]),
+                Row::new(vec![Value::Integer(2), Value::String("b".to_string())]),
+            ],
+        );
+
+        let distinct = table.distinct();
+        assert_eq!(distinct.row_count(), 3);
+        assert_eq!(distinct.rows[0].values[0], Value::Integer(1));
+
+        assert_eq!(table.count_distinct("id"), Some(3));
+        assert_eq!(table.count_distinct("tag"), Some(2));
+        assert_eq!(table.count_distinct("missing"), None);
+    }
+
+    #[test]
+    fn test_row_validation() {
+        let schema = Schema::new(vec![
+            Column::new("id", DataType::Integer),
+            Column::new("score", DataType::Float),
+        ]);
+        let mut table = Table::new("scores", schema.clone());
+        table
+            .try_add_row(Row::new(vec![Value::Integer(1), Value::Integer(7)]))
+            .unwrap();
+        table
+            .try_add_row(Row::new(vec![Value::Null, Value::Float(2.5)]))
+            .unwrap();
+
+        let err = table
+            .try_add_row(Row::new(vec![Value::Integer(3)]))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            RowError::Arity {
+                row: 2,
+                expected: 2,
+                found: 1
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Row 2 has 1 values but the schema has 2 columns"
+        );
+
+        let err = table
+            .try_add_row(Row::new(vec![
+                Value::String("x".to_string()),
+                Value::Float(1.0),
+            ]))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Row 2, column id: expected Integer, found String value x"
+        );
+        assert_eq!(table.row_count(), 2);
+        assert!(table.validate().is_ok());
+
+        // Unchecked rows are still accepted and caught by validate
+        table.add_row(Row::new(vec![Value::Boolean(true), Value::Null]));
+        assert!(matches!(
+            table.validate(),
+            Err(RowError::Type { row: 2, .. })
+        ));
+        assert!(Table::try_with_rows("scores", schema, table.rows.clone()).is_err());
+    }
+
+    #[test]
+    fn test_head_tail_and_slice() {
+        let schema = Schema::new(vec![Column::new("n", DataType::Integer)]);
+        let rows = (1..=5).map(|n| Row::new(vec![Value::Integer(n)])).collect();
+        let table = Table::with_rows("numbers", schema, rows);
+        let values = |table: Table| -> Vec<i64> {
+            table
+                .rows
+                .iter()
+                .filter_map(|row| row.values[0].as_integer())
+                .collect()
+        };
+
+        assert_eq!(values(table.head(2)), vec![1, 2]);
+        assert_eq!(values(table.head(10)), vec![1, 2, 3, 4, 5]);
+        assert_eq!(values(table.tail(2)), vec![4, 5]);
+        assert_eq!(values(table.tail(10)), vec![1, 2, 3, 4, 5]);
+        assert_eq!(values(table.slice(1, 3)), vec![2, 3, 4]);
+        assert_eq!(values(table.slice(4, 3)), vec![5]);
+        assert!(table.slice(7, 3).rows.is_empty());
+        assert_eq!(table.slice(1, usize::MAX).row_count(), 4);
+    }
+
+    #[test]
+    fn test_select_columns_and_sort_by() {
+        let schema = Schema::new(vec![
+            Column::new("id", DataType::Integer),
+            Column::new("team", DataType::String),
+            Column::new("score", DataType::Float),
+        ]);
+        let row = |id: i64, team: &str, score: Value| {
+            Row::new(vec![
+                Value::Integer(id),
+                Value::String(team.to_string()),
+                score,
+            ])
+        };
+        let table = Table::with_rows(
+            "scores",
+            schema,
+            vec![
+                row(1, "b", Value::Float(2.0)),
+                row(2, "a", Value::Float(1.0)),
+                row(3, "b", Value::Null),
+                row(4, "a", Value::Float(3.0)),
+            ],
+        );
+
+        let selected = table.select_columns(&["SCORE", "id"]).unwrap();
+        assert_eq!(selected.schema.column_names(), vec!["score", "id"]);
+        assert_eq!(
+            selected.rows[0].values,
+            vec![Value::Float(2.0), Value::Integer(1)]
+        );
+        assert!(table.select_columns(&["missing"]).is_none());
+
+        let ids = |table: Table| -> Vec<i64> {
+            table
+                .rows
+                .iter()
+                .filter_map(|row| row.values[0].as_integer())
+                .collect()
+        };
+        let sorted = table.sort_by(&[("team", true), ("score", false)]).unwrap();
+        assert_eq!(ids(sorted), vec![4, 2, 1, 3]);
+        let sorted = table.sort_by(&[("score", true)]).unwrap();
+        assert_eq!(ids(sorted), vec![3, 2, 1, 4]);
+        assert!(table.sort_by(&[("missing", true)]).is_none());
+    }
+
+    #[test]
+    fn test_estimated_size_grows_with_strings() {
+        let schema = Schema::new(vec![Column::new("value", DataType::String)]);
+        let mut table = Table::new("test", schema);
+        assert_eq!(table.estimated_size_bytes(), 0);
+
+        table.add_row(Row::new(vec![Value::String("a".to_string())]));
+        let small = table.estimated_size_bytes();
+        table.add_row(Row::new(vec![Value::String("a".repeat(1024))]));
+        assert!(table.estimated_size_bytes() >= small + 1024);
+    }
 }
@@ -0,0 +1,178 @@
+//! JSON files that record the version of their format, such as the GUI's
+//! `config.json`, so that a file saved by an older release is upgraded when
+//! it is loaded instead of being dropped for defaults.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value as JsonValue};
+
+/// Upgrades a document from one version of its format to the next.
+pub type Migration = fn(&mut Map<String, JsonValue>);
+
+/// Key of the format version in a saved document. Documents without it are
+/// version 0.
+pub const VERSION_KEY: &str = "version";
+
+/// Load the document saved at `path` by [`save_versioned`]. `migrations[n]`
+/// upgrades a version `n` document to version `n + 1`, so the current
+/// version is `migrations.len()`. A missing file is `Ok(None)`.
+///
+/// An older file is copied to `<path>.v<n>.bak`, upgraded, and written back
+/// before it is returned. A file that cannot be read is copied to
+/// `<path>.bak` and is an `InvalidData` error, so the caller may start over
+/// from defaults without losing it. A file saved by a newer release is an
+/// `Unsupported` error and should not be overwritten.
+pub fn load_versioned<T: DeserializeOwned + Serialize>(
+    path: &Path,
+    migrations: &[Migration],
+) -> io::Result<Option<T>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let mut document = match serde_json::from_str::<JsonValue>(&text) {
+        Ok(JsonValue::Object(document)) => document,
+        Ok(_) => return Err(unreadable(path, "expected a JSON object")?),
+        Err(e) => return Err(unreadable(path, &e.to_string())?),
+    };
+
+    let current = migrations.len() as u64;
+    let version = match document.get(VERSION_KEY) {
+        None => 0,
+        Some(version) => match version.as_u64() {
+            Some(version) => version,
+            None => return Err(unreadable(path, "the version is not a number")?),
+        },
+    };
+    if version > current {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "{} was saved by a newer version of knowhere (format {}, this version reads up to {})",
+                path.display(),
+                version,
+                current
+            ),
+        ));
+    }
+
+    for migrate in &migrations[version as usize..] {
+        migrate(&mut document);
+    }
+    let value = match serde_json::from_value::<T>(JsonValue::Object(document)) {
+        Ok(value) => value,
+        Err(e) => return Err(unreadable(path, &e.to_string())?),
+    };
+    if version < current {
+        fs::copy(path, backup_path(path, &format!("v{}.bak", version)))?;
+        save_versioned(path, &value, migrations.len() as u32)?;
+    }
+    Ok(Some(value))
+}
+
+/// Write `value`, which must serialize to a JSON object, to `path` as
+/// format `version`, creating its directory.
+pub fn save_versioned<T: Serialize>(path: &Path, value: &T, version: u32) -> io::Result<()> {
+    let mut document = match serde_json::to_value(value)? {
+        JsonValue::Object(document) => document,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "only JSON objects can be saved with a version",
+            ))
+        }
+    };
+    document.insert(VERSION_KEY.to_string(), version.into());
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(&document)?)
+}
+
+/// `path` with `.<suffix>` added to its file name, e.g. `config.json.bak`.
+pub fn backup_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Copy the file at `path` to `<path>.bak` and describe why it could not be
+/// read.
+fn unreadable(path: &Path, reason: &str) -> io::Result<io::Error> {
+    let backup = backup_path(path, "bak");
+    fs::copy(path, &backup)?;
+    Ok(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "{} could not be read ({}); it was copied to {}",
+            path.display(),
+            reason,
+            backup.display()
+        ),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Config {
+        queries: Vec<String>,
+        paths: Vec<String>,
+    }
+
+    const MIGRATIONS: &[Migration] = &[|document| {
+        document
+            .entry("paths")
+            .or_insert_with(|| JsonValue::Array(Vec::new()));
+    }];
+
+    #[test]
+    fn test_older_file_is_upgraded_and_backed_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        let old = r#"{"queries": ["SELECT 1"]}"#;
+        fs::write(&path, old).unwrap();
+
+        let config: Config = load_versioned(&path, MIGRATIONS).unwrap().unwrap();
+        assert_eq!(config.queries, vec!["SELECT 1"]);
+        assert!(config.paths.is_empty());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("config.json.v0.bak")).unwrap(),
+            old
+        );
+
+        let saved: JsonValue = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved[VERSION_KEY], 1);
+        let reloaded: Option<Config> = load_versioned(&path, MIGRATIONS).unwrap();
+        assert_eq!(reloaded, Some(config));
+    }
+
+    #[test]
+    fn test_unreadable_and_newer_files_are_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        assert!(load_versioned::<Config>(&path, MIGRATIONS)
+            .unwrap()
+            .is_none());
+
+        fs::write(&path, r#"{"queries": "SELECT 1", "paths": []}"#).unwrap();
+        let err = load_versioned::<Config>(&path, MIGRATIONS).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(dir.path().join("config.json.bak").exists());
+
+        let newer = r#"{"version": 2, "queries": [], "paths": []}"#;
+        fs::write(&path, newer).unwrap();
+        let err = load_versioned::<Config>(&path, MIGRATIONS).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+        assert_eq!(fs::read_to_string(&path).unwrap(), newer);
+    }
+}
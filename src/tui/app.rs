@@ -1,11 +1,36 @@
-use crate::datafusion::DataFusionContext;
-use crate::storage::table::Table;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::datafusion::{
+    fill_template, frequency_query, template_params, DataFusionContext, QueryHandle, Result,
+    SessionRecorder,
+};
+use crate::render::{self, MaskRules};
+use crate::storage::table::{Column, Table, SPILL_PAGE_ROWS};
+
+use super::history::{load_history, push_history, save_history, search_history};
+use super::memory::{format_bytes, process_memory_bytes};
+
+/// Result size above which the TUI warns and offers `:limit`.
+pub const DEFAULT_MEMORY_THRESHOLD: usize = 512 * 1024 * 1024;
+/// Row count applied by `:limit` when no explicit value is given.
+pub const DEFAULT_ROW_LIMIT: usize = 10_000;
+/// Rows a TUI query reads unless `--max-rows` or `SET max_rows` says
+/// otherwise, so that a runaway query stops before it is materialized.
+pub const DEFAULT_MAX_ROWS: usize = 1_000_000;
+/// How often the process memory shown in the status bar is re-read.
+const MEMORY_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Mode {
     Normal,
     Insert,
     Command,
+    Prompt,
+    Columns,
+    Frequency,
+    Search,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -14,6 +39,139 @@ pub enum Focus {
     Results,
 }
 
+/// How the current result is presented in the results pane.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ResultView {
+    #[default]
+    Table,
+    Expanded,
+    Json,
+    /// The last column as a bar chart, for histograms and frequency tables.
+    Bars,
+}
+
+impl ResultView {
+    pub fn next(self) -> Self {
+        match self {
+            ResultView::Table => ResultView::Expanded,
+            ResultView::Expanded => ResultView::Json,
+            ResultView::Json => ResultView::Bars,
+            ResultView::Bars => ResultView::Table,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ResultView::Table => "table",
+            ResultView::Expanded => "expanded",
+            ResultView::Json => "json",
+            ResultView::Bars => "bars",
+        }
+    }
+}
+
+/// A query with `{{param}}` placeholders waiting for the user to enter a
+/// value for each one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamPrompt {
+    pub sql: String,
+    pub names: Vec<String>,
+    /// Values entered so far, one per name in order.
+    pub values: Vec<(String, String)>,
+    pub input: String,
+    /// Position in the current parameter's history while browsing it.
+    pub history_index: Option<usize>,
+}
+
+impl ParamPrompt {
+    /// Name of the parameter being asked for.
+    pub fn current(&self) -> &str {
+        &self.names[self.values.len()]
+    }
+}
+
+/// The reverse search through the query history opened with Ctrl+R. The
+/// editor shows the match while searching.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistorySearch {
+    pub pattern: String,
+    /// History entry shown, or `None` if nothing matches the pattern.
+    pub found: Option<usize>,
+    /// The query and cursor to restore if the search is cancelled.
+    pub query: String,
+    pub cursor_pos: usize,
+    /// Mode to return to once the search ends.
+    pub mode: Mode,
+}
+
+/// The column checklist opened with `c` in the results. Changes apply to the
+/// results as they are made.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnChooser {
+    /// Every result column by index, in display order, and whether it is
+    /// shown.
+    pub columns: Vec<(usize, bool)>,
+    pub cursor: usize,
+}
+
+/// The most common values of one result column, shown in a popup over the
+/// results while the query counting them runs and once it has finished.
+pub struct FrequencyPopup {
+    pub column: String,
+    pub running: Option<QueryHandle>,
+    pub result: Option<Table>,
+    pub error: Option<String>,
+    pub scroll: usize,
+}
+
+/// Indices of the columns of `table` to show, in order: `visible` as picked
+/// in the column chooser, or every column.
+pub fn shown_columns(table: &Table, visible: Option<&[usize]>) -> Vec<usize> {
+    match visible {
+        Some(visible) => visible.to_vec(),
+        None => (0..table.schema.columns.len()).collect(),
+    }
+}
+
+/// The query, result and scroll state of one editor pane. The active
+/// pane's state lives in the [`App`] fields; other panes keep theirs here
+/// until they become active again.
+#[derive(Default)]
+pub struct Buffer {
+    pub query: String,
+    pub cursor_pos: usize,
+    pub result: Option<Table>,
+    pub error: Option<String>,
+    pub result_scroll: usize,
+    pub result_offset: usize,
+    pub result_horizontal_scroll: usize,
+    pub history_index: Option<usize>,
+    pub column_widths: Vec<usize>,
+    pub visible_columns: Option<Vec<usize>>,
+    pub result_size: usize,
+    pub last_query: Option<String>,
+    pub running: Option<QueryHandle>,
+    pub view: ResultView,
+}
+
+/// A tab opened with `:tabnew`: the state of its pane and, if the tab is
+/// split, of the other pane.
+pub struct Tab {
+    pub buffer: Buffer,
+    pub split: Option<Buffer>,
+    pub split_active_left: bool,
+}
+
+impl Default for Tab {
+    fn default() -> Self {
+        Self {
+            buffer: Buffer::default(),
+            split: None,
+            split_active_left: true,
+        }
+    }
+}
+
 pub struct App {
     pub query: String,
     pub cursor_pos: usize,
@@ -24,15 +182,63 @@ pub struct App {
     pub should_quit: bool,
     pub ctx: DataFusionContext,
     pub command_buffer: String,
+    /// Top row of the results, counted within the loaded rows.
     pub result_scroll: usize,
+    /// Row of the whole result at which the loaded rows start. Non-zero
+    /// only for a result spilled to disk, which is loaded a page at a time.
+    pub result_offset: usize,
     pub result_horizontal_scroll: usize,
+    /// Executed queries, oldest first, shared by every pane and tab.
     pub history: Vec<String>,
     pub history_index: Option<usize>,
+    /// File the history is saved to after each query. `None` keeps it for
+    /// this session only.
+    pub history_path: Option<PathBuf>,
+    pub search: Option<HistorySearch>,
+    /// The `--record` recording that queries are added to when they succeed.
+    pub recorder: Option<SessionRecorder>,
+    /// The query recorded last, so that re-running it is not recorded again.
+    pub last_recorded: Option<String>,
     pub column_widths: Vec<usize>,
+    /// Indices of the result columns to show, in order, as picked in the
+    /// column chooser. `None` shows every column.
+    pub visible_columns: Option<Vec<usize>>,
+    pub result_size: usize,
+    /// Resident memory of the process as last sampled by
+    /// [`sample_memory`](Self::sample_memory).
+    pub process_memory: Option<u64>,
+    memory_sampled_at: Option<Instant>,
+    pub memory_threshold: usize,
+    pub row_limit: usize,
+    pub warning: Option<String>,
+    pub notice: Option<String>,
+    pub last_query: Option<String>,
+    pub running: Option<QueryHandle>,
+    pub view: ResultView,
+    pub page_size: usize,
+    pub max_col_width: usize,
+    pub masks: MaskRules,
+    pub prompt: Option<ParamPrompt>,
+    pub column_chooser: Option<ColumnChooser>,
+    pub frequency: Option<FrequencyPopup>,
+    /// Values previously entered for each template parameter, oldest first.
+    pub param_history: HashMap<String, Vec<String>>,
+    /// The inactive pane of a `:vsplit`, whose results scroll along with
+    /// the active one.
+    pub split: Option<Buffer>,
+    /// Whether the active pane is the left one of a split.
+    pub split_active_left: bool,
+    /// Every tab in order. The active tab's entry is a placeholder; its
+    /// state lives in the fields above.
+    pub tabs: Vec<Tab>,
+    pub tab_index: usize,
+    /// First key of a two-key normal mode command such as `gt`.
+    pub pending_key: Option<char>,
 }
 
 impl App {
     pub fn new(ctx: DataFusionContext) -> Self {
+        let max_col_width = ctx.settings().max_col_width;
         Self {
             query: String::new(),
             cursor_pos: 0,
@@ -44,11 +250,150 @@ impl App {
             ctx,
             command_buffer: String::new(),
             result_scroll: 0,
+            result_offset: 0,
             result_horizontal_scroll: 0,
             history: Vec::new(),
             history_index: None,
+            history_path: None,
+            search: None,
+            recorder: None,
+            last_recorded: None,
             column_widths: Vec::new(),
+            visible_columns: None,
+            result_size: 0,
+            process_memory: None,
+            memory_sampled_at: None,
+            memory_threshold: DEFAULT_MEMORY_THRESHOLD,
+            row_limit: DEFAULT_ROW_LIMIT,
+            warning: None,
+            notice: None,
+            last_query: None,
+            running: None,
+            view: ResultView::Table,
+            page_size: 10,
+            max_col_width,
+            masks: MaskRules::default(),
+            prompt: None,
+            column_chooser: None,
+            frequency: None,
+            param_history: HashMap::new(),
+            split: None,
+            split_active_left: true,
+            tabs: vec![Tab::default()],
+            tab_index: 0,
+            pending_key: None,
+        }
+    }
+
+    /// Exchange the active tab's state with `tab`.
+    fn swap_tab(&mut self, tab: &mut Tab) {
+        self.swap_buffer(&mut tab.buffer);
+        std::mem::swap(&mut self.split, &mut tab.split);
+        std::mem::swap(&mut self.split_active_left, &mut tab.split_active_left);
+    }
+
+    /// Make tab `index` the active one.
+    pub fn switch_tab(&mut self, index: usize) {
+        if index == self.tab_index || index >= self.tabs.len() {
+            return;
+        }
+        let mut tabs = std::mem::take(&mut self.tabs);
+        self.swap_tab(&mut tabs[self.tab_index]);
+        self.swap_tab(&mut tabs[index]);
+        self.tabs = tabs;
+        self.tab_index = index;
+    }
+
+    /// Open an empty tab after the current one and switch to it.
+    pub fn new_tab(&mut self) {
+        self.tabs.insert(self.tab_index + 1, Tab::default());
+        self.switch_tab(self.tab_index + 1);
+    }
+
+    /// Close the active tab, cancelling its queries, unless it is the last.
+    pub fn close_tab(&mut self) {
+        if self.tabs.len() == 1 {
+            self.error = Some("Cannot close the last tab".to_string());
+            return;
+        }
+        let closing = self.tab_index;
+        let next = if closing + 1 < self.tabs.len() {
+            closing + 1
+        } else {
+            closing - 1
+        };
+        self.switch_tab(next);
+        let tab = self.tabs.remove(closing);
+        for buffer in std::iter::once(tab.buffer).chain(tab.split) {
+            if let Some(handle) = buffer.running {
+                handle.cancel();
+            }
+        }
+        if closing < self.tab_index {
+            self.tab_index -= 1;
+        }
+    }
+
+    pub fn next_tab(&mut self) {
+        self.switch_tab((self.tab_index + 1) % self.tabs.len());
+    }
+
+    pub fn prev_tab(&mut self) {
+        self.switch_tab((self.tab_index + self.tabs.len() - 1) % self.tabs.len());
+    }
+
+    /// Exchange the active pane's state with `buffer`.
+    fn swap_buffer(&mut self, buffer: &mut Buffer) {
+        std::mem::swap(&mut self.query, &mut buffer.query);
+        std::mem::swap(&mut self.cursor_pos, &mut buffer.cursor_pos);
+        std::mem::swap(&mut self.result, &mut buffer.result);
+        std::mem::swap(&mut self.error, &mut buffer.error);
+        std::mem::swap(&mut self.result_scroll, &mut buffer.result_scroll);
+        std::mem::swap(&mut self.result_offset, &mut buffer.result_offset);
+        std::mem::swap(
+            &mut self.result_horizontal_scroll,
+            &mut buffer.result_horizontal_scroll,
+        );
+        std::mem::swap(&mut self.history_index, &mut buffer.history_index);
+        std::mem::swap(&mut self.column_widths, &mut buffer.column_widths);
+        std::mem::swap(&mut self.visible_columns, &mut buffer.visible_columns);
+        std::mem::swap(&mut self.result_size, &mut buffer.result_size);
+        std::mem::swap(&mut self.last_query, &mut buffer.last_query);
+        std::mem::swap(&mut self.running, &mut buffer.running);
+        std::mem::swap(&mut self.view, &mut buffer.view);
+    }
+
+    /// Split the screen into two panes side by side. The new right pane
+    /// starts with a copy of the current query and becomes active.
+    pub fn open_split(&mut self) {
+        if self.split.is_some() {
+            return;
+        }
+        self.split = Some(Buffer {
+            query: self.query.clone(),
+            cursor_pos: self.cursor_pos,
+            ..Buffer::default()
+        });
+        self.switch_pane();
+    }
+
+    /// Make the other pane of a split active.
+    pub fn switch_pane(&mut self) {
+        if let Some(mut other) = self.split.take() {
+            self.swap_buffer(&mut other);
+            self.split = Some(other);
+            self.split_active_left = !self.split_active_left;
+        }
+    }
+
+    /// Close the inactive pane of a split, cancelling its query.
+    pub fn close_split(&mut self) {
+        if let Some(other) = self.split.take() {
+            if let Some(handle) = other.running {
+                handle.cancel();
+            }
         }
+        self.split_active_left = true;
     }
 
     pub fn execute_query(&mut self) {
@@ -56,43 +401,363 @@ impl App {
             return;
         }
 
-        // Add to history
-        if self.history.last() != Some(&self.query) {
-            self.history.push(self.query.clone());
-        }
+        push_history(&mut self.history, &self.query);
         self.history_index = None;
+        if let Some(path) = &self.history_path {
+            if let Err(e) = save_history(path, &self.history) {
+                self.warning = Some(format!("Could not save history: {}", e));
+            }
+        }
+
+        let sql = self.query.clone();
+        let names = template_params(&sql);
+        if !names.is_empty() {
+            self.start_prompt(sql, names);
+            return;
+        }
+        self.run_sql(&sql);
+        self.last_query = Some(sql);
+    }
+
+    /// Ask for a value for each of `names` before running `sql`.
+    fn start_prompt(&mut self, sql: String, names: Vec<String>) {
+        let input = self.last_param_value(&names[0]);
+        self.prompt = Some(ParamPrompt {
+            sql,
+            names,
+            values: Vec::new(),
+            input,
+            history_index: None,
+        });
+        self.mode = Mode::Prompt;
+    }
 
-        match self.ctx.execute_sql(&self.query) {
-            Ok(table) => {
+    fn last_param_value(&self, name: &str) -> String {
+        self.param_history
+            .get(name)
+            .and_then(|values| values.last())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Accept the value typed for the current parameter. Once every
+    /// parameter has a value the filled-in query runs, and `r` re-runs it
+    /// with the same values.
+    pub fn submit_param(&mut self) {
+        let Some(mut prompt) = self.prompt.take() else {
+            return;
+        };
+        let name = prompt.current().to_string();
+        let value = prompt.input.trim().to_string();
+        let history = self.param_history.entry(name.clone()).or_default();
+        if history.last() != Some(&value) {
+            history.push(value.clone());
+        }
+        prompt.values.push((name, value));
+
+        if prompt.values.len() < prompt.names.len() {
+            prompt.input = self.last_param_value(prompt.current());
+            prompt.history_index = None;
+            self.prompt = Some(prompt);
+            return;
+        }
+        self.mode = Mode::Normal;
+        let sql = fill_template(&prompt.sql, &prompt.values);
+        self.run_sql(&sql);
+        self.last_query = Some(sql);
+    }
+
+    pub fn cancel_prompt(&mut self) {
+        self.prompt = None;
+        self.mode = Mode::Normal;
+    }
+
+    /// Replace the prompt input with an older value of the current parameter.
+    pub fn param_history_up(&mut self) {
+        let Some(ref mut prompt) = self.prompt else {
+            return;
+        };
+        let Some(history) = self.param_history.get(prompt.current()) else {
+            return;
+        };
+        let index = match prompt.history_index {
+            None => history.len() - 1,
+            Some(i) => i.saturating_sub(1),
+        };
+        prompt.input = history[index].clone();
+        prompt.history_index = Some(index);
+    }
+
+    /// Replace the prompt input with a newer value of the current parameter,
+    /// clearing it after the newest.
+    pub fn param_history_down(&mut self) {
+        let Some(ref mut prompt) = self.prompt else {
+            return;
+        };
+        let (Some(i), Some(history)) = (
+            prompt.history_index,
+            self.param_history.get(prompt.current()),
+        ) else {
+            return;
+        };
+        if i + 1 < history.len() {
+            prompt.input = history[i + 1].clone();
+            prompt.history_index = Some(i + 1);
+        } else {
+            prompt.input.clear();
+            prompt.history_index = None;
+        }
+    }
+
+    /// Write the query text to `path` so it can be opened again with `:e`.
+    pub fn save_query(&mut self, path: &str) {
+        if path.is_empty() {
+            self.error = Some("Usage: :w <file>".to_string());
+            return;
+        }
+        match std::fs::write(path, &self.query) {
+            Ok(()) => self.notice = Some(format!("Saved query to {}", path)),
+            Err(e) => self.error = Some(format!("Failed to write {}: {}", path, e)),
+        }
+    }
+
+    /// Replace the query with the contents of `path`.
+    pub fn open_query(&mut self, path: &str) {
+        match std::fs::read_to_string(path) {
+            Ok(sql) => {
+                self.query = sql.trim_end().to_string();
+                self.cursor_pos = self.query.len();
+                self.notice = Some(format!("Opened {}", path));
+            }
+            Err(e) => self.error = Some(format!("Failed to read {}: {}", path, e)),
+        }
+    }
+
+    /// Execute the most recently run query again, e.g. after the data changed.
+    pub fn rerun_last_query(&mut self) {
+        if let Some(sql) = self.last_query.clone() {
+            self.run_sql(&sql);
+        }
+    }
+
+    /// Re-run the last query wrapped in a LIMIT so oversized results stay manageable.
+    pub fn apply_row_limit(&mut self, limit: Option<usize>) {
+        let Some(ref query) = self.last_query else {
+            return;
+        };
+        let limit = limit.unwrap_or(self.row_limit);
+        let inner = query.trim().trim_end_matches(';');
+        let sql = format!("SELECT * FROM ({}) AS limited LIMIT {}", inner, limit);
+        self.run_sql(&sql);
+    }
+
+    /// Store the result of the last query as an in-memory table named `name`
+    /// so later queries can reuse it without re-running the original.
+    pub fn materialize(&mut self, name: &str) {
+        let Some(ref query) = self.last_query else {
+            self.error = Some("No query to materialize".to_string());
+            return;
+        };
+        if name.is_empty() {
+            self.error = Some("Usage: :materialize <name>".to_string());
+            return;
+        }
+        let inner = query.trim().trim_end_matches(';');
+        let sql = format!("CACHE TABLE {} AS {}", name, inner);
+        self.run_sql(&sql);
+    }
+
+    /// Start `sql` in the background, cancelling any query that is still running.
+    fn run_sql(&mut self, sql: &str) {
+        if let Some(handle) = self.running.take() {
+            handle.cancel();
+        }
+        if let Err(e) = self.masks.check_statement(sql) {
+            self.error = Some(e.to_string());
+            return;
+        }
+        let notes = self.ctx.dialect_notes(sql);
+        self.warning = (!notes.is_empty()).then(|| format!("Translated {}", notes.join("; ")));
+        self.notice = None;
+        self.running = Some(self.ctx.execute_sql_async(sql));
+    }
+
+    /// Re-read `table` (or every table) from disk, then re-run the last query
+    /// so the results reflect the new data.
+    pub fn reload_tables(&mut self, table: Option<&str>) {
+        let reloaded = match table {
+            Some(name) => self.ctx.refresh_table(name).map(|_| vec![name.to_string()]),
+            None => self.ctx.refresh_all(),
+        };
+        match reloaded {
+            Ok(names) => {
+                self.rerun_last_query();
+                self.notice = Some(match names.as_slice() {
+                    [name] => format!("Reloaded {}", name),
+                    _ => format!("Reloaded {} tables", names.len()),
+                });
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    /// Re-read the process memory if the last sample is older than
+    /// [`MEMORY_SAMPLE_INTERVAL`], rather than on every redraw.
+    pub fn sample_memory(&mut self) {
+        if self
+            .memory_sampled_at
+            .is_some_and(|at| at.elapsed() < MEMORY_SAMPLE_INTERVAL)
+        {
+            return;
+        }
+        self.process_memory = process_memory_bytes();
+        self.memory_sampled_at = Some(Instant::now());
+    }
+
+    /// Collect the results of background queries that have finished, in
+    /// every pane of every tab.
+    pub fn poll_query(&mut self) {
+        self.poll_frequency();
+        self.poll_tab_queries();
+        for index in 0..self.tabs.len() {
+            if index == self.tab_index {
+                continue;
+            }
+            let mut tab = std::mem::take(&mut self.tabs[index]);
+            let running = tab.buffer.running.is_some()
+                || tab
+                    .split
+                    .as_ref()
+                    .is_some_and(|split| split.running.is_some());
+            if running {
+                self.swap_tab(&mut tab);
+                self.poll_tab_queries();
+                self.swap_tab(&mut tab);
+            }
+            self.tabs[index] = tab;
+        }
+    }
+
+    fn poll_frequency(&mut self) {
+        let Some(popup) = &mut self.frequency else {
+            return;
+        };
+        let Some(result) = popup
+            .running
+            .as_mut()
+            .and_then(|handle| handle.try_result())
+        else {
+            return;
+        };
+        popup.running = None;
+        match result {
+            Ok(mut table) => {
+                self.masks.apply(&mut table);
+                popup.result = Some(table);
+            }
+            Err(e) => popup.error = Some(e.to_string()),
+        }
+    }
+
+    fn poll_tab_queries(&mut self) {
+        self.poll_active_query();
+        if let Some(mut other) = self.split.take() {
+            if other.running.is_some() {
+                self.swap_buffer(&mut other);
+                self.poll_active_query();
+                self.swap_buffer(&mut other);
+            }
+            self.split = Some(other);
+        }
+    }
+
+    fn poll_active_query(&mut self) {
+        let Some(ref mut handle) = self.running else {
+            return;
+        };
+        if let Some(result) = handle.try_result() {
+            self.running = None;
+            self.finish_query(result);
+        }
+    }
+
+    pub fn cancel_query(&mut self) {
+        if let Some(handle) = self.running.take() {
+            handle.cancel();
+            self.error = Some("Query cancelled".to_string());
+            self.result = None;
+            self.result_size = 0;
+        }
+    }
+
+    fn finish_query(&mut self, result: Result<Table>) {
+        match result {
+            Ok(mut table) => {
+                self.masks.apply(&mut table);
+                // A `SET max_col_width` in the query takes effect from its result on
+                self.max_col_width = self.ctx.settings().max_col_width;
                 self.calculate_column_widths(&table);
+                self.result_size = table.estimated_size_bytes();
+                if let Some(spilled) = &table.spilled {
+                    self.warning = Some(format!(
+                        "Result of {} rows spilled to disk, showing {} rows at a time",
+                        spilled.row_count(),
+                        SPILL_PAGE_ROWS
+                    ));
+                } else if table.truncated {
+                    self.warning = Some(format!(
+                        "Stopped after {} rows (max_rows), run :limit to re-run with LIMIT {} \
+                         or SET max_rows = NONE for every row",
+                        table.row_count(),
+                        self.row_limit
+                    ));
+                } else if self.result_size > self.memory_threshold {
+                    self.warning = Some(format!(
+                        "Result uses {} of memory ({} rows), run :limit to re-run with LIMIT {}",
+                        format_bytes(self.result_size as u64),
+                        table.row_count(),
+                        self.row_limit
+                    ));
+                }
                 self.result = Some(table);
                 self.error = None;
                 self.result_scroll = 0;
+                self.result_offset = 0;
                 self.result_horizontal_scroll = 0;
+                self.visible_columns = None;
+                self.record_last_query();
             }
             Err(e) => {
                 self.error = Some(e.to_string());
                 self.result = None;
+                self.result_size = 0;
             }
         }
     }
 
+    /// Add the query that just succeeded to the recording, unless it is the
+    /// one recorded last.
+    fn record_last_query(&mut self) {
+        let (Some(recorder), Some(sql)) = (&self.recorder, &self.last_query) else {
+            return;
+        };
+        if self.last_recorded.as_ref() == Some(sql) {
+            return;
+        }
+        match recorder.record(sql) {
+            Ok(()) => self.last_recorded = Some(sql.clone()),
+            Err(e) => self.warning = Some(format!("Could not record query: {}", e)),
+        }
+    }
+
     fn calculate_column_widths(&mut self, table: &Table) {
-        self.column_widths = table
-            .schema
-            .columns
-            .iter()
-            .enumerate()
-            .map(|(i, col)| {
-                let header_width = col.name.len();
-                let max_value_width = table
-                    .rows
-                    .iter()
-                    .map(|row| row.values.get(i).map(|v| v.to_string().len()).unwrap_or(0))
-                    .max()
-                    .unwrap_or(0);
-                header_width.max(max_value_width).max(4) // minimum width of 4
-            })
+        self.column_widths = render::column_widths(table, self.max_col_width)
+            .into_iter()
+            .map(|w| w.max(4)) // minimum width of 4
             .collect();
     }
 
@@ -281,20 +946,147 @@ impl App {
         }
     }
 
-    pub fn scroll_results_up(&mut self) {
-        if self.result_scroll > 0 {
-            self.result_scroll -= 1;
+    /// Load the history saved at `path` and save it there after each query.
+    pub fn load_history(&mut self, path: PathBuf) {
+        match load_history(&path) {
+            Ok(history) => self.history = history,
+            // Saving would overwrite the newer file, so keep this session's
+            // history in memory only
+            Err(e) if e.kind() == std::io::ErrorKind::Unsupported => {
+                self.warning = Some(format!("History is not saved: {}", e));
+                return;
+            }
+            Err(e) => self.warning = Some(format!("Could not read history: {}", e)),
         }
+        self.history_path = Some(path);
     }
 
-    pub fn scroll_results_down(&mut self) {
-        if let Some(ref table) = self.result {
-            if self.result_scroll < table.row_count().saturating_sub(1) {
-                self.result_scroll += 1;
+    /// Start a reverse search through the history for queries containing
+    /// what is typed next.
+    pub fn start_history_search(&mut self) {
+        self.search = Some(HistorySearch {
+            pattern: String::new(),
+            found: None,
+            query: self.query.clone(),
+            cursor_pos: self.cursor_pos,
+            mode: self.mode,
+        });
+        self.mode = Mode::Search;
+        self.focus = Focus::Query;
+    }
+
+    pub fn search_push(&mut self, c: char) {
+        if let Some(search) = self.search.as_mut() {
+            search.pattern.push(c);
+        }
+        self.search_from(self.history.len());
+    }
+
+    pub fn search_pop(&mut self) {
+        if let Some(search) = self.search.as_mut() {
+            search.pattern.pop();
+        }
+        self.search_from(self.history.len());
+    }
+
+    /// Move to the next older match, as Ctrl+R does again in a shell.
+    pub fn search_older(&mut self) {
+        let before = self
+            .search
+            .as_ref()
+            .and_then(|search| search.found)
+            .unwrap_or(self.history.len());
+        self.search_from(before);
+    }
+
+    /// Show the newest match before history entry `before` in the editor.
+    /// Without an older match, the current one stays.
+    fn search_from(&mut self, before: usize) {
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+        if search.pattern.is_empty() {
+            search.found = None;
+            self.query = search.query.clone();
+            self.cursor_pos = search.cursor_pos;
+            return;
+        }
+        match search_history(&self.history, &search.pattern, before) {
+            Some(index) => {
+                search.found = Some(index);
+                self.query = self.history[index].clone();
+                self.cursor_pos = self.query.len();
             }
+            None if before == self.history.len() => search.found = None,
+            None => {}
+        }
+    }
+
+    /// Keep the matching query in the editor.
+    pub fn accept_search(&mut self) {
+        if let Some(search) = self.search.take() {
+            self.history_index = search.found;
+            self.mode = search.mode;
+        }
+    }
+
+    /// Put back the query from before the search.
+    pub fn cancel_search(&mut self) {
+        if let Some(search) = self.search.take() {
+            self.query = search.query;
+            self.cursor_pos = search.cursor_pos;
+            self.mode = search.mode;
         }
     }
 
+    /// Number of rows in the whole result, including any not loaded.
+    pub fn total_rows(&self) -> usize {
+        self.result
+            .as_ref()
+            .map_or(0, |table| table.total_row_count())
+    }
+
+    /// Row of the whole result at the top of the results.
+    pub fn absolute_row(&self) -> usize {
+        self.result_offset + self.result_scroll
+    }
+
+    /// Scroll so that `row` of the whole result is at the top, loading the
+    /// page holding it first if the result was spilled to disk.
+    fn move_to_row(&mut self, row: usize) {
+        let Some(table) = self.result.as_mut() else {
+            return;
+        };
+        let row = row.min(table.total_row_count().saturating_sub(1));
+        if let Some(spilled) = table.spilled.clone() {
+            if row < self.result_offset || row >= self.result_offset + table.row_count() {
+                let start = row / SPILL_PAGE_ROWS * SPILL_PAGE_ROWS;
+                match spilled.rows(start, SPILL_PAGE_ROWS) {
+                    Ok(rows) => {
+                        table.rows = rows;
+                        self.masks.apply(table);
+                        self.result_offset = start;
+                    }
+                    Err(e) => {
+                        self.warning = Some(format!("Could not read spilled rows: {}", e));
+                        return;
+                    }
+                }
+            }
+        }
+        self.result_scroll = row.saturating_sub(self.result_offset);
+    }
+
+    pub fn scroll_results_up(&mut self) {
+        if self.absolute_row() > 0 {
+            self.move_to_row(self.absolute_row() - 1);
+        }
+    }
+
+    pub fn scroll_results_down(&mut self) {
+        self.move_to_row(self.absolute_row() + 1);
+    }
+
     pub fn scroll_results_left(&mut self) {
         if self.result_horizontal_scroll > 0 {
             self.result_horizontal_scroll -= 1;
@@ -306,22 +1098,200 @@ impl App {
     }
 
     pub fn page_up(&mut self) {
-        self.result_scroll = self.result_scroll.saturating_sub(10);
+        self.move_to_row(self.absolute_row().saturating_sub(10));
     }
 
     pub fn page_down(&mut self) {
-        if let Some(ref table) = self.result {
-            self.result_scroll = (self.result_scroll + 10).min(table.row_count().saturating_sub(1));
+        self.move_to_row(self.absolute_row() + 10);
+    }
+
+    pub fn cycle_view(&mut self) {
+        self.view = self.view.next();
+    }
+
+    /// Jump to the start of the next page of results.
+    pub fn next_page(&mut self) {
+        let page_size = self.page_size.max(1);
+        let next = (self.absolute_row() / page_size + 1) * page_size;
+        if next < self.total_rows() {
+            self.move_to_row(next);
         }
     }
 
+    /// Jump to the start of the previous page of results.
+    pub fn prev_page(&mut self) {
+        let page_size = self.page_size.max(1);
+        let current = self.absolute_row() / page_size;
+        self.move_to_row(current.saturating_sub(1) * page_size);
+    }
+
+    /// Current page (1-based) and total number of pages.
+    pub fn page_position(&self) -> (usize, usize) {
+        let page_size = self.page_size.max(1);
+        (
+            self.absolute_row() / page_size + 1,
+            self.total_rows().div_ceil(page_size).max(1),
+        )
+    }
+
     pub fn scroll_to_top(&mut self) {
-        self.result_scroll = 0;
+        self.move_to_row(0);
     }
 
     pub fn scroll_to_bottom(&mut self) {
-        if let Some(ref table) = self.result {
-            self.result_scroll = table.row_count().saturating_sub(1);
+        self.move_to_row(self.total_rows().saturating_sub(1));
+    }
+
+    /// Scroll so that 1-based `row` is at the top of the results and focus
+    /// them.
+    pub fn goto_row(&mut self, row: Option<usize>) {
+        if self.result.is_none() {
+            self.error = Some("No results to go to".to_string());
+            return;
+        }
+        match row {
+            Some(row) if row >= 1 => {
+                self.move_to_row(row - 1);
+                self.focus = Focus::Results;
+            }
+            _ => self.error = Some("Usage: :goto <row number>".to_string()),
+        }
+    }
+
+    /// Open the column chooser for the current result, listing the shown
+    /// columns in their order followed by the hidden ones.
+    pub fn open_column_chooser(&mut self) {
+        let Some(table) = &self.result else {
+            return;
+        };
+        let count = table.schema.columns.len();
+        let mut columns: Vec<(usize, bool)> = match &self.visible_columns {
+            Some(visible) => visible.iter().map(|&i| (i, true)).collect(),
+            None => (0..count).map(|i| (i, true)).collect(),
+        };
+        for i in 0..count {
+            if !columns.iter().any(|&(c, _)| c == i) {
+                columns.push((i, false));
+            }
+        }
+        self.column_chooser = Some(ColumnChooser { columns, cursor: 0 });
+        self.mode = Mode::Columns;
+    }
+
+    pub fn close_column_chooser(&mut self) {
+        self.column_chooser = None;
+        self.mode = Mode::Normal;
+    }
+
+    pub fn column_chooser_up(&mut self) {
+        if let Some(chooser) = &mut self.column_chooser {
+            chooser.cursor = chooser.cursor.saturating_sub(1);
+        }
+    }
+
+    pub fn column_chooser_down(&mut self) {
+        if let Some(chooser) = &mut self.column_chooser {
+            chooser.cursor = (chooser.cursor + 1).min(chooser.columns.len().saturating_sub(1));
+        }
+    }
+
+    /// Show or hide the column under the cursor. The last shown column
+    /// cannot be hidden.
+    pub fn toggle_column(&mut self) {
+        let Some(chooser) = &mut self.column_chooser else {
+            return;
+        };
+        let shown = chooser.columns.iter().filter(|&&(_, shown)| shown).count();
+        let Some(entry) = chooser.columns.get_mut(chooser.cursor) else {
+            return;
+        };
+        if entry.1 && shown == 1 {
+            return;
+        }
+        entry.1 = !entry.1;
+        self.apply_column_chooser();
+    }
+
+    /// Move the column under the cursor one place earlier (`up`) or later.
+    pub fn move_column(&mut self, up: bool) {
+        let Some(chooser) = &mut self.column_chooser else {
+            return;
+        };
+        let cursor = chooser.cursor;
+        let target = if up {
+            cursor.checked_sub(1)
+        } else {
+            Some(cursor + 1).filter(|&t| t < chooser.columns.len())
+        };
+        let Some(target) = target else {
+            return;
+        };
+        chooser.columns.swap(cursor, target);
+        chooser.cursor = target;
+        self.apply_column_chooser();
+    }
+
+    fn apply_column_chooser(&mut self) {
+        let Some(chooser) = &self.column_chooser else {
+            return;
+        };
+        let visible: Vec<usize> = chooser
+            .columns
+            .iter()
+            .filter(|&&(_, shown)| shown)
+            .map(|&(i, _)| i)
+            .collect();
+        let all = visible.len() == chooser.columns.len()
+            && visible.iter().enumerate().all(|(n, &i)| n == i);
+        self.visible_columns = (!all).then_some(visible);
+        self.result_horizontal_scroll = 0;
+    }
+
+    /// The first visible result column, which the status bar reports and
+    /// the frequency table counts.
+    pub fn selected_column(&self) -> Option<&Column> {
+        let table = self.result.as_ref()?;
+        let columns = shown_columns(table, self.visible_columns.as_deref());
+        let col = self
+            .result_horizontal_scroll
+            .min(columns.len().checked_sub(1)?);
+        table.schema.columns.get(columns[col])
+    }
+
+    /// Count the values of the selected column over the last query's
+    /// result and show the most common ones in a popup.
+    pub fn show_frequency(&mut self) {
+        let (Some(column), Some(query)) = (self.selected_column(), &self.last_query) else {
+            return;
+        };
+        let column = column.name.clone();
+        let running = self.ctx.execute_sql_async(&frequency_query(query, &column));
+        self.frequency = Some(FrequencyPopup {
+            column,
+            running: Some(running),
+            result: None,
+            error: None,
+            scroll: 0,
+        });
+        self.mode = Mode::Frequency;
+    }
+
+    /// Close the frequency popup, cancelling its query if still running.
+    pub fn close_frequency(&mut self) {
+        if let Some(handle) = self.frequency.take().and_then(|popup| popup.running) {
+            handle.cancel();
+        }
+        self.mode = Mode::Normal;
+    }
+
+    pub fn scroll_frequency(&mut self, down: bool) {
+        if let Some(popup) = &mut self.frequency {
+            let rows = popup.result.as_ref().map_or(0, |table| table.row_count());
+            popup.scroll = if down {
+                (popup.scroll + 1).min(rows.saturating_sub(1))
+            } else {
+                popup.scroll.saturating_sub(1)
+            };
         }
     }
 
@@ -340,22 +1310,49 @@ impl App {
     }
 
     pub fn execute_command(&mut self) {
-        let cmd = self.command_buffer.trim();
+        let buffer = self.command_buffer.trim().to_string();
+        let (cmd, arg) = buffer
+            .split_once(' ')
+            .map(|(c, a)| (c, a.trim()))
+            .unwrap_or((buffer.as_str(), ""));
         match cmd {
             "q" | "quit" => self.should_quit = true,
+            "e" | "exec" | "execute" if !arg.is_empty() => self.open_query(arg),
             "e" | "exec" | "execute" => self.execute_query(),
-            "w" | "write" => {
-                // Could add export functionality here
-            }
+            "w" | "write" => self.save_query(arg),
             "clear" => {
                 self.clear_query();
                 self.result = None;
                 self.error = None;
+                self.warning = None;
+                self.notice = None;
+                self.result_size = 0;
+            }
+            "limit" => self.apply_row_limit(arg.parse().ok()),
+            "goto" => self.goto_row(arg.replace(',', "").parse().ok()),
+            "reload" | "refresh" => self.reload_tables(Some(arg).filter(|a| !a.is_empty())),
+            "materialize" | "mat" => self.materialize(arg),
+            "vsplit" | "vs" => self.open_split(),
+            "tabnew" => {
+                self.new_tab();
+                if !arg.is_empty() {
+                    self.open_query(arg);
+                }
+            }
+            "tabclose" | "tabc" => self.close_tab(),
+            "tabnext" | "tabn" => self.next_tab(),
+            "tabprevious" | "tabp" => self.prev_tab(),
+            "only" => self.close_split(),
+            "close" => {
+                self.switch_pane();
+                self.close_split();
             }
             _ => {}
         }
         self.command_buffer.clear();
-        self.mode = Mode::Normal;
+        if self.mode == Mode::Command {
+            self.mode = Mode::Normal;
+        }
     }
 
     pub fn toggle_focus(&mut self) {
@@ -0,0 +1,165 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::storage::versioned::{backup_path, VERSION_KEY};
+
+/// Most queries kept in the history, oldest dropped first.
+pub const MAX_HISTORY: usize = 1000;
+
+/// Format of the history file. Files start with a `{"version":1}` line;
+/// version 0 files, from before there was one, hold only queries.
+pub const HISTORY_VERSION: u64 = 1;
+
+/// `$HOME/knowhere/history`, or `None` if there is no home directory.
+pub fn default_history_path() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .filter(|home| !home.is_empty())
+        .map(|home| PathBuf::from(home).join("knowhere").join("history"))
+}
+
+/// Queries saved at `path`, oldest first. A missing file is an empty
+/// history, and lines that cannot be read are skipped. A file in an older
+/// format is copied to `<path>.v<n>.bak` and rewritten in the current one;
+/// a file saved by a newer version of knowhere is an `Unsupported` error.
+pub fn load_history(path: &Path) -> io::Result<Vec<String>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut lines = text.lines().peekable();
+    let version = lines.peek().and_then(|line| header_version(line));
+    if version.is_some() {
+        lines.next();
+    }
+    let version = version.unwrap_or(0);
+    if version > HISTORY_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "{} was saved by a newer version of knowhere (format {})",
+                path.display(),
+                version
+            ),
+        ));
+    }
+
+    let mut history = Vec::new();
+    for line in lines {
+        if let Ok(query) = serde_json::from_str::<String>(line) {
+            push_history(&mut history, &query);
+        }
+    }
+    if version < HISTORY_VERSION && !text.trim().is_empty() {
+        fs::copy(path, backup_path(path, &format!("v{}.bak", version)))?;
+        save_history(path, &history)?;
+    }
+    Ok(history)
+}
+
+/// The version in a `{"version":n}` header line. Queries are stored as JSON
+/// strings, so they are never mistaken for one.
+fn header_version(line: &str) -> Option<u64> {
+    serde_json::from_str::<serde_json::Value>(line)
+        .ok()?
+        .get(VERSION_KEY)?
+        .as_u64()
+}
+
+/// Write `history` to `path`, creating its directory. Each query is stored
+/// as a JSON string on its own line, so multi-line queries survive.
+pub fn save_history(path: &Path, history: &[String]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut text = format!("{{\"{}\":{}}}\n", VERSION_KEY, HISTORY_VERSION);
+    for query in history {
+        text.push_str(&serde_json::to_string(query)?);
+        text.push('\n');
+    }
+    fs::write(path, text)
+}
+
+/// Add `query` as the newest entry, removing an earlier copy of it and the
+/// oldest entries past [`MAX_HISTORY`].
+pub fn push_history(history: &mut Vec<String>, query: &str) {
+    if query.trim().is_empty() {
+        return;
+    }
+    history.retain(|entry| entry != query);
+    history.push(query.to_string());
+    if history.len() > MAX_HISTORY {
+        history.drain(..history.len() - MAX_HISTORY);
+    }
+}
+
+/// Index of the newest entry before `before` that contains `pattern`,
+/// ignoring case.
+pub fn search_history(history: &[String], pattern: &str, before: usize) -> Option<usize> {
+    let pattern = pattern.to_lowercase();
+    history[..before.min(history.len())]
+        .iter()
+        .rposition(|entry| entry.to_lowercase().contains(&pattern))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_history_deduplicates() {
+        let mut history = Vec::new();
+        push_history(&mut history, "SELECT 1");
+        push_history(&mut history, "SELECT 2");
+        push_history(&mut history, "SELECT 1");
+        push_history(&mut history, "  ");
+        assert_eq!(history, vec!["SELECT 2", "SELECT 1"]);
+    }
+
+    #[test]
+    fn test_history_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("knowhere").join("history");
+        assert!(load_history(&path).unwrap().is_empty());
+
+        let history = vec!["SELECT *\nFROM users".to_string(), "SELECT 2".to_string()];
+        save_history(&path, &history).unwrap();
+        assert_eq!(load_history(&path).unwrap(), history);
+    }
+
+    #[test]
+    fn test_history_without_version_is_upgraded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history");
+        let old = "\"SELECT 1\"\n\"SELECT 2\"\n";
+        fs::write(&path, old).unwrap();
+
+        assert_eq!(load_history(&path).unwrap(), vec!["SELECT 1", "SELECT 2"]);
+        assert_eq!(
+            fs::read_to_string(dir.path().join("history.v0.bak")).unwrap(),
+            old
+        );
+        assert!(fs::read_to_string(&path)
+            .unwrap()
+            .starts_with("{\"version\":1}\n"));
+        assert_eq!(load_history(&path).unwrap(), vec!["SELECT 1", "SELECT 2"]);
+
+        fs::write(&path, "{\"version\":2}\n\"SELECT 3\"\n").unwrap();
+        let err = load_history(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[test]
+    fn test_search_history() {
+        let history: Vec<String> = ["SELECT * FROM users", "SELECT 1", "select name from users"]
+            .iter()
+            .map(|query| query.to_string())
+            .collect();
+        assert_eq!(search_history(&history, "USERS", history.len()), Some(2));
+        assert_eq!(search_history(&history, "users", 2), Some(0));
+        assert_eq!(search_history(&history, "users", 0), None);
+        assert_eq!(search_history(&history, "orders", 3), None);
+    }
+}
@@ -15,7 +15,17 @@ pub fn handle_events(app: &mut App) -> std::io::Result<bool> {
 fn handle_key_event(app: &mut App, key: KeyEvent) {
     // Handle Ctrl+C globally
     if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
-        if app.mode == Mode::Insert {
+        if app.mode == Mode::Frequency {
+            app.close_frequency();
+        } else if app.mode == Mode::Search {
+            app.cancel_search();
+        } else if app.is_running() {
+            app.cancel_query();
+        } else if app.mode == Mode::Prompt {
+            app.cancel_prompt();
+        } else if app.mode == Mode::Columns {
+            app.close_column_chooser();
+        } else if app.mode == Mode::Insert {
             app.enter_normal_mode();
         } else {
             app.should_quit = true;
@@ -27,10 +37,27 @@ fn handle_key_event(app: &mut App, key: KeyEvent) {
         Mode::Normal => handle_normal_mode(app, key),
         Mode::Insert => handle_insert_mode(app, key),
         Mode::Command => handle_command_mode(app, key),
+        Mode::Prompt => handle_prompt_mode(app, key),
+        Mode::Columns => handle_columns_mode(app, key),
+        Mode::Frequency => handle_frequency_mode(app, key),
+        Mode::Search => handle_search_mode(app, key),
     }
 }
 
+// Keys that only act in one pane check the focus inside their arm rather
+// than in a guard, so that in the other pane they do nothing instead of
+// falling through to a later arm for the same key.
+#[allow(clippy::collapsible_match)]
 fn handle_normal_mode(app: &mut App, key: KeyEvent) {
+    if app.pending_key.take() == Some('g') {
+        match key.code {
+            KeyCode::Char('t') => return app.next_tab(),
+            KeyCode::Char('T') => return app.prev_tab(),
+            KeyCode::Char('g') if app.focus == Focus::Results => return app.scroll_to_top(),
+            _ => {}
+        }
+    }
+
     match key.code {
         // Mode switching
         KeyCode::Char('i') => app.enter_insert_mode(),
@@ -47,12 +74,26 @@ fn handle_normal_mode(app: &mut App, key: KeyEvent) {
             app.enter_insert_mode();
         }
         KeyCode::Char(':') => app.enter_command_mode(),
+        KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.start_history_search();
+        }
 
         // Quit
         KeyCode::Char('q') => app.should_quit = true,
 
+        // Results workflow
+        KeyCode::Char('r') => app.rerun_last_query(),
+        KeyCode::Char('F') => app.cycle_view(),
+        KeyCode::Char('c') if app.focus == Focus::Results => app.open_column_chooser(),
+        KeyCode::Char('f') if app.focus == Focus::Results => app.show_frequency(),
+        KeyCode::Char('n') => app.next_page(),
+        KeyCode::Char('N') => app.prev_page(),
+
         // Focus switching
         KeyCode::Tab => app.toggle_focus(),
+        KeyCode::Char('w') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.switch_pane();
+        }
 
         // Navigation in query
         KeyCode::Char('h') | KeyCode::Left => {
@@ -99,11 +140,8 @@ fn handle_normal_mode(app: &mut App, key: KeyEvent) {
                 app.move_cursor_word_backward();
             }
         }
-        KeyCode::Char('g') => {
-            if app.focus == Focus::Results {
-                app.scroll_to_top();
-            }
-        }
+        // The first key of `gg`, `gt` and `gT`
+        KeyCode::Char('g') => app.pending_key = Some('g'),
         KeyCode::Char('G') => {
             if app.focus == Focus::Results {
                 app.scroll_to_bottom();
@@ -170,6 +208,9 @@ fn handle_insert_mode(app: &mut App, key: KeyEvent) {
         KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => {
             app.move_cursor_end();
         }
+        KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.start_history_search();
+        }
 
         KeyCode::Char(c) => app.insert_char(c),
 
@@ -177,6 +218,44 @@ fn handle_insert_mode(app: &mut App, key: KeyEvent) {
     }
 }
 
+fn handle_columns_mode(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc | KeyCode::Enter | KeyCode::Char('c') | KeyCode::Char('q') => {
+            app.close_column_chooser();
+        }
+        KeyCode::Char('j') | KeyCode::Down => app.column_chooser_down(),
+        KeyCode::Char('k') | KeyCode::Up => app.column_chooser_up(),
+        KeyCode::Char(' ') => app.toggle_column(),
+        KeyCode::Char('J') => app.move_column(false),
+        KeyCode::Char('K') => app.move_column(true),
+        _ => {}
+    }
+}
+
+fn handle_frequency_mode(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc | KeyCode::Enter | KeyCode::Char('f') | KeyCode::Char('q') => {
+            app.close_frequency();
+        }
+        KeyCode::Char('j') | KeyCode::Down => app.scroll_frequency(true),
+        KeyCode::Char('k') | KeyCode::Up => app.scroll_frequency(false),
+        _ => {}
+    }
+}
+
+fn handle_search_mode(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc => app.cancel_search(),
+        KeyCode::Enter => app.accept_search(),
+        KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.search_older();
+        }
+        KeyCode::Backspace => app.search_pop(),
+        KeyCode::Char(c) => app.search_push(c),
+        _ => {}
+    }
+}
+
 fn handle_command_mode(app: &mut App, key: KeyEvent) {
     match key.code {
         KeyCode::Esc => {
@@ -194,3 +273,52 @@ fn handle_command_mode(app: &mut App, key: KeyEvent) {
         _ => {}
     }
 }
+
+fn handle_prompt_mode(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc => app.cancel_prompt(),
+        KeyCode::Enter => app.submit_param(),
+        KeyCode::Up => app.param_history_up(),
+        KeyCode::Down => app.param_history_down(),
+        KeyCode::Backspace => {
+            if let Some(prompt) = app.prompt.as_mut() {
+                prompt.input.pop();
+            }
+        }
+        KeyCode::Char(c) => {
+            if let Some(prompt) = app.prompt.as_mut() {
+                prompt.input.push(c);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datafusion::DataFusionContext;
+
+    fn press(app: &mut App, c: char) {
+        handle_key_event(app, KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+    }
+
+    #[test]
+    fn test_gg_scrolls_to_top_but_gt_does_not() {
+        let ctx = DataFusionContext::new().unwrap();
+        let result = ctx.execute_sql("SELECT * FROM generate_series(1, 100)");
+        let mut app = App::new(ctx);
+        app.result = Some(result.unwrap());
+        app.focus = Focus::Results;
+
+        app.result_scroll = 50;
+        press(&mut app, 'g');
+        assert_eq!(app.result_scroll, 50);
+        press(&mut app, 't');
+        assert_eq!(app.result_scroll, 50);
+
+        press(&mut app, 'g');
+        press(&mut app, 'g');
+        assert_eq!(app.result_scroll, 0);
+    }
+}
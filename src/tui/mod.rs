@@ -1,5 +1,7 @@
 pub mod app;
+pub mod history;
 pub mod input;
+pub mod memory;
 pub mod ui;
 
 pub use app::App;
@@ -1,37 +1,171 @@
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, Wrap},
     Frame,
 };
 
-use super::app::{App, Focus, Mode};
+use crate::datafusion::QueryHandle;
+use crate::render::{self, is_numeric, BarRenderer, NdjsonRenderer, ResultRenderer};
+use crate::storage::table::{Row as DataRow, Schema, Table as DataTable, Value};
+
+use super::app::{
+    shown_columns, App, Buffer, ColumnChooser, Focus, FrequencyPopup, HistorySearch, Mode,
+    ParamPrompt, ResultView,
+};
+use super::memory::format_bytes;
+
+const HEADER_HEIGHT: u16 = 1;
+const EDITOR_HEIGHT: u16 = 7;
+const STATUS_HEIGHT: u16 = 1;
+
+const SPINNER_FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
 
 pub fn draw(frame: &mut Frame, app: &App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(1), // Header
-            Constraint::Length(7), // Query editor (increased for multiline)
-            Constraint::Min(10),   // Results
-            Constraint::Length(1), // Status bar
+            Constraint::Length(HEADER_HEIGHT), // Header
+            Constraint::Length(EDITOR_HEIGHT), // Query editor (increased for multiline)
+            Constraint::Min(10),               // Results
+            Constraint::Length(STATUS_HEIGHT), // Status bar
         ])
         .split(frame.area());
 
-    draw_header(frame, chunks[0]);
-    draw_query_editor(frame, app, chunks[1]);
-    draw_results(frame, app, chunks[2]);
+    draw_header(frame, app, chunks[0]);
+    let active = Pane::active(app);
+    match app.split {
+        None => {
+            draw_query_editor(frame, app, &active, chunks[1]);
+            draw_results(frame, app, &active, chunks[2]);
+        }
+        Some(ref other) => {
+            let inactive = Pane::inactive(app, other);
+            let (left, right) = if app.split_active_left {
+                (&active, &inactive)
+            } else {
+                (&inactive, &active)
+            };
+            let editors = split_columns(chunks[1]);
+            let results = split_columns(chunks[2]);
+            draw_query_editor(frame, app, left, editors[0]);
+            draw_query_editor(frame, app, right, editors[1]);
+            draw_results(frame, app, left, results[0]);
+            draw_results(frame, app, right, results[1]);
+        }
+    }
     draw_status_bar(frame, app, chunks[3]);
 
     // Draw command line if in command mode
     if app.mode == Mode::Command {
         draw_command_line(frame, app);
     }
+    if let Some(ref prompt) = app.prompt {
+        draw_param_prompt(frame, prompt);
+    }
+    if let Some(ref search) = app.search {
+        draw_history_search(frame, search);
+    }
+    if let (Some(chooser), Some(table)) = (&app.column_chooser, &app.result) {
+        draw_column_chooser(frame, chooser, table);
+    }
+    if let Some(ref popup) = app.frequency {
+        draw_frequency(frame, popup);
+    }
+}
+
+/// The query and result state one pane draws: the active pane's from the
+/// [`App`], the other pane of a split from its [`Buffer`].
+struct Pane<'a> {
+    query: &'a str,
+    cursor_pos: usize,
+    result: Option<&'a DataTable>,
+    error: Option<&'a str>,
+    running: Option<&'a QueryHandle>,
+    /// Top row, counted within the loaded rows of `result`.
+    scroll: usize,
+    /// Row of the whole result at which the loaded rows start.
+    offset: usize,
+    horizontal_scroll: usize,
+    column_widths: &'a [usize],
+    visible_columns: Option<&'a [usize]>,
+    view: ResultView,
+    active: bool,
 }
 
-fn draw_header(frame: &mut Frame, area: Rect) {
-    let header = Line::from(vec![
+impl<'a> Pane<'a> {
+    fn active(app: &'a App) -> Self {
+        Self {
+            query: &app.query,
+            cursor_pos: app.cursor_pos,
+            result: app.result.as_ref(),
+            error: app.error.as_deref(),
+            running: app.running.as_ref(),
+            scroll: app.result_scroll,
+            offset: app.result_offset,
+            horizontal_scroll: app.result_horizontal_scroll,
+            column_widths: &app.column_widths,
+            visible_columns: app.visible_columns.as_deref(),
+            view: app.view,
+            active: true,
+        }
+    }
+
+    /// The inactive pane of a split. Its rows scroll with the active pane's.
+    fn inactive(app: &'a App, buffer: &'a Buffer) -> Self {
+        let rows = buffer.result.as_ref().map_or(0, |table| table.row_count());
+        Self {
+            query: &buffer.query,
+            cursor_pos: buffer.cursor_pos,
+            result: buffer.result.as_ref(),
+            error: buffer.error.as_deref(),
+            running: buffer.running.as_ref(),
+            scroll: app
+                .absolute_row()
+                .saturating_sub(buffer.result_offset)
+                .min(rows.saturating_sub(1)),
+            offset: buffer.result_offset,
+            horizontal_scroll: buffer.result_horizontal_scroll,
+            column_widths: &buffer.column_widths,
+            visible_columns: buffer.visible_columns.as_deref(),
+            view: buffer.view,
+            active: false,
+        }
+    }
+
+    /// Indices of the columns of `table` to draw, in order.
+    fn columns(&self, table: &DataTable) -> Vec<usize> {
+        shown_columns(table, self.visible_columns)
+    }
+
+    /// Current page (1-based) and total number of pages.
+    fn page_position(&self, page_size: usize) -> (usize, usize) {
+        let page_size = page_size.max(1);
+        let rows = self.result.map_or(0, |table| table.total_row_count());
+        (
+            (self.offset + self.scroll) / page_size + 1,
+            rows.div_ceil(page_size).max(1),
+        )
+    }
+}
+
+fn split_columns(area: Rect) -> std::rc::Rc<[Rect]> {
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area)
+}
+
+/// Number of result rows visible at once for a terminal of the given height.
+pub fn results_page_size(terminal_height: u16) -> usize {
+    // Results block borders (2) plus the table header row and its spacing (2)
+    let chrome = HEADER_HEIGHT + EDITOR_HEIGHT + STATUS_HEIGHT + 4;
+    terminal_height.saturating_sub(chrome).max(1) as usize
+}
+
+fn draw_header(frame: &mut Frame, app: &App, area: Rect) {
+    let mut spans = vec![
         Span::styled("  ", Style::default()),
         Span::styled("⚡", Style::default().fg(Color::Yellow)),
         Span::styled(
@@ -42,14 +176,41 @@ fn draw_header(frame: &mut Frame, area: Rect) {
         ),
         Span::styled(" | ", Style::default().fg(Color::DarkGray)),
         Span::styled("SQL Explorer", Style::default().fg(Color::DarkGray)),
-    ]);
+    ];
+    if app.tabs.len() > 1 {
+        spans.push(Span::styled(" | ", Style::default().fg(Color::DarkGray)));
+        for (i, tab) in app.tabs.iter().enumerate() {
+            let query = if i == app.tab_index {
+                &app.query
+            } else {
+                &tab.buffer.query
+            };
+            let label = format!(" {}:{} ", i + 1, tab_label(query));
+            spans.push(if i == app.tab_index {
+                Span::styled(label, Style::default().fg(Color::Black).bg(Color::Cyan))
+            } else {
+                Span::styled(label, Style::default().fg(Color::Gray))
+            });
+        }
+    }
+    let header = Line::from(spans);
 
     let paragraph = Paragraph::new(header).style(Style::default().bg(Color::Black));
     frame.render_widget(paragraph, area);
 }
 
-fn draw_query_editor(frame: &mut Frame, app: &App, area: Rect) {
-    let is_focused = app.focus == Focus::Query;
+/// A short name for a tab: the start of its query's first line.
+fn tab_label(query: &str) -> String {
+    let first = query.trim().lines().next().unwrap_or_default();
+    if first.is_empty() {
+        "[empty]".to_string()
+    } else {
+        render::truncate(first, 16)
+    }
+}
+
+fn draw_query_editor(frame: &mut Frame, app: &App, pane: &Pane, area: Rect) {
+    let is_focused = pane.active && app.focus == Focus::Query;
     let border_color = if is_focused {
         Color::Cyan
     } else {
@@ -65,17 +226,17 @@ fn draw_query_editor(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(block, area);
 
     // Syntax highlighting for SQL (multiline support)
-    let highlighted_lines = highlight_sql_multiline(&app.query);
+    let highlighted_lines = highlight_sql_multiline(pane.query);
     let paragraph = Paragraph::new(highlighted_lines).wrap(Wrap { trim: false });
 
     frame.render_widget(paragraph, inner);
 
     // Show cursor in insert mode with multiline support
     if app.mode == Mode::Insert && is_focused {
-        let text_before_cursor = &app.query[..app.cursor_pos.min(app.query.len())];
+        let text_before_cursor = &pane.query[..pane.cursor_pos.min(pane.query.len())];
         let lines: Vec<&str> = text_before_cursor.split('\n').collect();
         let cursor_y = inner.y + (lines.len() as u16).saturating_sub(1);
-        let cursor_x = inner.x + lines.last().map(|l| l.len()).unwrap_or(0) as u16;
+        let cursor_x = inner.x + lines.last().map(|l| render::display_width(l)).unwrap_or(0) as u16;
         frame.set_cursor_position((cursor_x, cursor_y));
     }
 }
@@ -215,17 +376,39 @@ fn colorize_word(word: &str, keywords: &[&str]) -> Span<'static> {
     }
 }
 
-fn draw_results(frame: &mut Frame, app: &App, area: Rect) {
-    let is_focused = app.focus == Focus::Results;
+fn draw_results(frame: &mut Frame, app: &App, pane: &Pane, area: Rect) {
+    let is_focused = pane.active && app.focus == Focus::Results;
     let border_color = if is_focused {
         Color::Cyan
     } else {
         Color::DarkGray
     };
 
-    let title = if let Some(ref table) = app.result {
-        format!(" Results ({} rows) ", table.row_count())
-    } else if let Some(ref error) = app.error {
+    let title = if let Some(handle) = pane.running {
+        let elapsed = handle.elapsed();
+        let frame_idx = (elapsed.as_millis() / 100) as usize % SPINNER_FRAMES.len();
+        let progress = handle.progress();
+        let scanned = if progress.rows_scanned > 0 {
+            format!(", {} rows scanned", progress.rows_scanned)
+        } else {
+            String::new()
+        };
+        format!(
+            " {} Running... {:.1}s{} (Ctrl+C to cancel) ",
+            SPINNER_FRAMES[frame_idx],
+            elapsed.as_secs_f64(),
+            scanned
+        )
+    } else if let Some(table) = pane.result {
+        let (page, pages) = pane.page_position(app.page_size);
+        format!(
+            " Results ({} rows, page {}/{}, {} view) ",
+            table.total_row_count(),
+            page,
+            pages,
+            pane.view.label()
+        )
+    } else if let Some(error) = pane.error {
         format!(" Error: {} ", error)
     } else {
         " Results ".to_string()
@@ -239,35 +422,90 @@ fn draw_results(frame: &mut Frame, app: &App, area: Rect) {
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
-    if let Some(ref error) = app.error {
-        let error_text = Paragraph::new(error.as_str())
+    if let Some(error) = pane.error {
+        let error_text = Paragraph::new(error)
             .style(Style::default().fg(Color::Red))
             .wrap(Wrap { trim: true });
         frame.render_widget(error_text, inner);
         return;
     }
 
-    if let Some(ref table) = app.result {
+    if let Some(table) = pane.result {
         if table.row_count() == 0 {
             let empty = Paragraph::new("No results");
             frame.render_widget(empty, inner);
             return;
         }
 
+        let columns = pane.columns(table);
+        match pane.view {
+            ResultView::Table => {}
+            ResultView::Expanded => {
+                let lines = expanded_lines(table, &columns, pane.scroll, inner.height as usize);
+                frame.render_widget(Paragraph::new(lines), inner);
+                return;
+            }
+            ResultView::Bars => {
+                let mut renderer = BarRenderer::new(inner.width as usize / 2);
+                renderer.prepare(table);
+                let lines: Vec<Line> = table
+                    .slice(pane.scroll, inner.height as usize)
+                    .rows
+                    .iter()
+                    .map(|row| {
+                        let mut buf = Vec::new();
+                        // Writing into a Vec<u8> cannot fail
+                        let _ = renderer.render_row(&mut buf, &table.schema, row);
+                        Line::from(String::from_utf8_lossy(&buf).trim_end().to_string())
+                    })
+                    .collect();
+                frame.render_widget(Paragraph::new(lines), inner);
+                return;
+            }
+            ResultView::Json => {
+                let mut renderer = NdjsonRenderer::new();
+                let schema = Schema::new(
+                    columns
+                        .iter()
+                        .map(|&i| table.schema.columns[i].clone())
+                        .collect(),
+                );
+                let lines: Vec<Line> = table
+                    .slice(pane.scroll, inner.height as usize)
+                    .rows
+                    .iter()
+                    .map(|row| {
+                        let row =
+                            DataRow::new(columns.iter().map(|&i| row.values[i].clone()).collect());
+                        let mut buf = Vec::new();
+                        // Writing into a Vec<u8> cannot fail
+                        let _ = renderer.render_row(&mut buf, &schema, &row);
+                        Line::from(String::from_utf8_lossy(&buf).trim_end().to_string())
+                    })
+                    .collect();
+                frame.render_widget(Paragraph::new(lines), inner);
+                return;
+            }
+        }
+
         // Build header
-        let header_cells: Vec<Cell> = table
-            .schema
-            .columns
+        let header_cells: Vec<Cell> = columns
             .iter()
-            .enumerate()
-            .skip(app.result_horizontal_scroll)
-            .map(|(i, col)| {
-                let width = app.column_widths.get(i).copied().unwrap_or(10);
-                Cell::from(truncate_string(&col.name, width)).style(
-                    Style::default()
-                        .fg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD),
-                )
+            .skip(pane.horizontal_scroll)
+            .map(|&i| {
+                let col = &table.schema.columns[i];
+                let width = pane.column_widths.get(i).copied().unwrap_or(10);
+                let alignment = if is_numeric(&col.data_type) {
+                    Alignment::Right
+                } else {
+                    Alignment::Left
+                };
+                Cell::from(Line::from(render::truncate(&col.name, width)).alignment(alignment))
+                    .style(
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    )
             })
             .collect();
 
@@ -276,20 +514,16 @@ fn draw_results(frame: &mut Frame, app: &App, area: Rect) {
         // Build rows
         let visible_height = inner.height.saturating_sub(2) as usize;
         let rows: Vec<Row> = table
+            .slice(pane.scroll, visible_height)
             .rows
             .iter()
-            .skip(app.result_scroll)
-            .take(visible_height)
             .map(|row| {
-                let cells: Vec<Cell> = row
-                    .values
+                let cells: Vec<Cell> = columns
                     .iter()
-                    .enumerate()
-                    .skip(app.result_horizontal_scroll)
-                    .map(|(i, val)| {
-                        let width = app.column_widths.get(i).copied().unwrap_or(10);
-                        let s = val.to_string();
-                        Cell::from(truncate_string(&s, width))
+                    .skip(pane.horizontal_scroll)
+                    .map(|&i| {
+                        let width = pane.column_widths.get(i).copied().unwrap_or(10);
+                        value_cell(&row.values[i], width)
                     })
                     .collect();
                 Row::new(cells)
@@ -297,11 +531,12 @@ fn draw_results(frame: &mut Frame, app: &App, area: Rect) {
             .collect();
 
         // Calculate column widths for display
-        let widths: Vec<Constraint> = app
-            .column_widths
+        let widths: Vec<Constraint> = columns
             .iter()
-            .skip(app.result_horizontal_scroll)
-            .map(|&w| Constraint::Length(w as u16 + 2))
+            .skip(pane.horizontal_scroll)
+            .map(|&i| {
+                Constraint::Length(pane.column_widths.get(i).copied().unwrap_or(10) as u16 + 2)
+            })
             .collect();
 
         let table_widget = Table::new(rows, &widths)
@@ -316,14 +551,75 @@ fn draw_results(frame: &mut Frame, app: &App, area: Rect) {
     }
 }
 
-fn truncate_string(s: &str, max_len: usize) -> String {
-    if s.len() <= max_len {
-        s.to_string()
-    } else if max_len > 3 {
-        format!("{}...", &s[..max_len - 3])
-    } else {
-        s[..max_len].to_string()
+/// Render rows as `-[ RECORD n ]-` blocks with one `column | value` line per field.
+fn expanded_lines(
+    table: &DataTable,
+    columns: &[usize],
+    start: usize,
+    max_lines: usize,
+) -> Vec<Line<'static>> {
+    let name_width = columns
+        .iter()
+        .map(|&i| render::display_width(&table.schema.columns[i].name))
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = Vec::new();
+    for (idx, row) in table.rows.iter().enumerate().skip(start) {
+        if lines.len() >= max_lines {
+            break;
+        }
+        lines.push(Line::from(Span::styled(
+            format!("-[ RECORD {} ]-", idx + 1),
+            Style::default().fg(Color::DarkGray),
+        )));
+        for &i in columns {
+            let (col, val) = (&table.schema.columns[i], &row.values[i]);
+            lines.push(Line::from(vec![
+                Span::styled(
+                    render::fit(&col.name, name_width, false),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::styled(" | ", Style::default().fg(Color::DarkGray)),
+                Span::raw(val.to_string()),
+            ]));
+        }
     }
+    lines.truncate(max_lines);
+    lines
+}
+
+const NULL_MARKER: &str = "∅";
+
+/// Build a result cell styled by value type: NULLs dimmed, numbers cyan and
+/// right-aligned, booleans magenta, strings in the default style.
+fn value_cell(value: &Value, width: usize) -> Cell<'static> {
+    let (text, style, alignment) = match value {
+        Value::Null => (
+            NULL_MARKER.to_string(),
+            Style::default().fg(Color::DarkGray),
+            Alignment::Left,
+        ),
+        Value::Integer(_) | Value::Float(_) => (
+            value.to_string(),
+            Style::default().fg(Color::Cyan),
+            Alignment::Right,
+        ),
+        Value::Boolean(_) => (
+            value.to_string(),
+            Style::default().fg(Color::Magenta),
+            Alignment::Left,
+        ),
+        Value::Date(_) | Value::Timestamp(_) => (
+            value.to_string(),
+            Style::default().fg(Color::Yellow),
+            Alignment::Left,
+        ),
+        Value::String(s) => (s.clone(), Style::default(), Alignment::Left),
+    };
+
+    let line = Line::from(render::truncate(&text, width)).alignment(alignment);
+    Cell::from(line).style(style)
 }
 
 fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
@@ -331,12 +627,20 @@ fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
         Mode::Normal => "NORMAL",
         Mode::Insert => "INSERT",
         Mode::Command => "COMMAND",
+        Mode::Prompt => "PARAM",
+        Mode::Columns => "COLUMNS",
+        Mode::Frequency => "FREQ",
+        Mode::Search => "SEARCH",
     };
 
     let mode_color = match app.mode {
         Mode::Normal => Color::Blue,
         Mode::Insert => Color::Green,
         Mode::Command => Color::Yellow,
+        Mode::Prompt => Color::Magenta,
+        Mode::Columns => Color::Cyan,
+        Mode::Frequency => Color::Cyan,
+        Mode::Search => Color::Yellow,
     };
 
     let focus_str = match app.focus {
@@ -345,12 +649,31 @@ fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
     };
 
     let help = match app.mode {
-        Mode::Normal => "i:insert  j/k:scroll  Tab:focus  :e:execute  ::command  q:quit",
+        Mode::Normal => {
+            "i:insert  j/k:scroll  n/N:page  r:rerun  F:view  c:columns  f:frequency  Tab:focus  gt/gT:tab  :e:execute  q:quit"
+        }
         Mode::Insert => "Esc:normal  Enter:newline  Ctrl+C:cancel",
-        Mode::Command => "e:execute  q:quit  Esc:cancel",
+        Mode::Command => "e:execute  e/w <file>:open/save  limit [n]:re-run with LIMIT  goto <row>  reload [table]:re-read files  vs/only:split  tabnew/tabc:tabs  q:quit  Esc:cancel",
+        Mode::Prompt => "Enter:next  Up/Down:previous values  Esc:cancel",
+        Mode::Columns => "j/k:move  Space:show/hide  J/K:reorder  Esc:close",
+        Mode::Frequency => "j/k:scroll  Esc:close",
+        Mode::Search => "type to search history  Ctrl+R:older  Enter:accept  Esc:cancel",
     };
 
-    let status = Line::from(vec![
+    let mut memory = Vec::new();
+    if let Some(rss) = app.process_memory {
+        memory.push(format!("mem {}", format_bytes(rss)));
+    }
+    if app.result.is_some() {
+        let spilled = app.result.as_ref().is_some_and(|t| t.spilled.is_some());
+        memory.push(format!(
+            "result {}{}",
+            format_bytes(app.result_size as u64),
+            if spilled { " (rest on disk)" } else { "" }
+        ));
+    }
+
+    let mut spans = vec![
         Span::styled(
             format!(" {} ", mode_str),
             Style::default().fg(Color::Black).bg(mode_color),
@@ -361,13 +684,72 @@ fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
             Style::default().fg(Color::DarkGray),
         ),
         Span::raw(" "),
-        Span::styled(help, Style::default().fg(Color::DarkGray)),
-    ]);
+    ];
+    if let Some(position) = result_position(app) {
+        spans.push(Span::styled(
+            format!("{} ", position),
+            Style::default().fg(Color::White),
+        ));
+    }
+    if !memory.is_empty() {
+        spans.push(Span::styled(
+            format!("{} ", memory.join(" • ")),
+            Style::default().fg(Color::Cyan),
+        ));
+    }
+    match (&app.warning, &app.notice) {
+        (Some(warning), _) => spans.push(Span::styled(
+            warning.as_str(),
+            Style::default().fg(Color::Yellow),
+        )),
+        (None, Some(notice)) => spans.push(Span::styled(
+            notice.as_str(),
+            Style::default().fg(Color::Green),
+        )),
+        (None, None) => spans.push(Span::styled(help, Style::default().fg(Color::DarkGray))),
+    }
+    let status = Line::from(spans);
 
     let paragraph = Paragraph::new(status);
     frame.render_widget(paragraph, area);
 }
 
+/// Where the results are scrolled to, e.g. `row 1,234 of 58,901 • col 3/12
+/// (amount: Float)`, counting the top row and the first visible column.
+fn result_position(app: &App) -> Option<String> {
+    let table = app.result.as_ref().filter(|t| t.row_count() > 0)?;
+    if app.focus != Focus::Results {
+        return None;
+    }
+    let columns = shown_columns(table, app.visible_columns.as_deref());
+    let col = app
+        .result_horizontal_scroll
+        .min(columns.len().checked_sub(1)?);
+    let column = &table.schema.columns[columns[col]];
+    Some(format!(
+        "row {} of {} • col {}/{} ({}: {:?})",
+        group_digits(app.absolute_row().min(table.total_row_count() - 1) + 1),
+        group_digits(table.total_row_count()),
+        col + 1,
+        columns.len(),
+        column.name,
+        column.data_type
+    ))
+}
+
+/// `n` with commas between groups of three digits.
+fn group_digits(n: usize) -> String {
+    let digits = n.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
 fn draw_command_line(frame: &mut Frame, app: &App) {
     let area = frame.area();
     let popup_area = Rect {
@@ -387,3 +769,181 @@ fn draw_command_line(frame: &mut Frame, app: &App) {
     // Position cursor
     frame.set_cursor_position((1 + app.command_buffer.len() as u16, popup_area.y));
 }
+
+fn draw_history_search(frame: &mut Frame, search: &HistorySearch) {
+    let area = frame.area();
+    let popup_area = Rect {
+        x: 0,
+        y: area.height - 1,
+        width: area.width,
+        height: 1,
+    };
+
+    frame.render_widget(Clear, popup_area);
+
+    let failing = !search.pattern.is_empty() && search.found.is_none();
+    let label = if failing {
+        "failing reverse-search: "
+    } else {
+        "reverse-search: "
+    };
+    let line = Line::from(vec![
+        Span::styled(label, Style::default().fg(Color::Yellow)),
+        Span::raw(search.pattern.as_str()),
+    ]);
+    frame.render_widget(Paragraph::new(line), popup_area);
+
+    let cursor = label.chars().count() + search.pattern.chars().count();
+    frame.set_cursor_position((cursor as u16, popup_area.y));
+}
+
+fn draw_param_prompt(frame: &mut Frame, prompt: &ParamPrompt) {
+    let area = frame.area();
+    let popup_area = Rect {
+        x: 0,
+        y: area.height - 1,
+        width: area.width,
+        height: 1,
+    };
+
+    frame.render_widget(Clear, popup_area);
+
+    let label = format!(
+        "{} ({}/{}): ",
+        prompt.current(),
+        prompt.values.len() + 1,
+        prompt.names.len()
+    );
+    let line = Line::from(vec![
+        Span::styled(label.clone(), Style::default().fg(Color::Magenta)),
+        Span::raw(prompt.input.as_str()),
+    ]);
+    frame.render_widget(Paragraph::new(line), popup_area);
+
+    let cursor = label.chars().count() + prompt.input.chars().count();
+    frame.set_cursor_position((cursor as u16, popup_area.y));
+}
+
+/// The column checklist as a centered popup, with the cursor row highlighted.
+fn draw_column_chooser(frame: &mut Frame, chooser: &ColumnChooser, table: &DataTable) {
+    let area = frame.area();
+    let name_width = table
+        .schema
+        .columns
+        .iter()
+        .map(|c| render::display_width(&c.name))
+        .max()
+        .unwrap_or(0);
+    let width = (name_width as u16 + 20).clamp(30, area.width);
+    let height = (chooser.columns.len() as u16 + 2).min(area.height.saturating_sub(2));
+    let popup_area = Rect {
+        x: area.width.saturating_sub(width) / 2,
+        y: area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+
+    let visible = height.saturating_sub(2) as usize;
+    let start = (chooser.cursor + 1).saturating_sub(visible);
+    let lines: Vec<Line> = chooser
+        .columns
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(visible)
+        .map(|(pos, &(i, shown))| {
+            let column = &table.schema.columns[i];
+            let text = format!(
+                "[{}] {} {:?}",
+                if shown { "x" } else { " " },
+                render::fit(&column.name, name_width, false),
+                column.data_type
+            );
+            let style = if pos == chooser.cursor {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else if shown {
+                Style::default()
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            Line::from(Span::styled(text, style))
+        })
+        .collect();
+
+    let block = Block::default()
+        .title(" Columns ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(Paragraph::new(lines).block(block), popup_area);
+}
+
+/// The frequency table of a column as a centered popup: each value with its
+/// count and a bar scaled to the most common value.
+fn draw_frequency(frame: &mut Frame, popup: &FrequencyPopup) {
+    let area = frame.area();
+    let width = 60.min(area.width);
+    let height = 20.min(area.height.saturating_sub(2));
+    let popup_area = Rect {
+        x: area.width.saturating_sub(width) / 2,
+        y: area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+    let block = Block::default()
+        .title(format!(" Frequency of {} ", popup.column))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(popup_area);
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(block, popup_area);
+
+    let Some(table) = &popup.result else {
+        let message = match &popup.error {
+            Some(error) => Paragraph::new(error.as_str())
+                .style(Style::default().fg(Color::Red))
+                .wrap(Wrap { trim: true }),
+            None => Paragraph::new("Counting...").style(Style::default().fg(Color::DarkGray)),
+        };
+        frame.render_widget(message, inner);
+        return;
+    };
+
+    let count = |row: &DataRow| match row.values.get(1) {
+        Some(Value::Integer(n)) => *n,
+        _ => 0,
+    };
+    let max = table.rows.iter().map(count).max().unwrap_or(0).max(1);
+    let value_width = (inner.width as usize / 2).max(4);
+    let count_width = table
+        .rows
+        .iter()
+        .map(|row| count(row).to_string().len())
+        .max()
+        .unwrap_or(1);
+    let bar_width = (inner.width as usize).saturating_sub(value_width + count_width + 2);
+
+    let lines: Vec<Line> = table
+        .rows
+        .iter()
+        .skip(popup.scroll)
+        .take(inner.height as usize)
+        .map(|row| {
+            let value = match &row.values[0] {
+                Value::Null => NULL_MARKER.to_string(),
+                value => value.to_string(),
+            };
+            let n = count(row);
+            let bar = "█".repeat((n as usize * bar_width).div_ceil(max as usize));
+            Line::from(vec![
+                Span::raw(render::fit(&value, value_width, false)),
+                Span::styled(
+                    format!(" {:>count_width$} ", n),
+                    Style::default().fg(Color::Cyan),
+                ),
+                Span::styled(bar, Style::default().fg(Color::Blue)),
+            ])
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), inner);
+}
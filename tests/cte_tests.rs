@@ -1,7 +1,6 @@
 use std::path::PathBuf;
 
 use knowhere::datafusion::{DataFusionContext, FileLoader};
-use knowhere::storage::table::Value;
 
 fn load_test_context() -> DataFusionContext {
     let mut loader = FileLoader::new().expect("Failed to create loader");
@@ -50,7 +49,6 @@ fn test_multiple_ctes() {
         SELECT * FROM high_depts
     "#;
     let result = ctx.execute_sql(sql).unwrap();
-    assert!(result.row_count() >= 0);
     assert_eq!(result.column_count(), 2);
 }
 
@@ -126,7 +124,6 @@ fn test_cte_filter_and_transform() {
         ORDER BY salary DESC
     "#;
     let result = ctx.execute_sql(sql).unwrap();
-    assert!(result.row_count() >= 0);
     assert_eq!(result.column_count(), 3);
 }
 
@@ -148,6 +145,5 @@ fn test_nested_cte_references() {
         ORDER BY salary DESC
     "#;
     let result = ctx.execute_sql(sql).unwrap();
-    assert!(result.row_count() >= 0);
     assert_eq!(result.column_count(), 3);
 }
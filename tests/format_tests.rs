@@ -1,16 +1,28 @@
+#[cfg(feature = "iceberg")]
 use std::collections::HashMap;
 use std::fs::File;
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use arrow_array::{Float64Array, Int64Array, StringArray};
+use arrow_array::types::Int32Type;
+use arrow_array::{Array, DictionaryArray, Float64Array, Int64Array, StringArray};
 use arrow_schema::{DataType as ArrowDataType, Field, Schema as ArrowSchema};
+#[cfg(feature = "delta")]
 use deltalake::kernel::{DataType as DeltaDataType, PrimitiveType};
+#[cfg(feature = "delta")]
 use deltalake::DeltaOps;
+#[cfg(feature = "iceberg")]
+use iceberg::io::FileIOBuilder;
+#[cfg(feature = "iceberg")]
 use iceberg::memory::{MemoryCatalogBuilder, MEMORY_CATALOG_WAREHOUSE};
+#[cfg(feature = "iceberg")]
 use iceberg::spec::{
-    NestedField, PrimitiveType as IcebergPrimitive, Schema as IcebergSchema, Type,
+    DataContentType, DataFile, DataFileBuilder, DataFileFormat, ManifestFile, ManifestListWriter,
+    ManifestWriterBuilder, NestedField, Operation, PrimitiveType as IcebergPrimitive,
+    Schema as IcebergSchema, Snapshot, SnapshotReference, SnapshotRetention, Struct, Summary,
+    TableMetadata, Type, MAIN_BRANCH,
 };
+#[cfg(feature = "iceberg")]
 use iceberg::{Catalog, CatalogBuilder, NamespaceIdent, TableCreation};
 use knowhere::datafusion::FileLoader;
 use parquet::arrow::ArrowWriter;
@@ -69,8 +81,7 @@ fn test_load_directory() {
     if samples_dir.exists() && samples_dir.is_dir() {
         let result = loader.load_directory(&samples_dir);
 
-        if result.is_ok() {
-            let tables = result.unwrap();
+        if let Ok(tables) = result {
             assert!(!tables.is_empty());
 
             let ctx = loader.into_context();
@@ -224,8 +235,7 @@ fn test_empty_result_handling() {
         let sql = "SELECT * FROM users WHERE age > 1000";
         let result = ctx.execute_sql(sql);
 
-        if result.is_ok() {
-            let table = result.unwrap();
+        if let Ok(table) = result {
             assert_eq!(table.row_count(), 0);
         }
     }
@@ -489,12 +499,21 @@ fn test_json_unnest_with_filter() {
 
 /// Create a minimal Delta table at `path` with columns (id INT64, name STRING,
 /// department STRING) and three rows, returning only after the write commits.
+#[cfg(feature = "delta")]
 fn create_delta_sample(path: &str) {
+    create_partitioned_delta_sample(path, &[]);
+}
+
+/// Like [`create_delta_sample`], with the data files partitioned by
+/// `partition_columns`.
+#[cfg(feature = "delta")]
+fn create_partitioned_delta_sample(path: &str, partition_columns: &[&str]) {
     let rt = tokio::runtime::Runtime::new().unwrap();
     rt.block_on(async {
         let ops = DeltaOps::try_from_uri(path).await.unwrap();
         let table = ops
             .create()
+            .with_partition_columns(partition_columns.iter().copied())
             .with_column(
                 "id",
                 DeltaDataType::Primitive(PrimitiveType::Long),
@@ -540,6 +559,7 @@ fn create_delta_sample(path: &str) {
     });
 }
 
+#[cfg(feature = "delta")]
 #[test]
 fn test_load_delta_table() {
     let tmp_dir = tempfile::tempdir().unwrap();
@@ -569,6 +589,7 @@ fn test_load_delta_table() {
     assert_eq!(table.column_count(), 3);
 }
 
+#[cfg(feature = "delta")]
 #[test]
 fn test_delta_query_with_filter() {
     let tmp_dir = tempfile::tempdir().unwrap();
@@ -589,6 +610,7 @@ fn test_delta_query_with_filter() {
     assert_eq!(table.row_count(), 2);
 }
 
+#[cfg(feature = "delta")]
 #[test]
 fn test_delta_aggregation() {
     let tmp_dir = tempfile::tempdir().unwrap();
@@ -613,6 +635,145 @@ fn test_delta_aggregation() {
     assert_eq!(table.column_count(), 2);
 }
 
+#[cfg(feature = "delta")]
+#[test]
+fn test_delta_table_layout() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let table_path = tmp_dir.path().join("employees_delta");
+    create_partitioned_delta_sample(table_path.to_str().unwrap(), &["department"]);
+
+    let mut loader = FileLoader::new().expect("Failed to create loader");
+    loader.load_directory(&table_path).unwrap();
+    let ctx = loader.into_context();
+
+    let details = ctx.table_details("employees_delta").unwrap();
+    assert_eq!(details.version, Some(1));
+    assert_eq!(details.file_count, Some(2));
+    assert_eq!(details.row_count, Some(3));
+    assert!(!details.row_count_estimated);
+    assert_eq!(details.partition_columns, vec!["department"]);
+    assert_eq!(
+        details.layout_summary().as_deref(),
+        Some("delta version 1: 2 data files, partitioned by department")
+    );
+}
+
+#[cfg(feature = "delta")]
+#[test]
+fn test_delta_load_filter_prunes_partitions() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let table_path = tmp_dir.path().join("employees_delta");
+    create_partitioned_delta_sample(table_path.to_str().unwrap(), &["department"]);
+
+    let mut loader = FileLoader::new().expect("Failed to create loader");
+    loader.set_load_filter(Some("department = 'Engineering'".to_string()));
+    loader.load_directory(&table_path).unwrap();
+    assert!(loader.load_report().warnings.is_empty());
+    let mut ctx = loader.into_context();
+
+    let names = |ctx: &knowhere::datafusion::DataFusionContext| {
+        let (table, pruning) = ctx
+            .execute_sql_with_pruning("SELECT name FROM employees_delta ORDER BY id")
+            .unwrap();
+        let names: Vec<String> = table
+            .rows
+            .iter()
+            .map(|row| row.values[0].to_string())
+            .collect();
+        (names, pruning.files_scanned)
+    };
+    // Only the Engineering partition's file is opened
+    assert_eq!(
+        names(&ctx),
+        (vec!["Alice".to_string(), "Charlie".to_string()], 1)
+    );
+
+    ctx.refresh_table("employees_delta").unwrap();
+    assert_eq!(names(&ctx).0, ["Alice", "Charlie"]);
+
+    // Filters naming a column the table does not have fail the load
+    let mut loader = FileLoader::new().expect("Failed to create loader");
+    loader.set_load_filter(Some("region = 'EU'".to_string()));
+    assert!(loader.load_directory(&table_path).is_err());
+
+    // Other formats are loaded whole, with a warning
+    let csv_path = tmp_dir.path().join("people.csv");
+    std::fs::write(
+        &csv_path,
+        "name,department\nAlice,Engineering\nBob,Marketing\n",
+    )
+    .unwrap();
+    let mut loader = FileLoader::new().expect("Failed to create loader");
+    loader.set_load_filter(Some("department = 'Engineering'".to_string()));
+    loader.load_file(&csv_path).unwrap();
+    assert_eq!(loader.load_report().warnings.len(), 1);
+    let table = loader
+        .context()
+        .execute_sql("SELECT COUNT(*) FROM people")
+        .unwrap();
+    assert_eq!(table.rows[0].values[0].to_string(), "2");
+}
+
+/// Commit a protocol upgrade to the sample table, as a writer enabling
+/// `reader_features` would.
+#[cfg(feature = "delta")]
+fn upgrade_delta_protocol(table_path: &std::path::Path, protocol: &str) {
+    std::fs::write(
+        table_path.join("_delta_log/00000000000000000002.json"),
+        format!("{{\"protocol\":{}}}\n", protocol),
+    )
+    .unwrap();
+}
+
+#[cfg(feature = "delta")]
+#[test]
+fn test_delta_unsupported_reader_features() {
+    let cases = [
+        (
+            r#"{"minReaderVersion":3,"minWriterVersion":7,"readerFeatures":["deletionVectors"],"writerFeatures":["deletionVectors"]}"#,
+            vec!["deletionVectors"],
+        ),
+        // Reader version 2 implies column mapping
+        (
+            r#"{"minReaderVersion":2,"minWriterVersion":5}"#,
+            vec!["columnMapping"],
+        ),
+    ];
+    for (protocol, expected) in cases {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let table_path = tmp_dir.path().join("employees_delta");
+        create_delta_sample(table_path.to_str().unwrap());
+        upgrade_delta_protocol(&table_path, protocol);
+
+        let mut loader = FileLoader::new().expect("Failed to create loader");
+        let err = loader.load_directory(&table_path).unwrap_err();
+        match &err {
+            knowhere::datafusion::DataFusionError::UnsupportedDeltaFeatures { table, features } => {
+                assert_eq!(table, "employees_delta");
+                assert_eq!(features, &expected);
+            }
+            other => panic!("expected UnsupportedDeltaFeatures, got {other:?}"),
+        }
+        assert!(err.to_string().contains("DROP FEATURE"));
+    }
+
+    // Features the reader implements do not stop the table from loading
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let table_path = tmp_dir.path().join("employees_delta");
+    create_delta_sample(table_path.to_str().unwrap());
+    upgrade_delta_protocol(
+        &table_path,
+        r#"{"minReaderVersion":3,"minWriterVersion":7,"readerFeatures":["timestampNtz"],"writerFeatures":["timestampNtz"]}"#,
+    );
+    let mut loader = FileLoader::new().expect("Failed to create loader");
+    loader.load_directory(&table_path).unwrap();
+    let table = loader
+        .into_context()
+        .execute_sql("SELECT COUNT(*) FROM employees_delta")
+        .unwrap();
+    assert_eq!(table.rows[0].values[0].to_string(), "3");
+}
+
 #[test]
 fn test_delta_detection_requires_delta_log() {
     // A plain directory with no _delta_log should NOT be treated as Delta,
@@ -637,6 +798,7 @@ fn test_delta_detection_requires_delta_log() {
 /// Create a minimal Iceberg table at `table_dir` using MemoryCatalog backed
 /// by the local filesystem. MemoryCatalog writes a real metadata JSON file,
 /// which register_iceberg() can then locate and register with DataFusion.
+#[cfg(feature = "iceberg")]
 fn create_iceberg_sample(table_dir: &std::path::Path) {
     let rt = tokio::runtime::Runtime::new().unwrap();
     rt.block_on(async {
@@ -674,6 +836,7 @@ fn create_iceberg_sample(table_dir: &std::path::Path) {
     });
 }
 
+#[cfg(feature = "iceberg")]
 #[test]
 fn test_load_iceberg_table() {
     let tmp_dir = tempfile::tempdir().unwrap();
@@ -694,6 +857,7 @@ fn test_load_iceberg_table() {
     assert_eq!(tables[0], "employees_iceberg");
 }
 
+#[cfg(feature = "iceberg")]
 #[test]
 fn test_iceberg_schema_inference() {
     let tmp_dir = tempfile::tempdir().unwrap();
@@ -715,6 +879,7 @@ fn test_iceberg_schema_inference() {
     assert_eq!(schema.columns[2].name, "department");
 }
 
+#[cfg(feature = "iceberg")]
 #[test]
 fn test_iceberg_empty_table_query() {
     let tmp_dir = tempfile::tempdir().unwrap();
@@ -736,6 +901,285 @@ fn test_iceberg_empty_table_query() {
     assert_eq!(result.unwrap().row_count(), 0);
 }
 
+#[cfg(feature = "iceberg")]
+#[test]
+fn test_iceberg_table_layout() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let table_path = tmp_dir.path().join("employees_iceberg");
+
+    create_iceberg_sample(&table_path);
+
+    let mut loader = FileLoader::new().expect("Failed to create loader");
+    loader.load_directory(&table_path).unwrap();
+    let ctx = loader.into_context();
+
+    let details = ctx.table_details("employees_iceberg").unwrap();
+    assert_eq!(details.version, None);
+    assert_eq!(details.file_count, Some(0));
+    assert_eq!(details.row_count, Some(0));
+    assert!(details.partition_columns.is_empty());
+    assert_eq!(
+        details.layout_summary().as_deref(),
+        Some("iceberg table without snapshots: 0 data files")
+    );
+    assert_eq!(ctx.table_details("missing"), None);
+}
+
+/// Write `columns` to a Parquet file at `path`, tagging each column with
+/// its Iceberg field id as Iceberg writers do.
+#[cfg(feature = "iceberg")]
+fn write_iceberg_parquet(path: &std::path::Path, columns: Vec<(&str, i32, arrow_array::ArrayRef)>) {
+    let fields: Vec<Field> = columns
+        .iter()
+        .map(|(name, id, array)| {
+            Field::new(*name, array.data_type().clone(), false).with_metadata(HashMap::from([(
+                "PARQUET:field_id".to_string(),
+                id.to_string(),
+            )]))
+        })
+        .collect();
+    let schema = Arc::new(ArrowSchema::new(fields));
+    let arrays = columns.into_iter().map(|(_, _, array)| array).collect();
+    let batch = arrow_array::RecordBatch::try_new(schema.clone(), arrays).unwrap();
+    let mut writer = ArrowWriter::try_new(File::create(path).unwrap(), schema, None).unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+}
+
+#[cfg(feature = "iceberg")]
+fn iceberg_data_file(path: &std::path::Path, content: DataContentType, rows: u64) -> DataFile {
+    let mut builder = DataFileBuilder::default();
+    builder
+        .content(content)
+        .file_path(path.to_str().unwrap().to_string())
+        .file_format(DataFileFormat::Parquet)
+        .partition(Struct::empty())
+        .partition_spec_id(0)
+        .record_count(rows)
+        .file_size_in_bytes(std::fs::metadata(path).unwrap().len());
+    if content == DataContentType::EqualityDeletes {
+        builder.equality_ids(Some(vec![1]));
+    }
+    builder.build().unwrap()
+}
+
+/// Commit merge-on-read snapshots to the empty table made by
+/// [`create_iceberg_sample`], by writing the manifests and metadata an
+/// Iceberg writer would. The first adds ids 1-5 with a position delete file
+/// removing the rows at positions 1 and 3 (ids 2 and 4). With
+/// `equality_delete`, a second adds an equality delete file removing id 5.
+#[cfg(feature = "iceberg")]
+fn add_iceberg_rows_with_deletes(table_dir: &std::path::Path, equality_delete: bool) {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async {
+        let file_io = FileIOBuilder::new_fs_io().build().unwrap();
+        let metadata_dir = table_dir.join("metadata");
+        let current = std::fs::read_dir(&metadata_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path.to_string_lossy().ends_with(".metadata.json"))
+            .unwrap();
+        let current = current.to_str().unwrap().to_string();
+        let metadata = TableMetadata::read_from(&file_io, &current).await.unwrap();
+        let schema = metadata.current_schema().clone();
+        let spec = metadata.default_partition_spec().as_ref().clone();
+
+        let data_dir = table_dir.join("data");
+        std::fs::create_dir_all(&data_dir).unwrap();
+        let data_path = data_dir.join("data-1.parquet");
+        write_iceberg_parquet(
+            &data_path,
+            vec![
+                ("id", 1, Arc::new(Int64Array::from(vec![1, 2, 3, 4, 5]))),
+                (
+                    "name",
+                    2,
+                    Arc::new(StringArray::from(vec!["a", "b", "c", "d", "e"])),
+                ),
+                ("department", 3, Arc::new(StringArray::from(vec!["x"; 5]))),
+            ],
+        );
+        let position_path = data_dir.join("position-deletes-1.parquet");
+        write_iceberg_parquet(
+            &position_path,
+            vec![
+                (
+                    "file_path",
+                    2147483546,
+                    Arc::new(StringArray::from(vec![data_path.to_str().unwrap(); 2])),
+                ),
+                ("pos", 2147483545, Arc::new(Int64Array::from(vec![1, 3]))),
+            ],
+        );
+        let equality_path = data_dir.join("equality-deletes-2.parquet");
+        write_iceberg_parquet(
+            &equality_path,
+            vec![("id", 1, Arc::new(Int64Array::from(vec![5])))],
+        );
+
+        let write_manifest = |name: &str, snapshot_id: i64, file: DataFile| {
+            let output = file_io
+                .new_output(metadata_dir.join(name).to_str().unwrap())
+                .unwrap();
+            let builder = ManifestWriterBuilder::new(
+                output,
+                Some(snapshot_id),
+                None,
+                schema.clone(),
+                spec.clone(),
+            );
+            let mut writer = match file.content_type() {
+                DataContentType::Data => builder.build_v2_data(),
+                _ => builder.build_v2_deletes(),
+            };
+            writer.add_file(file, snapshot_id).unwrap();
+            writer.write_manifest_file()
+        };
+        let data_manifest = write_manifest(
+            "data-1.avro",
+            1,
+            iceberg_data_file(&data_path, DataContentType::Data, 5),
+        )
+        .await
+        .unwrap();
+        let position_manifest = write_manifest(
+            "position-deletes-1.avro",
+            1,
+            iceberg_data_file(&position_path, DataContentType::PositionDeletes, 2),
+        )
+        .await
+        .unwrap();
+        let equality_manifest = write_manifest(
+            "equality-deletes-2.avro",
+            2,
+            iceberg_data_file(&equality_path, DataContentType::EqualityDeletes, 1),
+        )
+        .await
+        .unwrap();
+
+        // Snapshot n has sequence number n and lists its manifests plus
+        // those of earlier snapshots, whose sequence numbers are assigned
+        let mut snapshots = vec![(1, vec![data_manifest, position_manifest])];
+        if equality_delete {
+            snapshots.push((2, vec![equality_manifest]));
+        }
+        let mut builder = metadata.into_builder(Some(current));
+        let mut committed: Vec<ManifestFile> = Vec::new();
+        for (snapshot_id, manifests) in snapshots {
+            let list_path = metadata_dir.join(format!("snap-{}.avro", snapshot_id));
+            let list_path = list_path.to_str().unwrap().to_string();
+            let parent = (snapshot_id > 1).then_some(snapshot_id - 1);
+            let mut list = ManifestListWriter::v2(
+                file_io.new_output(&list_path).unwrap(),
+                snapshot_id,
+                parent,
+                snapshot_id,
+            );
+            list.add_manifests(committed.iter().cloned().chain(manifests.iter().cloned()))
+                .unwrap();
+            list.close().await.unwrap();
+            for mut manifest in manifests {
+                manifest.sequence_number = snapshot_id;
+                manifest.min_sequence_number = snapshot_id;
+                committed.push(manifest);
+            }
+
+            let operation = if snapshot_id == 1 {
+                Operation::Append
+            } else {
+                Operation::Delete
+            };
+            let snapshot = Snapshot::builder()
+                .with_snapshot_id(snapshot_id)
+                .with_parent_snapshot_id(parent)
+                .with_sequence_number(snapshot_id)
+                .with_timestamp_ms(chrono::Utc::now().timestamp_millis())
+                .with_manifest_list(list_path)
+                .with_summary(Summary {
+                    operation,
+                    additional_properties: HashMap::new(),
+                })
+                .with_schema_id(schema.schema_id())
+                .build();
+            builder = builder
+                .add_snapshot(snapshot)
+                .unwrap()
+                .set_ref(
+                    MAIN_BRANCH,
+                    SnapshotReference::new(
+                        snapshot_id,
+                        SnapshotRetention::branch(None, None, None),
+                    ),
+                )
+                .unwrap();
+        }
+        let metadata = builder.build().unwrap().metadata;
+        let next = metadata_dir.join("00001-6d7a3f0e-5b1c-4b8e-9a43-2f0c1d9e7a11.metadata.json");
+        metadata
+            .write_to(&file_io, next.to_str().unwrap())
+            .await
+            .unwrap();
+    });
+}
+
+#[cfg(feature = "iceberg")]
+#[test]
+fn test_iceberg_position_deletes() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let table_path = tmp_dir.path().join("employees_iceberg");
+
+    create_iceberg_sample(&table_path);
+    add_iceberg_rows_with_deletes(&table_path, false);
+
+    let mut loader = FileLoader::new().expect("Failed to create loader");
+    loader.load_directory(&table_path).unwrap();
+    let ctx = loader.into_context();
+
+    let column = |sql: &str| -> Vec<String> {
+        let table = ctx.execute_sql(sql).unwrap();
+        table
+            .rows
+            .iter()
+            .map(|row| row.values[0].to_string())
+            .collect()
+    };
+    assert_eq!(
+        column("SELECT id FROM employees_iceberg ORDER BY id"),
+        ["1", "3", "5"]
+    );
+    assert_eq!(column("SELECT COUNT(*) FROM employees_iceberg"), ["3"]);
+    assert_eq!(
+        column("SELECT name FROM employees_iceberg WHERE id >= 2 ORDER BY id"),
+        ["c", "e"]
+    );
+}
+
+#[cfg(feature = "iceberg")]
+#[test]
+fn test_iceberg_equality_deletes_are_reported() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let table_path = tmp_dir.path().join("employees_iceberg");
+
+    create_iceberg_sample(&table_path);
+    add_iceberg_rows_with_deletes(&table_path, true);
+
+    let mut loader = FileLoader::new().expect("Failed to create loader");
+    loader.load_directory(&table_path).unwrap();
+    let ctx = loader.into_context();
+
+    // Rows are never returned with the deletes silently skipped
+    let err = ctx
+        .execute_sql("SELECT id FROM employees_iceberg")
+        .unwrap_err();
+    assert!(
+        matches!(err, knowhere::datafusion::DataFusionError::Iceberg(_)),
+        "{err:?}"
+    );
+    assert!(err
+        .to_string()
+        .contains("equality-deletes-2.parquet' cannot be applied"));
+}
+
 #[test]
 fn test_iceberg_detection_requires_metadata_dir() {
     // A directory without a metadata/ subdirectory must NOT be detected as
@@ -803,6 +1247,7 @@ fn create_orders_parquet(path: &std::path::Path) {
 }
 
 /// Create a SQLite database with the same orders data.
+#[cfg(feature = "sqlite")]
 fn create_orders_sqlite(path: &std::path::Path) {
     let conn = rusqlite::Connection::open(path).unwrap();
     conn.execute_batch(
@@ -828,6 +1273,61 @@ fn create_orders_sqlite(path: &std::path::Path) {
     .unwrap();
 }
 
+#[cfg(feature = "sqlite")]
+#[test]
+fn test_describe_shows_declared_sqlite_types() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let sqlite_path = tmp_dir.path().join("orders.db");
+    create_orders_sqlite(&sqlite_path);
+
+    let mut loader = FileLoader::new().unwrap();
+    loader.load_file(&sqlite_path).unwrap();
+    let ctx = loader.into_context();
+
+    let schema = ctx.get_table_schema("orders").unwrap();
+    let amount = &schema.columns[schema.column_index("amount").unwrap()];
+    assert_eq!(amount.source_type.as_deref(), Some("REAL"));
+    assert!(!amount.nullable);
+
+    let described = ctx.describe_table("orders").unwrap();
+    let source_type = described.get_column_index("source_type").unwrap();
+    let declared: Vec<String> = described
+        .rows
+        .iter()
+        .map(|row| row.values[source_type].to_string())
+        .collect();
+    assert_eq!(declared, vec!["INTEGER", "INTEGER", "REAL", "TEXT"]);
+}
+
+#[test]
+fn test_parquet_dictionary_column_displays_values() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let path = tmp_dir.path().join("events.parquet");
+    let status: DictionaryArray<Int32Type> = vec!["open", "closed", "open"].into_iter().collect();
+    let schema = Arc::new(ArrowSchema::new(vec![Field::new(
+        "status",
+        status.data_type().clone(),
+        false,
+    )]));
+    let batch = arrow_array::RecordBatch::try_new(schema.clone(), vec![Arc::new(status)]).unwrap();
+    let mut writer = ArrowWriter::try_new(File::create(&path).unwrap(), schema, None).unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+
+    let mut loader = FileLoader::new().expect("Failed to create loader");
+    loader.load_file(&path).unwrap();
+    let ctx = loader.into_context();
+    let table = ctx
+        .execute_sql("SELECT status FROM events ORDER BY status")
+        .unwrap();
+    let values: Vec<String> = table
+        .rows
+        .iter()
+        .map(|row| row.values[0].to_string())
+        .collect();
+    assert_eq!(values, vec!["closed", "open", "open"]);
+}
+
 #[test]
 fn test_cross_format_join_parquet_csv() {
     let tmp_dir = tempfile::tempdir().unwrap();
@@ -909,6 +1409,7 @@ fn test_cross_format_join_json_csv() {
     assert_eq!(table.column_count(), 3);
 }
 
+#[cfg(feature = "sqlite")]
 #[test]
 fn test_cross_format_join_sqlite_csv() {
     let tmp_dir = tempfile::tempdir().unwrap();
@@ -973,3 +1474,122 @@ fn test_three_way_cross_format_join() {
     assert!(table.row_count() > 0);
     assert_eq!(table.column_count(), 4);
 }
+
+#[cfg(feature = "xlsx")]
+fn create_orders_workbook(path: &std::path::Path) {
+    use rust_xlsxwriter::{ExcelDateTime, Format, Workbook};
+
+    let mut workbook = Workbook::new();
+    let summary = workbook.add_worksheet();
+    summary.set_name("Summary").unwrap();
+    summary.write_string(0, 0, "note").unwrap();
+    summary.write_string(1, 0, "see Orders").unwrap();
+
+    let orders = workbook.add_worksheet();
+    orders.set_name("Orders").unwrap();
+    let date = Format::new().set_num_format("yyyy-mm-dd");
+    for (col, header) in ["id", "customer", "amount", "paid", "ordered", ""]
+        .iter()
+        .enumerate()
+    {
+        orders.write_string(0, col as u16, *header).unwrap();
+    }
+    let rows = [
+        (1, "Alice", 19.5, true, 15, "a"),
+        (2, "Bob", 5.0, false, 16, "b"),
+        (3, "Carol", 12.25, true, 17, "c"),
+    ];
+    for (i, (id, customer, amount, paid, day, extra)) in rows.into_iter().enumerate() {
+        let row = i as u32 + 1;
+        orders.write_number(row, 0, id).unwrap();
+        orders.write_string(row, 1, customer).unwrap();
+        orders.write_number(row, 2, amount).unwrap();
+        orders.write_boolean(row, 3, paid).unwrap();
+        let ordered = ExcelDateTime::from_ymd(2024, 3, day).unwrap();
+        orders
+            .write_datetime_with_format(row, 4, &ordered, &date)
+            .unwrap();
+        orders.write_string(row, 5, extra).unwrap();
+    }
+    workbook.save(path).unwrap();
+}
+
+#[cfg(feature = "xlsx")]
+#[test]
+fn test_load_xlsx_first_sheet() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("orders.xlsx");
+    create_orders_workbook(&path);
+
+    let mut loader = FileLoader::new().unwrap();
+    assert_eq!(loader.load_file(&path).unwrap(), vec!["orders"]);
+    assert_eq!(loader.load_report().loaded[0].format, "xlsx");
+
+    let ctx = loader.into_context();
+    let result = ctx.execute_sql("SELECT note FROM orders").unwrap();
+    assert_eq!(
+        result.rows[0].values[0],
+        knowhere::Value::String("see Orders".into())
+    );
+}
+
+#[cfg(feature = "xlsx")]
+#[test]
+fn test_load_xlsx_sheet_by_name_infers_types() {
+    use knowhere::datafusion::{Sheet, XlsxReader};
+    use knowhere::DataType;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("orders.xlsx");
+    create_orders_workbook(&path);
+
+    let mut loader = FileLoader::new().unwrap();
+    loader.register_reader(Box::new(XlsxReader::with_sheet(Sheet::Name(
+        "Orders".into(),
+    ))));
+    loader.load_file(&path).unwrap();
+    let mut ctx = loader.into_context();
+
+    let schema = ctx.get_table_schema("orders").unwrap();
+    let types: Vec<(&str, &DataType)> = schema
+        .columns
+        .iter()
+        .map(|column| (column.name.as_str(), &column.data_type))
+        .collect();
+    assert_eq!(
+        types,
+        vec![
+            ("id", &DataType::Integer),
+            ("customer", &DataType::String),
+            ("amount", &DataType::Float),
+            ("paid", &DataType::Boolean),
+            ("ordered", &DataType::Date),
+            ("column_6", &DataType::String),
+        ]
+    );
+
+    let result = ctx
+        .execute_sql("SELECT SUM(amount) FROM orders WHERE paid AND ordered >= DATE '2024-03-16'")
+        .unwrap();
+    assert_eq!(result.rows[0].values[0], knowhere::Value::Float(12.25));
+
+    // A refresh re-reads the same sheet rather than the first one
+    ctx.refresh_table("orders").unwrap();
+    assert_eq!(ctx.get_table_schema("orders").unwrap().columns.len(), 6);
+}
+
+#[cfg(feature = "xlsx")]
+#[test]
+fn test_load_xlsx_missing_sheet() {
+    use knowhere::datafusion::{Sheet, XlsxReader};
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("orders.xlsx");
+    create_orders_workbook(&path);
+
+    let mut loader = FileLoader::new().unwrap();
+    loader.register_reader(Box::new(XlsxReader::with_sheet(Sheet::Index(5))));
+    let err = loader.load_file(&path).unwrap_err().to_string();
+    assert!(err.contains("sheet 6"), "{}", err);
+    assert!(err.contains("Summary, Orders"), "{}", err);
+}
@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use knowhere::datafusion::{DataFusionContext, FileLoader};
+use knowhere::datafusion::{DataFusionContext, DataFusionError, FileLoader};
 use knowhere::storage::table::Value;
 
 fn load_test_context() -> DataFusionContext {
@@ -212,6 +212,30 @@ fn test_group_by_having() {
     assert_eq!(result.row_count(), 2);
 }
 
+#[test]
+fn test_having_on_aggregate_alias() {
+    let ctx = load_test_context();
+    let by_alias = ctx
+        .execute_sql(
+            "SELECT department, COUNT(*) AS n FROM users GROUP BY department \
+             HAVING n > 2 ORDER BY department",
+        )
+        .unwrap();
+    let restated = ctx
+        .execute_sql(
+            "SELECT department, COUNT(*) AS n FROM users GROUP BY department \
+             HAVING count(*) > 2 ORDER BY department",
+        )
+        .unwrap();
+
+    let values = |t: &knowhere::Table| -> Vec<Vec<Value>> {
+        t.rows.iter().map(|r| r.values.clone()).collect()
+    };
+    assert_eq!(values(&by_alias), values(&restated));
+    assert_eq!(by_alias.row_count(), 2);
+    assert_eq!(by_alias.schema.column_names(), vec!["department", "n"]);
+}
+
 #[test]
 fn test_join() {
     let ctx = load_test_context();
@@ -310,6 +334,21 @@ fn test_is_not_null() {
     assert_eq!(result.row_count(), 10);
 }
 
+#[test]
+fn test_non_boolean_predicates_are_rejected() {
+    let ctx = load_test_context();
+    for sql in [
+        "SELECT * FROM users WHERE name",
+        "SELECT * FROM users WHERE age AND active",
+        "SELECT * FROM users WHERE active OR department",
+    ] {
+        assert!(ctx.execute_sql(sql).is_err(), "{} should fail", sql);
+    }
+    assert!(ctx
+        .execute_sql("SELECT * FROM users WHERE active AND age > 30")
+        .is_ok());
+}
+
 #[test]
 fn test_column_alias() {
     let ctx = load_test_context();
@@ -347,3 +386,197 @@ fn test_complex_query() {
     assert!(result.row_count() <= 5);
     assert_eq!(result.column_count(), 3);
 }
+
+#[test]
+fn test_missing_column_suggests_closest_names() {
+    let ctx = load_test_context();
+    let err = ctx.execute_sql("SELECT nmae FROM users").unwrap_err();
+
+    match &err {
+        DataFusionError::ColumnNotFound {
+            suggestions,
+            available,
+            ..
+        } => {
+            assert_eq!(suggestions, &vec!["name".to_string()]);
+            assert!(available.contains(&"users.salary".to_string()));
+        }
+        other => panic!("expected ColumnNotFound, got {:?}", other),
+    }
+    assert!(err.to_string().contains("Did you mean name?"));
+}
+
+#[test]
+fn test_select_alias_in_where_is_explained() {
+    let ctx = load_test_context();
+    let err = ctx
+        .execute_sql("SELECT salary * 2 AS doubled FROM users WHERE doubled > 1")
+        .unwrap_err();
+
+    assert!(matches!(
+        err,
+        DataFusionError::ColumnNotFound { alias: true, .. }
+    ));
+    assert!(err.to_string().contains("SELECT alias"));
+}
+
+#[test]
+fn test_other_dialect_syntax_is_translated() {
+    let ctx = load_test_context();
+    let sql = "SELECT TOP 3 `name` FROM users ORDER BY id";
+    let result = ctx.execute_sql(sql).unwrap();
+
+    assert_eq!(result.row_count(), 3);
+    assert_eq!(ctx.dialect_notes(sql).len(), 2);
+    assert!(ctx.dialect_notes("SELECT name FROM users").is_empty());
+}
+
+#[test]
+fn test_strict_sql_rejects_other_dialect_syntax() {
+    let mut ctx = load_test_context();
+    ctx.set_strict_sql(true);
+
+    let err = ctx
+        .execute_sql("SELECT name FROM users LIMIT 2, 3")
+        .unwrap_err();
+    assert!(matches!(err, DataFusionError::ForeignSyntax(_)));
+    assert!(ctx
+        .execute_sql("SELECT name FROM users LIMIT 3 OFFSET 2")
+        .is_ok());
+}
+
+#[test]
+fn test_histogram_template() {
+    let ctx = load_test_context();
+    let result = ctx
+        .execute_sql("SELECT * FROM histogram(users, age, 4) ORDER BY bucket")
+        .unwrap();
+
+    assert_eq!(result.row_count(), 4);
+    let counts: i64 = result
+        .rows
+        .iter()
+        .map(|row| match row.values[3] {
+            Value::Integer(n) => n,
+            _ => panic!("count should be an integer"),
+        })
+        .sum();
+    assert_eq!(counts, 10);
+    let min = ctx.execute_sql("SELECT MIN(age) FROM users").unwrap();
+    assert_eq!(
+        result.rows[0].values[1],
+        match min.rows[0].values[0] {
+            Value::Integer(n) => Value::Float(n as f64),
+            ref other => other.clone(),
+        }
+    );
+}
+
+#[test]
+fn test_summarize_and_corr() {
+    let ctx = load_test_context();
+    let summary = ctx.execute_sql("SUMMARIZE users").unwrap();
+
+    assert_eq!(summary.row_count(), 7);
+    let names: Vec<&str> = summary
+        .schema
+        .columns
+        .iter()
+        .map(|c| c.name.as_str())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "column_name",
+            "data_type",
+            "count",
+            "null_count",
+            "mean",
+            "std",
+            "min",
+            "p25",
+            "p50",
+            "p75",
+            "max"
+        ]
+    );
+    let age = summary
+        .rows
+        .iter()
+        .find(|row| row.values[0] == Value::String("age".to_string()))
+        .unwrap();
+    assert_eq!(age.values[2], Value::Integer(10));
+    assert_eq!(age.values[3], Value::Integer(0));
+    assert_eq!(age.values[4], Value::Float(36.9));
+    assert_eq!(age.values[6], Value::String("24".to_string()));
+    assert_eq!(age.values[10], Value::String("55".to_string()));
+    let name = &summary.rows[1];
+    assert_eq!(name.values[4], Value::Null);
+    assert_eq!(name.values[6], Value::String("Alice Johnson".to_string()));
+
+    let query = ctx
+        .execute_sql("SUMMARIZE SELECT salary FROM users WHERE age > 40")
+        .unwrap();
+    assert_eq!(query.row_count(), 1);
+    assert_eq!(query.rows[0].values[2], Value::Integer(4));
+
+    let corr = ctx
+        .execute_sql("SELECT CORR(age, salary) > 0.5 FROM users")
+        .unwrap();
+    assert_eq!(corr.rows[0].values[0], Value::Boolean(true));
+}
+
+#[test]
+fn test_generated_dataset() {
+    use knowhere::datafusion::{generate_dataset, DatasetSpec, ExportFormat};
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("events.parquet");
+    let spec = DatasetSpec::parse(
+        "id:int:seq,category:string:skewed,amount:float:normal,flag:bool,ts:timestamp:seq",
+    )
+    .unwrap();
+    generate_dataset(&spec, 20_000, 7, &path, ExportFormat::Parquet).unwrap();
+
+    let mut loader = FileLoader::new().unwrap();
+    loader.load_file(&path).unwrap();
+    let ctx = loader.into_context();
+    let result = ctx
+        .execute_sql(
+            "SELECT COUNT(*), COUNT(DISTINCT id), MIN(id), MAX(id), \
+             MIN(ts) < MAX(ts) FROM events",
+        )
+        .unwrap();
+    assert_eq!(
+        result.rows[0].values,
+        vec![
+            Value::Integer(20_000),
+            Value::Integer(20_000),
+            Value::Integer(1),
+            Value::Integer(20_000),
+            Value::Boolean(true),
+        ]
+    );
+
+    // Skewed categories make the first word the most common
+    let top = ctx
+        .execute_sql(
+            "SELECT category, COUNT(*) AS n FROM events GROUP BY category ORDER BY n DESC LIMIT 1",
+        )
+        .unwrap();
+    assert_eq!(top.rows[0].values[0], Value::String("alpha".into()));
+
+    let mean = ctx.execute_sql("SELECT AVG(amount) FROM events").unwrap();
+    let Value::Float(mean) = mean.rows[0].values[0] else {
+        panic!("expected a float mean");
+    };
+    assert!((mean - 100.0).abs() < 1.0, "mean {}", mean);
+
+    // The same seed reproduces the same file
+    let again = dir.path().join("again.parquet");
+    generate_dataset(&spec, 20_000, 7, &again, ExportFormat::Parquet).unwrap();
+    assert_eq!(
+        std::fs::read(&path).unwrap(),
+        std::fs::read(&again).unwrap()
+    );
+}
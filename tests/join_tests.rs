@@ -0,0 +1,261 @@
+use std::path::PathBuf;
+
+use knowhere::datafusion::{DataFusionContext, FileLoader};
+use knowhere::storage::table::Value;
+
+fn load_test_context() -> DataFusionContext {
+    let mut loader = FileLoader::new().expect("Failed to create loader");
+    let samples_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("samples");
+
+    loader
+        .load_file(&samples_dir.join("users.csv"))
+        .expect("Failed to load users.csv");
+
+    loader
+        .load_file(&samples_dir.join("orders.csv"))
+        .expect("Failed to load orders.csv");
+
+    loader
+        .load_file(&samples_dir.join("products.csv"))
+        .expect("Failed to load products.csv");
+
+    loader
+        .load_file(&samples_dir.join("departments.json"))
+        .expect("Failed to load departments.json");
+
+    loader.into_context()
+}
+
+#[test]
+fn test_three_table_join() {
+    let ctx = load_test_context();
+    let result = ctx
+        .execute_sql(
+            "SELECT COUNT(*) FROM orders o \
+             JOIN users u ON u.id = o.user_id \
+             JOIN products p ON p.id = o.product_id",
+        )
+        .unwrap();
+
+    assert_eq!(result.rows[0].values[0], Value::Integer(12));
+}
+
+#[test]
+fn test_three_table_join_keeps_alias_scoping() {
+    // users, orders and products all have an `id` column, and users and
+    // products both have `name`; every reference must resolve by alias
+    let ctx = load_test_context();
+    let result = ctx
+        .execute_sql(
+            "SELECT u.name, p.name AS product, o.quantity \
+             FROM users u \
+             JOIN orders o ON u.id = o.user_id \
+             JOIN products p ON p.id = o.product_id \
+             WHERE o.id = 1",
+        )
+        .unwrap();
+
+    assert_eq!(result.row_count(), 1);
+    assert_eq!(
+        result.schema.column_names(),
+        vec!["name", "product", "quantity"]
+    );
+    assert_eq!(
+        result.rows[0].values,
+        vec![
+            Value::String("Alice Johnson".to_string()),
+            Value::String("Wireless Mouse".to_string()),
+            Value::Integer(2),
+        ]
+    );
+}
+
+#[test]
+fn test_four_table_join_with_aggregation() {
+    let ctx = load_test_context();
+    let result = ctx
+        .execute_sql(
+            "SELECT u.department, d.budget, COUNT(o.id) AS orders \
+             FROM users u \
+             JOIN orders o ON u.id = o.user_id \
+             JOIN products p ON p.id = o.product_id \
+             JOIN departments d ON d.department = u.department \
+             GROUP BY u.department, d.budget \
+             ORDER BY u.department",
+        )
+        .unwrap();
+
+    assert_eq!(result.row_count(), 3);
+    let orders: Vec<&Value> = result.rows.iter().map(|r| &r.values[2]).collect();
+    assert_eq!(
+        orders,
+        vec![&Value::Integer(8), &Value::Integer(3), &Value::Integer(1)]
+    );
+}
+
+#[test]
+fn test_chained_left_joins() {
+    let ctx = load_test_context();
+    let result = ctx
+        .execute_sql(
+            "SELECT u.name \
+             FROM users u \
+             LEFT JOIN orders o ON u.id = o.user_id \
+             LEFT JOIN products p ON p.id = o.product_id \
+             WHERE o.id IS NULL \
+             ORDER BY u.name",
+        )
+        .unwrap();
+
+    let names: Vec<String> = result
+        .rows
+        .iter()
+        .map(|r| r.values[0].to_string())
+        .collect();
+    assert_eq!(names, vec!["Diana Ross", "Fiona Apple", "Ivan Petrov"]);
+}
+
+#[test]
+fn test_unqualified_ambiguous_column_is_rejected() {
+    let ctx = load_test_context();
+    let result = ctx.execute_sql(
+        "SELECT name FROM users u \
+         JOIN orders o ON u.id = o.user_id \
+         JOIN products p ON p.id = o.product_id",
+    );
+
+    assert!(
+        result.is_err(),
+        "Ambiguous `name` should not resolve silently"
+    );
+}
+
+#[test]
+fn test_self_join_with_aliases() {
+    let ctx = load_test_context();
+    let result = ctx
+        .execute_sql(
+            "SELECT a.name, b.name AS colleague \
+             FROM users a \
+             JOIN users b ON a.department = b.department AND a.id < b.id",
+        )
+        .unwrap();
+
+    // Engineering has 5 users, Marketing 3 and Sales 2: 10 + 3 + 1 pairs
+    assert_eq!(result.row_count(), 14);
+    assert_eq!(result.schema.column_names(), vec!["name", "colleague"]);
+}
+
+#[test]
+fn test_self_join_select_star_keeps_both_sides() {
+    let ctx = load_test_context();
+    let result = ctx
+        .execute_sql("SELECT * FROM users a JOIN users b ON a.id = b.id")
+        .unwrap();
+
+    assert_eq!(result.row_count(), 10);
+    assert_eq!(result.column_count(), 14);
+}
+
+#[test]
+fn test_qualified_wildcard_expands_only_that_table() {
+    let ctx = load_test_context();
+    let result = ctx
+        .execute_sql(
+            "SELECT u.*, o.price FROM users u \
+             JOIN orders o ON u.id = o.user_id \
+             ORDER BY o.id LIMIT 1",
+        )
+        .unwrap();
+
+    assert_eq!(
+        result.schema.column_names(),
+        vec![
+            "id",
+            "name",
+            "email",
+            "age",
+            "department",
+            "salary",
+            "active",
+            "price"
+        ]
+    );
+    assert_eq!(
+        result.rows[0].values[1],
+        Value::String("Alice Johnson".to_string())
+    );
+}
+
+#[test]
+fn test_qualified_wildcard_by_table_name_and_order() {
+    let ctx = load_test_context();
+    let result = ctx
+        .execute_sql(
+            "SELECT orders.*, users.name FROM users \
+             JOIN orders ON users.id = orders.user_id \
+             ORDER BY orders.id LIMIT 1",
+        )
+        .unwrap();
+
+    assert_eq!(
+        result.schema.column_names(),
+        vec![
+            "id",
+            "user_id",
+            "product_id",
+            "quantity",
+            "price",
+            "order_date",
+            "status",
+            "name"
+        ]
+    );
+}
+
+#[test]
+fn test_qualified_wildcard_with_unknown_qualifier_is_rejected() {
+    let ctx = load_test_context();
+    let result = ctx.execute_sql("SELECT x.* FROM users u");
+
+    assert!(result.is_err(), "`x.*` should not match any table");
+}
+
+#[test]
+fn test_self_join_manager_hierarchy() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let path = tmp_dir.path().join("employees.csv");
+    std::fs::write(
+        &path,
+        "id,name,manager_id\n1,Ada,\n2,Grace,1\n3,Linus,1\n4,Ken,2\n",
+    )
+    .unwrap();
+
+    let mut loader = FileLoader::new().expect("Failed to create loader");
+    loader.load_file(&path).unwrap();
+    let ctx = loader.into_context();
+
+    let result = ctx
+        .execute_sql(
+            "SELECT e.name, m.name AS manager \
+             FROM employees e \
+             LEFT JOIN employees m ON e.manager_id = m.id \
+             ORDER BY e.id",
+        )
+        .unwrap();
+
+    let pairs: Vec<(String, String)> = result
+        .rows
+        .iter()
+        .map(|r| (r.values[0].to_string(), r.values[1].to_string()))
+        .collect();
+    assert_eq!(
+        pairs,
+        vec![
+            ("Ada".to_string(), "NULL".to_string()),
+            ("Grace".to_string(), "Ada".to_string()),
+            ("Linus".to_string(), "Ada".to_string()),
+            ("Ken".to_string(), "Grace".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,10 @@
+-- CASE, COALESCE and NULL handling
+SELECT name,
+       CASE WHEN age < 30 THEN 'junior'
+            WHEN age < 45 THEN 'mid'
+            ELSE 'senior' END AS band,
+       COALESCE(NULLIF(department, 'Sales'), 'n/a') AS dept,
+       NULL IS NULL AS null_check
+FROM users
+ORDER BY id
+LIMIT 5
@@ -0,0 +1,15 @@
+-- Chained common table expressions
+WITH spend AS (
+    SELECT user_id, SUM(quantity * price) AS total
+    FROM orders
+    GROUP BY user_id
+),
+ranked AS (
+    SELECT user_id, total, RANK() OVER (ORDER BY total DESC) AS spend_rank
+    FROM spend
+)
+SELECT u.name, ROUND(r.total, 2) AS total, r.spend_rank
+FROM ranked r
+JOIN users u ON u.id = r.user_id
+WHERE r.spend_rank <= 3
+ORDER BY r.spend_rank, u.name
@@ -0,0 +1,11 @@
+-- Derived tables in FROM, joined to a table and filtered with NOT IN
+SELECT u.name, t.order_count, t.total
+FROM (
+    SELECT user_id, COUNT(*) AS order_count, ROUND(SUM(quantity * price), 2) AS total
+    FROM orders
+    GROUP BY user_id
+) AS t
+JOIN users u ON u.id = t.user_id
+WHERE t.user_id NOT IN (SELECT id FROM users WHERE department = 'Sales')
+  AND t.order_count > (SELECT MIN(c) FROM (SELECT COUNT(*) AS c FROM orders GROUP BY user_id) AS counts)
+ORDER BY t.total DESC, u.name
@@ -0,0 +1,2 @@
+-- Column names, types and nullability
+DESCRIBE products
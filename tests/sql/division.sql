@@ -0,0 +1,6 @@
+-- Integer division, DIV and SAFE_DIVIDE
+SELECT 7 / 2 AS int_div,
+       7 / 2.0 AS float_div,
+       div(-7, 2) AS div_fn,
+       safe_divide(1, 0) AS safe_zero,
+       safe_divide(9, 4) AS safe_div
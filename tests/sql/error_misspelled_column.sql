@@ -0,0 +1,2 @@
+-- Misspelled columns come with suggestions
+SELECT nmae FROM users
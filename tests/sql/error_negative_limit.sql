@@ -0,0 +1,2 @@
+-- A negative LIMIT is rejected rather than treated as no limit
+SELECT * FROM users LIMIT -1
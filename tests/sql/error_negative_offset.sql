@@ -0,0 +1,2 @@
+-- A negative OFFSET is rejected too, even when computed
+SELECT * FROM users ORDER BY id OFFSET 1 - 3
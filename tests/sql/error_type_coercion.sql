@@ -0,0 +1,2 @@
+-- A string that is not a number cannot be compared with a number
+SELECT * FROM users WHERE age > 'abc'
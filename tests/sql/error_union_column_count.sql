@@ -0,0 +1,2 @@
+-- Both sides of a UNION must select the same number of columns
+SELECT id, name FROM users UNION SELECT department FROM departments
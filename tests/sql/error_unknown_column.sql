@@ -0,0 +1,2 @@
+-- Expected failures are golden-tested too
+SELECT salary_band FROM users
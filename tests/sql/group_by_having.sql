@@ -0,0 +1,10 @@
+-- Aggregates per group with a HAVING filter
+SELECT department,
+       COUNT(*) AS employees,
+       MIN(salary) AS min_salary,
+       MAX(salary) AS max_salary,
+       ROUND(AVG(age), 2) AS avg_age
+FROM users
+GROUP BY department
+HAVING COUNT(*) > 1
+ORDER BY department
@@ -0,0 +1,12 @@
+-- HAVING may use SELECT aliases, re-stated aggregates and aggregates that
+-- are not selected, in any mix
+SELECT department AS dept,
+       COUNT(*) AS employees,
+       SUM(salary) AS payroll
+FROM users
+GROUP BY dept
+HAVING employees >= 2
+   AND count(*) < 5
+   AND MAX(age) > 30
+   AND payroll > 100000
+ORDER BY payroll DESC
@@ -0,0 +1,3 @@
+-- INTERSECT of columns with matching types still runs
+WITH u AS (SELECT id FROM users WHERE id <= 3)
+SELECT id FROM users INTERSECT SELECT id FROM u ORDER BY id
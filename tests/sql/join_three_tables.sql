@@ -0,0 +1,7 @@
+-- Three-way join with aliases over tables that share column names
+SELECT o.id AS order_id, u.name AS customer, p.name AS product, o.quantity
+FROM orders o
+JOIN users u ON u.id = o.user_id
+JOIN products p ON p.id = o.product_id
+WHERE o.status = 'completed'
+ORDER BY o.id
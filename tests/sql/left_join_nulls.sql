@@ -0,0 +1,6 @@
+-- Users without orders surface as NULLs on the right side
+SELECT u.name, COUNT(o.id) AS orders, SUM(o.quantity) AS items
+FROM users u
+LEFT JOIN orders o ON u.id = o.user_id
+GROUP BY u.name
+ORDER BY orders DESC, u.name
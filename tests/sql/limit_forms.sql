@@ -0,0 +1,6 @@
+-- LIMIT accepts constant expressions and ALL; FETCH FIRST is planned as LIMIT
+SELECT
+    (SELECT COUNT(*) FROM (SELECT id FROM users LIMIT 1 + 2) AS a) AS limit_expr,
+    (SELECT COUNT(*) FROM (SELECT id FROM users LIMIT ALL) AS b) AS limit_all,
+    (SELECT COUNT(*) FROM (SELECT id FROM users FETCH FIRST 4 ROWS ONLY) AS c) AS fetch_first,
+    (SELECT MIN(id) FROM (SELECT id FROM users ORDER BY id OFFSET 2 ROWS FETCH NEXT ROW ONLY) AS d) AS offset_fetch
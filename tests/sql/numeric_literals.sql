@@ -0,0 +1,7 @@
+-- Exponents and digit separators in numeric literals
+SELECT
+    1e6 AS million,
+    1.5E-3 AS small,
+    1_000_000 AS separated,
+    0.000_1 AS fraction,
+    (SELECT COUNT(*) FROM users WHERE salary >= 100_000) AS high_earners
@@ -0,0 +1,5 @@
+-- Projection, filtering and ordering on a single table
+SELECT id, name, age
+FROM users
+WHERE active = true AND age >= 30
+ORDER BY age DESC, id
@@ -0,0 +1,18 @@
+-- UNION removes duplicates, UNION ALL keeps them, INTERSECT keeps common
+-- rows, EXCEPT subtracts
+SELECT 'union' AS op, COUNT(*) AS n FROM (
+    SELECT department FROM users UNION SELECT department FROM departments
+) t
+UNION ALL
+SELECT 'union all', COUNT(*) FROM (
+    SELECT department FROM users UNION ALL SELECT department FROM departments
+) t
+UNION ALL
+SELECT 'intersect', COUNT(*) FROM (
+    SELECT department FROM users INTERSECT SELECT department FROM departments
+) t
+UNION ALL
+SELECT 'except', COUNT(*) FROM (
+    SELECT id FROM users EXCEPT SELECT user_id FROM orders
+) t
+ORDER BY op
@@ -0,0 +1,2 @@
+-- The same as DESCRIBE
+SHOW COLUMNS FROM orders
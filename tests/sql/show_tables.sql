@@ -0,0 +1,2 @@
+-- Loaded tables, without the information_schema views
+SHOW TABLES
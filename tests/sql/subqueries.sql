@@ -0,0 +1,7 @@
+-- Scalar, IN and EXISTS subqueries
+SELECT name, salary
+FROM users u
+WHERE salary > (SELECT AVG(salary) FROM users)
+  AND id IN (SELECT user_id FROM orders)
+  AND EXISTS (SELECT 1 FROM orders o WHERE o.user_id = u.id AND o.status = 'completed')
+ORDER BY salary DESC
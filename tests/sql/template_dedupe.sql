@@ -0,0 +1,4 @@
+-- Latest order per user
+SELECT user_id, id, order_date
+FROM dedupe(orders, user_id, order_date)
+ORDER BY user_id
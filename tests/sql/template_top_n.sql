@@ -0,0 +1,4 @@
+-- Two best-paid users per department
+SELECT department, name, salary
+FROM top_n(users, department, salary, 2)
+ORDER BY department, salary DESC
@@ -0,0 +1,10 @@
+-- String literals compared with numbers and booleans take the column's type
+SELECT
+    (SELECT COUNT(*) FROM users WHERE salary = '95000') AS float_eq,
+    (SELECT COUNT(*) FROM users WHERE age > '40') AS int_gt,
+    (SELECT COUNT(*) FROM users WHERE age > '100') AS int_not_text,
+    (SELECT COUNT(*) FROM users WHERE age IN ('32', '45')) AS int_in,
+    (SELECT COUNT(*) FROM users WHERE age BETWEEN '30' AND '40') AS int_between,
+    (SELECT COUNT(*) FROM users WHERE active = 'yes') AS bool_text,
+    (SELECT COUNT(*) FROM users WHERE active = 0) AS bool_int,
+    (SELECT COUNT(*) FROM users WHERE salary > 90000) AS float_int
@@ -0,0 +1,2 @@
+-- DataFusion combines a number and text in a UNION as text
+SELECT 1 AS v UNION ALL SELECT 'a' ORDER BY v
@@ -0,0 +1,8 @@
+-- Ranking and running totals within partitions
+SELECT department,
+       name,
+       salary,
+       ROW_NUMBER() OVER (PARTITION BY department ORDER BY salary DESC) AS row_num,
+       SUM(salary) OVER (PARTITION BY department ORDER BY salary DESC) AS running_total
+FROM users
+ORDER BY department, row_num
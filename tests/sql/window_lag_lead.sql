@@ -0,0 +1,10 @@
+-- Ties share a rank; LAG/LEAD look at neighbouring rows within each partition
+SELECT user_id,
+       id,
+       quantity,
+       RANK() OVER (PARTITION BY user_id ORDER BY quantity DESC) AS qty_rank,
+       LAG(order_date) OVER (PARTITION BY user_id ORDER BY order_date, id) AS previous_order,
+       LEAD(quantity, 1, 0) OVER (PARTITION BY user_id ORDER BY order_date, id) AS next_quantity
+FROM orders
+WHERE user_id <= 4
+ORDER BY user_id, order_date, id
@@ -0,0 +1,113 @@
+//! Golden-file SQL conformance tests.
+//!
+//! Every `tests/sql/<name>.sql` file is run against the sample data and its
+//! rendered result is compared with `tests/sql/<name>.expected`. Queries must
+//! be deterministic, so use ORDER BY wherever row order matters.
+//!
+//! To add a case, write the `.sql` file and run
+//! `KNOWHERE_BLESS=1 cargo test --test sql_golden_tests`, then review the
+//! generated `.expected` file before committing it.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use knowhere::datafusion::{DataFusionContext, FileLoader};
+use knowhere::render::{render_to_string, TableRenderer};
+
+fn load_test_context() -> DataFusionContext {
+    let mut loader = FileLoader::new().expect("Failed to create loader");
+    let samples_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("samples");
+
+    for file in [
+        "users.csv",
+        "orders.csv",
+        "products.csv",
+        "departments.json",
+    ] {
+        loader
+            .load_file(&samples_dir.join(file))
+            .unwrap_or_else(|e| panic!("Failed to load {}: {}", file, e));
+    }
+
+    loader.into_context()
+}
+
+fn sql_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("sql")
+}
+
+fn sql_files() -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(sql_dir())
+        .expect("Failed to read tests/sql")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "sql"))
+        .collect();
+    files.sort();
+    files
+}
+
+/// Render the outcome of `sql` the way it is stored in `.expected` files.
+/// Errors are recorded too, so expected failures can be golden-tested.
+fn run_case(ctx: &DataFusionContext, sql: &str) -> String {
+    match ctx.execute_sql(sql) {
+        Ok(table) => render_to_string(&mut TableRenderer::new(usize::MAX), &table)
+            .expect("Rendering to a buffer cannot fail"),
+        Err(e) => format!("ERROR: {}\n", e),
+    }
+}
+
+/// Ignore trailing whitespace so editors that strip it don't break the goldens.
+fn normalize(output: &str) -> String {
+    let mut normalized: Vec<&str> = output.lines().map(str::trim_end).collect();
+    normalized.push("");
+    normalized.join("\n")
+}
+
+fn expected_path(sql_path: &Path) -> PathBuf {
+    sql_path.with_extension("expected")
+}
+
+#[test]
+fn test_sql_golden_files() {
+    let ctx = load_test_context();
+    let bless = std::env::var_os("KNOWHERE_BLESS").is_some();
+    let files = sql_files();
+    assert!(!files.is_empty(), "No .sql files found in tests/sql");
+
+    let mut failures = Vec::new();
+    for sql_path in &files {
+        let sql = fs::read_to_string(sql_path).unwrap();
+        let actual = normalize(&run_case(&ctx, &sql));
+        let expected_path = expected_path(sql_path);
+
+        if bless {
+            fs::write(&expected_path, &actual).unwrap();
+            continue;
+        }
+
+        match fs::read_to_string(&expected_path) {
+            Ok(expected) if normalize(&expected) == actual => {}
+            Ok(expected) => failures.push(format!(
+                "{}\n--- expected\n{}--- actual\n{}",
+                sql_path.display(),
+                expected,
+                actual
+            )),
+            Err(_) => failures.push(format!(
+                "{}: missing {} (run with KNOWHERE_BLESS=1 to create it)",
+                sql_path.display(),
+                expected_path.display()
+            )),
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} of {} SQL golden files differ:\n\n{}",
+        failures.len(),
+        files.len(),
+        failures.join("\n")
+    );
+}
@@ -1,7 +1,6 @@
 use std::path::PathBuf;
 
 use knowhere::datafusion::{DataFusionContext, FileLoader};
-use knowhere::storage::table::Value;
 
 fn load_test_context() -> DataFusionContext {
     let mut loader = FileLoader::new().expect("Failed to create loader");
@@ -64,7 +63,6 @@ fn test_correlated_subquery() {
         ORDER BY u.salary DESC
     "#;
     let result = ctx.execute_sql(sql).unwrap();
-    assert!(result.row_count() >= 0);
     assert_eq!(result.column_count(), 3);
 }
 
@@ -96,7 +94,6 @@ fn test_not_exists_subquery() {
         ORDER BY u.name
     "#;
     let result = ctx.execute_sql(sql).unwrap();
-    assert!(result.row_count() >= 0);
     assert_eq!(result.column_count(), 1);
 }
 
@@ -128,7 +125,6 @@ fn test_not_in_subquery() {
         ORDER BY name
     "#;
     let result = ctx.execute_sql(sql).unwrap();
-    assert!(result.row_count() >= 0);
     assert_eq!(result.column_count(), 1);
 }
 
@@ -146,7 +142,6 @@ fn test_subquery_in_from() {
         ORDER BY avg_sal DESC
     "#;
     let result = ctx.execute_sql(sql).unwrap();
-    assert!(result.row_count() >= 0);
     assert_eq!(result.column_count(), 2);
 }
 
@@ -168,7 +163,6 @@ fn test_nested_subquery() {
         ORDER BY salary DESC
     "#;
     let result = ctx.execute_sql(sql).unwrap();
-    assert!(result.row_count() >= 0);
     assert_eq!(result.column_count(), 2);
 }
 
@@ -187,7 +181,6 @@ fn test_subquery_with_join() {
         ORDER BY o.order_count DESC
     "#;
     let result = ctx.execute_sql(sql).unwrap();
-    assert!(result.row_count() >= 0);
     assert_eq!(result.column_count(), 2);
 }
 